@@ -1,6 +1,12 @@
+/// Owned CDDL AST
+pub mod owned;
 /// Parent visitor implementation
 pub mod parent;
 
+#[cfg(feature = "std")]
+#[doc(inline)]
+pub use owned::OwnedCDDL;
+
 #[cfg(target_arch = "wasm32")]
 extern crate console_error_panic_hook;
 
@@ -104,7 +110,7 @@ cddl_types_from_ast! {
 #[cfg(feature = "ast-comments")]
 #[derive(Default, Debug, PartialEq, Eq, Clone)]
 #[doc(hidden)]
-pub struct Comments<'a>(pub Vec<&'a str>);
+pub struct Comments<'a>(pub Vec<Cow<'a, str>>);
 
 #[cfg(feature = "ast-comments")]
 impl<'a> Comments<'a> {
@@ -220,7 +226,7 @@ impl<'a> fmt::Display for CDDL<'a> {
 #[derive(Debug, Clone)]
 pub struct Identifier<'a> {
   /// Identifier
-  pub ident: &'a str,
+  pub ident: Cow<'a, str>,
   /// Optional socket
   pub socket: Option<SocketPlug>,
   /// Span
@@ -255,7 +261,7 @@ impl<'a> From<&'static str> for Identifier<'a> {
         if let Some(c) = socket.next() {
           if c == '$' {
             return Identifier {
-              ident,
+              ident: ident.into(),
               socket: Some(SocketPlug::GROUP),
               #[cfg(feature = "ast-span")]
               span: (0, 0, 0),
@@ -264,7 +270,7 @@ impl<'a> From<&'static str> for Identifier<'a> {
         }
 
         return Identifier {
-          ident,
+          ident: ident.into(),
           socket: Some(SocketPlug::TYPE),
           #[cfg(feature = "ast-span")]
           span: (0, 0, 0),
@@ -273,7 +279,7 @@ impl<'a> From<&'static str> for Identifier<'a> {
     }
 
     Identifier {
-      ident,
+      ident: ident.into(),
       socket: None,
       #[cfg(feature = "ast-span")]
       span: (0, 0, 0),
@@ -400,7 +406,7 @@ impl<'a> fmt::Display for Rule<'a> {
         #[cfg(feature = "ast-comments")]
         if let Some(comments) = comments_after_rule {
           if comments.any_non_newline() {
-            if let Some(&"\n") = comments.0.first() {
+            if comments.0.first().map_or(false, |c| c == "\n") {
               rule_str.push_str(&comments.to_string());
             } else {
               let _ = write!(rule_str, " {}", comments);
@@ -423,7 +429,7 @@ impl<'a> fmt::Display for Rule<'a> {
         #[cfg(feature = "ast-comments")]
         if let Some(comments) = comments_after_rule {
           if comments.any_non_newline() {
-            if let Some(&"\n") = comments.0.first() {
+            if comments.0.first().map_or(false, |c| c == "\n") {
               rule_str.push_str(&comments.to_string());
             } else {
               let _ = write!(rule_str, " {}", comments);
@@ -1485,7 +1491,7 @@ impl<'a> fmt::Display for Type2<'a> {
         generic_args,
         ..
       } => {
-        let mut t2_str = String::new();
+        let mut t2_str = String::from("~");
 
         #[cfg(feature = "ast-comments")]
         if let Some(comments) = comments {
@@ -1618,7 +1624,7 @@ impl<'a> From<RangeValue<'a>> for Type2<'a> {
     match rv {
       RangeValue::IDENT(ident, socket) => Type2::Typename {
         ident: Identifier {
-          ident,
+          ident: ident.into(),
           socket,
           #[cfg(feature = "ast-span")]
           span,
@@ -1743,6 +1749,58 @@ impl<'a> From<ByteValue<'a>> for Type2<'a> {
   }
 }
 
+/// Array of an exponent and a mantissa, i.e. `[int, integer]`, tagged by the
+/// decfrac and bigfloat types in the standard prelude
+fn exponent_mantissa_type<'a>() -> Type<'a> {
+  let entry = |token| GroupEntry::ValueMemberKey {
+    ge: Box::from(ValueMemberKeyEntry {
+      occur: None,
+      member_key: None,
+      entry_type: type_from_token(token),
+    }),
+    #[cfg(feature = "ast-span")]
+    span: Span::default(),
+    #[cfg(feature = "ast-comments")]
+    leading_comments: None,
+    #[cfg(feature = "ast-comments")]
+    trailing_comments: None,
+  };
+
+  Type {
+    type_choices: vec![TypeChoice {
+      type1: Type1 {
+        type2: Type2::Array {
+          group: Group {
+            group_choices: vec![GroupChoice::new(vec![
+              entry(Token::INT),
+              entry(Token::INTEGER),
+            ])],
+            #[cfg(feature = "ast-span")]
+            span: Span::default(),
+          },
+          #[cfg(feature = "ast-span")]
+          span: Span::default(),
+          #[cfg(feature = "ast-comments")]
+          comments_before_group: None,
+          #[cfg(feature = "ast-comments")]
+          comments_after_group: None,
+        },
+        operator: None,
+        #[cfg(feature = "ast-span")]
+        span: Span::default(),
+        #[cfg(feature = "ast-comments")]
+        comments_after_type: None,
+      },
+      #[cfg(feature = "ast-comments")]
+      comments_before_type: None,
+      #[cfg(feature = "ast-comments")]
+      comments_after_type: None,
+    }],
+    #[cfg(feature = "ast-span")]
+    span: Span::default(),
+  }
+}
+
 /// Retrieve `Type2` from token if it is a tag type in the standard prelude
 pub fn tag_from_token<'a>(token: &Token) -> Option<Type2<'a>> {
   match token {
@@ -1786,8 +1844,26 @@ pub fn tag_from_token<'a>(token: &Token) -> Option<Type2<'a>> {
       #[cfg(feature = "ast-span")]
       span: Span::default(),
     }),
-    Token::DECFRAC => unimplemented!(),
-    Token::BIGFLOAT => unimplemented!(),
+    Token::DECFRAC => Some(Type2::TaggedData {
+      tag: Some(4),
+      t: exponent_mantissa_type(),
+      #[cfg(feature = "ast-comments")]
+      comments_before_type: None,
+      #[cfg(feature = "ast-comments")]
+      comments_after_type: None,
+      #[cfg(feature = "ast-span")]
+      span: Span::default(),
+    }),
+    Token::BIGFLOAT => Some(Type2::TaggedData {
+      tag: Some(5),
+      t: exponent_mantissa_type(),
+      #[cfg(feature = "ast-comments")]
+      comments_before_type: None,
+      #[cfg(feature = "ast-comments")]
+      comments_after_type: None,
+      #[cfg(feature = "ast-span")]
+      span: Span::default(),
+    }),
     Token::EB64URL => Some(Type2::TaggedData {
       tag: Some(21),
       t: type_from_token(Token::ANY),
@@ -1988,7 +2064,7 @@ impl<'a> fmt::Display for Group<'a> {
             group_str.push_str(gc_str.trim_start());
           }
         } else {
-          group_str.push_str(&gc.to_string());
+          group_str.push_str(&gc_str);
         }
 
         if self.group_choices.len() > 2 && gc.group_entries.len() <= 3 {
@@ -2382,7 +2458,7 @@ impl<'a> fmt::Display for OptionalComma<'a> {
           }
         }
 
-        for (idx, &comment) in comments.0.iter().enumerate() {
+        for (idx, comment) in comments.0.iter().enumerate() {
           if idx == 0 && comment != "\n" {
             let _ = writeln!(optcomma_str, ";{}", comment);
           } else if idx == 0 {
@@ -0,0 +1,841 @@
+use super::*;
+
+#[cfg(feature = "std")]
+use crate::{
+  lexer::Lexer,
+  parser::{self, Parser},
+};
+
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
+/// CDDL AST that owns all of its data, as opposed to a [`CDDL`] parsed with
+/// `cddl_from_str` which borrows from its source. An `OwnedCDDL` is
+/// `'static`, `Send` and `Sync`, so it can be cached in a `static` or shared
+/// across threads in an `Arc`. Cloning is cheap, since clones share the AST
+///
+/// # Example
+///
+/// ```
+/// use cddl::ast::OwnedCDDL;
+///
+/// let cddl = OwnedCDDL::parse(String::from("myrule = int")).unwrap();
+///
+/// let handle = std::thread::spawn({
+///   let cddl = cddl.clone();
+///   move || cddl.cddl().rules.len()
+/// });
+///
+/// assert_eq!(handle.join().unwrap(), 1);
+/// assert_eq!(cddl.source(), "myrule = int");
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct OwnedCDDL {
+  cddl: Arc<CDDL<'static>>,
+  source: Arc<str>,
+}
+
+#[cfg(feature = "std")]
+impl OwnedCDDL {
+  /// Parse a CDDL AST from the given source, taking ownership of it
+  pub fn parse(source: impl Into<Arc<str>>) -> Result<Self, String> {
+    Self::parse_with_custom_controls(source, &[])
+  }
+
+  /// Parse a CDDL AST from the given source, taking ownership of it and
+  /// recognizing control operators with the given names as custom control
+  /// operators
+  pub fn parse_with_custom_controls(
+    source: impl Into<Arc<str>>,
+    custom_controls: &[&'static str],
+  ) -> Result<Self, String> {
+    let source = source.into();
+    let cddl = parse(&source, custom_controls)?.into_owned();
+
+    Ok(OwnedCDDL {
+      cddl: Arc::new(cddl),
+      source,
+    })
+  }
+
+  /// Source the AST was parsed from
+  pub fn source(&self) -> &str {
+    &self.source
+  }
+
+  /// Owned AST
+  pub fn cddl(&self) -> &CDDL<'static> {
+    &self.cddl
+  }
+}
+
+#[cfg(feature = "std")]
+fn parse<'a>(source: &'a str, custom_controls: &[&'static str]) -> Result<CDDL<'a>, String> {
+  let lexer = Lexer::new(source).with_custom_controls(custom_controls);
+  let mut p = Parser::new(source, Box::new(lexer.iter())).map_err(|e| e.to_string())?;
+
+  match p.parse_cddl() {
+    Ok(c) => Ok(c),
+    Err(parser::Error::INCREMENTAL) => match p.report_errors(false) {
+      Ok(Some(e)) => Err(e),
+      _ => Err(parser::Error::INCREMENTAL.to_string()),
+    },
+    Err(e) => Err(e.to_string()),
+  }
+}
+
+fn owned_str(s: Cow<str>) -> Cow<'static, str> {
+  Cow::Owned(s.into_owned())
+}
+
+fn owned_bytes(b: Cow<[u8]>) -> Cow<'static, [u8]> {
+  Cow::Owned(b.into_owned())
+}
+
+impl<'a> CDDL<'a> {
+  /// Convert into an AST that owns all of its data and therefore no longer
+  /// borrows from the source it was parsed from
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use cddl::{ast::CDDL, cddl_from_str};
+  ///
+  /// let cddl: CDDL<'static> = {
+  ///   let source = String::from("myrule = int");
+  ///   cddl_from_str(&source, true).unwrap().into_owned()
+  /// };
+  ///
+  /// assert_eq!(cddl.rules.len(), 1);
+  /// ```
+  pub fn into_owned(self) -> CDDL<'static> {
+    CDDL {
+      rules: self.rules.into_iter().map(Rule::into_owned).collect(),
+      #[cfg(feature = "ast-comments")]
+      comments: self.comments.map(Comments::into_owned),
+    }
+  }
+}
+
+#[cfg(feature = "ast-comments")]
+impl<'a> Comments<'a> {
+  fn into_owned(self) -> Comments<'static> {
+    Comments(self.0.into_iter().map(owned_str).collect())
+  }
+}
+
+impl<'a> Identifier<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> Identifier<'static> {
+    Identifier {
+      ident: owned_str(self.ident),
+      socket: self.socket,
+      #[cfg(feature = "ast-span")]
+      span: self.span,
+    }
+  }
+}
+
+impl<'a> Rule<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> Rule<'static> {
+    match self {
+      Rule::Type {
+        rule,
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments_after_rule,
+      } => Rule::Type {
+        rule: rule.into_owned(),
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments_after_rule: comments_after_rule.map(Comments::into_owned),
+      },
+      Rule::Group {
+        rule,
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments_after_rule,
+      } => Rule::Group {
+        rule: Box::new(rule.into_owned()),
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments_after_rule: comments_after_rule.map(Comments::into_owned),
+      },
+    }
+  }
+}
+
+impl<'a> TypeRule<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> TypeRule<'static> {
+    TypeRule {
+      name: self.name.into_owned(),
+      generic_params: self.generic_params.map(GenericParams::into_owned),
+      is_type_choice_alternate: self.is_type_choice_alternate,
+      value: self.value.into_owned(),
+      #[cfg(feature = "ast-comments")]
+      comments_before_assignt: self.comments_before_assignt.map(Comments::into_owned),
+      #[cfg(feature = "ast-comments")]
+      comments_after_assignt: self.comments_after_assignt.map(Comments::into_owned),
+    }
+  }
+}
+
+impl<'a> GroupRule<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> GroupRule<'static> {
+    GroupRule {
+      name: self.name.into_owned(),
+      generic_params: self.generic_params.map(GenericParams::into_owned),
+      is_group_choice_alternate: self.is_group_choice_alternate,
+      entry: self.entry.into_owned(),
+      #[cfg(feature = "ast-comments")]
+      comments_before_assigng: self.comments_before_assigng.map(Comments::into_owned),
+      #[cfg(feature = "ast-comments")]
+      comments_after_assigng: self.comments_after_assigng.map(Comments::into_owned),
+    }
+  }
+}
+
+impl<'a> GenericParams<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> GenericParams<'static> {
+    GenericParams {
+      params: self
+        .params
+        .into_iter()
+        .map(GenericParam::into_owned)
+        .collect(),
+      #[cfg(feature = "ast-span")]
+      span: self.span,
+    }
+  }
+}
+
+impl<'a> GenericParam<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> GenericParam<'static> {
+    GenericParam {
+      param: self.param.into_owned(),
+      #[cfg(feature = "ast-comments")]
+      comments_before_ident: self.comments_before_ident.map(Comments::into_owned),
+      #[cfg(feature = "ast-comments")]
+      comments_after_ident: self.comments_after_ident.map(Comments::into_owned),
+    }
+  }
+}
+
+impl<'a> GenericArgs<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> GenericArgs<'static> {
+    GenericArgs {
+      args: self.args.into_iter().map(GenericArg::into_owned).collect(),
+      #[cfg(feature = "ast-span")]
+      span: self.span,
+    }
+  }
+}
+
+impl<'a> GenericArg<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> GenericArg<'static> {
+    GenericArg {
+      arg: Box::new(self.arg.into_owned()),
+      #[cfg(feature = "ast-comments")]
+      comments_before_type: self.comments_before_type.map(Comments::into_owned),
+      #[cfg(feature = "ast-comments")]
+      comments_after_type: self.comments_after_type.map(Comments::into_owned),
+    }
+  }
+}
+
+impl<'a> Type<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> Type<'static> {
+    Type {
+      type_choices: self
+        .type_choices
+        .into_iter()
+        .map(TypeChoice::into_owned)
+        .collect(),
+      #[cfg(feature = "ast-span")]
+      span: self.span,
+    }
+  }
+}
+
+impl<'a> TypeChoice<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> TypeChoice<'static> {
+    TypeChoice {
+      type1: self.type1.into_owned(),
+      #[cfg(feature = "ast-comments")]
+      comments_before_type: self.comments_before_type.map(Comments::into_owned),
+      #[cfg(feature = "ast-comments")]
+      comments_after_type: self.comments_after_type.map(Comments::into_owned),
+    }
+  }
+}
+
+impl<'a> Type1<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> Type1<'static> {
+    Type1 {
+      type2: self.type2.into_owned(),
+      operator: self.operator.map(Operator::into_owned),
+      #[cfg(feature = "ast-span")]
+      span: self.span,
+      #[cfg(feature = "ast-comments")]
+      comments_after_type: self.comments_after_type.map(Comments::into_owned),
+    }
+  }
+}
+
+impl<'a> Operator<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> Operator<'static> {
+    Operator {
+      operator: self.operator,
+      type2: self.type2.into_owned(),
+      #[cfg(feature = "ast-comments")]
+      comments_before_operator: self.comments_before_operator.map(Comments::into_owned),
+      #[cfg(feature = "ast-comments")]
+      comments_after_operator: self.comments_after_operator.map(Comments::into_owned),
+    }
+  }
+}
+
+impl<'a> Type2<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> Type2<'static> {
+    match self {
+      Type2::IntValue {
+        value,
+        #[cfg(feature = "ast-span")]
+        span,
+      } => Type2::IntValue {
+        value,
+        #[cfg(feature = "ast-span")]
+        span,
+      },
+      Type2::UintValue {
+        value,
+        #[cfg(feature = "ast-span")]
+        span,
+      } => Type2::UintValue {
+        value,
+        #[cfg(feature = "ast-span")]
+        span,
+      },
+      Type2::FloatValue {
+        value,
+        #[cfg(feature = "ast-span")]
+        span,
+      } => Type2::FloatValue {
+        value,
+        #[cfg(feature = "ast-span")]
+        span,
+      },
+      Type2::TextValue {
+        value,
+        #[cfg(feature = "ast-span")]
+        span,
+      } => Type2::TextValue {
+        value: owned_str(value),
+        #[cfg(feature = "ast-span")]
+        span,
+      },
+      Type2::UTF8ByteString {
+        value,
+        #[cfg(feature = "ast-span")]
+        span,
+      } => Type2::UTF8ByteString {
+        value: owned_bytes(value),
+        #[cfg(feature = "ast-span")]
+        span,
+      },
+      Type2::B16ByteString {
+        value,
+        #[cfg(feature = "ast-span")]
+        span,
+      } => Type2::B16ByteString {
+        value: owned_bytes(value),
+        #[cfg(feature = "ast-span")]
+        span,
+      },
+      Type2::B64ByteString {
+        value,
+        #[cfg(feature = "ast-span")]
+        span,
+      } => Type2::B64ByteString {
+        value: owned_bytes(value),
+        #[cfg(feature = "ast-span")]
+        span,
+      },
+      Type2::Typename {
+        ident,
+        generic_args,
+        #[cfg(feature = "ast-span")]
+        span,
+      } => Type2::Typename {
+        ident: ident.into_owned(),
+        generic_args: generic_args.map(GenericArgs::into_owned),
+        #[cfg(feature = "ast-span")]
+        span,
+      },
+      Type2::ParenthesizedType {
+        pt,
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments_before_type,
+        #[cfg(feature = "ast-comments")]
+        comments_after_type,
+      } => Type2::ParenthesizedType {
+        pt: pt.into_owned(),
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments_before_type: comments_before_type.map(Comments::into_owned),
+        #[cfg(feature = "ast-comments")]
+        comments_after_type: comments_after_type.map(Comments::into_owned),
+      },
+      Type2::Map {
+        group,
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments_before_group,
+        #[cfg(feature = "ast-comments")]
+        comments_after_group,
+      } => Type2::Map {
+        group: group.into_owned(),
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments_before_group: comments_before_group.map(Comments::into_owned),
+        #[cfg(feature = "ast-comments")]
+        comments_after_group: comments_after_group.map(Comments::into_owned),
+      },
+      Type2::Array {
+        group,
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments_before_group,
+        #[cfg(feature = "ast-comments")]
+        comments_after_group,
+      } => Type2::Array {
+        group: group.into_owned(),
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments_before_group: comments_before_group.map(Comments::into_owned),
+        #[cfg(feature = "ast-comments")]
+        comments_after_group: comments_after_group.map(Comments::into_owned),
+      },
+      Type2::Unwrap {
+        ident,
+        generic_args,
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments,
+      } => Type2::Unwrap {
+        ident: ident.into_owned(),
+        generic_args: generic_args.map(GenericArgs::into_owned),
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments: comments.map(Comments::into_owned),
+      },
+      Type2::ChoiceFromInlineGroup {
+        group,
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments,
+        #[cfg(feature = "ast-comments")]
+        comments_before_group,
+        #[cfg(feature = "ast-comments")]
+        comments_after_group,
+      } => Type2::ChoiceFromInlineGroup {
+        group: group.into_owned(),
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments: comments.map(Comments::into_owned),
+        #[cfg(feature = "ast-comments")]
+        comments_before_group: comments_before_group.map(Comments::into_owned),
+        #[cfg(feature = "ast-comments")]
+        comments_after_group: comments_after_group.map(Comments::into_owned),
+      },
+      Type2::ChoiceFromGroup {
+        ident,
+        generic_args,
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments,
+      } => Type2::ChoiceFromGroup {
+        ident: ident.into_owned(),
+        generic_args: generic_args.map(GenericArgs::into_owned),
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments: comments.map(Comments::into_owned),
+      },
+      Type2::TaggedData {
+        tag,
+        t,
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments_before_type,
+        #[cfg(feature = "ast-comments")]
+        comments_after_type,
+      } => Type2::TaggedData {
+        tag,
+        t: t.into_owned(),
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments_before_type: comments_before_type.map(Comments::into_owned),
+        #[cfg(feature = "ast-comments")]
+        comments_after_type: comments_after_type.map(Comments::into_owned),
+      },
+      Type2::DataMajorType {
+        mt,
+        constraint,
+        #[cfg(feature = "ast-span")]
+        span,
+      } => Type2::DataMajorType {
+        mt,
+        constraint,
+        #[cfg(feature = "ast-span")]
+        span,
+      },
+      Type2::Any {
+        #[cfg(feature = "ast-span")]
+        span,
+      } => Type2::Any {
+        #[cfg(feature = "ast-span")]
+        span,
+      },
+    }
+  }
+}
+
+impl<'a> Group<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> Group<'static> {
+    Group {
+      group_choices: self
+        .group_choices
+        .into_iter()
+        .map(GroupChoice::into_owned)
+        .collect(),
+      #[cfg(feature = "ast-span")]
+      span: self.span,
+    }
+  }
+}
+
+impl<'a> GroupChoice<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> GroupChoice<'static> {
+    GroupChoice {
+      group_entries: self
+        .group_entries
+        .into_iter()
+        .map(|(ge, oc)| (ge.into_owned(), oc.into_owned()))
+        .collect(),
+      #[cfg(feature = "ast-span")]
+      span: self.span,
+      #[cfg(feature = "ast-comments")]
+      comments_before_grpchoice: self.comments_before_grpchoice.map(Comments::into_owned),
+    }
+  }
+}
+
+impl<'a> GroupEntry<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> GroupEntry<'static> {
+    match self {
+      GroupEntry::ValueMemberKey {
+        ge,
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        leading_comments,
+        #[cfg(feature = "ast-comments")]
+        trailing_comments,
+      } => GroupEntry::ValueMemberKey {
+        ge: Box::new(ge.into_owned()),
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        leading_comments: leading_comments.map(Comments::into_owned),
+        #[cfg(feature = "ast-comments")]
+        trailing_comments: trailing_comments.map(Comments::into_owned),
+      },
+      GroupEntry::TypeGroupname {
+        ge,
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        leading_comments,
+        #[cfg(feature = "ast-comments")]
+        trailing_comments,
+      } => GroupEntry::TypeGroupname {
+        ge: ge.into_owned(),
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        leading_comments: leading_comments.map(Comments::into_owned),
+        #[cfg(feature = "ast-comments")]
+        trailing_comments: trailing_comments.map(Comments::into_owned),
+      },
+      GroupEntry::InlineGroup {
+        occur,
+        group,
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments_before_group,
+        #[cfg(feature = "ast-comments")]
+        comments_after_group,
+      } => GroupEntry::InlineGroup {
+        occur: occur.map(Occurrence::into_owned),
+        group: group.into_owned(),
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments_before_group: comments_before_group.map(Comments::into_owned),
+        #[cfg(feature = "ast-comments")]
+        comments_after_group: comments_after_group.map(Comments::into_owned),
+      },
+    }
+  }
+}
+
+impl<'a> OptionalComma<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> OptionalComma<'static> {
+    OptionalComma {
+      optional_comma: self.optional_comma,
+      #[cfg(feature = "ast-comments")]
+      trailing_comments: self.trailing_comments.map(Comments::into_owned),
+      _a: PhantomData,
+    }
+  }
+}
+
+impl<'a> Occurrence<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> Occurrence<'static> {
+    Occurrence {
+      occur: self.occur,
+      #[cfg(feature = "ast-comments")]
+      comments: self.comments.map(Comments::into_owned),
+      _a: PhantomData,
+    }
+  }
+}
+
+impl<'a> ValueMemberKeyEntry<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> ValueMemberKeyEntry<'static> {
+    ValueMemberKeyEntry {
+      occur: self.occur.map(Occurrence::into_owned),
+      member_key: self.member_key.map(MemberKey::into_owned),
+      entry_type: self.entry_type.into_owned(),
+    }
+  }
+}
+
+impl<'a> TypeGroupnameEntry<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> TypeGroupnameEntry<'static> {
+    TypeGroupnameEntry {
+      occur: self.occur.map(Occurrence::into_owned),
+      name: self.name.into_owned(),
+      generic_args: self.generic_args.map(GenericArgs::into_owned),
+    }
+  }
+}
+
+impl<'a> MemberKey<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> MemberKey<'static> {
+    match self {
+      MemberKey::Type1 {
+        t1,
+        is_cut,
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments_before_cut,
+        #[cfg(feature = "ast-comments")]
+        comments_after_cut,
+        #[cfg(feature = "ast-comments")]
+        comments_after_arrowmap,
+      } => MemberKey::Type1 {
+        t1: Box::new(t1.into_owned()),
+        is_cut,
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments_before_cut: comments_before_cut.map(Comments::into_owned),
+        #[cfg(feature = "ast-comments")]
+        comments_after_cut: comments_after_cut.map(Comments::into_owned),
+        #[cfg(feature = "ast-comments")]
+        comments_after_arrowmap: comments_after_arrowmap.map(Comments::into_owned),
+      },
+      MemberKey::Bareword {
+        ident,
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments,
+        #[cfg(feature = "ast-comments")]
+        comments_after_colon,
+      } => MemberKey::Bareword {
+        ident: ident.into_owned(),
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments: comments.map(Comments::into_owned),
+        #[cfg(feature = "ast-comments")]
+        comments_after_colon: comments_after_colon.map(Comments::into_owned),
+      },
+      MemberKey::Value {
+        value,
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments,
+        #[cfg(feature = "ast-comments")]
+        comments_after_colon,
+      } => MemberKey::Value {
+        value: value.into_owned(),
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        comments: comments.map(Comments::into_owned),
+        #[cfg(feature = "ast-comments")]
+        comments_after_colon: comments_after_colon.map(Comments::into_owned),
+      },
+      MemberKey::NonMemberKey {
+        non_member_key,
+        #[cfg(feature = "ast-comments")]
+        comments_before_type_or_group,
+        #[cfg(feature = "ast-comments")]
+        comments_after_type_or_group,
+      } => MemberKey::NonMemberKey {
+        non_member_key: non_member_key.into_owned(),
+        #[cfg(feature = "ast-comments")]
+        comments_before_type_or_group: comments_before_type_or_group.map(Comments::into_owned),
+        #[cfg(feature = "ast-comments")]
+        comments_after_type_or_group: comments_after_type_or_group.map(Comments::into_owned),
+      },
+    }
+  }
+}
+
+impl<'a> NonMemberKey<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> NonMemberKey<'static> {
+    match self {
+      NonMemberKey::Group(group) => NonMemberKey::Group(group.into_owned()),
+      NonMemberKey::Type(t) => NonMemberKey::Type(t.into_owned()),
+    }
+  }
+}
+
+impl<'a> Value<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> Value<'static> {
+    match self {
+      Value::INT(i) => Value::INT(i),
+      Value::UINT(ui) => Value::UINT(ui),
+      Value::FLOAT(f) => Value::FLOAT(f),
+      Value::TEXT(text) => Value::TEXT(owned_str(text)),
+      Value::BYTE(bv) => Value::BYTE(bv.into_owned()),
+    }
+  }
+}
+
+impl<'a> ByteValue<'a> {
+  /// See [`CDDL::into_owned`]
+  pub fn into_owned(self) -> ByteValue<'static> {
+    match self {
+      ByteValue::UTF8(b) => ByteValue::UTF8(owned_bytes(b)),
+      ByteValue::B16(b) => ByteValue::B16(owned_bytes(b)),
+      ByteValue::B64(b) => ByteValue::B64(owned_bytes(b)),
+    }
+  }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+  use super::*;
+
+  fn assert_send_sync<T: Send + Sync + 'static>() {}
+
+  #[test]
+  fn owned_cddl_outlives_borrowed_source() {
+    assert_send_sync::<OwnedCDDL>();
+
+    let source = String::from("person = { name: tstr }\nname = tstr");
+    let cddl = OwnedCDDL::parse(source.clone()).unwrap();
+    drop(source);
+
+    let clone = cddl.clone();
+    drop(cddl);
+
+    let names = std::thread::spawn(move || {
+      clone
+        .cddl()
+        .rules
+        .iter()
+        .map(|r| match r {
+          Rule::Type { rule, .. } => rule.name.ident.to_string(),
+          Rule::Group { rule, .. } => rule.name.ident.to_string(),
+        })
+        .collect::<Vec<_>>()
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(names, vec!["person", "name"]);
+    assert!(OwnedCDDL::parse("person = {").is_err());
+  }
+
+  #[test]
+  fn into_owned_preserves_ast() {
+    let (owned, expected) = {
+      let source = String::from(
+        r#"
+          ; comment
+          message = { ? kind: $kind, body: body<tstr>, 'raw' => h'0102', * tstr => any }
+          body<t> = [* t .size 4] / #6.32(tstr) / &colors
+          $kind /= "a" / "b"
+          colors = ( red: 1, green: 2 )
+        "#,
+      );
+      let cddl = crate::cddl_from_str(&source, true).unwrap();
+
+      (cddl.clone().into_owned(), cddl.to_string())
+    };
+
+    assert_eq!(owned.to_string(), expected);
+  }
+}
@@ -6,10 +6,15 @@
 extern crate log;
 
 use cddl::{
-  cddl_from_str, parser::root_type_name_from_cddl_str, validate_cbor_from_slice,
-  validate_json_from_str,
+  cddl_from_str, edn, generate_cbor_from_str, generate_json_from_str,
+  parser::root_type_name_from_cddl_str,
+  validate_cbor_from_slice, validate_cbor_from_slice_deterministic,
+  validate_cbor_from_slice_with_rule, validate_cbor_seq_from_reader, validate_json_from_str,
+  validate_json_from_str_with_rule, validate_json_records_from_reader,
+  validator::{cbor, json, json_seq},
 };
-use clap::{ArgGroup, Args, Parser, Subcommand};
+use clap::{ArgEnum, ArgGroup, Args, Parser, Subcommand};
+use serde::Serialize;
 
 use simplelog::*;
 use std::{
@@ -44,11 +49,12 @@ enum Commands {
     file: String,
   },
   Validate(Validate),
+  Generate(Generate),
 }
 
 #[derive(Args)]
 #[clap(about = "Validate JSON and/or CBOR against a CDDL definition")]
-#[clap(group(ArgGroup::new("targets").required(true).multiple(true).args(&["stdin", "json", "cbor"])))]
+#[clap(group(ArgGroup::new("targets").required(true).multiple(true).args(&["stdin", "json", "json-lines", "json-seq", "cbor", "cbor-seq", "edn"])))]
 struct Validate {
   #[clap(short = 'd', long = "cddl", help = "CDDL document")]
   cddl: String,
@@ -67,6 +73,20 @@ struct Validate {
     multiple_values = true
   )]
   json: Option<Vec<String>>,
+  #[clap(
+    long = "json-lines",
+    help = "JSON Lines (NDJSON) file(s) to validate, each line being validated as a separate record",
+    use_value_delimiter = true,
+    multiple_values = true
+  )]
+  json_lines: Option<Vec<String>>,
+  #[clap(
+    long = "json-seq",
+    help = "JSON text sequence file(s) (RFC 7464) to validate, each JSON text being validated as a separate record",
+    use_value_delimiter = true,
+    multiple_values = true
+  )]
+  json_seq: Option<Vec<String>>,
   #[clap(
     short = 'c',
     long = "cbor",
@@ -75,11 +95,387 @@ struct Validate {
     use_value_delimiter = true
   )]
   cbor: Option<Vec<String>>,
+  #[clap(
+    long = "cbor-seq",
+    help = "CBOR sequence file(s) (RFC 8742) to validate against a group or array rule, reporting errors per data item index",
+    multiple_values = true,
+    use_value_delimiter = true
+  )]
+  cbor_seq: Option<Vec<String>>,
+  #[clap(
+    long = "edn",
+    help = "CBOR data item(s) in extended diagnostic notation (EDN) to validate",
+    multiple_values = true,
+    use_value_delimiter = true
+  )]
+  edn: Option<Vec<String>>,
   #[clap(
     long = "stdin",
     help = "JSON or CBOR input from stdin. Assumes UTF-8 encoding is JSON, otherwise parses as CBOR"
   )]
   stdin: bool,
+  #[clap(
+    short = 'r',
+    long = "rule",
+    help = "Name of the type or group rule to validate against. Defaults to the root type rule"
+  )]
+  rule: Option<String>,
+  #[clap(
+    long = "deterministic",
+    help = "Require CBOR to use core deterministic encoding (RFC 8949 section 4.2.1). Cannot be combined with --cbor-seq",
+    conflicts_with = "cbor-seq"
+  )]
+  deterministic: bool,
+  #[clap(
+    short = 'o',
+    long = "output",
+    arg_enum,
+    default_value = "text",
+    help = "Format of the validation report. Reports other than text are written to stdout"
+  )]
+  output: OutputFormat,
+}
+
+/// Format of the report written by the validate command
+#[derive(ArgEnum, Clone, Copy, PartialEq)]
+enum OutputFormat {
+  /// Human-readable log messages
+  Text,
+  /// JSON array with one record per validated document
+  Json,
+  /// SARIF 2.1.0 log
+  Sarif,
+  /// JUnit XML with one test case per validated document
+  Junit,
+}
+
+/// Validation outcome of a single document
+#[derive(Serialize)]
+struct Report {
+  file: String,
+  valid: bool,
+  errors: Vec<ReportError>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum ReportError {
+  Json(json::ValidationError),
+  Cbor(cbor::ValidationError),
+  Other {
+    reason: String,
+  },
+  /// Error of a record of a JSON Lines file or JSON text sequence
+  Record {
+    record: usize,
+    line: usize,
+    #[serde(flatten)]
+    error: json::ValidationError,
+  },
+}
+
+impl ReportError {
+  fn reason(&self) -> &str {
+    match self {
+      ReportError::Json(e) => &e.reason,
+      ReportError::Cbor(e) => &e.reason,
+      ReportError::Other { reason } => reason,
+      ReportError::Record { error, .. } => &error.reason,
+    }
+  }
+
+  fn cddl_location(&self) -> Option<&str> {
+    match self {
+      ReportError::Json(e) => Some(e.cddl_location.as_str()),
+      ReportError::Cbor(e) => Some(e.cddl_location.as_str()),
+      ReportError::Other { .. } => None,
+      ReportError::Record { error, .. } => Some(error.cddl_location.as_str()),
+    }
+    .filter(|l| !l.is_empty())
+  }
+
+  fn document_location(&self) -> Option<&str> {
+    match self {
+      ReportError::Json(e) => Some(&e.json_location),
+      ReportError::Cbor(e) => Some(&e.cbor_location),
+      ReportError::Other { .. } => None,
+      ReportError::Record { error, .. } => Some(&error.json_location),
+    }
+  }
+
+  fn line(&self) -> Option<usize> {
+    match self {
+      ReportError::Record { line, .. } => Some(*line),
+      _ => None,
+    }
+  }
+}
+
+impl Report {
+  fn from_missing_file(file: &str) -> Self {
+    Report {
+      file: file.to_string(),
+      valid: false,
+      errors: vec![ReportError::Other {
+        reason: "file does not exist".to_string(),
+      }],
+    }
+  }
+
+  fn from_json(file: &str, r: json::Result) -> Self {
+    let errors = match r {
+      Ok(()) => Vec::new(),
+      Err(json::Error::Validation(errors)) => errors.into_iter().map(ReportError::Json).collect(),
+      Err(e) => vec![ReportError::Other {
+        reason: e.to_string().trim_end().to_string(),
+      }],
+    };
+
+    Report {
+      file: file.to_string(),
+      valid: errors.is_empty(),
+      errors,
+    }
+  }
+
+  fn from_json_records(file: &str, r: json_seq::Result) -> Self {
+    let errors = match r {
+      Ok(()) => Vec::new(),
+      Err(json_seq::Error::Records(errors)) => errors
+        .into_iter()
+        .flat_map(|e| {
+          let (record, line) = (e.record, e.line);
+          // Records that fail to parse are reported with the reason alone
+          let errors = match e.error {
+            json::Error::Validation(errors) => errors,
+            e => vec![json::ValidationError {
+              reason: e.to_string().trim_end().to_string(),
+              cddl_location: String::new(),
+              json_location: String::new(),
+              is_multi_type_choice: false,
+              is_multi_group_choice: false,
+              is_group_to_choice_enum: false,
+              type_group_name_entry: None,
+            }],
+          };
+
+          errors.into_iter().map(move |error| ReportError::Record {
+            record,
+            line,
+            error,
+          })
+        })
+        .collect(),
+      Err(e) => vec![ReportError::Other {
+        reason: e.to_string().trim_end().to_string(),
+      }],
+    };
+
+    Report {
+      file: file.to_string(),
+      valid: errors.is_empty(),
+      errors,
+    }
+  }
+
+  fn from_cbor(file: &str, r: cbor::Result<std::io::Error>) -> Self {
+    let errors = match r {
+      Ok(()) => Vec::new(),
+      Err(cbor::Error::Validation(errors)) => errors.into_iter().map(ReportError::Cbor).collect(),
+      Err(e) => vec![ReportError::Other {
+        reason: e.to_string().trim_end().to_string(),
+      }],
+    };
+
+    Report {
+      file: file.to_string(),
+      valid: errors.is_empty(),
+      errors,
+    }
+  }
+}
+
+fn sarif_from_reports(reports: &[Report]) -> serde_json::Value {
+  let mut results = Vec::new();
+  for report in reports {
+    for e in report.errors.iter() {
+      let mut location = serde_json::json!({
+        "physicalLocation": {
+          "artifactLocation": { "uri": report.file }
+        }
+      });
+      if let Some(line) = e.line() {
+        location["physicalLocation"]["region"] = serde_json::json!({ "startLine": line });
+      }
+      if let Some(document_location) = e.document_location() {
+        location["logicalLocations"] =
+          serde_json::json!([{ "fullyQualifiedName": document_location }]);
+      }
+
+      let mut result = serde_json::json!({
+        "ruleId": "cddl-validation",
+        "level": "error",
+        "message": { "text": e.reason() },
+        "locations": [location],
+      });
+      if let Some(cddl_location) = e.cddl_location() {
+        result["properties"] = serde_json::json!({ "cddlLocation": cddl_location });
+      }
+
+      results.push(result);
+    }
+  }
+
+  serde_json::json!({
+    "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+    "version": "2.1.0",
+    "runs": [{
+      "tool": {
+        "driver": {
+          "name": "cddl",
+          "version": env!("CARGO_PKG_VERSION"),
+          "informationUri": env!("CARGO_PKG_HOMEPAGE"),
+        }
+      },
+      "results": results,
+    }]
+  })
+}
+
+fn junit_from_reports(reports: &[Report]) -> String {
+  let failures = reports.iter().filter(|r| !r.valid).count();
+
+  let mut junit = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+  let _ = writeln!(
+    junit,
+    "<testsuites tests=\"{0}\" failures=\"{1}\">\n  <testsuite name=\"cddl validate\" tests=\"{0}\" failures=\"{1}\">",
+    reports.len(),
+    failures
+  );
+  for report in reports {
+    let name = xml_escape(&report.file);
+    if report.valid {
+      let _ = writeln!(
+        junit,
+        "    <testcase name=\"{}\" classname=\"cddl\"/>",
+        name
+      );
+      continue;
+    }
+
+    let _ = writeln!(junit, "    <testcase name=\"{}\" classname=\"cddl\">", name);
+    let message = report
+      .errors
+      .first()
+      .map(|e| e.reason())
+      .unwrap_or_default();
+    let mut details = String::new();
+    for e in report.errors.iter() {
+      if let ReportError::Record { record, line, .. } = e {
+        let _ = write!(details, "record {} (line {}): ", record, line);
+      }
+      details.push_str(e.reason());
+      if let Some(document_location) = e.document_location() {
+        let _ = write!(details, " (at \"{}\")", document_location);
+      }
+      if let Some(cddl_location) = e.cddl_location() {
+        let _ = write!(details, " (CDDL \"{}\")", cddl_location);
+      }
+      details.push('\n');
+    }
+    let _ = writeln!(
+      junit,
+      "      <failure message=\"{}\">{}</failure>\n    </testcase>",
+      xml_escape(message),
+      xml_escape(details.trim_end())
+    );
+  }
+  junit.push_str("  </testsuite>\n</testsuites>");
+
+  junit
+}
+
+fn xml_escape(s: &str) -> String {
+  let mut escaped = String::with_capacity(s.len());
+  for c in s.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&apos;"),
+      c => escaped.push(c),
+    }
+  }
+
+  escaped
+}
+
+/// Validate CBOR against the rule and encoding requirements of the validate
+/// command
+fn validate_cbor(
+  cddl: &str,
+  cbor: &[u8],
+  validate: &Validate,
+  #[cfg(feature = "additional-controls")] enabled_features: Option<&[&str]>,
+) -> cbor::Result<std::io::Error> {
+  #[cfg(feature = "additional-controls")]
+  if validate.deterministic {
+    return validate_cbor_from_slice_deterministic(
+      cddl,
+      cbor,
+      validate.rule.as_deref(),
+      enabled_features,
+    );
+  }
+  #[cfg(not(feature = "additional-controls"))]
+  if validate.deterministic {
+    return validate_cbor_from_slice_deterministic(cddl, cbor, validate.rule.as_deref());
+  }
+
+  #[cfg(feature = "additional-controls")]
+  let c = match &validate.rule {
+    Some(rule) => validate_cbor_from_slice_with_rule(cddl, cbor, rule, enabled_features),
+    None => validate_cbor_from_slice(cddl, cbor, enabled_features),
+  };
+  #[cfg(not(feature = "additional-controls"))]
+  let c = match &validate.rule {
+    Some(rule) => validate_cbor_from_slice_with_rule(cddl, cbor, rule),
+    None => validate_cbor_from_slice(cddl, cbor),
+  };
+
+  c
+}
+
+#[derive(Args)]
+#[clap(about = "Generate JSON and/or CBOR conforming to a CDDL definition")]
+#[clap(group(ArgGroup::new("formats").required(true).multiple(true).args(&["json", "cbor"])))]
+struct Generate {
+  #[clap(short = 'd', long = "cddl", help = "CDDL document")]
+  cddl: String,
+  #[clap(
+    long = "json",
+    help = "Generate a JSON document and print it to stdout"
+  )]
+  json: bool,
+  #[clap(
+    short = 'c',
+    long = "cbor",
+    help = "Generate a CBOR data item and write it to the given binary file"
+  )]
+  cbor: Option<String>,
+  #[clap(
+    short = 'r',
+    long = "rule",
+    help = "Name of the rule to generate from. Defaults to the root type rule"
+  )]
+  rule: Option<String>,
+  #[clap(
+    short = 's',
+    long = "seed",
+    help = "Seed for the random number generator, for reproducible output"
+  )]
+  seed: Option<u64>,
 }
 
 macro_rules! error {
@@ -92,8 +488,17 @@ macro_rules! error {
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
+  let cli = Cli::parse();
+
+  // Informational messages are written to stdout, which is reserved for the
+  // report when a machine-readable output format is requested
+  let level = match &cli.command {
+    Commands::Validate(validate) if validate.output != OutputFormat::Text => LevelFilter::Warn,
+    _ => LevelFilter::Info,
+  };
+
   TermLogger::init(
-    LevelFilter::Info,
+    level,
     ConfigBuilder::new()
       .set_time_level(LevelFilter::Off)
       .build(),
@@ -101,8 +506,6 @@ fn main() -> Result<(), Box<dyn Error>> {
     ColorChoice::Auto,
   )?;
 
-  let cli = Cli::parse();
-
   match &cli.command {
     Commands::CompileCddl { file } => {
       let p = Path::new(file);
@@ -161,33 +564,116 @@ fn main() -> Result<(), Box<dyn Error>> {
 
       let cddl_str = fs::read_to_string(&validate.cddl)?;
 
-      info!(
-        "Root type for validation: {}",
-        root_type_name_from_cddl_str(&cddl_str)?
-      );
+      match &validate.rule {
+        Some(rule) => info!("Root type for validation: {}", rule),
+        None => info!(
+          "Root type for validation: {}",
+          root_type_name_from_cddl_str(&cddl_str)?
+        ),
+      }
+
+      let mut reports = Vec::new();
 
       if let Some(files) = &validate.json {
         for file in files {
           let p = Path::new(file);
           if !p.exists() {
-            error!(cli.ci, "File {:?} does not exist", p);
+            if validate.output != OutputFormat::Text {
+              reports.push(Report::from_missing_file(file));
+            } else {
+              error!(cli.ci, "File {:?} does not exist", p);
+            }
 
             continue;
           }
 
+          let json = fs::read_to_string(file)?;
+
           #[cfg(feature = "additional-controls")]
-          let r = validate_json_from_str(
+          let r = match &validate.rule {
+            Some(rule) => {
+              validate_json_from_str_with_rule(&cddl_str, &json, rule, enabled_features.as_deref())
+            }
+            None => validate_json_from_str(&cddl_str, &json, enabled_features.as_deref()),
+          };
+          #[cfg(not(feature = "additional-controls"))]
+          let r = match &validate.rule {
+            Some(rule) => validate_json_from_str_with_rule(&cddl_str, &json, rule),
+            None => validate_json_from_str(&cddl_str, &json),
+          };
+
+          if validate.output != OutputFormat::Text {
+            reports.push(Report::from_json(file, r));
+
+            continue;
+          }
+
+          match r {
+            Ok(_) => {
+              info!("Validation of {:?} is successful", p);
+            }
+            Err(e) => {
+              error!(
+                cli.ci,
+                "Validation of {:?} failed: {}",
+                p,
+                e.to_string().trim_end()
+              );
+            }
+          }
+        }
+      }
+
+      for (files, format) in [
+        (&validate.json_lines, json_seq::Format::Lines),
+        (&validate.json_seq, json_seq::Format::TextSequence),
+      ] {
+        for file in files.iter().flatten() {
+          let p = Path::new(file);
+          if !p.exists() {
+            if validate.output != OutputFormat::Text {
+              reports.push(Report::from_missing_file(file));
+            } else {
+              error!(cli.ci, "File {:?} does not exist", p);
+            }
+
+            continue;
+          }
+          let reader = BufReader::new(File::open(p)?);
+
+          #[cfg(feature = "additional-controls")]
+          let r = validate_json_records_from_reader(
             &cddl_str,
-            &fs::read_to_string(file)?,
+            reader,
+            format,
+            validate.rule.as_deref(),
             enabled_features.as_deref(),
           );
           #[cfg(not(feature = "additional-controls"))]
-          let r = validate_json_from_str(&cddl_str, &fs::read_to_string(file)?);
+          let r =
+            validate_json_records_from_reader(&cddl_str, reader, format, validate.rule.as_deref());
+
+          if validate.output != OutputFormat::Text {
+            reports.push(Report::from_json_records(file, r));
+
+            continue;
+          }
 
           match r {
             Ok(_) => {
               info!("Validation of {:?} is successful", p);
             }
+            Err(json_seq::Error::Records(errors)) => {
+              // Every failing record is logged before failing in CI mode
+              for e in errors.iter() {
+                log::error!("Validation of {:?} failed, {}", p, e);
+              }
+              if cli.ci {
+                return Err(
+                  format!("validation of {} record(s) of {:?} failed", errors.len(), p).into(),
+                );
+              }
+            }
             Err(e) => {
               error!(
                 cli.ci,
@@ -204,7 +690,11 @@ fn main() -> Result<(), Box<dyn Error>> {
         for file in files {
           let p = Path::new(file);
           if !p.exists() {
-            error!(cli.ci, "CBOR binary file {:?} does not exist", p);
+            if validate.output != OutputFormat::Text {
+              reports.push(Report::from_missing_file(file));
+            } else {
+              error!(cli.ci, "CBOR binary file {:?} does not exist", p);
+            }
 
             continue;
           }
@@ -213,9 +703,15 @@ fn main() -> Result<(), Box<dyn Error>> {
           f.read_to_end(&mut data)?;
 
           #[cfg(feature = "additional-controls")]
-          let c = validate_cbor_from_slice(&cddl_str, &data, None);
+          let c = validate_cbor(&cddl_str, &data, validate, None);
           #[cfg(not(feature = "additional-controls"))]
-          let c = validate_cbor_from_slice(&cddl_str, &data);
+          let c = validate_cbor(&cddl_str, &data, validate);
+
+          if validate.output != OutputFormat::Text {
+            reports.push(Report::from_cbor(file, c));
+
+            continue;
+          }
 
           match c {
             Ok(_) => {
@@ -233,50 +729,217 @@ fn main() -> Result<(), Box<dyn Error>> {
         }
       }
 
-      if validate.stdin {
-        let stdin = io::stdin();
+      if let Some(files) = &validate.edn {
+        for file in files {
+          let p = Path::new(file);
+          if !p.exists() {
+            if validate.output != OutputFormat::Text {
+              reports.push(Report::from_missing_file(file));
+            } else {
+              error!(cli.ci, "EDN file {:?} does not exist", p);
+            }
+
+            continue;
+          }
+
+          let data = match edn::to_vec(&fs::read_to_string(file)?) {
+            Ok(data) => data,
+            Err(e) => {
+              if validate.output != OutputFormat::Text {
+                reports.push(Report {
+                  file: file.to_string(),
+                  valid: false,
+                  errors: vec![ReportError::Other {
+                    reason: e.to_string(),
+                  }],
+                });
+              } else {
+                error!(cli.ci, "Parsing of {:?} failed: {}", p, e);
+              }
+
+              continue;
+            }
+          };
 
-        let mut reader = stdin.lock();
-        let mut data = Vec::new();
-        reader.read_to_end(&mut data)?;
-        if let Ok(json) = std::str::from_utf8(&data) {
           #[cfg(feature = "additional-controls")]
-          let r = validate_json_from_str(&cddl_str, json, None);
+          let c = validate_cbor(&cddl_str, &data, validate, enabled_features.as_deref());
           #[cfg(not(feature = "additional-controls"))]
-          let r = validate_json_from_str(&cddl_str, json);
+          let c = validate_cbor(&cddl_str, &data, validate);
 
-          match r {
+          if validate.output != OutputFormat::Text {
+            reports.push(Report::from_cbor(file, c));
+
+            continue;
+          }
+
+          match c {
             Ok(_) => {
-              info!("Validation from stdin is successful");
+              info!("Validation of {:?} is successful", p);
             }
             Err(e) => {
               error!(
                 cli.ci,
-                "Validation from stdin failed: {}",
+                "Validation of {:?} failed: {}",
+                p,
                 e.to_string().trim_end()
               );
             }
           }
-        } else {
+        }
+      }
+
+      if let Some(files) = &validate.cbor_seq {
+        for file in files {
+          let p = Path::new(file);
+          if !p.exists() {
+            if validate.output != OutputFormat::Text {
+              reports.push(Report::from_missing_file(file));
+            } else {
+              error!(cli.ci, "CBOR sequence file {:?} does not exist", p);
+            }
+
+            continue;
+          }
+          let reader = BufReader::new(File::open(p)?);
+
           #[cfg(feature = "additional-controls")]
-          let c = validate_cbor_from_slice(&cddl_str, &data, enabled_features.as_deref());
+          let c = validate_cbor_seq_from_reader(
+            &cddl_str,
+            reader,
+            validate.rule.as_deref(),
+            enabled_features.as_deref(),
+          );
           #[cfg(not(feature = "additional-controls"))]
-          let c = validate_cbor_from_slice(&cddl_str, &data);
+          let c = validate_cbor_seq_from_reader(&cddl_str, reader, validate.rule.as_deref());
+
+          if validate.output != OutputFormat::Text {
+            reports.push(Report::from_cbor(file, c));
+
+            continue;
+          }
 
           match c {
             Ok(_) => {
-              info!("Validation from stdin is successful");
+              info!("Validation of {:?} is successful", p);
             }
             Err(e) => {
               error!(
                 cli.ci,
-                "Validation from stdin failed: {}",
+                "Validation of {:?} failed: {}",
+                p,
                 e.to_string().trim_end()
               );
             }
           }
         }
       }
+
+      if validate.stdin {
+        let stdin = io::stdin();
+
+        let mut reader = stdin.lock();
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        if let Ok(json) = std::str::from_utf8(&data) {
+          #[cfg(feature = "additional-controls")]
+          let r = match &validate.rule {
+            Some(rule) => validate_json_from_str_with_rule(&cddl_str, json, rule, None),
+            None => validate_json_from_str(&cddl_str, json, None),
+          };
+          #[cfg(not(feature = "additional-controls"))]
+          let r = match &validate.rule {
+            Some(rule) => validate_json_from_str_with_rule(&cddl_str, json, rule),
+            None => validate_json_from_str(&cddl_str, json),
+          };
+
+          if validate.output != OutputFormat::Text {
+            reports.push(Report::from_json("<stdin>", r));
+          } else {
+            match r {
+              Ok(_) => {
+                info!("Validation from stdin is successful");
+              }
+              Err(e) => {
+                error!(
+                  cli.ci,
+                  "Validation from stdin failed: {}",
+                  e.to_string().trim_end()
+                );
+              }
+            }
+          }
+        } else {
+          #[cfg(feature = "additional-controls")]
+          let c = validate_cbor(&cddl_str, &data, validate, enabled_features.as_deref());
+          #[cfg(not(feature = "additional-controls"))]
+          let c = validate_cbor(&cddl_str, &data, validate);
+
+          if validate.output != OutputFormat::Text {
+            reports.push(Report::from_cbor("<stdin>", c));
+          } else {
+            match c {
+              Ok(_) => {
+                info!("Validation from stdin is successful");
+              }
+              Err(e) => {
+                error!(
+                  cli.ci,
+                  "Validation from stdin failed: {}",
+                  e.to_string().trim_end()
+                );
+              }
+            }
+          }
+        }
+      }
+
+      match validate.output {
+        OutputFormat::Text => {}
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&reports)?),
+        OutputFormat::Sarif => println!(
+          "{}",
+          serde_json::to_string_pretty(&sarif_from_reports(&reports))?
+        ),
+        OutputFormat::Junit => println!("{}", junit_from_reports(&reports)),
+      }
+
+      if cli.ci && reports.iter().any(|r| !r.valid) {
+        return Err("validation failed".into());
+      }
+    }
+    Commands::Generate(generate) => {
+      let p = Path::new(&generate.cddl);
+      if !p.exists() {
+        error!(cli.ci, "CDDL document {:?} does not exist", p);
+
+        return Ok(());
+      }
+
+      let cddl_str = fs::read_to_string(&generate.cddl)?;
+
+      if generate.json {
+        match generate_json_from_str(&cddl_str, generate.rule.as_deref(), generate.seed) {
+          Ok(value) => println!("{}", serde_json::to_string_pretty(&value)?),
+          Err(e) => {
+            error!(cli.ci, "Generation of JSON failed: {}", e);
+          }
+        }
+      }
+
+      if let Some(file) = &generate.cbor {
+        match generate_cbor_from_str(&cddl_str, generate.rule.as_deref(), generate.seed) {
+          Ok(value) => {
+            let mut data = Vec::new();
+            ciborium::ser::into_writer(&value, &mut data)?;
+            fs::write(file, data)?;
+
+            info!("Generated CBOR written to {:?}", Path::new(file));
+          }
+          Err(e) => {
+            error!(cli.ci, "Generation of CBOR failed: {}", e);
+          }
+        }
+      }
     }
   }
 
@@ -0,0 +1,1148 @@
+#![cfg(feature = "cbor")]
+#![cfg(not(feature = "lsp"))]
+
+use std::{
+  convert::TryFrom,
+  fmt::{self, Write},
+};
+
+use ciborium::value::Value;
+
+use crate::validator::{
+  control::{
+    decode_base2n, BASE16_ALPHABET, BASE32HEX_ALPHABET, BASE32_ALPHABET, BASE64URL_ALPHABET,
+    BASE64_ALPHABET,
+  },
+  raw::{
+    self, f16_from_f64, is_float_representable, FloatWidth, RawItem, RawKind, RECURSION_LIMIT,
+  },
+};
+
+/// Result of parsing EDN
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Error parsing EDN or printing a CBOR data item as EDN
+#[derive(Debug)]
+pub enum Error {
+  /// Syntax error at the given line and column, both starting at 1
+  Syntax {
+    /// Line of the error
+    line: usize,
+    /// Column of the error, in characters
+    column: usize,
+    /// Reason of the error
+    msg: String,
+  },
+  /// Error decoding a CBOR data item
+  CBOR(raw::Error),
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Error::Syntax { line, column, msg } => write!(
+        f,
+        "error parsing EDN at line {}, column {}: {}",
+        line, column, msg
+      ),
+      Error::CBOR(error) => write!(f, "error decoding cbor: {:?}", error),
+    }
+  }
+}
+
+impl std::error::Error for Error {}
+
+/// Encode the CBOR data item written in extended diagnostic notation (RFC 8949
+/// section 8 and RFC 8610 appendix G). Data items are encoded with preferred
+/// serialization unless encoding indicators such as `_1` or the `_` indicator
+/// of indefinite-length strings, arrays and maps are given
+///
+/// # Example
+///
+/// ```
+/// use cddl::edn;
+///
+/// assert_eq!(edn::to_vec("[_ 1, h'ff']").unwrap(), [0x9f, 0x01, 0x41, 0xff, 0xff]);
+/// assert_eq!(edn::to_vec("1.5_2").unwrap(), [0xfa, 0x3f, 0xc0, 0x00, 0x00]);
+/// ```
+pub fn to_vec(edn: &str) -> Result<Vec<u8>> {
+  let mut parser = Parser {
+    src: edn,
+    pos: 0,
+    depth: 0,
+  };
+
+  let mut out = Vec::new();
+  parser.item(&mut out)?;
+  parser.skip_whitespace()?;
+  if parser.pos < edn.len() {
+    return parser.error("unexpected content following the data item");
+  }
+
+  Ok(out)
+}
+
+/// Decode the CBOR data item written in extended diagnostic notation. See
+/// [`to_vec`]
+pub fn from_str(edn: &str) -> Result<Value> {
+  let cbor = to_vec(edn)?;
+
+  raw::from_slice(&cbor)
+    .map(|(value, _)| value)
+    .map_err(Error::CBOR)
+}
+
+/// Print the given data item in diagnostic notation
+///
+/// # Example
+///
+/// ```
+/// use cddl::edn;
+/// use ciborium::value::Value;
+///
+/// let value = Value::Array(vec![Value::Bytes(vec![0xff]), Value::Tag(1, Box::new(0.into()))]);
+/// assert_eq!(edn::to_string(&value), "[h'ff', 1(0)]");
+/// ```
+pub fn to_string(value: &Value) -> String {
+  let mut edn = String::new();
+  write_item(&mut edn, value, None);
+
+  edn
+}
+
+/// Print the CBOR data item encoded in the given slice in extended diagnostic
+/// notation, including the encoding indicators of arguments and floats that
+/// do not use preferred serialization and of indefinite-length items, so that
+/// [`to_vec`] encodes the result into the same bytes. Bytes following the data
+/// item are ignored
+pub fn to_string_from_slice(cbor: &[u8]) -> Result<String> {
+  let (value, raw) = raw::from_slice(cbor).map_err(Error::CBOR)?;
+
+  let mut edn = String::new();
+  write_item(&mut edn, &value, Some(&raw));
+
+  Ok(edn)
+}
+
+/// Encoding indicator following a data item or the opening bracket of an
+/// array or map
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Indicator {
+  /// `_i`, argument in the initial byte
+  Immediate,
+  /// `_0` to `_3`, argument of 1, 2, 4 or 8 bytes
+  Size(u8),
+  /// `_`, indefinite length
+  Indefinite,
+}
+
+struct Parser<'a> {
+  src: &'a str,
+  pos: usize,
+  depth: usize,
+}
+
+impl<'a> Parser<'a> {
+  fn error<T>(&self, msg: impl Into<String>) -> Result<T> {
+    self.error_at(self.pos, msg)
+  }
+
+  fn error_at<T>(&self, pos: usize, msg: impl Into<String>) -> Result<T> {
+    let before = &self.src[..pos];
+
+    Err(Error::Syntax {
+      line: before.matches('\n').count() + 1,
+      column: before
+        .rsplit('\n')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .count()
+        + 1,
+      msg: msg.into(),
+    })
+  }
+
+  fn peek(&self) -> Option<u8> {
+    self.src.as_bytes().get(self.pos).copied()
+  }
+
+  fn rest(&self) -> &'a str {
+    &self.src[self.pos..]
+  }
+
+  fn eat(&mut self, c: u8) -> bool {
+    if self.peek() == Some(c) {
+      self.pos += 1;
+      return true;
+    }
+
+    false
+  }
+
+  fn expect(&mut self, c: u8) -> Result<()> {
+    self.skip_whitespace()?;
+    if !self.eat(c) {
+      return self.error(format!("expected '{}'", c as char));
+    }
+
+    Ok(())
+  }
+
+  // Skip whitespace along with `/ ... /` and `# ...` comments
+  fn skip_whitespace(&mut self) -> Result<()> {
+    loop {
+      match self.peek() {
+        Some(c) if c.is_ascii_whitespace() => self.pos += 1,
+        Some(b'/') => match self.src[self.pos + 1..].find('/') {
+          Some(end) => self.pos += end + 2,
+          None => return self.error("unterminated comment"),
+        },
+        Some(b'#') => match self.rest().find('\n') {
+          Some(end) => self.pos += end + 1,
+          None => self.pos = self.src.len(),
+        },
+        _ => return Ok(()),
+      }
+    }
+  }
+
+  fn nested(&mut self, f: impl FnOnce(&mut Self) -> Result<()>) -> Result<()> {
+    if self.depth == RECURSION_LIMIT {
+      return self.error("data item is nested too deeply");
+    }
+
+    self.depth += 1;
+    let result = f(self);
+    self.depth -= 1;
+
+    result
+  }
+
+  fn indicator(&mut self) -> Result<Option<Indicator>> {
+    if !self.eat(b'_') {
+      return Ok(None);
+    }
+
+    let bytes = self.rest().as_bytes();
+    let is_delimited = !matches!(bytes.get(1), Some(c) if c.is_ascii_alphanumeric());
+    match bytes.first() {
+      Some(b'i') if is_delimited => {
+        self.pos += 1;
+        Ok(Some(Indicator::Immediate))
+      }
+      Some(c @ b'0'..=b'3') if is_delimited => {
+        self.pos += 1;
+        Ok(Some(Indicator::Size(c - b'0')))
+      }
+      Some(c) if c.is_ascii_alphanumeric() => self.error("invalid encoding indicator"),
+      _ => Ok(Some(Indicator::Indefinite)),
+    }
+  }
+
+  // Append the head of a data item with the given major type and argument,
+  // encoded as required by the encoding indicator if any
+  fn head(
+    &self,
+    out: &mut Vec<u8>,
+    major: u8,
+    argument: u64,
+    indicator: Option<Indicator>,
+    pos: usize,
+  ) -> Result<()> {
+    let info = match indicator {
+      None if argument < 24 => argument as u8,
+      None if argument <= u8::MAX as u64 => 24,
+      None if argument <= u16::MAX as u64 => 25,
+      None if argument <= u32::MAX as u64 => 26,
+      None => 27,
+      Some(Indicator::Immediate) if argument < 24 => argument as u8,
+      Some(Indicator::Size(size)) if size == 3 || argument >> (8 << size) == 0 => 24 + size,
+      Some(Indicator::Indefinite) => {
+        return self.error_at(pos, "data item cannot be of indefinite length")
+      }
+      Some(_) => {
+        return self.error_at(
+          pos,
+          format!("argument {} does not fit the encoding indicator", argument),
+        )
+      }
+    };
+
+    out.push(major << 5 | info);
+    let len = raw::argument_len(info);
+    out.extend_from_slice(&argument.to_be_bytes()[8 - len..]);
+
+    Ok(())
+  }
+
+  fn item(&mut self, out: &mut Vec<u8>) -> Result<()> {
+    self.skip_whitespace()?;
+
+    match self.peek() {
+      None => self.error("expected a data item"),
+      Some(b'[') => self.nested(|p| p.array(out)),
+      Some(b'{') => self.nested(|p| p.map(out)),
+      Some(b'(') => self.nested(|p| p.indefinite_string(out)),
+      Some(b'<') if self.rest().starts_with("<<") => self.nested(|p| p.embedded(out)),
+      Some(b'"') | Some(b'\'') => self.string(out),
+      Some(b'-') | Some(b'0'..=b'9') => self.number(out),
+      Some(c) if c.is_ascii_alphabetic() => {
+        let start = self.pos;
+        let len = self
+          .rest()
+          .find(|c: char| !c.is_ascii_alphanumeric())
+          .unwrap_or_else(|| self.rest().len());
+        let word = &self.src[start..start + len];
+
+        if self.src[start + len..].starts_with('\'') && matches!(word, "h" | "b32" | "h32" | "b64")
+        {
+          return self.string(out);
+        }
+
+        self.pos += len;
+        match word {
+          "false" => out.push(0xf4),
+          "true" => out.push(0xf5),
+          "null" => out.push(0xf6),
+          "undefined" => out.push(0xf7),
+          "simple" => self.simple(out, start)?,
+          "Infinity" => self.float(out, f64::INFINITY, start)?,
+          "NaN" => self.float(out, f64::NAN, start)?,
+          _ => return self.error_at(start, format!("unexpected identifier {}", word)),
+        }
+
+        Ok(())
+      }
+      Some(_) => self.error(format!(
+        "unexpected character '{}'",
+        self.rest().chars().next().unwrap_or_default()
+      )),
+    }
+  }
+
+  fn array(&mut self, out: &mut Vec<u8>) -> Result<()> {
+    let start = self.pos;
+    self.pos += 1;
+    let indicator = self.indicator()?;
+
+    let mut items = Vec::new();
+    let count = self.items(&mut items, b']', |p, items| p.item(items))?;
+
+    match indicator {
+      Some(Indicator::Indefinite) => {
+        out.push(0x9f);
+        out.append(&mut items);
+        out.push(0xff);
+      }
+      indicator => {
+        self.head(out, 4, count, indicator, start)?;
+        out.append(&mut items);
+      }
+    }
+
+    Ok(())
+  }
+
+  fn map(&mut self, out: &mut Vec<u8>) -> Result<()> {
+    let start = self.pos;
+    self.pos += 1;
+    let indicator = self.indicator()?;
+
+    let mut entries = Vec::new();
+    let count = self.items(&mut entries, b'}', |p, entries| {
+      p.item(entries)?;
+      p.expect(b':')?;
+      p.item(entries)
+    })?;
+
+    match indicator {
+      Some(Indicator::Indefinite) => {
+        out.push(0xbf);
+        out.append(&mut entries);
+        out.push(0xff);
+      }
+      indicator => {
+        self.head(out, 5, count, indicator, start)?;
+        out.append(&mut entries);
+      }
+    }
+
+    Ok(())
+  }
+
+  // Parse comma-separated items up to the given closing delimiter, returning
+  // their number
+  fn items(
+    &mut self,
+    out: &mut Vec<u8>,
+    close: u8,
+    mut item: impl FnMut(&mut Self, &mut Vec<u8>) -> Result<()>,
+  ) -> Result<u64> {
+    let mut count = 0;
+
+    self.skip_whitespace()?;
+    if !self.eat(close) {
+      loop {
+        item(self, out)?;
+        count += 1;
+
+        self.skip_whitespace()?;
+        if !self.eat(b',') {
+          break;
+        }
+      }
+
+      self.expect(close)?;
+    }
+
+    Ok(count)
+  }
+
+  // Indefinite-length string written as (_ chunk, chunk, ...)
+  fn indefinite_string(&mut self, out: &mut Vec<u8>) -> Result<()> {
+    let start = self.pos;
+    self.pos += 1;
+    if self.indicator()? != Some(Indicator::Indefinite) {
+      return self.error_at(start, "expected '(_' of an indefinite-length string");
+    }
+
+    let mut chunks = Vec::new();
+    let mut major = None;
+    self.items(&mut chunks, b')', |p, chunks| {
+      p.skip_whitespace()?;
+      let chunk_start = p.pos;
+      let offset = chunks.len();
+      p.item(chunks)?;
+
+      let initial = chunks[offset];
+      if !matches!(initial >> 5, 2 | 3)
+        || initial & 0x1f == 31
+        || *major.get_or_insert(initial >> 5) != initial >> 5
+      {
+        return p.error_at(
+          chunk_start,
+          "chunks of an indefinite-length string must be definite-length strings of the same type",
+        );
+      }
+
+      Ok(())
+    })?;
+
+    match major {
+      Some(major) => {
+        out.push(major << 5 | 31);
+        out.append(&mut chunks);
+        out.push(0xff);
+
+        Ok(())
+      }
+      None => self.error_at(
+        start,
+        "empty indefinite-length strings are written as ''_ or \"\"_",
+      ),
+    }
+  }
+
+  // Byte string holding the encoding of a sequence of data items
+  fn embedded(&mut self, out: &mut Vec<u8>) -> Result<()> {
+    let start = self.pos;
+    self.pos += 2;
+
+    let mut items = Vec::new();
+    self.skip_whitespace()?;
+    if !self.rest().starts_with(">>") {
+      loop {
+        self.item(&mut items)?;
+
+        self.skip_whitespace()?;
+        if !self.eat(b',') {
+          break;
+        }
+      }
+    }
+
+    self.skip_whitespace()?;
+    if !self.rest().starts_with(">>") {
+      return self.error("expected '>>'");
+    }
+    self.pos += 2;
+
+    let indicator = self.indicator()?;
+    self.head(out, 2, items.len() as u64, indicator, start)?;
+    out.append(&mut items);
+
+    Ok(())
+  }
+
+  // Text or byte string, concatenated from juxtaposed string literals
+  fn string(&mut self, out: &mut Vec<u8>) -> Result<()> {
+    let start = self.pos;
+
+    let (is_text, mut content) = self.string_literal()?;
+    loop {
+      let end = self.pos;
+      self.skip_whitespace()?;
+
+      let rest = self.rest();
+      let is_literal = rest.starts_with('"')
+        || rest.starts_with('\'')
+        || ["h'", "b32'", "h32'", "b64'"]
+          .iter()
+          .any(|p| rest.starts_with(p));
+      if !is_literal {
+        self.pos = end;
+        break;
+      }
+
+      let literal_start = self.pos;
+      let (is_literal_text, mut literal) = self.string_literal()?;
+      if is_literal_text != is_text {
+        return self.error_at(
+          literal_start,
+          "text strings and byte strings cannot be concatenated",
+        );
+      }
+      content.append(&mut literal);
+    }
+
+    let major = if is_text { 3 } else { 2 };
+    match self.indicator()? {
+      Some(Indicator::Indefinite) => {
+        out.push(major << 5 | 31);
+        if !content.is_empty() {
+          self.head(out, major, content.len() as u64, None, start)?;
+          out.append(&mut content);
+        }
+        out.push(0xff);
+      }
+      indicator => {
+        self.head(out, major, content.len() as u64, indicator, start)?;
+        out.append(&mut content);
+      }
+    }
+
+    Ok(())
+  }
+
+  // Content of a single string literal and whether it is a text string
+  fn string_literal(&mut self) -> Result<(bool, Vec<u8>)> {
+    let start = self.pos;
+
+    if self.eat(b'"') {
+      return Ok((true, self.quoted(b'"')?.into_bytes()));
+    }
+    if self.eat(b'\'') {
+      return Ok((false, self.quoted(b'\'')?.into_bytes()));
+    }
+
+    let prefix_len = self.rest().find('\'').unwrap_or_default();
+    let prefix = &self.src[start..start + prefix_len];
+    self.pos += prefix_len + 1;
+
+    let content_start = self.pos;
+    let len = match self.rest().find('\'') {
+      Some(len) => len,
+      None => return self.error_at(start, "unterminated string"),
+    };
+    self.pos += len + 1;
+
+    let content = &self.src[content_start..content_start + len];
+    match decode_byte_string(prefix, content) {
+      Some(bytes) => Ok((false, bytes)),
+      None => self.error_at(start, format!("invalid {}'' byte string", prefix)),
+    }
+  }
+
+  // Content of a double- or single-quoted string following the opening quote,
+  // with JSON escape sequences
+  fn quoted(&mut self, quote: u8) -> Result<String> {
+    let start = self.pos - 1;
+    let mut s = String::new();
+
+    let mut chars = self.rest().char_indices();
+    loop {
+      let (idx, c) = match chars.next() {
+        Some(next) => next,
+        None => return self.error_at(start, "unterminated string"),
+      };
+
+      match c {
+        c if c as u32 == quote as u32 => {
+          self.pos += idx + 1;
+          return Ok(s);
+        }
+        '\\' => {
+          let escape_pos = self.pos + idx;
+          let c = match chars.next().map(|(_, c)| c) {
+            Some('"') => '"',
+            Some('\'') => '\'',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+              let mut code = hex4(&mut chars);
+              if let Some(high @ 0xd800..=0xdbff) = code {
+                code = match (chars.next(), chars.next(), hex4(&mut chars)) {
+                  (Some((_, '\\')), Some((_, 'u')), Some(low @ 0xdc00..=0xdfff)) => {
+                    Some(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00))
+                  }
+                  _ => None,
+                };
+              }
+
+              match code.and_then(char::from_u32) {
+                Some(c) => c,
+                None => return self.error_at(escape_pos, "invalid unicode escape"),
+              }
+            }
+            _ => return self.error_at(escape_pos, "invalid escape sequence"),
+          };
+          s.push(c);
+        }
+        c => s.push(c),
+      }
+    }
+  }
+
+  fn simple(&mut self, out: &mut Vec<u8>, start: usize) -> Result<()> {
+    self.expect(b'(')?;
+    self.skip_whitespace()?;
+    let len = self
+      .rest()
+      .find(|c: char| !c.is_ascii_digit())
+      .unwrap_or_else(|| self.rest().len());
+    let value = self.rest()[..len].parse::<u8>();
+    self.pos += len;
+    self.expect(b')')?;
+
+    match value {
+      Ok(value @ 0..=23) => out.push(0xe0 | value),
+      Ok(value @ 32..=255) => out.extend_from_slice(&[0xf8, value]),
+      _ => return self.error_at(start, "simple values must be 0 to 23 or 32 to 255"),
+    }
+
+    Ok(())
+  }
+
+  fn number(&mut self, out: &mut Vec<u8>) -> Result<()> {
+    let start = self.pos;
+    let is_negative = self.eat(b'-');
+
+    if self.rest().starts_with("Infinity") {
+      self.pos += "Infinity".len();
+      let f = if is_negative {
+        f64::NEG_INFINITY
+      } else {
+        f64::INFINITY
+      };
+
+      return self.float(out, f, start);
+    }
+
+    let digits_start = self.pos;
+    let (radix, is_float) = match self.rest().get(..2) {
+      Some("0x") | Some("0X") => {
+        self.pos += 2;
+        let mut is_float = false;
+        while let Some(c) = self.peek() {
+          match c {
+            b'.' | b'p' | b'P' => is_float = true,
+            b'+' | b'-' if matches!(self.src.as_bytes()[self.pos - 1], b'p' | b'P') => (),
+            c if c.is_ascii_hexdigit() => (),
+            _ => break,
+          }
+          self.pos += 1;
+        }
+
+        (16, is_float)
+      }
+      Some("0o") | Some("0O") | Some("0b") | Some("0B") => {
+        let radix = if self.rest()[1..].starts_with(|c| c == 'o' || c == 'O') {
+          8
+        } else {
+          2
+        };
+        self.pos += 2;
+        while matches!(self.peek(), Some(c) if c.is_ascii_digit()) {
+          self.pos += 1;
+        }
+
+        (radix, false)
+      }
+      _ => {
+        let mut is_float = false;
+        while let Some(c) = self.peek() {
+          match c {
+            b'.' | b'e' | b'E' => is_float = true,
+            b'+' | b'-' if matches!(self.src.as_bytes()[self.pos - 1], b'e' | b'E') => (),
+            c if c.is_ascii_digit() => (),
+            _ => break,
+          }
+          self.pos += 1;
+        }
+
+        (10, is_float)
+      }
+    };
+
+    let text = &self.src[digits_start..self.pos];
+    if is_float {
+      let f = if radix == 16 {
+        hexf_parse::parse_hexf64(text, false).ok()
+      } else {
+        text.parse::<f64>().ok()
+      };
+
+      return match f {
+        Some(f) => self.float(out, if is_negative { -f } else { f }, start),
+        None => self.error_at(
+          start,
+          format!("invalid number {}", &self.src[start..self.pos]),
+        ),
+      };
+    }
+
+    let digits = if radix == 10 { text } else { &text[2..] };
+    let magnitude = match u128::from_str_radix(digits, radix) {
+      Ok(magnitude) if !digits.starts_with('+') => magnitude,
+      _ => {
+        return self.error_at(
+          start,
+          format!("invalid number {}", &self.src[start..self.pos]),
+        )
+      }
+    };
+
+    let indicator = self.indicator()?;
+
+    // Tag number followed by the tagged data item
+    if self.eat(b'(') {
+      let tag = match u64::try_from(magnitude) {
+        Ok(tag) if !is_negative => tag,
+        _ => return self.error_at(start, "tag numbers must be unsigned 64-bit integers"),
+      };
+      self.head(out, 6, tag, indicator, start)?;
+
+      return self.nested(|p| {
+        p.item(out)?;
+        p.expect(b')')
+      });
+    }
+
+    let (major, argument) = if is_negative && magnitude > 0 {
+      (1, magnitude - 1)
+    } else {
+      (0, magnitude)
+    };
+
+    match u64::try_from(argument) {
+      Ok(argument) => self.head(out, major, argument, indicator, start),
+      // Integers outside of the range of major types 0 and 1 are bignums
+      Err(_) if indicator.is_none() => {
+        let bytes = argument.to_be_bytes();
+        let content = &bytes[argument.leading_zeros() as usize / 8..];
+        out.push(0xc2 + major);
+        self.head(out, 2, content.len() as u64, None, start)?;
+        out.extend_from_slice(content);
+
+        Ok(())
+      }
+      Err(_) => self.error_at(start, "bignums cannot have an encoding indicator"),
+    }
+  }
+
+  fn float(&mut self, out: &mut Vec<u8>, f: f64, start: usize) -> Result<()> {
+    let width = match self.indicator()? {
+      None => [FloatWidth::Half, FloatWidth::Single]
+        .iter()
+        .copied()
+        .find(|w| is_float_representable(f, *w))
+        .unwrap_or(FloatWidth::Double),
+      Some(Indicator::Size(1)) => FloatWidth::Half,
+      Some(Indicator::Size(2)) => FloatWidth::Single,
+      Some(Indicator::Size(3)) => FloatWidth::Double,
+      Some(_) => return self.error_at(start, "invalid encoding indicator for a float"),
+    };
+
+    if !is_float_representable(f, width) {
+      return self.error_at(
+        start,
+        format!("{} cannot be represented as a {:?} float", f, width).to_lowercase(),
+      );
+    }
+
+    out.push(0xe0 | width.additional_info());
+    match width {
+      FloatWidth::Half => {
+        let bits = if f.is_nan() {
+          0x7e00
+        } else {
+          f16_from_f64(f).unwrap_or_default()
+        };
+        out.extend_from_slice(&bits.to_be_bytes());
+      }
+      FloatWidth::Single => out.extend_from_slice(&(f as f32).to_bits().to_be_bytes()),
+      FloatWidth::Double => out.extend_from_slice(&f.to_bits().to_be_bytes()),
+    }
+
+    Ok(())
+  }
+}
+
+fn hex4(chars: &mut std::str::CharIndices) -> Option<u32> {
+  let mut code = 0;
+  for _ in 0..4 {
+    code = code * 16 + chars.next()?.1.to_digit(16)?;
+  }
+
+  Some(code)
+}
+
+// Bytes of the content of a byte string literal with the given prefix, which
+// may be interspersed with whitespace, and with comments if base16 encoded.
+// Base32 and base64 content may be padded, and base64 content may use either
+// the classic or the URL-safe alphabet
+fn decode_byte_string(prefix: &str, content: &str) -> Option<Vec<u8>> {
+  let mut text = String::new();
+  let mut chars = content.chars();
+  while let Some(c) = chars.next() {
+    match c {
+      '/' if prefix == "h" => {
+        chars.find(|c| *c == '/')?;
+      }
+      '#' if prefix == "h" => {
+        chars.find(|c| *c == '\n');
+      }
+      c if c.is_whitespace() => (),
+      c => text.push(c),
+    }
+  }
+
+  let decoded = match prefix {
+    "h" => decode_base2n(&text.to_ascii_uppercase(), BASE16_ALPHABET, 4, false, false),
+    "b32" | "h32" => {
+      let alphabet = if prefix == "b32" {
+        BASE32_ALPHABET
+      } else {
+        BASE32HEX_ALPHABET
+      };
+      let text = text.trim_end_matches('=').to_ascii_uppercase();
+
+      decode_base2n(&text, alphabet, 5, false, false)
+    }
+    _ => {
+      let text = text.trim_end_matches('=');
+      let alphabet = if text.contains(|c| c == '-' || c == '_') {
+        BASE64URL_ALPHABET
+      } else {
+        BASE64_ALPHABET
+      };
+
+      decode_base2n(text, alphabet, 6, false, false)
+    }
+  };
+
+  decoded.ok()
+}
+
+fn write_item(edn: &mut String, value: &Value, raw: Option<&RawItem>) {
+  // Encoding indicator of an argument or float that does not use preferred
+  // serialization
+  let indicator = match raw {
+    Some(raw) if !raw.is_preferred() && raw.info >= 24 && !raw.is_indefinite() => {
+      format!("_{}", raw.info - 24)
+    }
+    _ => String::new(),
+  };
+  let is_indefinite = matches!(raw, Some(raw) if raw.is_indefinite());
+
+  match value {
+    Value::Integer(i) => match raw.map(|raw| (&raw.kind, raw.argument)) {
+      // Bignums that fit in 128 bits are decoded as integers
+      Some((RawKind::Tag(_), Some(tag))) => {
+        let i = i128::from(*i);
+        let n = if tag == 3 { !i } else { i } as u128;
+        let bytes = n.to_be_bytes();
+        let content = &bytes[(n.leading_zeros() as usize / 8).min(15)..];
+        let _ = write!(edn, "{}(h'{}')", tag, base16::encode_lower(content));
+      }
+      _ => {
+        let _ = write!(edn, "{}{}", i128::from(*i), indicator);
+      }
+    },
+    Value::Bytes(b) => {
+      write_string(edn, b, raw, &indicator, |edn, b| {
+        let _ = write!(edn, "h'{}'", base16::encode_lower(b));
+      });
+    }
+    Value::Text(t) => {
+      write_string(edn, t.as_bytes(), raw, &indicator, |edn, t| {
+        let t = String::from_utf8_lossy(t);
+        edn.push_str(&serde_json::to_string(&t).unwrap_or_default());
+      });
+    }
+    Value::Float(f) => {
+      if f.is_nan() {
+        edn.push_str("NaN");
+      } else if f.is_infinite() {
+        edn.push_str(if *f > 0.0 { "Infinity" } else { "-Infinity" });
+      } else {
+        let _ = write!(edn, "{:?}", f);
+      }
+      edn.push_str(&indicator);
+    }
+    Value::Bool(_) | Value::Null => {
+      let simple = match (raw.map(|raw| &raw.kind), value) {
+        (Some(RawKind::Simple(simple)), _) => *simple,
+        (_, Value::Bool(false)) => 20,
+        (_, Value::Bool(true)) => 21,
+        _ => 22,
+      };
+
+      match simple {
+        20 => edn.push_str("false"),
+        21 => edn.push_str("true"),
+        22 => edn.push_str("null"),
+        23 => edn.push_str("undefined"),
+        simple => {
+          let _ = write!(edn, "simple({})", simple);
+        }
+      }
+    }
+    Value::Tag(tag, item) => {
+      let _ = write!(edn, "{}{}(", tag, indicator);
+      write_item(edn, item, raw.and_then(|raw| raw.tagged_item()));
+      edn.push(')');
+    }
+    Value::Array(items) => {
+      edn.push('[');
+      write_bracket_indicator(edn, is_indefinite, &indicator, items.is_empty());
+      for (idx, item) in items.iter().enumerate() {
+        if idx > 0 {
+          edn.push_str(", ");
+        }
+        write_item(edn, item, raw.and_then(|raw| raw.array_item(idx)));
+      }
+      edn.push(']');
+    }
+    Value::Map(entries) => {
+      edn.push('{');
+      write_bracket_indicator(edn, is_indefinite, &indicator, entries.is_empty());
+      for (idx, (k, v)) in entries.iter().enumerate() {
+        if idx > 0 {
+          edn.push_str(", ");
+        }
+        let entry = raw.and_then(|raw| raw.map_entry(idx));
+        write_item(edn, k, entry.map(|(k, _)| k));
+        edn.push_str(": ");
+        write_item(edn, v, entry.map(|(_, v)| v));
+      }
+      edn.push('}');
+    }
+    _ => edn.push_str("undefined"),
+  }
+}
+
+// Encoding indicator following the opening bracket of an array or map
+fn write_bracket_indicator(edn: &mut String, is_indefinite: bool, indicator: &str, is_empty: bool) {
+  if is_indefinite {
+    edn.push('_');
+  } else {
+    edn.push_str(indicator);
+  }
+
+  if (is_indefinite || !indicator.is_empty()) && !is_empty {
+    edn.push(' ');
+  }
+}
+
+// Text or byte string, written as its chunks if of indefinite length
+fn write_string(
+  edn: &mut String,
+  content: &[u8],
+  raw: Option<&RawItem>,
+  indicator: &str,
+  write_literal: impl Fn(&mut String, &[u8]),
+) {
+  let chunks = match raw.map(|raw| (&raw.kind, raw.is_indefinite())) {
+    Some((RawKind::Bytes(chunks), true)) | Some((RawKind::Text(chunks), true)) => chunks,
+    _ => {
+      write_literal(edn, content);
+      edn.push_str(indicator);
+      return;
+    }
+  };
+
+  if chunks.is_empty() {
+    write_literal(edn, content);
+    edn.push('_');
+    return;
+  }
+
+  edn.push_str("(_ ");
+  let mut offset = 0;
+  for (idx, chunk) in chunks.iter().enumerate() {
+    if idx > 0 {
+      edn.push_str(", ");
+    }
+
+    let len = chunk.argument.unwrap_or_default() as usize;
+    write_literal(edn, &content[offset..offset + len]);
+    if !chunk.is_preferred() {
+      let _ = write!(edn, "_{}", chunk.info - 24);
+    }
+    offset += len;
+  }
+  edn.push(')');
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn hex(edn: &str) -> String {
+    match to_vec(edn) {
+      Ok(cbor) => base16::encode_lower(&cbor),
+      Err(e) => panic!("{}: {}", edn, e),
+    }
+  }
+
+  #[test]
+  fn parse_rfc8949_examples() {
+    for (edn, cbor) in [
+      ("0", "00"),
+      ("23", "17"),
+      ("24", "1818"),
+      ("1000000", "1a000f4240"),
+      ("18446744073709551615", "1bffffffffffffffff"),
+      ("18446744073709551616", "c249010000000000000000"),
+      ("-18446744073709551616", "3bffffffffffffffff"),
+      ("-18446744073709551617", "c349010000000000000000"),
+      ("-1000", "3903e7"),
+      ("0.0", "f90000"),
+      ("-0.0", "f98000"),
+      ("1.5", "f93e00"),
+      ("65504.0", "f97bff"),
+      ("100000.0", "fa47c35000"),
+      ("1.1", "fb3ff199999999999a"),
+      ("1.0e+300", "fb7e37e43c8800759c"),
+      ("5.960464477539063e-8", "f90001"),
+      ("Infinity", "f97c00"),
+      ("NaN", "f97e00"),
+      ("-Infinity", "f9fc00"),
+      ("false", "f4"),
+      ("undefined", "f7"),
+      ("simple(16)", "f0"),
+      ("simple(255)", "f8ff"),
+      (
+        "0(\"2013-03-21T20:04:00Z\")",
+        "c074323031332d30332d32315432303a30343a30305a",
+      ),
+      ("1(1363896240)", "c11a514b67b0"),
+      ("23(h'01020304')", "d74401020304"),
+      ("24(h'6449455446')", "d818456449455446"),
+      ("h''", "40"),
+      ("\"\\u00fc\"", "62c3bc"),
+      ("\"\\ud800\\udd51\"", "64f0908591"),
+      ("[1, [2, 3], [4, 5]]", "8301820203820405"),
+      ("{1: 2, 3: 4}", "a201020304"),
+      ("{\"a\": 1, \"b\": [2, 3]}", "a26161016162820203"),
+      ("(_ h'0102', h'030405')", "5f42010243030405ff"),
+      ("(_ \"strea\", \"ming\")", "7f657374726561646d696e67ff"),
+      ("[_ ]", "9fff"),
+      ("[_ 1, [2, 3], [_ 4, 5]]", "9f018202039f0405ffff"),
+      ("{_ \"a\": 1, \"b\": [_ 2, 3]}", "bf61610161629f0203ffff"),
+    ] {
+      assert_eq!(hex(edn), cbor, "{}", edn);
+    }
+  }
+
+  #[test]
+  fn parse_extended_notation() {
+    for (edn, cbor) in [
+      // Encoding indicators
+      ("1_0", "1801"),
+      ("1_3", "1b0000000000000001"),
+      ("1.5_2", "fa3fc00000"),
+      ("1.5_3", "fb3ff8000000000000"),
+      ("\"a\"_1", "790001 61"),
+      ("[_0 1]", "980101"),
+      ("{_1 }", "b90000"),
+      ("24_0(1)", "d81801"),
+      ("''_", "5fff"),
+      ("\"\"_", "7fff"),
+      // Application-oriented literals, concatenation and comments
+      ("h'01 02 / two / 03 # three\n'", "43010203"),
+      ("b64'AQID'", "43010203"),
+      ("b64'-_8'", "42fbff"),
+      ("b32'MZXW6==='", "43666f6f"),
+      ("h32'CPNMU==='", "43666f6f"),
+      ("'Hello ' h'776f726c64'", "4b48656c6c6f20776f726c64"),
+      ("\"a\" / comment / \"b\"", "626162"),
+      ("'it' 's'", "43697473"),
+      ("<<1, \"a\">>", "43016161"),
+      ("0x10", "10"),
+      ("-0b11", "22"),
+      ("0o17", "0f"),
+      ("0x1.8p1", "f94200"),
+    ] {
+      assert_eq!(hex(edn), cbor.replace(' ', ""), "{}", edn);
+    }
+  }
+
+  #[test]
+  fn parse_errors() {
+    for (edn, line, column) in [
+      ("[1, 2", 1, 6),
+      ("{1: 2,\n  3}", 2, 4),
+      ("\"a\" h'01'", 1, 5),
+      ("1.1_1", 1, 1),
+      ("256_0", 1, 1),
+      ("(_ 1)", 1, 4),
+      ("simple(24)", 1, 1),
+      ("h'0'", 1, 1),
+      ("foo", 1, 1),
+      ("1 2", 1, 3),
+    ] {
+      match to_vec(edn) {
+        Err(Error::Syntax {
+          line: l, column: c, ..
+        }) => assert_eq!((l, c), (line, column), "{}", edn),
+        r => panic!("{}: expected a syntax error, got {:?}", edn, r),
+      }
+    }
+  }
+
+  #[test]
+  fn print_edn() -> Result<()> {
+    assert_eq!(
+      to_string(&from_str(
+        r#"{"a": [1, -2.5, h'00ff', true, null], 1(2): "\n"}"#
+      )?),
+      r#"{"a": [1, -2.5, h'00ff', true, null], 1(2): "\n"}"#
+    );
+
+    for edn in [
+      "[_ 1, [2, 3], {_ \"a\": (_ h'01', h'0203'), \"b\": ''_}]",
+      "[1_0, 1.5_3, \"a\"_1, [_1 ], undefined, simple(16), 24_0(h'ff')]",
+      "[18446744073709551616, 2(h'010000000000000000000000000000000000')]",
+      "[Infinity, -Infinity, NaN, 1.0e300, -0.0]",
+    ] {
+      let cbor = to_vec(edn)?;
+      let printed = to_string_from_slice(&cbor)?;
+      assert_eq!(to_vec(&printed)?, cbor, "{} printed as {}", edn, printed);
+    }
+
+    assert_eq!(
+      to_string_from_slice(&to_vec("[_ 1_0, (_ \"a\", \"b\")]")?)?,
+      "[_ 1_0, (_ \"a\", \"b\")]"
+    );
+
+    Ok(())
+  }
+}
@@ -0,0 +1,931 @@
+#![cfg(feature = "std")]
+#![cfg(feature = "cbor")]
+#![cfg(not(feature = "lsp"))]
+
+use super::*;
+use crate::{
+  ast::{Group, GroupEntry, MemberKey, Operator, RangeCtlOp, Type, Type1, Type2, CDDL},
+  token::{self, ControlOperator},
+  validator::{
+    cbor::{self as cbor_validator, token_value_into_cbor_value, CBORValidator},
+    Validator,
+  },
+};
+
+#[cfg(feature = "additional-controls")]
+use crate::validator::control::{cat_operation, plus_operation};
+
+use chrono::{SecondsFormat, TimeZone, Utc};
+use ciborium::value::{Integer, Value};
+use std::convert::TryFrom;
+
+/// CBOR members generated from a group, either the entries of a map or the
+/// items of an array
+enum Members {
+  Map(Vec<(Value, Value)>),
+  Array(Vec<Value>),
+}
+
+/// CBOR instance generator type
+pub struct CBORGenerator<'a> {
+  cddl: &'a CDDL<'a>,
+  rng: StdRng,
+  depth: usize,
+}
+
+impl<'a> CBORGenerator<'a> {
+  /// New CBORGenerator from CDDL AST. Generated values are deterministic for a
+  /// given seed
+  pub fn new(cddl: &'a CDDL<'a>, seed: Option<u64>) -> Self {
+    CBORGenerator {
+      cddl,
+      rng: rng_from_seed(seed),
+      depth: 0,
+    }
+  }
+
+  /// Generate a CBOR value from the root type rule
+  pub fn generate(&mut self) -> Result<Value> {
+    let rule = root_rule(self.cddl)?;
+
+    self.generate_valid(rule, None)
+  }
+
+  /// Generate a CBOR value from the type rule with the given name
+  pub fn generate_from_rule(&mut self, name: &str) -> Result<Value> {
+    let rule = rule_from_name(self.cddl, name)?;
+
+    self.generate_valid(rule, Some(name))
+  }
+
+  /// Generate values from the given rule until one passes validation against
+  /// it
+  fn generate_valid(&mut self, rule: &'a Rule<'a>, name: Option<&str>) -> Result<Value> {
+    let mut error = None;
+    for _ in 0..MAX_ATTEMPTS {
+      let value = match self.generate_rule(rule) {
+        Ok(value) => value,
+        Err(e) => {
+          error = Some(e);
+          continue;
+        }
+      };
+
+      #[cfg(feature = "additional-controls")]
+      let mut cv = CBORValidator::new(self.cddl, value.clone(), None);
+      #[cfg(not(feature = "additional-controls"))]
+      let mut cv = CBORValidator::new(self.cddl, value.clone());
+
+      let r: cbor_validator::Result<std::io::Error> = match name {
+        Some(name) => cv.validate_rule(name),
+        None => cv.validate(),
+      };
+      match r {
+        Ok(()) => return Ok(value),
+        Err(e) => error = Some(Error::Validation(e.to_string().trim_end().to_string())),
+      }
+    }
+
+    Err(error.unwrap_or_else(|| Error::Validation(format!("rule {}", rule.name()))))
+  }
+
+  fn generate_rule(&mut self, rule: &'a Rule<'a>) -> Result<Value> {
+    self.depth = 0;
+
+    match rule {
+      Rule::Type { rule, .. } => self.generate_typename(&rule.name, None, &Rc::default()),
+      Rule::Group { rule, .. } => Err(Error::Unsupported(format!(
+        "group rule {} cannot be represented as a CBOR data item",
+        rule.name
+      ))),
+    }
+  }
+
+  fn generate_type(&mut self, t: &Type<'a>, bindings: &Rc<Bindings<'a>>) -> Result<Value> {
+    if t.type_choices.is_empty() {
+      return Err(Error::Unsupported(format!("empty type {}", t)));
+    }
+
+    let idx = self.rng.gen_range(0..t.type_choices.len());
+    self.generate_type1(&t.type_choices[idx].type1, bindings)
+  }
+
+  fn generate_type1(&mut self, t1: &Type1<'a>, bindings: &Rc<Bindings<'a>>) -> Result<Value> {
+    self.depth += 1;
+    if self.depth > MAX_DEPTH {
+      return Err(Error::Unsupported(
+        "maximum nesting depth exceeded".to_string(),
+      ));
+    }
+
+    let value = match &t1.operator {
+      Some(Operator {
+        operator: RangeCtlOp::RangeOp { is_inclusive, .. },
+        type2: upper,
+        ..
+      }) => self.generate_range(&t1.type2, upper, *is_inclusive, bindings),
+      Some(Operator {
+        operator: RangeCtlOp::CtlOp { ctrl, .. },
+        type2: controller,
+        ..
+      }) => self.generate_control(&t1.type2, *ctrl, controller, bindings),
+      None => self.generate_type2(&t1.type2, bindings),
+    };
+
+    self.depth -= 1;
+
+    value
+  }
+
+  fn generate_type2(&mut self, t2: &Type2<'a>, bindings: &Rc<Bindings<'a>>) -> Result<Value> {
+    match t2 {
+      Type2::IntValue { value, .. } => int_value(*value as i128),
+      Type2::UintValue { value, .. } => int_value(*value as i128),
+      Type2::FloatValue { value, .. } => Ok(Value::Float(*value)),
+      Type2::TextValue { value, .. } => Ok(Value::Text(value.to_string())),
+      Type2::UTF8ByteString { value, .. } => Ok(Value::Bytes(value.to_vec())),
+      Type2::B16ByteString { value, .. } => base16::decode(value)
+        .map(Value::Bytes)
+        .map_err(|e| Error::Unsupported(format!("invalid base16 byte string {}: {}", t2, e))),
+      Type2::B64ByteString { value, .. } => base64::decode_config(value, base64::URL_SAFE)
+        .map(Value::Bytes)
+        .map_err(|e| Error::Unsupported(format!("invalid base64 byte string {}: {}", t2, e))),
+      Type2::Typename {
+        ident,
+        generic_args,
+        ..
+      } => self.generate_typename(ident, generic_args.as_ref(), bindings),
+      Type2::ParenthesizedType { pt, .. } => self.generate_type(pt, bindings),
+      Type2::Map { group, .. } => {
+        let mut members = Members::Map(Vec::new());
+        self.generate_group(group, bindings, &mut members)?;
+        Ok(members.into())
+      }
+      Type2::Array { group, .. } => {
+        let mut members = Members::Array(Vec::new());
+        self.generate_group(group, bindings, &mut members)?;
+        Ok(members.into())
+      }
+      Type2::ChoiceFromInlineGroup { group, .. } => {
+        self.generate_choice_from_group(group, bindings)
+      }
+      Type2::ChoiceFromGroup {
+        ident,
+        generic_args,
+        ..
+      } => {
+        let rules = group_rules_from_ident(self.cddl, ident);
+        if rules.is_empty() {
+          return Err(Error::Unsupported(format!("no group rule named {}", ident)));
+        }
+
+        let rule = rules[self.rng.gen_range(0..rules.len())];
+        let bindings = Bindings::new(
+          ident,
+          rule.generic_params.as_ref(),
+          generic_args.as_ref(),
+          bindings,
+        )?;
+
+        self.generate_choice_from_group_entry(&rule.entry, &bindings)
+      }
+      Type2::TaggedData { tag, t, .. } => {
+        let value = self.generate_type(t, bindings)?;
+
+        Ok(Value::Tag(tag.unwrap_or(0) as u64, Box::new(value)))
+      }
+      Type2::DataMajorType { mt, constraint, .. } => match (mt, constraint) {
+        (0, Some(c)) => int_value(*c as i128),
+        (0, None) => int_value(int_in_bounds(&mut self.rng, (Some(0), None))?),
+        (1, Some(c)) => int_value(-(*c as i128)),
+        (1, None) => int_value(int_in_bounds(&mut self.rng, (None, Some(-1)))?),
+        (2, Some(c)) => Ok(Value::Bytes(bytes_of_len(&mut self.rng, *c))),
+        (2, None) => Ok(Value::Bytes(bytes(&mut self.rng))),
+        (3, Some(c)) => Ok(Value::Text(text_of_len(&mut self.rng, *c))),
+        (3, None) => Ok(Value::Text(text(&mut self.rng))),
+        (4, c) => {
+          let len = c.unwrap_or_else(|| self.rng.gen_range(0..=2));
+          let items = (0..len)
+            .map(|_| self.generate_any())
+            .collect::<Result<Vec<_>>>()?;
+
+          Ok(Value::Array(items))
+        }
+        (5, c) => {
+          let len = c.unwrap_or_else(|| self.rng.gen_range(0..=2));
+          let entries = (0..len)
+            .map(|idx| Ok((int_value(idx as i128)?, self.generate_any()?)))
+            .collect::<Result<Vec<_>>>()?;
+
+          Ok(Value::Map(entries))
+        }
+        (6, c) => {
+          let tag = c.unwrap_or_else(|| self.rng.gen_range(0..=NUMERIC_BOUND as usize));
+          let value = self.generate_any()?;
+
+          Ok(Value::Tag(tag as u64, Box::new(value)))
+        }
+        (7, Some(20)) => Ok(Value::Bool(false)),
+        (7, Some(21)) => Ok(Value::Bool(true)),
+        (7, Some(22)) => Ok(Value::Null),
+        (7, None) => match self.rng.gen_range(0..3) {
+          0 => Ok(Value::Bool(self.rng.gen())),
+          1 => Ok(Value::Null),
+          _ => Ok(Value::Float(float_in_bounds(
+            &mut self.rng,
+            -(NUMERIC_BOUND as f64),
+            NUMERIC_BOUND as f64,
+          )?)),
+        },
+        _ => Err(Error::Unsupported(format!(
+          "data type {} is not supported",
+          t2
+        ))),
+      },
+      Type2::Any { .. } => self.generate_any(),
+      Type2::Unwrap { .. } => Err(Error::Unsupported(format!(
+        "unwrapped group {} cannot be represented as a single CBOR data item",
+        t2
+      ))),
+    }
+  }
+
+  fn generate_typename(
+    &mut self,
+    ident: &Identifier<'a>,
+    generic_args: Option<&GenericArgs<'a>>,
+    bindings: &Rc<Bindings<'a>>,
+  ) -> Result<Value> {
+    if let Some((arg, arg_bindings)) = bindings.get(ident) {
+      let (arg, arg_bindings) = (arg.clone(), arg_bindings.clone());
+      return self.generate_type1(&arg, &arg_bindings);
+    }
+
+    let rules = type_rules_from_ident(self.cddl, ident);
+    if let Some((rule, tc)) = type_choice_from_rules(&mut self.rng, &rules) {
+      let bindings = Bindings::new(ident, rule.generic_params.as_ref(), generic_args, bindings)?;
+
+      return self.generate_type1(&tc.type1, &bindings);
+    }
+
+    if !group_rules_from_ident(self.cddl, ident).is_empty() {
+      return Err(Error::Unsupported(format!(
+        "group {} cannot be used as a type",
+        ident
+      )));
+    }
+
+    if ident.socket.is_some() {
+      return Err(Error::Unsupported(format!(
+        "socket {} has no plugs defined",
+        ident
+      )));
+    }
+
+    self.generate_prelude(ident)
+  }
+
+  fn generate_prelude(&mut self, ident: &Identifier<'a>) -> Result<Value> {
+    let rng = &mut self.rng;
+
+    match token::lookup_ident(&ident.ident) {
+      Token::FALSE => Ok(Value::Bool(false)),
+      Token::TRUE => Ok(Value::Bool(true)),
+      Token::BOOL => Ok(Value::Bool(rng.gen())),
+      Token::NIL | Token::NULL => Ok(Value::Null),
+      Token::UINT | Token::UNSIGNED => int_value(int_in_bounds(rng, (Some(0), None))?),
+      Token::NINT => int_value(int_in_bounds(rng, (None, Some(-1)))?),
+      Token::INT | Token::INTEGER => int_value(int_in_bounds(rng, (None, None))?),
+      // Quarters below 256 have at most 10 significant bits and can therefore
+      // be encoded as half-precision floats
+      Token::FLOAT16 => Ok(Value::Float(
+        (float_in_bounds(rng, -255.0, 255.0)? * 4.0).round() / 4.0,
+      )),
+      Token::FLOAT32 | Token::FLOAT1632 => Ok(Value::Float(
+        (float_in_bounds(rng, -(NUMERIC_BOUND as f64), NUMERIC_BOUND as f64)? * 4.0).round() / 4.0,
+      )),
+      Token::FLOAT | Token::FLOAT64 | Token::FLOAT3264 => Ok(Value::Float(float_in_bounds(
+        rng,
+        -(NUMERIC_BOUND as f64),
+        NUMERIC_BOUND as f64,
+      )?)),
+      Token::NUMBER => {
+        if rng.gen() {
+          int_value(int_in_bounds(rng, (None, None))?)
+        } else {
+          Ok(Value::Float(float_in_bounds(
+            rng,
+            -(NUMERIC_BOUND as f64),
+            NUMERIC_BOUND as f64,
+          )?))
+        }
+      }
+      Token::TSTR | Token::TEXT => Ok(Value::Text(text(rng))),
+      Token::BSTR | Token::BYTES => Ok(Value::Bytes(bytes(rng))),
+      Token::TDATE => {
+        let secs = rng.gen_range(0..=4_102_444_800);
+        let date = Utc
+          .timestamp_opt(secs, 0)
+          .single()
+          .ok_or_else(|| Error::Unsupported(format!("invalid timestamp {}", secs)))?;
+
+        Ok(tagged(
+          0,
+          Value::Text(date.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        ))
+      }
+      Token::TIME => Ok(tagged(1, int_value(rng.gen_range(0..=4_102_444_800))?)),
+      Token::BIGUINT => Ok(tagged(2, Value::Bytes(bignum_bytes(rng)))),
+      Token::BIGNINT => Ok(tagged(3, Value::Bytes(bignum_bytes(rng)))),
+      Token::BIGINT => Ok(tagged(
+        rng.gen_range(2..=3),
+        Value::Bytes(bignum_bytes(rng)),
+      )),
+      Token::DECFRAC => Ok(tagged(4, exponent_mantissa(rng)?)),
+      Token::BIGFLOAT => Ok(tagged(5, exponent_mantissa(rng)?)),
+      Token::EB64URL => Ok(tagged(21, self.generate_any()?)),
+      Token::EB64LEGACY => Ok(tagged(22, self.generate_any()?)),
+      Token::EB16 => Ok(tagged(23, self.generate_any()?)),
+      Token::ENCODEDCBOR => {
+        let value = self.generate_any()?;
+
+        Ok(tagged(24, Value::Bytes(encode(&value)?)))
+      }
+      Token::URI => Ok(tagged(
+        32,
+        Value::Text(format!("https://example.com/{}", text(rng))),
+      )),
+      Token::B64URL => Ok(tagged(
+        33,
+        Value::Text(base64_url::encode(text(rng).as_bytes())),
+      )),
+      Token::B64LEGACY => Ok(tagged(
+        34,
+        Value::Text(base64::encode(text(rng).as_bytes())),
+      )),
+      Token::REGEXP => Ok(tagged(35, Value::Text(text(rng)))),
+      Token::MIMEMESSAGE => Ok(tagged(36, Value::Text(text(rng)))),
+      Token::CBORANY => Ok(tagged(55799, self.generate_any()?)),
+      Token::ANY => self.generate_any(),
+      t if t.in_standard_prelude().is_some() => Err(Error::Unsupported(format!(
+        "prelude type {} is not supported",
+        ident
+      ))),
+      _ => Err(Error::MissingRule(format!("no rule named {}", ident))),
+    }
+  }
+
+  fn generate_any(&mut self) -> Result<Value> {
+    match self.rng.gen_range(0..5) {
+      0 => Ok(Value::Bool(self.rng.gen())),
+      1 => int_value(int_in_bounds(&mut self.rng, (None, None))?),
+      2 => Ok(Value::Text(text(&mut self.rng))),
+      3 => Ok(Value::Bytes(bytes(&mut self.rng))),
+      _ => Ok(Value::Null),
+    }
+  }
+
+  fn generate_range(
+    &mut self,
+    lower: &Type2<'a>,
+    upper: &Type2<'a>,
+    is_inclusive: bool,
+    bindings: &Rc<Bindings<'a>>,
+  ) -> Result<Value> {
+    let lower_value = literal_from_type2(self.cddl, lower, bindings);
+    let upper_value = literal_from_type2(self.cddl, upper, bindings);
+
+    match (lower_value, upper_value) {
+      (Some(Literal::Int(l)), Some(Literal::Int(u))) => {
+        let u = if is_inclusive { u } else { u - 1 };
+        let i = int_in_bounds(&mut self.rng, (Some(l), Some(u)))?;
+        int_value(i)
+      }
+      (Some(Literal::Float(l)), Some(Literal::Float(u))) => {
+        let mut f = float_in_bounds(&mut self.rng, l, u)?;
+        if !is_inclusive && (f == u || f == l) {
+          f = (l + u) / 2.0;
+        }
+
+        Ok(Value::Float(f))
+      }
+      _ => Err(Error::Unsupported(format!(
+        "range {}{}{} has no numeric bounds",
+        lower,
+        if is_inclusive { ".." } else { "..." },
+        upper
+      ))),
+    }
+  }
+
+  fn generate_control(
+    &mut self,
+    target: &Type2<'a>,
+    ctrl: ControlOperator,
+    controller: &Type2<'a>,
+    bindings: &Rc<Bindings<'a>>,
+  ) -> Result<Value> {
+    match ctrl {
+      ControlOperator::SIZE => match target_token(self.cddl, target) {
+        Some(Token::TSTR) | Some(Token::TEXT) => {
+          let (lower, upper) = size_bounds(self.cddl, controller, bindings)?;
+          let len = self.rng.gen_range(lower..=upper);
+
+          Ok(Value::Text(text_of_len(&mut self.rng, len)))
+        }
+        Some(Token::BSTR) | Some(Token::BYTES) => {
+          let (lower, upper) = size_bounds(self.cddl, controller, bindings)?;
+          let len = self.rng.gen_range(lower..=upper);
+
+          Ok(Value::Bytes(bytes_of_len(&mut self.rng, len)))
+        }
+        Some(Token::UINT) | Some(Token::UNSIGNED) => {
+          let (lower, upper) = size_bounds(self.cddl, controller, bindings)?;
+          let size = self.rng.gen_range(lower..=upper);
+          let max = if size >= 8 {
+            u64::MAX
+          } else {
+            (1u64 << (size * 8)) - 1
+          };
+
+          Ok(Value::Integer(self.rng.gen_range(0..=max).into()))
+        }
+        _ => Err(Error::Unsupported(format!(
+          "{} control on {} is not supported",
+          ctrl, target
+        ))),
+      },
+      ControlOperator::BITS => {
+        let bit = match self.generate_type2(controller, bindings)? {
+          Value::Integer(i) => u32::try_from(i128::from(i)).ok(),
+          _ => None,
+        }
+        .ok_or_else(|| {
+          Error::Unsupported(format!(
+            "{} control requires an unsigned integer controller, got {}",
+            ctrl, controller
+          ))
+        })?;
+
+        match target_token(self.cddl, target) {
+          Some(Token::BSTR) | Some(Token::BYTES) => {
+            let mut b = vec![0u8; (bit / 8) as usize + 1];
+            b[(bit / 8) as usize] = 1 << (bit % 8);
+
+            Ok(Value::Bytes(b))
+          }
+          Some(Token::UINT) | Some(Token::UNSIGNED) if bit < 32 => {
+            Ok(Value::Integer((1u64 << bit).into()))
+          }
+          _ => Err(Error::Unsupported(format!(
+            "{} control on {} with bit {} is not supported",
+            ctrl, target, bit
+          ))),
+        }
+      }
+      ControlOperator::CBOR => {
+        let value = self.generate_type2(controller, bindings)?;
+
+        Ok(Value::Bytes(encode(&value)?))
+      }
+      // The items of the array generated from the controller make up the
+      // sequence
+      ControlOperator::CBORSEQ => match self.generate_type2(controller, bindings)? {
+        Value::Array(items) => {
+          let mut b = Vec::new();
+          for item in items.iter() {
+            b.extend(encode(item)?);
+          }
+
+          Ok(Value::Bytes(b))
+        }
+        _ => Err(Error::Unsupported(format!(
+          "{} control requires an array controller, got {}",
+          ctrl, controller
+        ))),
+      },
+      ControlOperator::REGEXP | ControlOperator::PCRE => {
+        match literal_from_type2(self.cddl, controller, bindings) {
+          Some(Literal::Text(pattern)) => {
+            Ok(Value::Text(text_from_regex(&mut self.rng, &pattern)?))
+          }
+          _ => Err(Error::Unsupported(format!(
+            "{} control requires a text string controller, got {}",
+            ctrl, controller
+          ))),
+        }
+      }
+      ControlOperator::LT | ControlOperator::LE | ControlOperator::GT | ControlOperator::GE => {
+        match number_from_control(&mut self.rng, self.cddl, target, ctrl, controller, bindings)? {
+          Literal::Int(i) => int_value(i),
+          Literal::Float(f) => Ok(Value::Float(f)),
+          Literal::Text(t) => Ok(Value::Text(t)),
+        }
+      }
+      ControlOperator::EQ => self.generate_type2(controller, bindings),
+      ControlOperator::NE => {
+        let controller_value = self.generate_type2(controller, bindings).ok();
+        for _ in 0..MAX_ATTEMPTS {
+          let value = self.generate_type2(target, bindings)?;
+          if Some(&value) != controller_value.as_ref() {
+            return Ok(value);
+          }
+        }
+
+        Err(Error::Unsupported(format!(
+          "no value of {} found that is not equal to {}",
+          target, controller
+        )))
+      }
+      ControlOperator::DEFAULT | ControlOperator::WITHIN | ControlOperator::AND => {
+        self.generate_type2(target, bindings)
+      }
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::FEATURE => self.generate_type2(target, bindings),
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::CAT | ControlOperator::DET | ControlOperator::PLUS => {
+        let values = if let ControlOperator::PLUS = ctrl {
+          plus_operation(self.cddl, target, controller)
+        } else {
+          cat_operation(
+            self.cddl,
+            target,
+            controller,
+            matches!(ctrl, ControlOperator::DET),
+          )
+        }
+        .map_err(Error::Unsupported)?;
+
+        if values.is_empty() {
+          return Err(Error::Unsupported(format!(
+            "{} control on {} yields no values",
+            ctrl, target
+          )));
+        }
+
+        let idx = self.rng.gen_range(0..values.len());
+        self.generate_type2(&values[idx], bindings)
+      }
+      _ => Err(Error::Unsupported(format!(
+        "{} control cannot be used to generate CBOR",
+        ctrl
+      ))),
+    }
+  }
+
+  fn generate_choice_from_group(
+    &mut self,
+    group: &Group<'a>,
+    bindings: &Rc<Bindings<'a>>,
+  ) -> Result<Value> {
+    let entries = group
+      .group_choices
+      .iter()
+      .flat_map(|gc| gc.group_entries.iter().map(|ge| &ge.0))
+      .collect::<Vec<_>>();
+
+    if entries.is_empty() {
+      return Err(Error::Unsupported(format!(
+        "choice from empty group {}",
+        group
+      )));
+    }
+
+    let entry = entries[self.rng.gen_range(0..entries.len())];
+    self.generate_choice_from_group_entry(entry, bindings)
+  }
+
+  fn generate_choice_from_group_entry(
+    &mut self,
+    entry: &GroupEntry<'a>,
+    bindings: &Rc<Bindings<'a>>,
+  ) -> Result<Value> {
+    match entry {
+      GroupEntry::ValueMemberKey { ge, .. } => self.generate_type(&ge.entry_type, bindings),
+      GroupEntry::TypeGroupname { ge, .. } => {
+        let rules = group_rules_from_ident(self.cddl, &ge.name);
+        if rules.is_empty() {
+          return self.generate_typename(&ge.name, ge.generic_args.as_ref(), bindings);
+        }
+
+        let rule = rules[self.rng.gen_range(0..rules.len())];
+        let bindings = Bindings::new(
+          &ge.name,
+          rule.generic_params.as_ref(),
+          ge.generic_args.as_ref(),
+          bindings,
+        )?;
+
+        self.generate_choice_from_group_entry(&rule.entry, &bindings)
+      }
+      GroupEntry::InlineGroup { group, .. } => self.generate_choice_from_group(group, bindings),
+    }
+  }
+
+  fn generate_group(
+    &mut self,
+    group: &Group<'a>,
+    bindings: &Rc<Bindings<'a>>,
+    members: &mut Members,
+  ) -> Result<()> {
+    if group.group_choices.is_empty() {
+      return Ok(());
+    }
+
+    let idx = self.rng.gen_range(0..group.group_choices.len());
+    for ge in group.group_choices[idx].group_entries.iter() {
+      self.generate_group_entry(&ge.0, bindings, members)?;
+    }
+
+    Ok(())
+  }
+
+  fn generate_group_entry(
+    &mut self,
+    entry: &GroupEntry<'a>,
+    bindings: &Rc<Bindings<'a>>,
+    members: &mut Members,
+  ) -> Result<()> {
+    self.depth += 1;
+    if self.depth > MAX_DEPTH {
+      return Err(Error::Unsupported(
+        "maximum nesting depth exceeded".to_string(),
+      ));
+    }
+
+    let result = match entry {
+      GroupEntry::ValueMemberKey { ge, .. } => {
+        let count = occurrence_count(
+          &mut self.rng,
+          ge.occur.as_ref().map(|o| &o.occur),
+          self.depth,
+        );
+
+        match (
+          &ge.member_key,
+          group_from_entry_type(self.cddl, &ge.entry_type),
+        ) {
+          (None, Some(t2)) => self.generate_group_from_type2(t2, count, bindings, members),
+          (member_key, _) => {
+            for _ in 0..count {
+              match members {
+                Members::Array(items) => items.push(self.generate_type(&ge.entry_type, bindings)?),
+                Members::Map(entries) => {
+                  let key = match member_key {
+                    Some(MemberKey::Bareword { ident, .. }) => Value::Text(ident.ident.to_string()),
+                    Some(MemberKey::Value { value, .. }) => {
+                      token_value_into_cbor_value(value.clone())
+                    }
+                    Some(MemberKey::Type1 { t1, .. }) => self.generate_type1(t1, bindings)?,
+                    _ => {
+                      return Err(Error::Unsupported(format!(
+                        "group entry {} cannot be represented as a CBOR map entry",
+                        ge
+                      )))
+                    }
+                  };
+
+                  // Keys generated from the same type may collide, in which
+                  // case only the first entry is kept
+                  if entries.iter().any(|(k, _)| *k == key) {
+                    continue;
+                  }
+
+                  let value = self.generate_type(&ge.entry_type, bindings)?;
+                  entries.push((key, value));
+                }
+              }
+            }
+
+            Ok(())
+          }
+        }
+      }
+      GroupEntry::TypeGroupname { ge, .. } => {
+        let count = occurrence_count(
+          &mut self.rng,
+          ge.occur.as_ref().map(|o| &o.occur),
+          self.depth,
+        );
+
+        let rules = group_rules_from_ident(self.cddl, &ge.name);
+        for _ in 0..count {
+          if rules.is_empty() {
+            match members {
+              Members::Array(items) => {
+                items.push(self.generate_typename(&ge.name, ge.generic_args.as_ref(), bindings)?)
+              }
+              Members::Map(_) => {
+                return Err(Error::Unsupported(format!(
+                  "type {} cannot be used as a CBOR map entry without a key",
+                  ge.name
+                )))
+              }
+            }
+
+            continue;
+          }
+
+          let rule = rules[self.rng.gen_range(0..rules.len())];
+          let bindings = Bindings::new(
+            &ge.name,
+            rule.generic_params.as_ref(),
+            ge.generic_args.as_ref(),
+            bindings,
+          )?;
+
+          self.generate_group_entry(&rule.entry, &bindings, members)?;
+        }
+
+        Ok(())
+      }
+      GroupEntry::InlineGroup { occur, group, .. } => {
+        let count = occurrence_count(&mut self.rng, occur.as_ref().map(|o| &o.occur), self.depth);
+        for _ in 0..count {
+          self.generate_group(group, bindings, members)?;
+        }
+
+        Ok(())
+      }
+    };
+
+    self.depth -= 1;
+
+    result
+  }
+
+  /// Generate the members of a group referenced by the type of a group entry
+  /// without a member key, i.e. a group name or an unwrapped map or array
+  fn generate_group_from_type2(
+    &mut self,
+    t2: &Type2<'a>,
+    count: usize,
+    bindings: &Rc<Bindings<'a>>,
+    members: &mut Members,
+  ) -> Result<()> {
+    let (ident, generic_args) = match t2 {
+      Type2::Typename {
+        ident,
+        generic_args,
+        ..
+      }
+      | Type2::Unwrap {
+        ident,
+        generic_args,
+        ..
+      } => (ident, generic_args.as_ref()),
+      _ => return Ok(()),
+    };
+
+    for _ in 0..count {
+      if let Type2::Unwrap { .. } = t2 {
+        let rules = type_rules_from_ident(self.cddl, ident);
+        let (rule, tc) = type_choice_from_rules(&mut self.rng, &rules)
+          .ok_or_else(|| Error::MissingRule(format!("no rule named {}", ident)))?;
+        let bindings = Bindings::new(ident, rule.generic_params.as_ref(), generic_args, bindings)?;
+
+        match &tc.type1.type2 {
+          Type2::Map { group, .. } | Type2::Array { group, .. } => {
+            self.generate_group(group, &bindings, members)?
+          }
+          _ => {
+            return Err(Error::Unsupported(format!(
+              "only maps and arrays can be unwrapped, got {}",
+              tc.type1
+            )))
+          }
+        }
+
+        continue;
+      }
+
+      let rules = group_rules_from_ident(self.cddl, ident);
+      let rule = rules[self.rng.gen_range(0..rules.len())];
+      let bindings = Bindings::new(ident, rule.generic_params.as_ref(), generic_args, bindings)?;
+
+      self.generate_group_entry(&rule.entry, &bindings, members)?;
+    }
+
+    Ok(())
+  }
+}
+
+impl From<Members> for Value {
+  fn from(members: Members) -> Self {
+    match members {
+      Members::Map(entries) => Value::Map(entries),
+      Members::Array(items) => Value::Array(items),
+    }
+  }
+}
+
+fn int_value(i: i128) -> Result<Value> {
+  Integer::try_from(i)
+    .map(Value::Integer)
+    .map_err(|_| Error::Unsupported(format!("integer {} cannot be represented in CBOR", i)))
+}
+
+fn tagged(tag: u64, value: Value) -> Value {
+  Value::Tag(tag, Box::new(value))
+}
+
+/// Encode a CBOR value, e.g. for embedding in a byte string
+fn encode(value: &Value) -> Result<Vec<u8>> {
+  let mut b = Vec::new();
+  ciborium::ser::into_writer(value, &mut b)
+    .map_err(|e| Error::Unsupported(format!("error encoding CBOR: {}", e)))?;
+
+  Ok(b)
+}
+
+/// Bignum content which does not fit into a 64-bit integer, in preferred
+/// serialization, i.e. without leading zero bytes
+fn bignum_bytes(rng: &mut StdRng) -> Vec<u8> {
+  let len = rng.gen_range(9..=MAX_BYTES_LEN);
+  let mut b = bytes_of_len(rng, len);
+  b[0] = rng.gen_range(1..=u8::MAX);
+
+  b
+}
+
+/// Exponent and mantissa array of a decimal fraction or bigfloat
+fn exponent_mantissa(rng: &mut StdRng) -> Result<Value> {
+  Ok(Value::Array(vec![
+    int_value(rng.gen_range(-8..=8))?,
+    int_value(int_in_bounds(rng, (None, None))?)?,
+  ]))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::cddl_from_str;
+  use indoc::indoc;
+
+  fn assert_generates_valid_cbor(
+    cddl: &str,
+  ) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = cddl_from_str(cddl, true).map_err(Error::CDDLParsing)?;
+
+    for seed in 0..10 {
+      let value = CBORGenerator::new(&cddl, Some(seed)).generate()?;
+
+      let mut cv = CBORValidator::new(&cddl, value, None);
+      let r: cbor_validator::Result<std::io::Error> = cv.validate();
+      r?;
+    }
+
+    Ok(())
+  }
+
+  #[test]
+  fn generate_map_with_tags_and_byte_strings() -> std::result::Result<(), Box<dyn std::error::Error>>
+  {
+    let cddl = indoc!(
+      r#"
+        message = {
+          1 => bstr .size 16,
+          2 => #6.1234(tstr),
+          ? 3 => uint .bits flags,
+          4 => biguint,
+          5 => tdate,
+          6 => #1.2,
+          7 => decfrac,
+          8 => bigfloat,
+          9 => float16,
+          10 => float32,
+          * int => bytes,
+        }
+        flags = &(read: 0, write: 1, exec: 2)
+      "#
+    );
+
+    assert_generates_valid_cbor(cddl)
+  }
+
+  #[test]
+  fn generate_embedded_cbor() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = indoc!(
+      r#"
+        envelope = [
+          payload: bstr .cbor payload,
+          items: bstr .cborseq [* uint],
+        ]
+        payload = { id: uint, name: tstr }
+      "#
+    );
+
+    assert_generates_valid_cbor(cddl)
+  }
+
+  #[test]
+  fn generate_is_deterministic_for_seed() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = cddl_from_str("root = [+ bstr / int]", true).map_err(Error::CDDLParsing)?;
+
+    let v1 = CBORGenerator::new(&cddl, Some(42)).generate()?;
+    let v2 = CBORGenerator::new(&cddl, Some(42)).generate()?;
+    assert_eq!(v1, v2);
+
+    Ok(())
+  }
+}
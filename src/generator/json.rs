@@ -0,0 +1,841 @@
+#![cfg(feature = "std")]
+#![cfg(feature = "json")]
+#![cfg(not(feature = "lsp"))]
+
+use super::*;
+use crate::{
+  ast::{Group, GroupEntry, MemberKey, Operator, RangeCtlOp, Type, Type1, Type2, CDDL},
+  token::{self, ControlOperator},
+  validator::{
+    json::{ByteStringEncoding, JSONValidator},
+    Validator,
+  },
+};
+
+#[cfg(feature = "additional-controls")]
+use crate::validator::control::{cat_operation, plus_operation};
+
+use chrono::{SecondsFormat, TimeZone, Utc};
+use serde_json::{Map, Number, Value};
+use std::convert::TryFrom;
+
+/// JSON members generated from a group, either the entries of an object or the
+/// items of an array
+enum Members {
+  Object(Map<String, Value>),
+  Array(Vec<Value>),
+}
+
+/// JSON instance generator type
+pub struct JSONGenerator<'a> {
+  cddl: &'a CDDL<'a>,
+  rng: StdRng,
+  depth: usize,
+}
+
+impl<'a> JSONGenerator<'a> {
+  /// New JSONGenerator from CDDL AST. Generated values are deterministic for a
+  /// given seed
+  pub fn new(cddl: &'a CDDL<'a>, seed: Option<u64>) -> Self {
+    JSONGenerator {
+      cddl,
+      rng: rng_from_seed(seed),
+      depth: 0,
+    }
+  }
+
+  /// Generate a JSON value from the root type rule
+  pub fn generate(&mut self) -> Result<Value> {
+    let rule = root_rule(self.cddl)?;
+
+    self.generate_valid(rule, None)
+  }
+
+  /// Generate a JSON value from the type rule with the given name
+  pub fn generate_from_rule(&mut self, name: &str) -> Result<Value> {
+    let rule = rule_from_name(self.cddl, name)?;
+
+    self.generate_valid(rule, Some(name))
+  }
+
+  /// Generate values from the given rule until one passes validation against
+  /// it
+  fn generate_valid(&mut self, rule: &'a Rule<'a>, name: Option<&str>) -> Result<Value> {
+    let mut error = None;
+    for _ in 0..MAX_ATTEMPTS {
+      let value = match self.generate_rule(rule) {
+        Ok(value) => value,
+        Err(e) => {
+          error = Some(e);
+          continue;
+        }
+      };
+
+      #[cfg(feature = "additional-controls")]
+      let mut jv = JSONValidator::new(self.cddl, value.clone(), None);
+      #[cfg(not(feature = "additional-controls"))]
+      let mut jv = JSONValidator::new(self.cddl, value.clone());
+
+      let r = match name {
+        Some(name) => jv.validate_rule(name),
+        None => jv.validate(),
+      };
+      match r {
+        Ok(()) => return Ok(value),
+        Err(e) => error = Some(Error::Validation(e.to_string().trim_end().to_string())),
+      }
+    }
+
+    Err(error.unwrap_or_else(|| Error::Validation(format!("rule {}", rule.name()))))
+  }
+
+  fn generate_rule(&mut self, rule: &'a Rule<'a>) -> Result<Value> {
+    self.depth = 0;
+
+    match rule {
+      Rule::Type { rule, .. } => self.generate_typename(&rule.name, None, &Rc::default()),
+      Rule::Group { rule, .. } => Err(Error::Unsupported(format!(
+        "group rule {} cannot be represented as a JSON value",
+        rule.name
+      ))),
+    }
+  }
+
+  fn generate_type(&mut self, t: &Type<'a>, bindings: &Rc<Bindings<'a>>) -> Result<Value> {
+    if t.type_choices.is_empty() {
+      return Err(Error::Unsupported(format!("empty type {}", t)));
+    }
+
+    let idx = self.rng.gen_range(0..t.type_choices.len());
+    self.generate_type1(&t.type_choices[idx].type1, bindings)
+  }
+
+  fn generate_type1(&mut self, t1: &Type1<'a>, bindings: &Rc<Bindings<'a>>) -> Result<Value> {
+    self.depth += 1;
+    if self.depth > MAX_DEPTH {
+      return Err(Error::Unsupported(
+        "maximum nesting depth exceeded".to_string(),
+      ));
+    }
+
+    let value = match &t1.operator {
+      Some(Operator {
+        operator: RangeCtlOp::RangeOp { is_inclusive, .. },
+        type2: upper,
+        ..
+      }) => self.generate_range(&t1.type2, upper, *is_inclusive, bindings),
+      Some(Operator {
+        operator: RangeCtlOp::CtlOp { ctrl, .. },
+        type2: controller,
+        ..
+      }) => self.generate_control(&t1.type2, *ctrl, controller, bindings),
+      None => self.generate_type2(&t1.type2, bindings),
+    };
+
+    self.depth -= 1;
+
+    value
+  }
+
+  fn generate_type2(&mut self, t2: &Type2<'a>, bindings: &Rc<Bindings<'a>>) -> Result<Value> {
+    match t2 {
+      Type2::IntValue { value, .. } => Ok(Value::from(*value as i64)),
+      Type2::UintValue { value, .. } => Ok(Value::from(*value as u64)),
+      Type2::FloatValue { value, .. } => float_value(*value),
+      Type2::TextValue { value, .. } => Ok(Value::String(value.to_string())),
+      Type2::UTF8ByteString { value, .. } => Ok(byte_string(value)),
+      Type2::B16ByteString { value, .. } => base16::decode(value)
+        .map(|b| byte_string(&b))
+        .map_err(|e| Error::Unsupported(format!("invalid base16 byte string {}: {}", t2, e))),
+      Type2::B64ByteString { value, .. } => base64::decode_config(value, base64::URL_SAFE)
+        .map(|b| byte_string(&b))
+        .map_err(|e| Error::Unsupported(format!("invalid base64 byte string {}: {}", t2, e))),
+      Type2::Typename {
+        ident,
+        generic_args,
+        ..
+      } => self.generate_typename(ident, generic_args.as_ref(), bindings),
+      Type2::ParenthesizedType { pt, .. } => self.generate_type(pt, bindings),
+      Type2::Map { group, .. } => {
+        let mut members = Members::Object(Map::new());
+        self.generate_group(group, bindings, &mut members)?;
+        Ok(members.into())
+      }
+      Type2::Array { group, .. } => {
+        let mut members = Members::Array(Vec::new());
+        self.generate_group(group, bindings, &mut members)?;
+        Ok(members.into())
+      }
+      Type2::ChoiceFromInlineGroup { group, .. } => {
+        self.generate_choice_from_group(group, bindings)
+      }
+      Type2::ChoiceFromGroup {
+        ident,
+        generic_args,
+        ..
+      } => {
+        let rules = group_rules_from_ident(self.cddl, ident);
+        if rules.is_empty() {
+          return Err(Error::Unsupported(format!("no group rule named {}", ident)));
+        }
+
+        let rule = rules[self.rng.gen_range(0..rules.len())];
+        let bindings = Bindings::new(
+          ident,
+          rule.generic_params.as_ref(),
+          generic_args.as_ref(),
+          bindings,
+        )?;
+
+        self.generate_choice_from_group_entry(&rule.entry, &bindings)
+      }
+      Type2::TaggedData { t, .. } => self.generate_type(t, bindings),
+      Type2::DataMajorType { mt, constraint, .. } => match (mt, constraint) {
+        (0, _) => Ok(Value::from(self.rng.gen_range(0..=NUMERIC_BOUND as u64))),
+        (1, _) => Ok(Value::from(self.rng.gen_range(-(NUMERIC_BOUND as i64)..0))),
+        (2, Some(c)) => Ok(byte_string(&bytes_of_len(&mut self.rng, *c))),
+        (2, None) => Ok(byte_string(&bytes(&mut self.rng))),
+        (3, _) => Ok(Value::String(text(&mut self.rng))),
+        (7, Some(20)) => Ok(Value::Bool(false)),
+        (7, Some(21)) => Ok(Value::Bool(true)),
+        (7, Some(22)) | (7, None) => Ok(Value::Null),
+        (7, Some(25)) | (7, Some(26)) | (7, Some(27)) => float_value(float_in_bounds(
+          &mut self.rng,
+          -(NUMERIC_BOUND as f64),
+          NUMERIC_BOUND as f64,
+        )?),
+        _ => Err(Error::Unsupported(format!(
+          "data type {} cannot be represented in JSON",
+          t2
+        ))),
+      },
+      Type2::Any { .. } => self.generate_any(),
+      Type2::Unwrap { .. } => Err(Error::Unsupported(format!(
+        "unwrapped group {} cannot be represented as a single JSON value",
+        t2
+      ))),
+    }
+  }
+
+  fn generate_typename(
+    &mut self,
+    ident: &Identifier<'a>,
+    generic_args: Option<&GenericArgs<'a>>,
+    bindings: &Rc<Bindings<'a>>,
+  ) -> Result<Value> {
+    if let Some((arg, arg_bindings)) = bindings.get(ident) {
+      let (arg, arg_bindings) = (arg.clone(), arg_bindings.clone());
+      return self.generate_type1(&arg, &arg_bindings);
+    }
+
+    let rules = type_rules_from_ident(self.cddl, ident);
+    if let Some((rule, tc)) = type_choice_from_rules(&mut self.rng, &rules) {
+      let bindings = Bindings::new(ident, rule.generic_params.as_ref(), generic_args, bindings)?;
+
+      return self.generate_type1(&tc.type1, &bindings);
+    }
+
+    if !group_rules_from_ident(self.cddl, ident).is_empty() {
+      return Err(Error::Unsupported(format!(
+        "group {} cannot be used as a type",
+        ident
+      )));
+    }
+
+    if ident.socket.is_some() {
+      return Err(Error::Unsupported(format!(
+        "socket {} has no plugs defined",
+        ident
+      )));
+    }
+
+    self.generate_prelude(ident)
+  }
+
+  fn generate_prelude(&mut self, ident: &Identifier<'a>) -> Result<Value> {
+    let rng = &mut self.rng;
+
+    match token::lookup_ident(&ident.ident) {
+      Token::FALSE => Ok(Value::Bool(false)),
+      Token::TRUE => Ok(Value::Bool(true)),
+      Token::BOOL => Ok(Value::Bool(rng.gen())),
+      Token::NIL | Token::NULL => Ok(Value::Null),
+      Token::UINT | Token::UNSIGNED => Ok(Value::from(int_in_bounds(rng, (Some(0), None))? as u64)),
+      Token::NINT => Ok(Value::from(int_in_bounds(rng, (None, Some(-1)))? as i64)),
+      Token::INT | Token::INTEGER => Ok(Value::from(int_in_bounds(rng, (None, None))? as i64)),
+      Token::FLOAT
+      | Token::FLOAT16
+      | Token::FLOAT32
+      | Token::FLOAT64
+      | Token::FLOAT1632
+      | Token::FLOAT3264 => float_value(float_in_bounds(
+        rng,
+        -(NUMERIC_BOUND as f64),
+        NUMERIC_BOUND as f64,
+      )?),
+      Token::NUMBER => {
+        if rng.gen() {
+          Ok(Value::from(int_in_bounds(rng, (None, None))? as i64))
+        } else {
+          float_value(float_in_bounds(
+            rng,
+            -(NUMERIC_BOUND as f64),
+            NUMERIC_BOUND as f64,
+          )?)
+        }
+      }
+      Token::TSTR | Token::TEXT | Token::REGEXP | Token::MIMEMESSAGE => {
+        Ok(Value::String(text(rng)))
+      }
+      Token::BSTR | Token::BYTES => Ok(byte_string(&bytes(rng))),
+      Token::TDATE => {
+        let secs = rng.gen_range(0..=4_102_444_800);
+        let date = Utc
+          .timestamp_opt(secs, 0)
+          .single()
+          .ok_or_else(|| Error::Unsupported(format!("invalid timestamp {}", secs)))?;
+
+        Ok(Value::String(
+          date.to_rfc3339_opts(SecondsFormat::Secs, true),
+        ))
+      }
+      Token::TIME => Ok(Value::from(rng.gen_range(0..=4_102_444_800i64))),
+      Token::URI => Ok(Value::String(format!("https://example.com/{}", text(rng)))),
+      Token::B64URL => Ok(Value::String(base64_url::encode(text(rng).as_bytes()))),
+      Token::B64LEGACY => Ok(Value::String(base64::encode(text(rng).as_bytes()))),
+      Token::ANY => self.generate_any(),
+      t if t.in_standard_prelude().is_some() => Err(Error::Unsupported(format!(
+        "prelude type {} cannot be represented in JSON",
+        ident
+      ))),
+      _ => Err(Error::MissingRule(format!("no rule named {}", ident))),
+    }
+  }
+
+  fn generate_any(&mut self) -> Result<Value> {
+    match self.rng.gen_range(0..4) {
+      0 => Ok(Value::Bool(self.rng.gen())),
+      1 => Ok(Value::from(
+        int_in_bounds(&mut self.rng, (None, None))? as i64
+      )),
+      2 => Ok(Value::String(text(&mut self.rng))),
+      _ => Ok(Value::Null),
+    }
+  }
+
+  fn generate_range(
+    &mut self,
+    lower: &Type2<'a>,
+    upper: &Type2<'a>,
+    is_inclusive: bool,
+    bindings: &Rc<Bindings<'a>>,
+  ) -> Result<Value> {
+    let lower_value = literal_from_type2(self.cddl, lower, bindings);
+    let upper_value = literal_from_type2(self.cddl, upper, bindings);
+
+    match (lower_value, upper_value) {
+      (Some(Literal::Int(l)), Some(Literal::Int(u))) => {
+        let u = if is_inclusive { u } else { u - 1 };
+        let i = int_in_bounds(&mut self.rng, (Some(l), Some(u)))?;
+        int_value(i)
+      }
+      (Some(Literal::Float(l)), Some(Literal::Float(u))) => {
+        let mut f = float_in_bounds(&mut self.rng, l, u)?;
+        if !is_inclusive && (f == u || f == l) {
+          f = (l + u) / 2.0;
+        }
+
+        float_value(f)
+      }
+      _ => Err(Error::Unsupported(format!(
+        "range {}{}{} has no numeric bounds",
+        lower,
+        if is_inclusive { ".." } else { "..." },
+        upper
+      ))),
+    }
+  }
+
+  fn generate_control(
+    &mut self,
+    target: &Type2<'a>,
+    ctrl: ControlOperator,
+    controller: &Type2<'a>,
+    bindings: &Rc<Bindings<'a>>,
+  ) -> Result<Value> {
+    match ctrl {
+      ControlOperator::SIZE => match target_token(self.cddl, target) {
+        Some(Token::TSTR) | Some(Token::TEXT) => {
+          let (lower, upper) = size_bounds(self.cddl, controller, bindings)?;
+          let len = self.rng.gen_range(lower..=upper);
+
+          Ok(Value::String(text_of_len(&mut self.rng, len)))
+        }
+        Some(Token::BSTR) | Some(Token::BYTES) => {
+          let (lower, upper) = size_bounds(self.cddl, controller, bindings)?;
+          let len = self.rng.gen_range(lower..=upper);
+
+          Ok(byte_string(&bytes_of_len(&mut self.rng, len)))
+        }
+        Some(Token::UINT) | Some(Token::UNSIGNED) => {
+          let (lower, upper) = size_bounds(self.cddl, controller, bindings)?;
+          let size = self.rng.gen_range(lower..=upper);
+          let max = if size >= 8 {
+            u64::MAX
+          } else {
+            (1u64 << (size * 8)) - 1
+          };
+
+          Ok(Value::from(self.rng.gen_range(0..=max)))
+        }
+        _ => Err(Error::Unsupported(format!(
+          "{} control on {} cannot be represented in JSON",
+          ctrl, target
+        ))),
+      },
+      ControlOperator::REGEXP | ControlOperator::PCRE => {
+        match literal_from_type2(self.cddl, controller, bindings) {
+          Some(Literal::Text(pattern)) => {
+            Ok(Value::String(text_from_regex(&mut self.rng, &pattern)?))
+          }
+          _ => Err(Error::Unsupported(format!(
+            "{} control requires a text string controller, got {}",
+            ctrl, controller
+          ))),
+        }
+      }
+      ControlOperator::LT | ControlOperator::LE | ControlOperator::GT | ControlOperator::GE => {
+        match number_from_control(&mut self.rng, self.cddl, target, ctrl, controller, bindings)? {
+          Literal::Int(i) => int_value(i),
+          Literal::Float(f) => float_value(f),
+          Literal::Text(t) => Ok(Value::String(t)),
+        }
+      }
+      ControlOperator::EQ => self.generate_type2(controller, bindings),
+      ControlOperator::NE => {
+        let controller_value = self.generate_type2(controller, bindings).ok();
+        for _ in 0..MAX_ATTEMPTS {
+          let value = self.generate_type2(target, bindings)?;
+          if Some(&value) != controller_value.as_ref() {
+            return Ok(value);
+          }
+        }
+
+        Err(Error::Unsupported(format!(
+          "no value of {} found that is not equal to {}",
+          target, controller
+        )))
+      }
+      ControlOperator::DEFAULT | ControlOperator::WITHIN | ControlOperator::AND => {
+        self.generate_type2(target, bindings)
+      }
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::FEATURE => self.generate_type2(target, bindings),
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::CAT | ControlOperator::DET | ControlOperator::PLUS => {
+        let values = if let ControlOperator::PLUS = ctrl {
+          plus_operation(self.cddl, target, controller)
+        } else {
+          cat_operation(
+            self.cddl,
+            target,
+            controller,
+            matches!(ctrl, ControlOperator::DET),
+          )
+        }
+        .map_err(Error::Unsupported)?;
+
+        if values.is_empty() {
+          return Err(Error::Unsupported(format!(
+            "{} control on {} yields no values",
+            ctrl, target
+          )));
+        }
+
+        let idx = self.rng.gen_range(0..values.len());
+        self.generate_type2(&values[idx], bindings)
+      }
+      _ => Err(Error::Unsupported(format!(
+        "{} control cannot be used to generate JSON",
+        ctrl
+      ))),
+    }
+  }
+
+  fn generate_choice_from_group(
+    &mut self,
+    group: &Group<'a>,
+    bindings: &Rc<Bindings<'a>>,
+  ) -> Result<Value> {
+    let entries = group
+      .group_choices
+      .iter()
+      .flat_map(|gc| gc.group_entries.iter().map(|ge| &ge.0))
+      .collect::<Vec<_>>();
+
+    if entries.is_empty() {
+      return Err(Error::Unsupported(format!(
+        "choice from empty group {}",
+        group
+      )));
+    }
+
+    let entry = entries[self.rng.gen_range(0..entries.len())];
+    self.generate_choice_from_group_entry(entry, bindings)
+  }
+
+  fn generate_choice_from_group_entry(
+    &mut self,
+    entry: &GroupEntry<'a>,
+    bindings: &Rc<Bindings<'a>>,
+  ) -> Result<Value> {
+    match entry {
+      GroupEntry::ValueMemberKey { ge, .. } => self.generate_type(&ge.entry_type, bindings),
+      GroupEntry::TypeGroupname { ge, .. } => {
+        let rules = group_rules_from_ident(self.cddl, &ge.name);
+        if rules.is_empty() {
+          return self.generate_typename(&ge.name, ge.generic_args.as_ref(), bindings);
+        }
+
+        let rule = rules[self.rng.gen_range(0..rules.len())];
+        let bindings = Bindings::new(
+          &ge.name,
+          rule.generic_params.as_ref(),
+          ge.generic_args.as_ref(),
+          bindings,
+        )?;
+
+        self.generate_choice_from_group_entry(&rule.entry, &bindings)
+      }
+      GroupEntry::InlineGroup { group, .. } => self.generate_choice_from_group(group, bindings),
+    }
+  }
+
+  fn generate_group(
+    &mut self,
+    group: &Group<'a>,
+    bindings: &Rc<Bindings<'a>>,
+    members: &mut Members,
+  ) -> Result<()> {
+    if group.group_choices.is_empty() {
+      return Ok(());
+    }
+
+    let idx = self.rng.gen_range(0..group.group_choices.len());
+    for ge in group.group_choices[idx].group_entries.iter() {
+      self.generate_group_entry(&ge.0, bindings, members)?;
+    }
+
+    Ok(())
+  }
+
+  fn generate_group_entry(
+    &mut self,
+    entry: &GroupEntry<'a>,
+    bindings: &Rc<Bindings<'a>>,
+    members: &mut Members,
+  ) -> Result<()> {
+    self.depth += 1;
+    if self.depth > MAX_DEPTH {
+      return Err(Error::Unsupported(
+        "maximum nesting depth exceeded".to_string(),
+      ));
+    }
+
+    let result = match entry {
+      GroupEntry::ValueMemberKey { ge, .. } => {
+        let count = occurrence_count(
+          &mut self.rng,
+          ge.occur.as_ref().map(|o| &o.occur),
+          self.depth,
+        );
+
+        match (
+          &ge.member_key,
+          group_from_entry_type(self.cddl, &ge.entry_type),
+        ) {
+          (None, Some(t2)) => self.generate_group_from_type2(t2, count, bindings, members),
+          (member_key, _) => {
+            for _ in 0..count {
+              match members {
+                Members::Array(items) => items.push(self.generate_type(&ge.entry_type, bindings)?),
+                Members::Object(map) => {
+                  let key = match member_key {
+                    Some(MemberKey::Bareword { ident, .. }) => ident.ident.to_string(),
+                    Some(MemberKey::Value {
+                      value: token::Value::TEXT(t),
+                      ..
+                    }) => t.to_string(),
+                    Some(MemberKey::Type1 { t1, .. }) => {
+                      match self.generate_type1(t1, bindings)? {
+                        Value::String(s) => s,
+                        k => {
+                          return Err(Error::Unsupported(format!(
+                            "JSON object keys must be strings, got {}",
+                            k
+                          )))
+                        }
+                      }
+                    }
+                    _ => {
+                      return Err(Error::Unsupported(format!(
+                        "group entry {} cannot be represented as a JSON object member",
+                        ge
+                      )))
+                    }
+                  };
+
+                  let value = self.generate_type(&ge.entry_type, bindings)?;
+                  map.insert(key, value);
+                }
+              }
+            }
+
+            Ok(())
+          }
+        }
+      }
+      GroupEntry::TypeGroupname { ge, .. } => {
+        let count = occurrence_count(
+          &mut self.rng,
+          ge.occur.as_ref().map(|o| &o.occur),
+          self.depth,
+        );
+
+        let rules = group_rules_from_ident(self.cddl, &ge.name);
+        for _ in 0..count {
+          if rules.is_empty() {
+            match members {
+              Members::Array(items) => {
+                items.push(self.generate_typename(&ge.name, ge.generic_args.as_ref(), bindings)?)
+              }
+              Members::Object(_) => {
+                return Err(Error::Unsupported(format!(
+                  "type {} cannot be used as a JSON object member without a key",
+                  ge.name
+                )))
+              }
+            }
+
+            continue;
+          }
+
+          let rule = rules[self.rng.gen_range(0..rules.len())];
+          let bindings = Bindings::new(
+            &ge.name,
+            rule.generic_params.as_ref(),
+            ge.generic_args.as_ref(),
+            bindings,
+          )?;
+
+          self.generate_group_entry(&rule.entry, &bindings, members)?;
+        }
+
+        Ok(())
+      }
+      GroupEntry::InlineGroup { occur, group, .. } => {
+        let count = occurrence_count(&mut self.rng, occur.as_ref().map(|o| &o.occur), self.depth);
+        for _ in 0..count {
+          self.generate_group(group, bindings, members)?;
+        }
+
+        Ok(())
+      }
+    };
+
+    self.depth -= 1;
+
+    result
+  }
+
+  /// Generate the members of a group referenced by the type of a group entry
+  /// without a member key, i.e. a group name or an unwrapped map or array
+  fn generate_group_from_type2(
+    &mut self,
+    t2: &Type2<'a>,
+    count: usize,
+    bindings: &Rc<Bindings<'a>>,
+    members: &mut Members,
+  ) -> Result<()> {
+    let (ident, generic_args) = match t2 {
+      Type2::Typename {
+        ident,
+        generic_args,
+        ..
+      }
+      | Type2::Unwrap {
+        ident,
+        generic_args,
+        ..
+      } => (ident, generic_args.as_ref()),
+      _ => return Ok(()),
+    };
+
+    for _ in 0..count {
+      if let Type2::Unwrap { .. } = t2 {
+        let rules = type_rules_from_ident(self.cddl, ident);
+        let (rule, tc) = type_choice_from_rules(&mut self.rng, &rules)
+          .ok_or_else(|| Error::MissingRule(format!("no rule named {}", ident)))?;
+        let bindings = Bindings::new(ident, rule.generic_params.as_ref(), generic_args, bindings)?;
+
+        match &tc.type1.type2 {
+          Type2::Map { group, .. } | Type2::Array { group, .. } => {
+            self.generate_group(group, &bindings, members)?
+          }
+          _ => {
+            return Err(Error::Unsupported(format!(
+              "only maps and arrays can be unwrapped, got {}",
+              tc.type1
+            )))
+          }
+        }
+
+        continue;
+      }
+
+      let rules = group_rules_from_ident(self.cddl, ident);
+      let rule = rules[self.rng.gen_range(0..rules.len())];
+      let bindings = Bindings::new(ident, rule.generic_params.as_ref(), generic_args, bindings)?;
+
+      self.generate_group_entry(&rule.entry, &bindings, members)?;
+    }
+
+    Ok(())
+  }
+}
+
+impl From<Members> for Value {
+  fn from(members: Members) -> Self {
+    match members {
+      Members::Object(map) => Value::Object(map),
+      Members::Array(items) => Value::Array(items),
+    }
+  }
+}
+
+fn int_value(i: i128) -> Result<Value> {
+  if let Ok(u) = u64::try_from(i) {
+    return Ok(Value::from(u));
+  }
+
+  i64::try_from(i)
+    .map(Value::from)
+    .map_err(|_| Error::Unsupported(format!("integer {} cannot be represented in JSON", i)))
+}
+
+/// Byte string represented as a base64url encoded JSON string, as per the
+/// convention of RFC 8610 Appendix E
+fn byte_string(b: &[u8]) -> Value {
+  Value::String(ByteStringEncoding::Base64Url.encode(b))
+}
+
+fn float_value(f: f64) -> Result<Value> {
+  Number::from_f64(f)
+    .map(Value::Number)
+    .ok_or_else(|| Error::Unsupported(format!("float {} cannot be represented in JSON", f)))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{cddl_from_str, validator::json::JSONValidator};
+  use indoc::indoc;
+
+  fn assert_generates_valid_json(
+    cddl: &str,
+  ) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = cddl_from_str(cddl, true).map_err(Error::CDDLParsing)?;
+
+    for seed in 0..10 {
+      let value = JSONGenerator::new(&cddl, Some(seed)).generate()?;
+
+      let mut jv = JSONValidator::new(&cddl, value, None);
+      jv.validate()?;
+    }
+
+    Ok(())
+  }
+
+  #[test]
+  fn generate_map_with_occurrences() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = indoc!(
+      r#"
+        person = {
+          name: tstr,
+          age: uint .lt 130,
+          ? email: tstr .regexp "[a-z]+@example\\.com",
+          nickname: tstr .size (2..8),
+          * tstr => int,
+        }
+      "#
+    );
+
+    assert_generates_valid_json(cddl)
+  }
+
+  #[test]
+  fn generate_generics_sockets_and_choices() -> std::result::Result<(), Box<dyn std::error::Error>>
+  {
+    let cddl = indoc!(
+      r#"
+        message = {
+          header: header<"v1", 1..5>,
+          color: &colors,
+          $$extension,
+        }
+        header<version, count> = [version, count, * $flag]
+        colors = (red: 1, green: 2, blue: 3)
+        $flag /= "a"
+        $flag /= "b"
+        $$extension //= (ext: float)
+      "#
+    );
+
+    assert_generates_valid_json(cddl)
+  }
+
+  #[test]
+  fn generate_byte_strings() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = indoc!(
+      r#"
+        blob = {
+          id: bstr .size 8,
+          data: bytes,
+          magic: h'cafe',
+          ? label: 'abc',
+          chunks: [* #2],
+        }
+      "#
+    );
+
+    assert_generates_valid_json(cddl)
+  }
+
+  #[test]
+  fn generate_is_deterministic_for_seed() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = cddl_from_str("root = [+ tstr / int]", true).map_err(Error::CDDLParsing)?;
+
+    let v1 = JSONGenerator::new(&cddl, Some(42)).generate()?;
+    let v2 = JSONGenerator::new(&cddl, Some(42)).generate()?;
+    assert_eq!(v1, v2);
+
+    Ok(())
+  }
+
+  #[test]
+  fn generate_from_named_rule() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = cddl_from_str("a = tstr\nb = 1..3\nc<t> = [t]", true).map_err(Error::CDDLParsing)?;
+
+    let value = JSONGenerator::new(&cddl, Some(1)).generate_from_rule("b")?;
+    assert!(matches!(value.as_u64(), Some(1..=3)));
+
+    assert!(JSONGenerator::new(&cddl, None)
+      .generate_from_rule("c")
+      .is_err());
+    assert!(JSONGenerator::new(&cddl, None)
+      .generate_from_rule("d")
+      .is_err());
+
+    Ok(())
+  }
+}
@@ -0,0 +1,680 @@
+#![cfg(not(feature = "lsp"))]
+#![cfg(not(target_arch = "wasm32"))]
+
+/// CBOR generation implementation
+pub mod cbor;
+/// JSON generation implementation
+pub mod json;
+
+use crate::{
+  ast::{
+    GenericArgs, GenericParams, GroupRule, Identifier, Occur, Rule, Type, Type1, Type2, TypeChoice,
+    TypeRule, CDDL,
+  },
+  token::{lookup_ident, ControlOperator, Token},
+  validator::{format_regex, root_rule_from_name},
+};
+
+use std::{borrow::Cow, fmt, rc::Rc};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use regex_syntax::hir::{self, Hir, HirKind};
+
+#[cfg(feature = "cbor")]
+use cbor::CBORGenerator;
+#[cfg(feature = "json")]
+use json::JSONGenerator;
+
+/// Generation Result
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Generation error
+#[derive(Debug)]
+pub enum Error {
+  /// CDDL parsing error
+  CDDLParsing(String),
+  /// Rule with the given name does not exist or cannot be used as the root of
+  /// a generated instance
+  MissingRule(String),
+  /// A value cannot be generated from the given CDDL construct
+  Unsupported(String),
+  /// No generated instance passed validation against the CDDL definition
+  Validation(String),
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Error::CDDLParsing(error) => write!(f, "error parsing CDDL: {}", error),
+      Error::MissingRule(error) => write!(f, "error finding rule: {}", error),
+      Error::Unsupported(error) => write!(f, "error generating value: {}", error),
+      Error::Validation(error) => write!(
+        f,
+        "error generating value, no generated value passed validation: {}",
+        error
+      ),
+    }
+  }
+}
+
+impl std::error::Error for Error {}
+
+/// Nesting depth after which only the minimum number of occurrences of group
+/// entries are generated, so that recursive rules terminate
+const SHALLOW_DEPTH: usize = 8;
+/// Nesting depth at which generation is abandoned
+const MAX_DEPTH: usize = 64;
+/// Number of instances generated until one passes validation
+const MAX_ATTEMPTS: usize = 32;
+/// Bound used for numeric types whose range is otherwise unconstrained
+const NUMERIC_BOUND: i128 = 1000;
+/// Upper bound for the length of unconstrained text strings
+const MAX_TEXT_LEN: usize = 12;
+/// Upper bound for the length of unconstrained byte strings
+const MAX_BYTES_LEN: usize = 16;
+
+/// Generate a JSON value conforming to a given CDDL document string. The value
+/// is generated from the rule with the given name, or from the root type rule
+/// if no name is given. Generation is deterministic for a given seed.
+/// Generated values are validated against the rule before being returned.
+#[cfg(feature = "json")]
+pub fn generate_json_from_str(
+  cddl: &str,
+  rule: Option<&str>,
+  seed: Option<u64>,
+) -> Result<serde_json::Value> {
+  let cddl = crate::cddl_from_str(cddl, true).map_err(Error::CDDLParsing)?;
+
+  let mut generator = JSONGenerator::new(&cddl, seed);
+  match rule {
+    Some(name) => generator.generate_from_rule(name),
+    None => generator.generate(),
+  }
+}
+
+/// Generate a CBOR value conforming to a given CDDL document string. The value
+/// is generated from the rule with the given name, or from the root type rule
+/// if no name is given. Generation is deterministic for a given seed.
+/// Generated values are validated against the rule before being returned.
+#[cfg(feature = "cbor")]
+pub fn generate_cbor_from_str(
+  cddl: &str,
+  rule: Option<&str>,
+  seed: Option<u64>,
+) -> Result<ciborium::value::Value> {
+  let cddl = crate::cddl_from_str(cddl, true).map_err(Error::CDDLParsing)?;
+
+  let mut generator = CBORGenerator::new(&cddl, seed);
+  match rule {
+    Some(name) => generator.generate_from_rule(name),
+    None => generator.generate(),
+  }
+}
+
+/// Generic arguments bound to the generic parameters of the rule currently
+/// being generated. Arguments are resolved in the bindings of the rule in
+/// which they were given.
+#[derive(Debug, Default)]
+struct Bindings<'a> {
+  params: Vec<(Cow<'a, str>, Type1<'a>, Rc<Bindings<'a>>)>,
+}
+
+impl<'a> Bindings<'a> {
+  /// Bind the generic arguments given with a reference to a rule to its
+  /// generic parameters
+  fn new(
+    name: &Identifier,
+    params: Option<&GenericParams<'a>>,
+    args: Option<&GenericArgs<'a>>,
+    parent: &Rc<Bindings<'a>>,
+  ) -> Result<Rc<Self>> {
+    match (params, args) {
+      (None, None) => Ok(Rc::default()),
+      (Some(params), Some(args)) if params.params.len() == args.args.len() => {
+        Ok(Rc::new(Bindings {
+          params: params
+            .params
+            .iter()
+            .zip(args.args.iter())
+            .map(|(p, a)| (p.param.ident.clone(), (*a.arg).clone(), parent.clone()))
+            .collect(),
+        }))
+      }
+      _ => Err(Error::Unsupported(format!(
+        "generic arguments given for rule {} do not match its generic parameters",
+        name
+      ))),
+    }
+  }
+
+  /// Argument bound to the generic parameter with the given name
+  fn get(&self, ident: &Identifier) -> Option<(&Type1<'a>, &Rc<Bindings<'a>>)> {
+    if ident.socket.is_some() {
+      return None;
+    }
+
+    self
+      .params
+      .iter()
+      .find(|(p, ..)| *p == ident.ident)
+      .map(|(_, arg, bindings)| (arg, bindings))
+  }
+}
+
+/// Literal value a type resolves to
+#[derive(Debug, Clone, PartialEq)]
+enum Literal {
+  Int(i128),
+  Float(f64),
+  Text(String),
+}
+
+/// Create the random number generator, seeded from the OS if no seed is given
+fn rng_from_seed(seed: Option<u64>) -> StdRng {
+  match seed {
+    Some(seed) => StdRng::seed_from_u64(seed),
+    None => StdRng::from_entropy(),
+  }
+}
+
+/// Rule to generate values from when no rule name is given. As with
+/// validation, the first non-generic type rule is the root
+fn root_rule<'a>(cddl: &'a CDDL<'a>) -> Result<&'a Rule<'a>> {
+  cddl
+    .rules
+    .iter()
+    .find(|r| matches!(r, Rule::Type { rule, .. } if rule.generic_params.is_none()))
+    .ok_or_else(|| Error::MissingRule("no non-generic type rule to use as the root".to_string()))
+}
+
+/// Non-alternate, non-generic rule with the given name
+fn rule_from_name<'a>(cddl: &'a CDDL<'a>, name: &str) -> Result<&'a Rule<'a>> {
+  root_rule_from_name(cddl, name).map_err(Error::MissingRule)
+}
+
+/// All type rules with the given name, including type choice alternates and
+/// type socket plugs
+fn type_rules_from_ident<'a>(cddl: &'a CDDL<'a>, ident: &Identifier) -> Vec<&'a TypeRule<'a>> {
+  cddl
+    .rules
+    .iter()
+    .filter_map(|r| match r {
+      Rule::Type { rule, .. } if rule.name == *ident => Some(rule),
+      _ => None,
+    })
+    .collect()
+}
+
+/// All group rules with the given name, including group choice alternates and
+/// group socket plugs
+fn group_rules_from_ident<'a>(cddl: &'a CDDL<'a>, ident: &Identifier) -> Vec<&'a GroupRule<'a>> {
+  cddl
+    .rules
+    .iter()
+    .filter_map(|r| match r {
+      Rule::Group { rule, .. } if rule.name == *ident => Some(rule.as_ref()),
+      _ => None,
+    })
+    .collect()
+}
+
+/// Type of a group entry without member key which refers to a group, i.e. a
+/// group name or an unwrapped map or array
+fn group_from_entry_type<'a, 'b>(cddl: &'a CDDL<'a>, t: &'b Type<'a>) -> Option<&'b Type2<'a>> {
+  if t.type_choices.len() != 1 || t.type_choices[0].type1.operator.is_some() {
+    return None;
+  }
+
+  match &t.type_choices[0].type1.type2 {
+    t2 @ Type2::Unwrap { .. } => Some(t2),
+    t2 @ Type2::Typename { ident, .. } if !group_rules_from_ident(cddl, ident).is_empty() => {
+      Some(t2)
+    }
+    _ => None,
+  }
+}
+
+/// Pick one of the type choices from all type rules with the given name
+fn type_choice_from_rules<'a>(
+  rng: &mut StdRng,
+  rules: &[&'a TypeRule<'a>],
+) -> Option<(&'a TypeRule<'a>, &'a TypeChoice<'a>)> {
+  let choices = rules
+    .iter()
+    .flat_map(|r| r.value.type_choices.iter().map(move |tc| (*r, tc)))
+    .collect::<Vec<_>>();
+
+  if choices.is_empty() {
+    return None;
+  }
+
+  Some(choices[rng.gen_range(0..choices.len())])
+}
+
+/// Prelude type an identifier resolves to, following rules that alias a single
+/// other type name
+fn prelude_token<'a>(cddl: &'a CDDL<'a>, ident: &Identifier<'a>) -> Option<Token<'a>> {
+  let mut ident = ident.clone();
+  for _ in 0..MAX_DEPTH {
+    let rules = type_rules_from_ident(cddl, &ident);
+    if rules.is_empty() {
+      return lookup_ident(&ident.ident)
+        .in_standard_prelude()
+        .map(lookup_ident);
+    }
+
+    match rules.as_slice() {
+      [rule] if rule.value.type_choices.len() == 1 => {
+        let type1 = &rule.value.type_choices[0].type1;
+        match &type1.type2 {
+          Type2::Typename {
+            ident: alias,
+            generic_args: None,
+            ..
+          } if type1.operator.is_none() => ident = alias.clone(),
+          _ => return None,
+        }
+      }
+      _ => return None,
+    }
+  }
+
+  None
+}
+
+/// Literal value of a given type, following rules which define a single value
+fn literal_from_type2<'a>(
+  cddl: &'a CDDL<'a>,
+  t2: &Type2<'a>,
+  bindings: &Rc<Bindings<'a>>,
+) -> Option<Literal> {
+  match t2 {
+    Type2::IntValue { value, .. } => Some(Literal::Int(*value as i128)),
+    Type2::UintValue { value, .. } => Some(Literal::Int(*value as i128)),
+    Type2::FloatValue { value, .. } => Some(Literal::Float(*value)),
+    Type2::TextValue { value, .. } => Some(Literal::Text(value.to_string())),
+    Type2::ParenthesizedType { pt, .. } if pt.type_choices.len() == 1 => {
+      let type1 = &pt.type_choices[0].type1;
+      if type1.operator.is_some() {
+        return None;
+      }
+
+      literal_from_type2(cddl, &type1.type2, bindings)
+    }
+    Type2::Typename { ident, .. } => {
+      if let Some((arg, bindings)) = bindings.get(ident) {
+        if arg.operator.is_some() {
+          return None;
+        }
+
+        return literal_from_type2(cddl, &arg.type2, bindings);
+      }
+
+      match type_rules_from_ident(cddl, ident).as_slice() {
+        [rule] if rule.value.type_choices.len() == 1 && rule.generic_params.is_none() => {
+          let type1 = &rule.value.type_choices[0].type1;
+          if type1.operator.is_some() {
+            return None;
+          }
+
+          literal_from_type2(cddl, &type1.type2, &Rc::default())
+        }
+        _ => None,
+      }
+    }
+    _ => None,
+  }
+}
+
+/// Integer value of a given type
+fn int_from_type2<'a>(
+  cddl: &'a CDDL<'a>,
+  t2: &Type2<'a>,
+  bindings: &Rc<Bindings<'a>>,
+) -> Option<i128> {
+  match literal_from_type2(cddl, t2, bindings) {
+    Some(Literal::Int(i)) => Some(i),
+    _ => None,
+  }
+}
+
+/// Inclusive bounds of a `.size` controller, which is either an unsigned
+/// integer or a range of unsigned integers
+fn size_bounds<'a>(
+  cddl: &'a CDDL<'a>,
+  controller: &Type2<'a>,
+  bindings: &Rc<Bindings<'a>>,
+) -> Result<(usize, usize)> {
+  if let Some(size) = int_from_type2(cddl, controller, bindings) {
+    if size >= 0 {
+      return Ok((size as usize, size as usize));
+    }
+  }
+
+  if let Type2::ParenthesizedType { pt, .. } = controller {
+    if pt.type_choices.len() == 1 {
+      let type1 = &pt.type_choices[0].type1;
+      if let Some(crate::ast::Operator {
+        operator: crate::ast::RangeCtlOp::RangeOp { is_inclusive, .. },
+        type2: upper,
+        ..
+      }) = &type1.operator
+      {
+        let lower = int_from_type2(cddl, &type1.type2, bindings);
+        let upper = int_from_type2(cddl, upper, bindings);
+        if let (Some(lower), Some(upper)) = (lower, upper) {
+          let upper = if *is_inclusive { upper } else { upper - 1 };
+          if lower >= 0 && lower <= upper {
+            return Ok((lower as usize, upper as usize));
+          }
+        }
+      }
+    }
+  }
+
+  Err(Error::Unsupported(format!(
+    "invalid .size controller {}",
+    controller
+  )))
+}
+
+/// Prelude type of the target of a control operator
+fn target_token<'a>(cddl: &'a CDDL<'a>, target: &Type2<'a>) -> Option<Token<'a>> {
+  match target {
+    Type2::Typename { ident, .. } => prelude_token(cddl, ident),
+    _ => None,
+  }
+}
+
+/// Number of the given numeric target type satisfying a `.lt`, `.le`, `.gt`
+/// or `.ge` control operator
+fn number_from_control<'a>(
+  rng: &mut StdRng,
+  cddl: &'a CDDL<'a>,
+  target: &Type2<'a>,
+  ctrl: ControlOperator,
+  controller: &Type2<'a>,
+  bindings: &Rc<Bindings<'a>>,
+) -> Result<Literal> {
+  let value = literal_from_type2(cddl, controller, bindings);
+
+  match target_token(cddl, target) {
+    Some(t @ Token::UINT)
+    | Some(t @ Token::UNSIGNED)
+    | Some(t @ Token::NINT)
+    | Some(t @ Token::INT)
+    | Some(t @ Token::INTEGER) => {
+      let value = match value {
+        Some(Literal::Int(i)) => i,
+        Some(Literal::Float(f)) => match ctrl {
+          ControlOperator::LT | ControlOperator::GE => f.ceil() as i128,
+          _ => f.floor() as i128,
+        },
+        _ => {
+          return Err(Error::Unsupported(format!(
+            "{} control requires a numeric controller, got {}",
+            ctrl, controller
+          )))
+        }
+      };
+
+      let bounds = match t {
+        Token::UINT | Token::UNSIGNED => (Some(0), None),
+        Token::NINT => (None, Some(-1)),
+        _ => (None, None),
+      };
+
+      int_in_bounds(rng, apply_numeric_control(bounds, ctrl, value)).map(Literal::Int)
+    }
+    Some(Token::FLOAT)
+    | Some(Token::FLOAT16)
+    | Some(Token::FLOAT32)
+    | Some(Token::FLOAT64)
+    | Some(Token::FLOAT1632)
+    | Some(Token::FLOAT3264)
+    | Some(Token::NUMBER) => {
+      let value = match value {
+        Some(Literal::Int(i)) => i as f64,
+        Some(Literal::Float(f)) => f,
+        _ => {
+          return Err(Error::Unsupported(format!(
+            "{} control requires a numeric controller, got {}",
+            ctrl, controller
+          )))
+        }
+      };
+
+      let bound = NUMERIC_BOUND as f64;
+      let (lower, upper) = match ctrl {
+        ControlOperator::LT => (value - bound, value - 0.01),
+        ControlOperator::LE => (value - bound, value),
+        ControlOperator::GT => (value + 0.01, value + bound),
+        _ => (value, value + bound),
+      };
+
+      float_in_bounds(rng, lower, upper).map(Literal::Float)
+    }
+    _ => Err(Error::Unsupported(format!(
+      "{} control on {} requires a numeric type",
+      ctrl, target
+    ))),
+  }
+}
+
+/// Inclusive bounds of a type's value range after applying a numeric control
+/// operator with the given controller value
+fn apply_numeric_control(
+  bounds: (Option<i128>, Option<i128>),
+  ctrl: ControlOperator,
+  value: i128,
+) -> (Option<i128>, Option<i128>) {
+  let (lower, upper) = bounds;
+  match ctrl {
+    ControlOperator::LT => (lower, Some(upper.map_or(value - 1, |u| u.min(value - 1)))),
+    ControlOperator::LE => (lower, Some(upper.map_or(value, |u| u.min(value)))),
+    ControlOperator::GT => (Some(lower.map_or(value + 1, |l| l.max(value + 1))), upper),
+    ControlOperator::GE => (Some(lower.map_or(value, |l| l.max(value))), upper),
+    _ => (lower, upper),
+  }
+}
+
+/// Pick an integer within the given optional inclusive bounds
+fn int_in_bounds(rng: &mut StdRng, bounds: (Option<i128>, Option<i128>)) -> Result<i128> {
+  let (lower, upper) = match bounds {
+    (Some(l), Some(u)) => (l, u),
+    (Some(l), None) => (l, l.saturating_add(NUMERIC_BOUND)),
+    (None, Some(u)) => (u.saturating_sub(NUMERIC_BOUND), u),
+    (None, None) => (-NUMERIC_BOUND, NUMERIC_BOUND),
+  };
+
+  if lower > upper {
+    return Err(Error::Unsupported(format!(
+      "empty integer range {}..{}",
+      lower, upper
+    )));
+  }
+
+  Ok(rng.gen_range(lower..=upper))
+}
+
+/// Pick a float within the given bounds. The value always has a fractional
+/// part so that it is not mistaken for an integer
+fn float_in_bounds(rng: &mut StdRng, lower: f64, upper: f64) -> Result<f64> {
+  if lower > upper {
+    return Err(Error::Unsupported(format!(
+      "empty float range {}..{}",
+      lower, upper
+    )));
+  }
+
+  for _ in 0..MAX_ATTEMPTS {
+    let f = if lower == upper {
+      lower
+    } else {
+      rng.gen_range(lower..=upper)
+    };
+    let f = (f * 100.0).round() / 100.0;
+    if f >= lower && f <= upper && f.fract() != 0.0 {
+      return Ok(f);
+    }
+  }
+
+  Ok(lower)
+}
+
+/// Number of occurrences of a group entry with the given occurrence indicator
+fn occurrence_count(rng: &mut StdRng, occur: Option<&Occur>, depth: usize) -> usize {
+  let (lower, upper) = match occur {
+    Some(Occur::ZeroOrMore { .. }) => (0, 2),
+    Some(Occur::OneOrMore { .. }) => (1, 3),
+    Some(Occur::Optional { .. }) => (0, 1),
+    Some(Occur::Exact { lower, upper, .. }) => {
+      let lower = lower.unwrap_or(0);
+      (lower, upper.unwrap_or(lower + 2).min(lower + 2))
+    }
+    None => (1, 1),
+  };
+
+  if depth >= SHALLOW_DEPTH {
+    return lower;
+  }
+
+  rng.gen_range(lower..=upper)
+}
+
+/// Random alphanumeric text string with the given length
+fn text_of_len(rng: &mut StdRng, len: usize) -> String {
+  const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+  (0..len)
+    .map(|_| CHARS[rng.gen_range(0..CHARS.len())] as char)
+    .collect()
+}
+
+/// Random alphanumeric text string of arbitrary length
+fn text(rng: &mut StdRng) -> String {
+  let len = rng.gen_range(1..=MAX_TEXT_LEN);
+  text_of_len(rng, len)
+}
+
+/// Random byte string with the given length
+fn bytes_of_len(rng: &mut StdRng, len: usize) -> Vec<u8> {
+  (0..len).map(|_| rng.gen()).collect()
+}
+
+/// Random byte string of arbitrary length
+fn bytes(rng: &mut StdRng) -> Vec<u8> {
+  let len = rng.gen_range(0..=MAX_BYTES_LEN);
+  bytes_of_len(rng, len)
+}
+
+/// Text string matching the given `.regexp` or `.pcre` pattern
+fn text_from_regex(rng: &mut StdRng, pattern: &str) -> Result<String> {
+  let re = format_regex(pattern)
+    .ok_or_else(|| Error::Unsupported(format!("unsupported regex pattern {}", pattern)))?;
+  let hir = regex_syntax::Parser::new()
+    .parse(&re)
+    .map_err(|e| Error::Unsupported(format!("invalid regex pattern {}: {}", pattern, e)))?;
+
+  let mut s = String::new();
+  text_from_hir(rng, &hir, &mut s)?;
+
+  Ok(s)
+}
+
+fn text_from_hir(rng: &mut StdRng, hir: &Hir, s: &mut String) -> Result<()> {
+  match hir.kind() {
+    HirKind::Empty | HirKind::Anchor(_) | HirKind::WordBoundary(_) => (),
+    HirKind::Literal(hir::Literal::Unicode(c)) => s.push(*c),
+    HirKind::Literal(hir::Literal::Byte(b)) => s.push(*b as char),
+    HirKind::Class(hir::Class::Unicode(class)) => {
+      // Prefer printable ASCII characters where the class allows
+      let ranges = class
+        .ranges()
+        .iter()
+        .filter_map(|r| {
+          let start = r.start().max(' ');
+          let end = r.end().min('~');
+          if start <= end {
+            Some((start, end))
+          } else {
+            None
+          }
+        })
+        .collect::<Vec<_>>();
+
+      let (start, end) = if ranges.is_empty() {
+        let ranges = class.ranges();
+        if ranges.is_empty() {
+          return Err(Error::Unsupported(
+            "empty regex character class".to_string(),
+          ));
+        }
+        let r = ranges[rng.gen_range(0..ranges.len())];
+        (r.start(), r.end())
+      } else {
+        ranges[rng.gen_range(0..ranges.len())]
+      };
+
+      let c = (0..MAX_ATTEMPTS)
+        .find_map(|_| std::char::from_u32(rng.gen_range(start as u32..=end as u32)))
+        .unwrap_or(start);
+      s.push(c);
+    }
+    HirKind::Class(hir::Class::Bytes(class)) => {
+      let ranges = class.ranges();
+      if ranges.is_empty() {
+        return Err(Error::Unsupported(
+          "empty regex character class".to_string(),
+        ));
+      }
+      let r = ranges[rng.gen_range(0..ranges.len())];
+      let b = rng.gen_range(r.start().min(0x7f)..=r.end().min(0x7f));
+      s.push(b as char);
+    }
+    HirKind::Repetition(repetition) => {
+      let (lower, upper) = match &repetition.kind {
+        hir::RepetitionKind::ZeroOrOne => (0, 1),
+        hir::RepetitionKind::ZeroOrMore => (0, 3),
+        hir::RepetitionKind::OneOrMore => (1, 4),
+        hir::RepetitionKind::Range(hir::RepetitionRange::Exactly(n)) => (*n, *n),
+        hir::RepetitionKind::Range(hir::RepetitionRange::AtLeast(n)) => (*n, *n + 3),
+        hir::RepetitionKind::Range(hir::RepetitionRange::Bounded(m, n)) => (*m, *n),
+      };
+
+      for _ in 0..rng.gen_range(lower..=upper) {
+        text_from_hir(rng, &repetition.hir, s)?;
+      }
+    }
+    HirKind::Group(group) => text_from_hir(rng, &group.hir, s)?,
+    HirKind::Concat(hirs) => {
+      for hir in hirs.iter() {
+        text_from_hir(rng, hir, s)?;
+      }
+    }
+    HirKind::Alternation(hirs) => {
+      let hir = &hirs[rng.gen_range(0..hirs.len())];
+      text_from_hir(rng, hir, s)?;
+    }
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn generate_text_from_regex() -> Result<()> {
+    let re = regex::Regex::new("^[A-Z]{2}-[0-9]{3,5}(x|yz)?$").unwrap();
+
+    let mut rng = rng_from_seed(Some(1));
+    for _ in 0..20 {
+      let s = text_from_regex(&mut rng, "^[A-Z]{2}-[0-9]{3,5}(x|yz)?$")?;
+      assert!(re.is_match(&s), "{} does not match", s);
+    }
+
+    Ok(())
+  }
+}
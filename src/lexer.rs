@@ -3,7 +3,7 @@ use super::{
     ErrorMsg,
     MsgType::{self, *},
   },
-  token::{self, ByteValue, Token, Value},
+  token::{self, ByteValue, ControlOperator, Token, Value},
 };
 use codespan_reporting::{
   diagnostic::{Diagnostic, Label},
@@ -274,6 +274,9 @@ pub struct Lexer<'a> {
   multipeek: itertools::MultiPeek<CharIndices<'a>>,
   /// Lexer position in input
   pub position: Position,
+  // Names of custom control operators recognized in addition to the
+  // predefined ones
+  custom_controls: Vec<&'static str>,
 }
 
 /// Iterator over a lexer
@@ -316,9 +319,17 @@ impl<'a> Lexer<'a> {
         range: (0, 0),
         index: 0,
       },
+      custom_controls: Vec::new(),
     }
   }
 
+  /// Recognize control operators with the given names, including the leading
+  /// `.`, as custom control operators in addition to the predefined ones
+  pub fn with_custom_controls(mut self, names: &[&'static str]) -> Self {
+    self.custom_controls = names.to_vec();
+    self
+  }
+
   /// Creates a Lexer from a byte slice
   pub fn from_slice(input: &[u8]) -> Lexer {
     let str_input = std::str::from_utf8(input).unwrap();
@@ -550,8 +561,16 @@ impl<'a> Lexer<'a> {
               return Ok((self.position, Token::RANGEOP(true)));
             } else if is_ealpha(c.1) {
               // Controlop
-              let ctrlop =
-                token::lookup_control_from_str(self.read_identifier(idx)?).ok_or_else(|| {
+              let ident = self.read_identifier(idx)?;
+              let ctrlop = token::lookup_control_from_str(ident)
+                .or_else(|| {
+                  self
+                    .custom_controls
+                    .iter()
+                    .find(|name| **name == ident)
+                    .map(|name| ControlOperator::CUSTOM(name))
+                })
+                .ok_or_else(|| {
                   self.position.range = (token_offset, self.position.index + 1);
 
                   Error::from((self.str_input, self.position, InvalidControlOperator))
@@ -43,7 +43,7 @@
 //! - [x] Validate CBOR data structures
 //! - [x] Validate JSON documents
 //! - [x] Basic REPL
-//! - [ ] Generate dummy JSON from conformant CDDL
+//! - [x] Generate dummy JSON from conformant CDDL
 //! - [x] As close to zero-copy as possible
 //! - [x] Compile WebAssembly target for browser and Node.js
 //! - [x] `no_std` support (lexing and parsing only)
@@ -54,7 +54,6 @@
 //! - Performance (if this crate gains enough traction, it may be prudent to
 //!   conduct more formal profiling and/or explore using a parser-combinator
 //!   framework like [nom](https://github.com/Geal/nom))
-//! - Support CBOR diagnostic notation
 //! - I-JSON compatibility
 //!
 //! ## Why Rust?
@@ -115,7 +114,7 @@
 //! You can validate JSON documents and/or CBOR binary files:
 //!
 //! ```sh
-//! cddl validate [OPTIONS] --cddl <CDDL> <--stdin|--json <JSON>...|--cbor <CBOR>...>
+//! cddl validate [OPTIONS] --cddl <CDDL> <--stdin|--json <JSON>...|--json-lines <JSON_LINES>...|--json-seq <JSON_SEQ>...|--cbor <CBOR>...|--cbor-seq <CBOR_SEQ>...|--edn <EDN>...>
 //! ```
 //!
 //! It also supports validating files from STDIN (if it detects the input as
@@ -133,6 +132,87 @@
 //! docker run -i --rm -v $PWD:/data -w /data ghcr.io/anweiss/cddl-cli:0.9.1 validate --cddl reputon.cddl --stdin < reputon.json
 //! ```
 //!
+//! By default, documents are validated against the first type rule in the CDDL
+//! definition. Use `--rule` to validate against any other named type or group
+//! rule instead:
+//!
+//! ```sh
+//! cddl validate --cddl messages.cddl --rule response --json response.json
+//! ```
+//!
+//! Validation results can also be written to STDOUT as a machine-readable
+//! report for CI systems using `--output json`, `--output sarif` or `--output
+//! junit`. The JSON report contains one record per validated document with the
+//! fields of each validation error. Documents that do not exist are reported as
+//! invalid, with the reason `file does not exist`:
+//!
+//! ```sh
+//! cddl validate --cddl reputon.cddl --json reputon.json --output sarif > cddl.sarif
+//! ```
+//!
+//! Maps with duplicate keys and JSON objects with duplicate member names are
+//! always reported as validation errors at the location of the offending map
+//! or object, since RFC 8949 section 5.6 and RFC 8259 section 4 leave their
+//! interpretation undefined.
+//!
+//! Protocols such as COSE and CTAP require CBOR to use the core deterministic
+//! encoding of RFC 8949 section 4.2.1. Use `--deterministic` to additionally
+//! report integers and lengths that are not encoded in their shortest form,
+//! floats that are not encoded in their narrowest width, indefinite-length
+//! items and map keys that are not sorted in the bytewise lexicographic order
+//! of their encodings, along with the CBOR location of each:
+//!
+//! ```sh
+//! cddl validate --cddl cose.cddl --cbor message.cbor --deterministic
+//! ```
+//!
+//! A file holding a CBOR sequence (RFC 8742), i.e. CBOR data items concatenated
+//! without an enclosing array, such as a log of records, can be validated as a
+//! whole with `--cbor-seq`. The data items are read one at a time and matched
+//! against the first rule in the CDDL definition, or the rule given with
+//! `--rule`, which must be a group rule or an array type rule, as if they were
+//! the items of an array. Validation continues past data items that fail to
+//! match, and errors are reported with the index of each offending data item in
+//! the sequence. The encoding of the data items is not checked, so `--cbor-seq`
+//! cannot be combined with `--deterministic`:
+//!
+//! ```sh
+//! cddl validate --cddl log.cddl --rule log --cbor-seq log.cbor
+//! ```
+//!
+//! CBOR data items written in extended diagnostic notation (EDN, RFC 8949
+//! section 8 and RFC 8610 appendix G), such as hand-written test vectors, can
+//! be validated with `--edn`. Each file is encoded into CBOR before being
+//! validated as with `--cbor`, honoring the encoding indicators it holds, such
+//! as `_1` or the `_` of indefinite-length items, so they can be checked with
+//! `--deterministic`:
+//!
+//! ```sh
+//! cddl validate --cddl reputon.cddl --edn reputon.diag
+//! ```
+//!
+//! Logs in JSON Lines (NDJSON) format and JSON text sequences (RFC 7464) can be
+//! validated record by record with `--json-lines` and `--json-seq`. Each line
+//! or JSON text is validated independently against the root type rule, or the
+//! rule given with `--rule`. Validation continues past records that fail to
+//! parse or validate, and each error is reported with the number of the record
+//! and of the line it starts on, alongside its JSON location:
+//!
+//! ```sh
+//! cddl validate --cddl event.cddl --json-lines events.ndjson --json-seq feed.json-seq
+//! ```
+//!
+//! You can also generate a JSON document and/or a CBOR binary file conforming
+//! to a CDDL definition. The document is generated from the root type rule
+//! unless a rule name is given, and generation is reproducible when a seed is
+//! given. JSON is printed to STDOUT and CBOR is written to the given file:
+//!
+//! ```sh
+//! cddl generate [OPTIONS] --cddl <CDDL> <--json|--cbor <CBOR>>
+//! cddl generate --cddl reputon.cddl --json --rule reputon --seed 42
+//! cddl generate --cddl reputon.cddl --cbor reputon.cbor --seed 42
+//! ```
+//!
 //! ## Website
 //!
 //! You can also find a simple RFC 8610 conformance tool at
@@ -229,6 +309,24 @@
 //! assert!(cddl_from_str(input, true).is_ok())
 //! ```
 //!
+//! The AST returned by `cddl_from_str` borrows from its input.
+//! `CDDL::into_owned` converts it into a `CDDL<'static>` that owns all of its
+//! data, and `cddl::ast::OwnedCDDL` parses an owned AST that is cheap to clone.
+//! Both are `'static`, `Send` and `Sync` and can be cached or shared across
+//! threads in an `Arc`:
+//!
+//! ```rust
+//! use cddl::{ast::OwnedCDDL, cddl_from_str};
+//!
+//! let input = String::from("myrule = int");
+//! let cddl = cddl_from_str(&input, true).unwrap().into_owned();
+//! drop(input);
+//! assert_eq!(cddl.rules.len(), 1);
+//!
+//! let cddl = OwnedCDDL::parse(String::from("myrule = int")).unwrap();
+//! assert_eq!(cddl.cddl().rules.len(), 1);
+//! ```
+//!
 //! ### Validating JSON
 //!
 //! ```rust
@@ -319,7 +417,7 @@
 //! | `bool / true / false`  | boolean                                                     |
 //! | `null / nil`           | null                                                        |
 //! | `any`                  | any valid JSON                                              |
-//! | byte strings           | not yet implemented                                         |
+//! | byte strings           | string<sup>[4](#bytes)</sup>                                |
 //! | unwrap (`~`)           | any JSON that matches unwrapped type from map, array or tag |
 //!
 //! CDDL groups, generics, sockets/plugs and group-to-choice enumerations can
@@ -343,9 +441,9 @@
 //! | `.pcre`          | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji><sup>[3](#regex)</sup>                     |
 //! | `.regex`         | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji><sup>[3](#regex)</sup> (alias for `.pcre`) |
 //! | `.size`          | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji>                                           |
-//! | `.bits`          | Ignored when validating JSON                                                                                                                                                                |
-//! | `.cbor`          | Ignored when validating JSON                                                                                                                                                                |
-//! | `.cborseq`       | Ignored when validating JSON                                                                                                                                                                |
+//! | `.bits`          | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji><sup>[4](#bytes)</sup>                     |
+//! | `.cbor`          | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji><sup>[4](#bytes)</sup>                     |
+//! | `.cborseq`       | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji><sup>[4](#bytes)</sup>                     |
 //! | `.within`        | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji>                                           |
 //! | `.and`           | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji>                                           |
 //! | `.lt`            | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji>                                           |
@@ -357,12 +455,12 @@
 //! | `.default`       | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji>                                           |
 //!
 //! <a name="arrays">1</a>: When groups with multiple group entries are used to
-//! validate arrays, occurrence indicators are "greedy" in that only the first
-//! occurrence indicator that is come across is used in the validation.
-//! Subsequent entries with occurrence indicators are ignored due to
-//! complexities involved with processing these ambiguities. For proper JSON
-//! validation, avoid writing CDDL that looks like the following: `[ * a: int,
-//! b: tstr, ? c: int ]`.
+//! validate arrays, the array items are matched against all of the group's
+//! entries and occurrence indicators with backtracking, so arrays like `[ * a:
+//! int, b: tstr, ? c: int ]` validate as expected. Arrays with control
+//! operators, and arrays whose groups reference recursive or generic group
+//! rules, fall back to entry-by-entry validation in which only the first
+//! occurrence indicator that is come across is used.
 //!
 //! <a name="number">2</a>: While JSON itself does not distinguish between
 //! integers and floating-point numbers, this crate does provide the ability to
@@ -381,6 +479,15 @@
 //! Ensure that your regex string is properly JSON escaped when using this
 //! control.
 //!
+//! <a name="bytes">4</a>: Following the convention of [Appendix
+//! E.](https://tools.ietf.org/html/rfc8610#appendix-E), JSON strings are
+//! validated against byte string types and values by decoding them as base64url
+//! without padding. Base16 encoded byte strings can be validated instead with
+//! `JSONValidator::with_byte_string_encoding` or
+//! `ValidationOptions::with_base16_byte_strings`. The `.size`, `.bits`,
+//! `.cbor`, `.cborseq` and `.abnfb` controls are applied to the decoded bytes,
+//! so CBOR data items embedded in JSON documents are validated as well.
+//!
 //! If you've enabled the `additional-controls` feature, the table of controls
 //! below is also available for use:
 //!
@@ -390,8 +497,42 @@
 //! | `.cat`           | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
 //! | `.det`           | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
 //! | `.abnf`          | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
-//! | `.abnfb`         | Ignored when validating JSON                                                                                                                      |
+//! | `.abnfb`         | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji><sup>[4](#bytes)</sup> |
 //! | `.feature`       | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.b64u`          | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.b64u-sloppy`   | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.b64c`          | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.b64c-sloppy`   | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.b45`           | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.b32`           | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.h32`           | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.hex`           | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.hexlc`         | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.hexuc`         | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.decimal`       | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.printf`        | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.json`          | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.join`          | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//!
+//! The text conversion controls of [RFC
+//! 9741](https://www.rfc-editor.org/rfc/rfc9741) decode the targeted text
+//! string and validate the resulting bytes against the controller, e.g. `text
+//! .b64u (bstr .size 32)`. Only the canonical encoding of the bytes is
+//! accepted, except that the `-sloppy` variants allow non-zero unused bits in
+//! the last character.
+//!
+//! `.decimal` matches a text string that is the decimal representation of an
+//! integer matching the controller, e.g. `text .decimal (0..255)`. `.printf`
+//! formats the array of literal arguments in the controller with the format
+//! string in the target, e.g. `"0x%04x" .printf [4711]`, using the `%d`, `%i`,
+//! `%x`, `%X`, `%o`, `%b`, `%c`, `%s` and `%f` conversions. Like `.cat`, the
+//! formatted text string can be composed with other `.cat` operations.
+//!
+//! `.json` parses the targeted text string as JSON and validates the resulting
+//! document against the controller, e.g. `text .json { name: tstr }`.
+//! Validating CBOR against `.json` requires the `json` feature. `.join` matches
+//! a text string that can be split into pieces forming an array that matches
+//! the controller, e.g. `text .join [bytetext, ".", bytetext]`.
 //!
 //! You can activate features during validation as follows:
 //!
@@ -443,7 +584,11 @@
 //! validator, this crate also supports validating CBOR tags (e.g.
 //! `#6.32(tstr)`), CBOR major types (e.g. `#1.2`), table types (e.g. `{ [ +
 //! tstr ] => int }`) and byte strings. The `.bits`, `.cbor` and `.cborseq`
-//! control operators are all supported as well.
+//! control operators are all supported as well. A byte string controlled by
+//! `.cborseq` is decoded as a [CBOR
+//! sequence](https://www.rfc-editor.org/rfc/rfc8742), i.e. zero or more
+//! concatenated data items, which are matched against the items of the
+//! controlling array (e.g. `bstr .cborseq [* int]`).
 //!
 //! The following tags are supported when validating CBOR:
 //!
@@ -453,8 +598,8 @@
 //! | `time = #6.1(number)`                    | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
 //! | `biguint = #6.2(bstr)`                   | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
 //! | `bignint = #6.3(bstr)`                   | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
-//! | `decfrac = #6.4([e10: int, m: integer])` | not yet implemented                                                                                                                               |
-//! | `bigfloat = #6.5([e2: int, m: integer])` | not yet implemented                                                                                                                               |
+//! | `decfrac = #6.4([e10: int, m: integer])` | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `bigfloat = #6.5([e2: int, m: integer])` | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
 //! | `eb64url = #6.21(any)`                   | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
 //! | `eb64legacy = #6.22(any)`                | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
 //! | `eb16 = #6.23(any)`                      | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
@@ -466,6 +611,32 @@
 //! | `mime-message = #6.36(tstr)`             | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
 //! | `cbor-any = #6.55799(any)`               | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
 //!
+//! Standard prelude types defined with a tag, such as `tdate`, `biguint` or
+//! `uri`, match data items with that tag whose content matches the tagged type
+//! of their definition, e.g. `2(h'0102')` matches `biguint` and `bigint`.
+//!
+//! Decimal fractions and bigfloats can also be compared against numeric values
+//! using the `.lt`, `.le`, `.gt`, `.ge`, `.eq` and `.ne` control operators as
+//! well as ranges (e.g. `decfrac .lt 100` or `0.0..1.0`). Comparisons use the
+//! exact value of the data item, including bignum mantissas, rather than a
+//! floating point approximation.
+//!
+//! Major type 7 constraints are validated against the encoding of the data
+//! item, so `#7.25`, `#7.26` and `#7.27` match floats encoded in at most 16, 32
+//! and 64 bits respectively, and `#7.n` matches the simple value `n`, including
+//! `undefined` (`#7.23`) and unassigned simple values that `ciborium` cannot
+//! represent. Float widths are upper bounds rather than exact widths, since
+//! preferred serialization encodes a float in the fewest bits that preserve its
+//! value, e.g. `1.0` in 16 bits. The `float16`, `float32`, `float16-32` and
+//! `float64` prelude types are defined with these constraints and match the
+//! same floats, e.g. a half- or single-precision float matches both `float32`
+//! and `#7.26`, while a double-precision float matches neither. When validating
+//! an already decoded `ciborium::value::Value`, the encoding is unknown, so
+//! floats only need to be representable at the given width and `null` matches
+//! both `#7.22` and `#7.23`. Data items whose arguments or floats are not
+//! encoded in their shortest form can be reported by
+//! `CBORValidator::with_preferred_serialization` (RFC 8949 section 4.1).
+//!
 //! If you've enabled the `additional-controls` feature, the table of controls
 //! below is also available for use:
 //!
@@ -477,6 +648,20 @@
 //! | `.abnf`          | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
 //! | `.abnfb`         | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
 //! | `.feature`       | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.b64u`          | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.b64u-sloppy`   | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.b64c`          | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.b64c-sloppy`   | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.b45`           | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.b32`           | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.h32`           | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.hex`           | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.hexlc`         | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.hexuc`         | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.decimal`       | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.printf`        | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.json`          | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
+//! | `.join`          | <g-emoji class="g-emoji" alias="heavy_check_mark" fallback-src="https://github.githubassets.com/images/icons/emoji/unicode/2714.png">✔️</g-emoji> |
 //!
 //! You can activate features during validation by passing a slice of feature
 //! strings as follows:
@@ -494,6 +679,235 @@
 //! assert!(validate_cbor_from_slice(cddl, cbor, Some(&["cbor"])).is_ok())
 //! ```
 //!
+//! ### Validating with a parsed schema
+//!
+//! `cddl::Schema` parses a CDDL definition once, owning its source, for
+//! validating any number of JSON and CBOR documents. Unlike the functions above,
+//! the signatures of its methods do not depend on the enabled cargo features or
+//! the target. The `Error` and `Document` enums of `cddl::validator::schema` are
+//! `#[non_exhaustive]`, as their JSON and CBOR variants only exist with the
+//! `json` and `cbor` cargo features, so matching on them requires a wildcard
+//! arm. Validation is configured with `cddl::ValidationOptions`, which sets the
+//! rule to validate against instead of the root type rule, the features enabled
+//! for the `.feature` control operator, limits on the size and nesting depth of
+//! documents and whether to report only the first validation error:
+//!
+//! ```rust
+//! use cddl::{Schema, ValidationOptions};
+//!
+//! let schema = Schema::new(
+//!   r#"
+//!     person = {
+//!       name: tstr,
+//!       age: uint,
+//!     }
+//!   "#,
+//! )
+//! .unwrap();
+//!
+//! let options = ValidationOptions::new()
+//!   .with_root_rule("person")
+//!   .with_max_size(1 << 20)
+//!   .with_max_depth(16)
+//!   .with_first_error_only();
+//!
+//! assert!(schema.validate_json(r#"{ "name": "John", "age": 42 }"#, &options).is_ok());
+//! // { "name": "John", "age": 42 }
+//! assert!(schema.validate_cbor(b"\xa2\x64name\x64John\x63age\x18\x2a", &options).is_ok());
+//! assert!(schema.validate_value(serde_json::json!({ "name": "John" }), &options).is_err());
+//! ```
+//!
+//! A `Schema` is compiled once it is parsed: the alternates of type and group
+//! sockets are merged into a single rule, each instantiation of a generic rule
+//! is replaced by a rule in which its generic parameters are substituted by the
+//! arguments, the resulting rules are indexed by name, and the regular
+//! expressions and ABNF grammars of the `.regexp`, `.pcre`, `.abnf` and
+//! `.abnfb` control operators are compiled ahead of time rather than for each
+//! validated value. When using `JSONValidator` or `CBORValidator` directly, the
+//! same is achieved by passing a `cddl::validator::compiled::CompiledSchema` to
+//! their `with_compiled_schema` method, after which they validate against the
+//! resolved AST of the compiled schema.
+//!
+//! Large CBOR documents can be validated as they are read from any
+//! `std::io::Read` with `Schema::validate_cbor_reader` or
+//! `cddl::validate_cbor_from_reader`, without first decoding them into a
+//! `ciborium::value::Value`. Arrays and maps are validated entry by entry as
+//! they are decoded, so only individual entries are buffered, up to the limit
+//! set by `ValidationOptions::with_max_buffer_size` (1 MiB by default). Data
+//! items that cannot be validated entry by entry, such as arrays or maps
+//! matched against several type choices of the same major type, are decoded as
+//! a whole within the same limit. Duplicate map keys are detected by retaining
+//! the preferred serialization of each key, for up to as many keys per map as
+//! fit in the same limit.
+//!
+//! CBOR sequences (RFC 8742) are validated item by item with
+//! `Schema::validate_cbor_seq_reader` or `cddl::validate_cbor_seq_from_reader`,
+//! matching their data items against a group rule or an array type rule until
+//! the end of the input. Errors are located at the index of each data item in
+//! the sequence, and data items that fail to match are skipped so that the
+//! remaining ones are still validated.
+//!
+//! JSON Lines files and JSON text sequences (RFC 7464) are validated record by
+//! record with `cddl::validate_json_records_from_reader`, or
+//! `cddl::validator::json_seq::validate_records` given a parsed CDDL AST. The
+//! errors of every failing record are returned along with the number of the
+//! record and of the line it starts on.
+//!
+//! The `cddl::edn` module converts between EDN and CBOR. `edn::to_vec` encodes
+//! EDN into CBOR bytes, including byte strings written as `h''`, `b32''`,
+//! `h32''` or `b64''` literals, embedded CBOR written as `<< >>`, tags,
+//! comments and encoding indicators, and `edn::from_str` decodes it into a
+//! `ciborium::value::Value`. `edn::to_string` prints a `Value` as EDN, and
+//! `edn::to_string_from_slice` prints encoded CBOR along with the encoding
+//! indicators needed to encode it back into the same bytes.
+//!
+//! ### Custom control operators
+//!
+//! Control operators other than the ones above can be added to a
+//! `cddl::validator::custom::CustomControls` set, providing the name of the
+//! control operator and a callback. CDDL parsed with
+//! `CustomControls::cddl_from_str` accepts the control operators of the set,
+//! and validators given the set with `with_custom_controls` invoke their
+//! callbacks. The callback receives the data item matching the target type of
+//! the control operator, along with the target and controller types, and
+//! returns the reason the data item is invalid. Sets are independent of each
+//! other, so the control operators of one set have no effect on CDDL parsed or
+//! validated without it. A `cddl::Schema` is created with a set of custom
+//! control operators with `Schema::with_custom_controls`:
+//!
+//! ```rust
+//! use cddl::{
+//!   ast::Type2,
+//!   validator::custom::{ControlValue, CustomControls},
+//!   Schema, ValidationOptions,
+//! };
+//!
+//! let mut controls = CustomControls::new();
+//! controls
+//!   .register(".oid", |value, _, controller| match (value, controller) {
+//!     (ControlValue::JSON(serde_json::Value::String(s)), Type2::TextValue { value: arc, .. })
+//!       if s.starts_with(&format!("{}.", arc)) =>
+//!     {
+//!       Ok(())
+//!     }
+//!     _ => Err(format!("expected OID under arc {}", controller)),
+//!   })
+//!   .unwrap();
+//!
+//! let cddl = r#"algorithm = tstr .oid "1.2.840""#;
+//! let schema = Schema::with_custom_controls(cddl, controls).unwrap();
+//!
+//! let options = ValidationOptions::new();
+//! assert!(schema.validate_json(r#""1.2.840.10045""#, &options).is_ok());
+//! assert!(schema.validate_json(r#""1.3.101""#, &options).is_err());
+//! ```
+//!
+//! ### CBOR tag registry
+//!
+//! When a tagged data item matches `#6.n(...)`, including the standard prelude
+//! types defined with tags such as `tdate`, `uri` or `biguint`, the CBOR
+//! validator also checks the content of the tag against its semantics. Checks
+//! are predefined for common tags in the IANA CBOR tags registry: standard and
+//! epoch-based date/time (0 and 1), bignums (2 and 3), decimal fractions and
+//! bigfloats (4 and 5), encoded CBOR data items (24), URIs (32), base64url and
+//! base64 text (33 and 34), regular expressions (35), MIME messages (36), UUIDs
+//! (37), IPv4 and IPv6 addresses and prefixes (52 and 54), days since the epoch
+//! (100) and RFC 3339 full-dates (1004) as per RFC 8943 and extended time
+//! (1001) as per RFC 9581. When validating JSON, which has no tags, the
+//! standard date/time tags 0 and 1 and the date tags 100 and 1004 are validated
+//! against their untagged representation, including the RFC 3339 format of
+//! date/time and full-date strings.
+//!
+//! The predefined checks are used unless a `cddl::validator::tags::TagRegistry`
+//! is given to the validator with `with_tag_registry`, or to a `Schema` with
+//! its `with_tag_registry` method. `TagRegistry::iana()` creates a registry
+//! with the predefined checks, to which checks for other tags can be added or
+//! predefined checks replaced, while with a registry created with
+//! `TagRegistry::new()` the content of data items matching `#6.n(...)` is only
+//! constrained by the schema and the checks registered with it. The standard
+//! prelude types defined with tags are checked with the predefined check of
+//! their tag if the registry has no check for it:
+//!
+//! ```rust
+//! use cddl::{
+//!   cddl_from_str,
+//!   validator::{cbor::CBORValidator, tags::TagRegistry, Validator},
+//! };
+//! use ciborium::value::Value;
+//!
+//! let mut registry = TagRegistry::iana();
+//! registry.register(65000, |value| match value {
+//!   Value::Text(s) if s.chars().all(|c| c.is_ascii_uppercase()) => Ok(()),
+//!   _ => Err("expected upper case text".to_string()),
+//! });
+//!
+//! let cddl = cddl_from_str(r#"thing = [#6.37(bstr), #6.65000(tstr)]"#, true).unwrap();
+//!
+//! let validate = |id: Vec<u8>, code: &str| {
+//!   let thing = Value::Array(vec![
+//!     Value::Tag(37, Box::new(Value::Bytes(id))),
+//!     Value::Tag(65000, Box::new(Value::Text(code.to_string()))),
+//!   ]);
+//!
+//!   let mut cv = CBORValidator::new(&cddl, thing, None).with_tag_registry(&registry);
+//!   cv.validate()
+//! };
+//!
+//! assert!(validate(vec![0; 16], "ABC").is_ok());
+//! assert!(validate(vec![0; 4], "ABC").is_err());
+//! assert!(validate(vec![0; 16], "abc").is_err());
+//! ```
+//!
+//! ### Generating JSON and CBOR
+//!
+//! JSON values can be generated from a CDDL definition for use as examples or
+//! test fixtures. Generic arguments, sockets, group-to-choice enumerations and
+//! the `.size`, `.lt`, `.le`, `.gt`, `.ge`, `.eq`, `.ne`, `.regexp` and `.pcre`
+//! control operators are taken into account. Byte strings are generated as
+//! base64url encoded strings, as per the convention of RFC 8610 Appendix E.
+//! Generated values are validated against the rule they were generated from
+//! before being returned.
+//!
+//! ```rust
+//! use cddl::{generate_json_from_str, validate_json_from_str};
+//!
+//! let cddl = r#"
+//!   person = {
+//!     name: tstr .size (1..16),
+//!     age: uint .lt 130,
+//!     ? email: tstr .regexp "[a-z]+@example\\.com",
+//!   }
+//! "#;
+//!
+//! let json = generate_json_from_str(cddl, None, Some(42)).unwrap();
+//!
+//! assert!(validate_json_from_str(cddl, &json.to_string(), None).is_ok())
+//! ```
+//!
+//! CBOR values can be generated in the same way. Tagged data, major types,
+//! byte strings, bignums and the `.bits`, `.cbor` and `.cborseq` control
+//! operators are additionally taken into account.
+//!
+//! ```rust
+//! use cddl::{generate_cbor_from_str, validate_cbor_from_slice};
+//!
+//! let cddl = r#"
+//!   message = {
+//!     1 => #6.1234(tstr),
+//!     2 => bstr .size 16,
+//!     3 => bstr .cbor payload,
+//!   }
+//!   payload = [* uint]
+//! "#;
+//!
+//! let cbor = generate_cbor_from_str(cddl, None, Some(42)).unwrap();
+//!
+//! let mut data = Vec::new();
+//! ciborium::ser::into_writer(&cbor, &mut data).unwrap();
+//!
+//! assert!(validate_cbor_from_slice(cddl, &data, None).is_ok())
+//! ```
+//!
 //! ## `no_std` support
 //!
 //! Only the lexer and parser can be used in a `no_std` context provided that a
@@ -543,9 +957,15 @@ extern crate base64_url;
 
 /// Abstract syntax tree representing a CDDL definition
 pub mod ast;
+/// CBOR extended diagnostic notation (EDN)
+#[cfg(feature = "std")]
+pub mod edn;
 /// Static error messages
 #[allow(missing_docs)]
 pub mod error;
+/// Generators for JSON and CBOR data structures conforming to CDDL definitions
+#[cfg(feature = "std")]
+pub mod generator;
 /// Lexer for CDDL
 pub mod lexer;
 /// Parser for CDDL
@@ -573,11 +993,36 @@ pub use self::{
 #[cfg(feature = "cbor")]
 #[cfg(not(feature = "lsp"))]
 #[cfg(not(target_arch = "wasm32"))]
-pub use self::validator::validate_cbor_from_slice;
+pub use self::validator::{
+  validate_cbor_from_reader, validate_cbor_from_slice, validate_cbor_from_slice_deterministic,
+  validate_cbor_from_slice_with_rule, validate_cbor_seq_from_reader,
+};
+
+#[doc(inline)]
+#[cfg(feature = "std")]
+#[cfg(feature = "json")]
+#[cfg(not(feature = "lsp"))]
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::validator::{
+  validate_json_from_str, validate_json_from_str_with_rule, validate_json_records_from_reader,
+};
+
+#[doc(inline)]
+#[cfg(feature = "std")]
+#[cfg(any(feature = "json", feature = "cbor"))]
+#[cfg(not(feature = "lsp"))]
+pub use self::validator::schema::{Schema, ValidationOptions};
+
+#[doc(inline)]
+#[cfg(feature = "std")]
+#[cfg(feature = "cbor")]
+#[cfg(not(feature = "lsp"))]
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::generator::generate_cbor_from_str;
 
 #[doc(inline)]
 #[cfg(feature = "std")]
 #[cfg(feature = "json")]
 #[cfg(not(feature = "lsp"))]
 #[cfg(not(target_arch = "wasm32"))]
-pub use self::validator::validate_json_from_str;
+pub use self::generator::generate_json_from_str;
@@ -52,10 +52,10 @@ pub struct Parser<'a> {
   /// Vec of collected parsing errors
   pub errors: Vec<Error>,
   #[cfg(feature = "ast-span")]
-  visited_rule_idents: Vec<(&'a str, Span)>,
+  visited_rule_idents: Vec<(Cow<'a, str>, Span)>,
   #[cfg(not(feature = "ast-span"))]
-  visited_rule_idents: Vec<&'a str>,
-  current_rule_generic_param_idents: Option<Vec<&'a str>>,
+  visited_rule_idents: Vec<Cow<'a, str>>,
+  current_rule_generic_param_idents: Option<Vec<Cow<'a, str>>>,
 }
 
 /// Parsing error types
@@ -297,14 +297,20 @@ impl<'a> Parser<'a> {
 
     while let Token::COMMENT(_comment) = self.cur_token {
       #[cfg(not(feature = "lsp"))]
-      comments.get_or_insert(Comments::default()).0.push(_comment);
+      comments
+        .get_or_insert(Comments::default())
+        .0
+        .push(_comment.into());
 
       self.next_token()?;
     }
 
     while let Token::NEWLINE = self.cur_token {
       #[cfg(feature = "lsp")]
-      comments.get_or_insert(Comments::default()).0.push("\n");
+      comments
+        .get_or_insert(Comments::default())
+        .0
+        .push("\n".into());
 
       self.next_token()?;
     }
@@ -313,7 +319,10 @@ impl<'a> Parser<'a> {
       if let Some(c) = self.collect_comments()? {
         #[cfg_attr(not(feature = "lsp"), allow(unused_mut))]
         for comment in c.0.iter() {
-          comments.get_or_insert(Comments::default()).0.push(comment);
+          comments
+            .get_or_insert(Comments::default())
+            .0
+            .push(comment.clone());
         }
       }
     }
@@ -325,7 +334,10 @@ impl<'a> Parser<'a> {
   fn advance_newline(&mut self) -> Result<()> {
     while let Token::NEWLINE = self.cur_token {
       #[cfg(feature = "lsp")]
-      comments.get_or_insert(Comments::default()).0.push("\n");
+      comments
+        .get_or_insert(Comments::default())
+        .0
+        .push("\n".into());
 
       self.next_token()?;
     }
@@ -473,7 +485,7 @@ impl<'a> Parser<'a> {
       let mut param_list = Vec::default();
 
       for param in params.params.iter() {
-        param_list.push(param.param.ident);
+        param_list.push(param.param.ident.clone());
       }
 
       self.current_rule_generic_param_idents = Some(param_list);
@@ -855,18 +867,11 @@ impl<'a> Parser<'a> {
         })
       }
       _ => {
-        // If type rule is an unwrap type, advance token after parsing type
-        let advance_token = matches!(self.cur_token, Token::UNWRAP);
-
         #[cfg(feature = "ast-comments")]
         let mut t = self.parse_type(None)?;
         #[cfg(not(feature = "ast-comments"))]
         let t = self.parse_type(None)?;
 
-        if advance_token {
-          self.next_token()?;
-        }
-
         #[cfg(feature = "ast-comments")]
         let comments_after_rule = if let Some(comments) = t.split_comments_after_type() {
           Some(comments)
@@ -1617,23 +1622,23 @@ impl<'a> Parser<'a> {
             });
           }
 
-          return Ok(Type2::Unwrap {
+          Ok(Type2::Unwrap {
             #[cfg(feature = "ast-comments")]
             comments,
             ident,
             generic_args: None,
             #[cfg(feature = "ast-span")]
             span: (0, 0, 0),
+          })
+        } else {
+          self.errors.push(Error::PARSER {
+            #[cfg(feature = "ast-span")]
+            position: self.parser_position,
+            msg: InvalidUnwrapSyntax.into(),
           });
-        }
 
-        self.errors.push(Error::PARSER {
-          #[cfg(feature = "ast-span")]
-          position: self.parser_position,
-          msg: InvalidUnwrapSyntax.into(),
-        });
-
-        Err(Error::INCREMENTAL)
+          Err(Error::INCREMENTAL)
+        }
       }
 
       // & ( group )
@@ -2171,16 +2176,20 @@ impl<'a> Parser<'a> {
         #[cfg(feature = "ast-span")]
         if let Some((name, generic_args, _)) = entry_type.groupname_entry() {
           if name.socket.is_none()
-            && token::lookup_ident(name.ident)
+            && token::lookup_ident(&name.ident)
               .in_standard_prelude()
               .is_none()
           {
             if let Some(params) = &self.current_rule_generic_param_idents {
-              if !params.iter().any(|&p| p == name.ident) {
-                self.visited_rule_idents.push((name.ident, name.span));
+              if !params.contains(&name.ident) {
+                self
+                  .visited_rule_idents
+                  .push((name.ident.clone(), name.span));
               }
             } else {
-              self.visited_rule_idents.push((name.ident, name.span));
+              self
+                .visited_rule_idents
+                .push((name.ident.clone(), name.span));
             }
           }
 
@@ -2201,16 +2210,16 @@ impl<'a> Parser<'a> {
         #[cfg(not(feature = "ast-span"))]
         if let Some((name, generic_args)) = entry_type.groupname_entry() {
           if name.socket.is_none()
-            && token::lookup_ident(name.ident)
+            && token::lookup_ident(&name.ident)
               .in_standard_prelude()
               .is_none()
           {
             if let Some(params) = &self.current_rule_generic_param_idents {
-              if !params.iter().any(|&p| p == name.ident) {
-                self.visited_rule_idents.push(name.ident);
+              if !params.contains(&name.ident) {
+                self.visited_rule_idents.push(name.ident.clone());
               }
             } else {
-              self.visited_rule_idents.push(name.ident);
+              self.visited_rule_idents.push(name.ident.clone());
             }
           }
 
@@ -2244,16 +2253,20 @@ impl<'a> Parser<'a> {
         #[cfg(feature = "ast-span")]
         if let Some((ident, _, _)) = entry_type.groupname_entry() {
           if ident.socket.is_none()
-            && token::lookup_ident(ident.ident)
+            && token::lookup_ident(&ident.ident)
               .in_standard_prelude()
               .is_none()
           {
             if let Some(params) = &self.current_rule_generic_param_idents {
-              if !params.iter().any(|&p| p == ident.ident) {
-                self.visited_rule_idents.push((ident.ident, ident.span));
+              if !params.contains(&ident.ident) {
+                self
+                  .visited_rule_idents
+                  .push((ident.ident.clone(), ident.span));
               }
             } else {
-              self.visited_rule_idents.push((ident.ident, ident.span));
+              self
+                .visited_rule_idents
+                .push((ident.ident.clone(), ident.span));
             }
           }
         }
@@ -2261,16 +2274,16 @@ impl<'a> Parser<'a> {
         #[cfg(not(feature = "ast-span"))]
         if let Some((ident, _)) = entry_type.groupname_entry() {
           if ident.socket.is_none()
-            && token::lookup_ident(ident.ident)
+            && token::lookup_ident(&ident.ident)
               .in_standard_prelude()
               .is_none()
           {
             if let Some(params) = &self.current_rule_generic_param_idents {
-              if !params.iter().any(|&p| p == ident.ident) {
-                self.visited_rule_idents.push(ident.ident);
+              if !params.contains(&ident.ident) {
+                self.visited_rule_idents.push(ident.ident.clone());
               }
             } else {
-              self.visited_rule_idents.push(ident.ident);
+              self.visited_rule_idents.push(ident.ident.clone());
             }
           }
         }
@@ -2334,16 +2347,20 @@ impl<'a> Parser<'a> {
         #[cfg(feature = "ast-span")]
         if let Some((ident, _, _)) = entry_type.groupname_entry() {
           if ident.socket.is_none()
-            && token::lookup_ident(ident.ident)
+            && token::lookup_ident(&ident.ident)
               .in_standard_prelude()
               .is_none()
           {
             if let Some(params) = &self.current_rule_generic_param_idents {
-              if !params.iter().any(|&p| p == ident.ident) {
-                self.visited_rule_idents.push((ident.ident, ident.span));
+              if !params.contains(&ident.ident) {
+                self
+                  .visited_rule_idents
+                  .push((ident.ident.clone(), ident.span));
               }
             } else {
-              self.visited_rule_idents.push((ident.ident, ident.span));
+              self
+                .visited_rule_idents
+                .push((ident.ident.clone(), ident.span));
             }
           }
         }
@@ -2351,16 +2368,16 @@ impl<'a> Parser<'a> {
         #[cfg(not(feature = "ast-span"))]
         if let Some((ident, _)) = entry_type.groupname_entry() {
           if ident.socket.is_none()
-            && token::lookup_ident(ident.ident)
+            && token::lookup_ident(&ident.ident)
               .in_standard_prelude()
               .is_none()
           {
             if let Some(params) = &self.current_rule_generic_param_idents {
-              if !params.iter().any(|&p| p == ident.ident) {
-                self.visited_rule_idents.push(ident.ident);
+              if !params.contains(&ident.ident) {
+                self.visited_rule_idents.push(ident.ident.clone());
               }
             } else {
-              self.visited_rule_idents.push(ident.ident);
+              self.visited_rule_idents.push(ident.ident.clone());
             }
           }
         }
@@ -2415,16 +2432,20 @@ impl<'a> Parser<'a> {
           }
 
           if name.socket.is_none()
-            && token::lookup_ident(name.ident)
+            && token::lookup_ident(&name.ident)
               .in_standard_prelude()
               .is_none()
           {
             if let Some(params) = &self.current_rule_generic_param_idents {
-              if !params.iter().any(|&p| p == name.ident) {
-                self.visited_rule_idents.push((name.ident, name.span));
+              if !params.contains(&name.ident) {
+                self
+                  .visited_rule_idents
+                  .push((name.ident.clone(), name.span));
               }
             } else {
-              self.visited_rule_idents.push((name.ident, name.span));
+              self
+                .visited_rule_idents
+                .push((name.ident.clone(), name.span));
             }
           }
 
@@ -2453,16 +2474,16 @@ impl<'a> Parser<'a> {
           }
 
           if name.socket.is_none()
-            && token::lookup_ident(name.ident)
+            && token::lookup_ident(&name.ident)
               .in_standard_prelude()
               .is_none()
           {
             if let Some(params) = &self.current_rule_generic_param_idents {
-              if !params.iter().any(|&p| p == name.ident) {
-                self.visited_rule_idents.push(name.ident);
+              if !params.contains(&name.ident) {
+                self.visited_rule_idents.push(name.ident.clone());
               }
             } else {
-              self.visited_rule_idents.push(name.ident);
+              self.visited_rule_idents.push(name.ident.clone());
             }
           }
 
@@ -2482,16 +2503,20 @@ impl<'a> Parser<'a> {
         #[cfg(feature = "ast-span")]
         if let Some((ident, _, _)) = entry_type.groupname_entry() {
           if ident.socket.is_none()
-            && token::lookup_ident(ident.ident)
+            && token::lookup_ident(&ident.ident)
               .in_standard_prelude()
               .is_none()
           {
             if let Some(params) = &self.current_rule_generic_param_idents {
-              if !params.iter().any(|&p| p == ident.ident) {
-                self.visited_rule_idents.push((ident.ident, ident.span));
+              if !params.contains(&ident.ident) {
+                self
+                  .visited_rule_idents
+                  .push((ident.ident.clone(), ident.span));
               }
             } else {
-              self.visited_rule_idents.push((ident.ident, ident.span));
+              self
+                .visited_rule_idents
+                .push((ident.ident.clone(), ident.span));
             }
           }
         }
@@ -2499,16 +2524,16 @@ impl<'a> Parser<'a> {
         #[cfg(not(feature = "ast-span"))]
         if let Some((ident, _)) = entry_type.groupname_entry() {
           if ident.socket.is_none()
-            && token::lookup_ident(ident.ident)
+            && token::lookup_ident(&ident.ident)
               .in_standard_prelude()
               .is_none()
           {
             if let Some(params) = &self.current_rule_generic_param_idents {
-              if !params.iter().any(|&p| p == ident.ident) {
-                self.visited_rule_idents.push(ident.ident);
+              if !params.contains(&ident.ident) {
+                self.visited_rule_idents.push(ident.ident.clone());
               }
             } else {
-              self.visited_rule_idents.push(ident.ident);
+              self.visited_rule_idents.push(ident.ident.clone());
             }
           }
         }
@@ -2890,16 +2915,26 @@ impl<'a> Parser<'a> {
 
         let mut has_group_entries = false;
         let mut closing_parend = false;
+        // Nesting level of maps and arrays within the parentheses
+        let mut nested_bracket_count = 0;
         #[cfg(feature = "ast-span")]
         let mut closing_parend_index = 0;
         while !closing_parend {
-          if let Token::ARROWMAP
-          | Token::COLON
-          | Token::OPTIONAL
-          | Token::ASTERISK
-          | Token::GCHOICE = &self.cur_token
-          {
-            has_group_entries = true;
+          // Entries of maps and arrays nested within the parentheses say
+          // nothing about whether the parentheses themselves enclose a group
+          match &self.cur_token {
+            Token::LBRACE | Token::LBRACKET => nested_bracket_count += 1,
+            Token::RBRACE | Token::RBRACKET => nested_bracket_count -= 1,
+            Token::ARROWMAP | Token::COLON | Token::OPTIONAL | Token::ASTERISK | Token::GCHOICE
+              if nested_bracket_count == 0 =>
+            {
+              has_group_entries = true
+            }
+            // A parenthesized type cannot hold several comma-separated entries
+            Token::COMMA if nested_parend_count == 0 && nested_bracket_count == 0 => {
+              has_group_entries = true
+            }
+            _ => (),
           }
 
           // TODO: parse nested comments
@@ -3452,7 +3487,7 @@ impl<'a> Parser<'a> {
     socket: Option<token::SocketPlug>,
   ) -> Identifier<'a> {
     Identifier {
-      ident,
+      ident: ident.into(),
       socket,
       #[cfg(feature = "ast-span")]
       span: (
@@ -2427,3 +2427,84 @@ mod tests {
     Ok(())
   }
 }
+
+#[cfg(test)]
+mod group_entry_tests {
+  use crate::{ast::*, lexer::Lexer, parser::*};
+
+  fn array_entries<'a>(cddl: &'a CDDL<'a>) -> Vec<&'a GroupEntry<'a>> {
+    match &cddl.rules[0] {
+      Rule::Type { rule, .. } => match &rule.value.type_choices[0].type1.type2 {
+        Type2::Array { group, .. } => group.group_choices[0]
+          .group_entries
+          .iter()
+          .map(|(ge, _)| ge)
+          .collect(),
+        t2 => panic!("expected array, got {}", t2),
+      },
+      rule => panic!("expected type rule, got {}", rule),
+    }
+  }
+
+  #[test]
+  fn verify_parenthesized_entries_with_commas() -> Result<()> {
+    let inputs = [
+      ("a = [int, (int, int)]", 2),
+      ("a = [(int, [int, int])]", 2),
+      ("a = [* (b: int, c: tstr)]", 2),
+    ];
+
+    for (input, len) in inputs.iter() {
+      let cddl = Parser::new(input, Box::new(Lexer::new(input).iter()))?.parse_cddl()?;
+      match array_entries(&cddl).last() {
+        Some(GroupEntry::InlineGroup { group, .. }) => {
+          assert_eq!(
+            group.group_choices[0].group_entries.len(),
+            *len,
+            "{}",
+            input
+          )
+        }
+        ge => panic!("expected inline group in {}, got {:?}", input, ge),
+      }
+    }
+
+    // Commas nested in arrays or maps do not make a parenthesized type a group
+    let inputs = [
+      "a = [int, (tstr / int)]",
+      "a = [([int, int])]",
+      "a = [({ b: int, c: int })]",
+    ];
+
+    for input in inputs.iter() {
+      let cddl = Parser::new(input, Box::new(Lexer::new(input).iter()))?.parse_cddl()?;
+      match array_entries(&cddl).last() {
+        Some(GroupEntry::ValueMemberKey { ge, .. }) => assert!(ge.member_key.is_none()),
+        ge => panic!("expected parenthesized type in {}, got {:?}", input, ge),
+      }
+    }
+
+    Ok(())
+  }
+
+  #[test]
+  fn verify_unwrapped_entries() -> Result<()> {
+    let input = "a = [~b, c: ~time]";
+    let cddl = Parser::new(input, Box::new(Lexer::new(input).iter()))?.parse_cddl()?;
+    let entries = array_entries(&cddl);
+
+    assert_eq!(entries.len(), 2);
+    for ge in entries {
+      match ge {
+        GroupEntry::ValueMemberKey { ge, .. } => assert!(matches!(
+          ge.entry_type.type_choices[0].type1.type2,
+          Type2::Unwrap { .. }
+        )),
+        ge => panic!("expected unwrapped type, got {:?}", ge),
+      }
+    }
+    assert_eq!(cddl.to_string().trim(), "a = [ ~b, c: ~time ]");
+
+    Ok(())
+  }
+}
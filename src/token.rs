@@ -245,6 +245,51 @@ pub enum ControlOperator {
   #[cfg(feature = "additional-controls")]
   /// .feature control operator (rfc 9165)
   FEATURE,
+  #[cfg(feature = "additional-controls")]
+  /// .b64u control operator (rfc 9741)
+  B64U,
+  #[cfg(feature = "additional-controls")]
+  /// .b64u-sloppy control operator (rfc 9741)
+  B64USLOPPY,
+  #[cfg(feature = "additional-controls")]
+  /// .b64c control operator (rfc 9741)
+  B64C,
+  #[cfg(feature = "additional-controls")]
+  /// .b64c-sloppy control operator (rfc 9741)
+  B64CSLOPPY,
+  #[cfg(feature = "additional-controls")]
+  /// .b45 control operator (rfc 9741)
+  B45,
+  #[cfg(feature = "additional-controls")]
+  /// .b32 control operator (rfc 9741)
+  B32,
+  #[cfg(feature = "additional-controls")]
+  /// .h32 control operator (rfc 9741)
+  H32,
+  #[cfg(feature = "additional-controls")]
+  /// .hex control operator (rfc 9741)
+  HEX,
+  #[cfg(feature = "additional-controls")]
+  /// .hexlc control operator (rfc 9741)
+  HEXLC,
+  #[cfg(feature = "additional-controls")]
+  /// .hexuc control operator (rfc 9741)
+  HEXUC,
+  #[cfg(feature = "additional-controls")]
+  /// .decimal control operator (rfc 9741)
+  DECIMAL,
+  #[cfg(feature = "additional-controls")]
+  /// .printf control operator (rfc 9741)
+  PRINTF,
+  #[cfg(feature = "additional-controls")]
+  /// .json control operator (rfc 9741)
+  JSON,
+  #[cfg(feature = "additional-controls")]
+  /// .join control operator (rfc 9741)
+  JOIN,
+  /// Custom control operator recognized by a lexer created with
+  /// `Lexer::with_custom_controls`
+  CUSTOM(&'static str),
 }
 
 impl<'a> Token<'a> {
@@ -505,6 +550,35 @@ impl fmt::Display for ControlOperator {
       ControlOperator::ABNFB => write!(f, ".abnfb"),
       #[cfg(feature = "additional-controls")]
       ControlOperator::FEATURE => write!(f, ".feature"),
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::B64U => write!(f, ".b64u"),
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::B64USLOPPY => write!(f, ".b64u-sloppy"),
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::B64C => write!(f, ".b64c"),
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::B64CSLOPPY => write!(f, ".b64c-sloppy"),
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::B45 => write!(f, ".b45"),
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::B32 => write!(f, ".b32"),
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::H32 => write!(f, ".h32"),
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::HEX => write!(f, ".hex"),
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::HEXLC => write!(f, ".hexlc"),
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::HEXUC => write!(f, ".hexuc"),
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::DECIMAL => write!(f, ".decimal"),
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::PRINTF => write!(f, ".printf"),
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::JSON => write!(f, ".json"),
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::JOIN => write!(f, ".join"),
+      ControlOperator::CUSTOM(name) => write!(f, "{}", name),
       ControlOperator::AND => write!(f, ".and"),
       ControlOperator::LT => write!(f, ".lt"),
       ControlOperator::LE => write!(f, ".le"),
@@ -636,6 +710,34 @@ pub fn lookup_control_from_str(ident: &str) -> Option<ControlOperator> {
     ".abnfb" => Some(ControlOperator::ABNFB),
     #[cfg(feature = "additional-controls")]
     ".feature" => Some(ControlOperator::FEATURE),
+    #[cfg(feature = "additional-controls")]
+    ".b64u" => Some(ControlOperator::B64U),
+    #[cfg(feature = "additional-controls")]
+    ".b64u-sloppy" => Some(ControlOperator::B64USLOPPY),
+    #[cfg(feature = "additional-controls")]
+    ".b64c" => Some(ControlOperator::B64C),
+    #[cfg(feature = "additional-controls")]
+    ".b64c-sloppy" => Some(ControlOperator::B64CSLOPPY),
+    #[cfg(feature = "additional-controls")]
+    ".b45" => Some(ControlOperator::B45),
+    #[cfg(feature = "additional-controls")]
+    ".b32" => Some(ControlOperator::B32),
+    #[cfg(feature = "additional-controls")]
+    ".h32" => Some(ControlOperator::H32),
+    #[cfg(feature = "additional-controls")]
+    ".hex" => Some(ControlOperator::HEX),
+    #[cfg(feature = "additional-controls")]
+    ".hexlc" => Some(ControlOperator::HEXLC),
+    #[cfg(feature = "additional-controls")]
+    ".hexuc" => Some(ControlOperator::HEXUC),
+    #[cfg(feature = "additional-controls")]
+    ".decimal" => Some(ControlOperator::DECIMAL),
+    #[cfg(feature = "additional-controls")]
+    ".printf" => Some(ControlOperator::PRINTF),
+    #[cfg(feature = "additional-controls")]
+    ".json" => Some(ControlOperator::JSON),
+    #[cfg(feature = "additional-controls")]
+    ".join" => Some(ControlOperator::JOIN),
     _ => None,
   }
 }
@@ -0,0 +1,657 @@
+#![cfg(any(feature = "json", feature = "cbor"))]
+#![cfg(not(feature = "lsp"))]
+
+use super::{compiled::RuleLookup, Lookup};
+use crate::ast::{
+  Group, GroupEntry, GroupRule, Identifier, Occur, Rule, Type, Type2, TypeGroupnameEntry, TypeRule,
+};
+
+use std::{
+  borrow::Cow,
+  collections::{BTreeSet, HashMap},
+};
+
+/// Single array item constraint produced by flattening an array group
+#[derive(Debug)]
+pub enum ArrayItem<'a, 'b> {
+  /// Type of a value member key entry. Member keys are annotation only in an
+  /// array context and are dropped
+  Type(&'b Type<'a>),
+  /// Named type entry with its occurrence indicator removed
+  Typename(TypeGroupnameEntry<'a>),
+}
+
+/// Array group flattened into a regular expression over array items. Group
+/// names, inline groups and unwrapped arrays are expanded so that every
+/// `ArrayPattern::Item` consumes exactly one array item.
+#[derive(Debug)]
+pub enum ArrayPattern<'a, 'b> {
+  /// Single item with a unique identifier used to memoize item validation
+  Item(usize, ArrayItem<'a, 'b>),
+  /// Ordered sequence of patterns, each with a lower and optional upper
+  /// occurrence bound
+  Sequence(Vec<(ArrayPattern<'a, 'b>, usize, Option<usize>)>),
+  /// Group choice alternatives
+  Choice(Vec<ArrayPattern<'a, 'b>>),
+}
+
+/// Reason an array failed to match its array group
+#[derive(Debug)]
+pub enum ArrayMismatch<V> {
+  /// Validation errors of the items which could not be matched at the index
+  /// furthest into the array
+  Items(Vec<V>),
+  /// No group entry remained to match the item at the given index
+  UnexpectedItem(usize),
+  /// All items matched, but required group entries remain
+  MissingItems,
+}
+
+impl<'a, 'b> ArrayPattern<'a, 'b> {
+  /// Build an array pattern from an array group. Returns `None` for array
+  /// groups that cannot be expanded into a pattern, i.e. recursive or generic
+  /// group rules, in which case validators fall back to validating array
+  /// items entry by entry
+  pub(crate) fn from_group(lookup: RuleLookup<'a>, group: &'b Group<'a>) -> Option<Self> {
+    PatternBuilder {
+      lookup,
+      next_id: 0,
+      expanding: Vec::new(),
+    }
+    .group(group)
+  }
+}
+
+struct PatternBuilder<'a> {
+  lookup: RuleLookup<'a>,
+  next_id: usize,
+  expanding: Vec<Cow<'a, str>>,
+}
+
+impl<'a: 'b, 'b> PatternBuilder<'a> {
+  fn group(&mut self, group: &'b Group<'a>) -> Option<ArrayPattern<'a, 'b>> {
+    let mut choices = Vec::new();
+    for gc in group.group_choices.iter() {
+      let mut sequence = Vec::new();
+      for ge in gc.group_entries.iter() {
+        sequence.push(self.group_entry(&ge.0)?);
+      }
+      choices.push(ArrayPattern::Sequence(sequence));
+    }
+
+    if choices.len() == 1 {
+      return choices.pop();
+    }
+
+    Some(ArrayPattern::Choice(choices))
+  }
+
+  fn group_entry(
+    &mut self,
+    ge: &'b GroupEntry<'a>,
+  ) -> Option<(ArrayPattern<'a, 'b>, usize, Option<usize>)> {
+    match ge {
+      GroupEntry::ValueMemberKey { ge, .. } => {
+        let (lower, upper) = occurrence_bounds(ge.occur.as_ref().map(|o| &o.occur));
+
+        if ge.entry_type.type_choices.len() == 1 {
+          let type1 = &ge.entry_type.type_choices[0].type1;
+          if type1.operator.is_none() {
+            match &type1.type2 {
+              Type2::Typename {
+                ident,
+                generic_args,
+                ..
+              } if ge.member_key.is_none() && !self.group_rules(ident).is_empty() => {
+                if generic_args.is_some() {
+                  return None;
+                }
+
+                return Some((self.group_name(ident)?, lower, upper));
+              }
+              Type2::Unwrap {
+                ident,
+                generic_args,
+                ..
+              } => {
+                if generic_args.is_some() {
+                  return None;
+                }
+
+                // Unwrapped arrays splice their items into the enclosing
+                // array. Any other unwrapped type, e.g. the content of a
+                // tag, is a single item
+                if self.is_array_rule(ident) {
+                  return Some((self.unwrapped_array(ident)?, lower, upper));
+                }
+              }
+              _ => (),
+            }
+          }
+        }
+
+        Some((self.item(ArrayItem::Type(&ge.entry_type)), lower, upper))
+      }
+      GroupEntry::TypeGroupname { ge, .. } => {
+        let (lower, upper) = occurrence_bounds(ge.occur.as_ref().map(|o| &o.occur));
+
+        if !self.group_rules(&ge.name).is_empty() {
+          if ge.generic_args.is_some() {
+            return None;
+          }
+
+          return Some((self.group_name(&ge.name)?, lower, upper));
+        }
+
+        let mut entry = ge.clone();
+        entry.occur = None;
+
+        Some((self.item(ArrayItem::Typename(entry)), lower, upper))
+      }
+      GroupEntry::InlineGroup { occur, group, .. } => {
+        let (lower, upper) = occurrence_bounds(occur.as_ref().map(|o| &o.occur));
+
+        Some((self.group(group)?, lower, upper))
+      }
+    }
+  }
+
+  fn group_name(&mut self, ident: &Identifier<'a>) -> Option<ArrayPattern<'a, 'b>> {
+    if self.expanding.contains(&ident.ident) {
+      return None;
+    }
+
+    self.expanding.push(ident.ident.clone());
+
+    // Group sockets may only be defined by group choice alternates
+    let mut choices = Vec::new();
+    for rule in self.group_rules(ident) {
+      if rule.generic_params.is_some() {
+        return None;
+      }
+
+      choices.push(self.group_entry_pattern(&rule.entry)?);
+    }
+
+    self.expanding.pop();
+
+    if choices.len() == 1 {
+      return choices.pop();
+    }
+
+    Some(ArrayPattern::Choice(choices))
+  }
+
+  fn is_array_rule(&self, ident: &Identifier) -> bool {
+    self.type_rule(ident).map_or(false, |rule| {
+      rule
+        .value
+        .type_choices
+        .iter()
+        .any(|tc| matches!(tc.type1.type2, Type2::Array { .. }))
+    })
+  }
+
+  fn unwrapped_array(&mut self, ident: &Identifier<'a>) -> Option<ArrayPattern<'a, 'b>> {
+    let rule = self.type_rule(ident)?;
+    if rule.generic_params.is_some()
+      || rule.value.type_choices.len() != 1
+      || self.expanding.contains(&ident.ident)
+    {
+      return None;
+    }
+
+    let type1 = &rule.value.type_choices[0].type1;
+    if type1.operator.is_some() {
+      return None;
+    }
+
+    if let Type2::Array { group, .. } = &type1.type2 {
+      self.expanding.push(ident.ident.clone());
+      let pattern = self.group(group);
+      self.expanding.pop();
+
+      return pattern;
+    }
+
+    None
+  }
+
+  fn group_entry_pattern(&mut self, ge: &'b GroupEntry<'a>) -> Option<ArrayPattern<'a, 'b>> {
+    let (pattern, lower, upper) = self.group_entry(ge)?;
+    if lower == 1 && upper == Some(1) {
+      return Some(pattern);
+    }
+
+    Some(ArrayPattern::Sequence(vec![(pattern, lower, upper)]))
+  }
+
+  fn group_rules(&self, ident: &Identifier) -> Vec<&'b GroupRule<'a>> {
+    // The rules of a compiled schema have no alternates
+    if self.lookup.compiled.is_some() {
+      return self
+        .lookup
+        .group_rule_from_ident(ident)
+        .into_iter()
+        .collect();
+    }
+
+    self
+      .lookup
+      .cddl
+      .rules
+      .iter()
+      .filter_map(|r| match r {
+        Rule::Group { rule, .. } if rule.name == *ident => Some(rule.as_ref()),
+        _ => None,
+      })
+      .collect()
+  }
+
+  fn type_rule(&self, ident: &Identifier) -> Option<&'b TypeRule<'a>> {
+    match self.lookup.rule_from_ident(ident)? {
+      Rule::Type { rule, .. } => Some(rule),
+      _ => None,
+    }
+  }
+
+  fn item(&mut self, item: ArrayItem<'a, 'b>) -> ArrayPattern<'a, 'b> {
+    let id = self.next_id;
+    self.next_id += 1;
+
+    ArrayPattern::Item(id, item)
+  }
+}
+
+/// Lower and optional upper bound of a given optional occurrence indicator
+pub(crate) fn occurrence_bounds(occur: Option<&Occur>) -> (usize, Option<usize>) {
+  match occur {
+    Some(Occur::ZeroOrMore { .. }) => (0, None),
+    Some(Occur::OneOrMore { .. }) => (1, None),
+    Some(Occur::Optional { .. }) => (0, Some(1)),
+    Some(Occur::Exact { lower, upper, .. }) => (lower.unwrap_or(0), *upper),
+    None => (1, Some(1)),
+  }
+}
+
+/// Matches array patterns against a sequence of positions, where each item
+/// constraint consumes the positions from a start index to any of its ends
+trait PatternMatcher {
+  type Error;
+
+  /// Returns the indices at which the given item constraint can stop matching
+  /// when starting from the given index
+  fn item_ends(
+    &mut self,
+    id: usize,
+    item: &ArrayItem,
+    start: usize,
+  ) -> std::result::Result<Vec<usize>, Self::Error>;
+
+  /// Returns the set of indices at which the given pattern can stop matching
+  /// when starting from any of the given indices
+  fn ends(
+    &mut self,
+    pattern: &ArrayPattern,
+    starts: &BTreeSet<usize>,
+  ) -> std::result::Result<BTreeSet<usize>, Self::Error> {
+    let mut ends = BTreeSet::new();
+
+    match pattern {
+      ArrayPattern::Item(id, item) => {
+        for &idx in starts.iter() {
+          ends.extend(self.item_ends(*id, item, idx)?);
+        }
+      }
+      ArrayPattern::Choice(choices) => {
+        for choice in choices.iter() {
+          ends.append(&mut self.ends(choice, starts)?);
+        }
+      }
+      ArrayPattern::Sequence(sequence) => {
+        ends = starts.clone();
+        for (pattern, lower, upper) in sequence.iter() {
+          ends = self.repeat(pattern, &ends, *lower, *upper)?;
+          if ends.is_empty() {
+            break;
+          }
+        }
+      }
+    }
+
+    Ok(ends)
+  }
+
+  fn repeat(
+    &mut self,
+    pattern: &ArrayPattern,
+    starts: &BTreeSet<usize>,
+    lower: usize,
+    upper: Option<usize>,
+  ) -> std::result::Result<BTreeSet<usize>, Self::Error> {
+    let mut ends = BTreeSet::new();
+    if lower == 0 {
+      ends = starts.clone();
+    }
+
+    let mut frontier = starts.clone();
+    let mut count = 0;
+    while !frontier.is_empty() && !matches!(upper, Some(u) if count >= u) {
+      let mut next = self.ends(pattern, &frontier)?;
+      count += 1;
+
+      if count >= lower {
+        // Indices already reachable with enough repetitions need not be
+        // explored again
+        next.retain(|idx| !ends.contains(idx));
+        ends.extend(next.iter().copied());
+      } else if next == frontier {
+        // Pattern can only match an empty sequence of items
+        count = lower - 1;
+      }
+
+      frontier = next;
+    }
+
+    Ok(ends)
+  }
+}
+
+struct Matcher<V, F> {
+  len: usize,
+  validate_item: F,
+  results: HashMap<(usize, usize), Vec<V>>,
+  furthest: usize,
+}
+
+impl<V, E, F> PatternMatcher for Matcher<V, F>
+where
+  F: FnMut(&ArrayItem, usize) -> std::result::Result<Vec<V>, E>,
+{
+  type Error = E;
+
+  fn item_ends(
+    &mut self,
+    id: usize,
+    item: &ArrayItem,
+    idx: usize,
+  ) -> std::result::Result<Vec<usize>, E> {
+    if idx >= self.len {
+      return Ok(Vec::new());
+    }
+
+    if !self.results.contains_key(&(id, idx)) {
+      let errors = (self.validate_item)(item, idx)?;
+      self.results.insert((id, idx), errors);
+    }
+
+    if !self.results[&(id, idx)].is_empty() {
+      return Ok(Vec::new());
+    }
+
+    self.furthest = self.furthest.max(idx + 1);
+
+    Ok(vec![idx + 1])
+  }
+}
+
+#[cfg(feature = "additional-controls")]
+struct TextMatcher<'t, F> {
+  text: &'t str,
+  validate_piece: F,
+  results: HashMap<(usize, usize), Vec<usize>>,
+}
+
+#[cfg(feature = "additional-controls")]
+impl<'t, E, F> PatternMatcher for TextMatcher<'t, F>
+where
+  F: FnMut(&ArrayItem, &str) -> std::result::Result<bool, E>,
+{
+  type Error = E;
+
+  fn item_ends(
+    &mut self,
+    id: usize,
+    item: &ArrayItem,
+    start: usize,
+  ) -> std::result::Result<Vec<usize>, E> {
+    if let Some(ends) = self.results.get(&(id, start)) {
+      return Ok(ends.clone());
+    }
+
+    let text = self.text;
+    let mut ends = Vec::new();
+    for end in (start..=text.len()).filter(|&end| text.is_char_boundary(end)) {
+      if (self.validate_piece)(item, &text[start..end])? {
+        ends.push(end);
+      }
+    }
+
+    self.results.insert((id, start), ends.clone());
+
+    Ok(ends)
+  }
+}
+
+/// Match the items of an array of the given length against an array pattern.
+/// The given closure validates the array item at the given index against a
+/// single item constraint and returns the resulting validation errors. Each
+/// item is validated at most once per constraint.
+pub fn match_array_items<V, E, F>(
+  pattern: &ArrayPattern,
+  len: usize,
+  validate_item: F,
+) -> std::result::Result<std::result::Result<(), ArrayMismatch<V>>, E>
+where
+  F: FnMut(&ArrayItem, usize) -> std::result::Result<Vec<V>, E>,
+{
+  let mut matcher = Matcher {
+    len,
+    validate_item,
+    results: HashMap::new(),
+    furthest: 0,
+  };
+
+  let starts = std::iter::once(0).collect::<BTreeSet<_>>();
+  if matcher.ends(pattern, &starts)?.contains(&len) {
+    return Ok(Ok(()));
+  }
+
+  let furthest = matcher.furthest;
+  if furthest == len {
+    return Ok(Err(ArrayMismatch::MissingItems));
+  }
+
+  let mut keys = matcher
+    .results
+    .keys()
+    .filter(|(_, idx)| *idx == furthest)
+    .copied()
+    .collect::<Vec<_>>();
+  keys.sort_unstable();
+
+  let mut errors = Vec::new();
+  for key in keys {
+    if let Some(mut e) = matcher.results.remove(&key) {
+      errors.append(&mut e);
+    }
+  }
+
+  if errors.is_empty() {
+    return Ok(Err(ArrayMismatch::UnexpectedItem(furthest)));
+  }
+
+  Ok(Err(ArrayMismatch::Items(errors)))
+}
+
+#[cfg(feature = "additional-controls")]
+/// Match a text string against an array pattern, as used by the .join control
+/// operator. The text matches if it can be split into consecutive pieces that
+/// form an array matching the pattern. The given closure validates a single
+/// piece of the text against a single item constraint and returns whether it
+/// matches. Each piece is validated at most once per constraint.
+pub fn match_joined_text<E, F>(
+  pattern: &ArrayPattern,
+  text: &str,
+  validate_piece: F,
+) -> std::result::Result<bool, E>
+where
+  F: FnMut(&ArrayItem, &str) -> std::result::Result<bool, E>,
+{
+  let mut matcher = TextMatcher {
+    text,
+    validate_piece,
+    results: HashMap::new(),
+  };
+
+  let starts = std::iter::once(0).collect::<BTreeSet<_>>();
+
+  Ok(matcher.ends(pattern, &starts)?.contains(&text.len()))
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+  use super::*;
+  use crate::{
+    ast::{Type2, CDDL},
+    cddl_from_str,
+  };
+
+  fn lookup<'a>(cddl: &'a CDDL<'a>) -> RuleLookup<'a> {
+    RuleLookup {
+      cddl,
+      compiled: None,
+    }
+  }
+
+  fn pattern_matches(cddl: &str, items: &[&str]) -> bool {
+    let cddl = cddl_from_str(cddl, true).unwrap();
+    let group = match &cddl.rules[0] {
+      crate::ast::Rule::Type { rule, .. } => match &rule.value.type_choices[0].type1.type2 {
+        Type2::Array { group, .. } => group,
+        _ => unreachable!(),
+      },
+      _ => unreachable!(),
+    };
+
+    let pattern = ArrayPattern::from_group(lookup(&cddl), group).unwrap();
+
+    // Items are matched by comparing the type name of an entry to the item
+    match_array_items::<(), (), _>(&pattern, items.len(), |item, idx| {
+      let name = match item {
+        ArrayItem::Type(t) => t.to_string(),
+        ArrayItem::Typename(tge) => tge.name.to_string(),
+      };
+
+      Ok(if name == items[idx] { vec![] } else { vec![()] })
+    })
+    .unwrap()
+    .is_ok()
+  }
+
+  #[test]
+  fn validate_zero_or_more_followed_by_required_and_optional() {
+    let cddl = "root = [ * a: int, b: tstr, ? c: int ]";
+
+    assert!(pattern_matches(cddl, &["tstr"]));
+    assert!(pattern_matches(cddl, &["int", "int", "tstr"]));
+    assert!(pattern_matches(cddl, &["int", "tstr", "int"]));
+    assert!(!pattern_matches(cddl, &["int", "int"]));
+    assert!(!pattern_matches(cddl, &["tstr", "int", "int"]));
+  }
+
+  #[test]
+  fn validate_backtracking_over_shared_item_types() {
+    let cddl = "root = [ * int, int, int ]";
+
+    assert!(!pattern_matches(cddl, &["int"]));
+    assert!(pattern_matches(cddl, &["int", "int"]));
+    assert!(pattern_matches(cddl, &["int", "int", "int", "int"]));
+  }
+
+  #[test]
+  fn validate_bounded_groups_and_choices() {
+    let cddl = r#"
+      root = [ 2*3 pair, ? (tstr // bool) ]
+      pair = (int, bstr)
+    "#;
+
+    assert!(pattern_matches(cddl, &["int", "bstr", "int", "bstr"]));
+    assert!(pattern_matches(
+      cddl,
+      &["int", "bstr", "int", "bstr", "int", "bstr", "bool"]
+    ));
+    assert!(!pattern_matches(cddl, &["int", "bstr"]));
+    assert!(!pattern_matches(
+      cddl,
+      &["int", "bstr", "int", "bstr", "int", "bstr", "int", "bstr"]
+    ));
+    assert!(!pattern_matches(cddl, &["int", "bstr", "int"]));
+  }
+
+  #[cfg(feature = "additional-controls")]
+  #[test]
+  fn validate_joined_text_pieces() {
+    let cddl = cddl_from_str(
+      r#"
+        root = [ 1*3 digits, ".", digits ]
+        digits = tstr
+      "#,
+      true,
+    )
+    .unwrap();
+    let group = match &cddl.rules[0] {
+      crate::ast::Rule::Type { rule, .. } => match &rule.value.type_choices[0].type1.type2 {
+        Type2::Array { group, .. } => group,
+        _ => unreachable!(),
+      },
+      _ => unreachable!(),
+    };
+
+    let pattern = ArrayPattern::from_group(lookup(&cddl), group).unwrap();
+
+    // Pieces match a digits entry if they consist of digits and any other
+    // entry if they are equal to its text string literal
+    let joined_text_matches = |text: &str| {
+      match_joined_text::<(), _>(&pattern, text, |item, piece| {
+        let name = match item {
+          ArrayItem::Type(t) => t.to_string(),
+          ArrayItem::Typename(tge) => tge.name.to_string(),
+        };
+
+        Ok(if name == "digits" {
+          !piece.is_empty() && piece.chars().all(|c| c.is_ascii_digit())
+        } else {
+          name == format!("\"{}\"", piece)
+        })
+      })
+      .unwrap()
+    };
+
+    assert!(joined_text_matches("42.5"));
+    assert!(joined_text_matches("1.2"));
+    assert!(!joined_text_matches("42"));
+    assert!(!joined_text_matches("42."));
+    assert!(!joined_text_matches(".5"));
+    assert!(!joined_text_matches("4a.5"));
+  }
+
+  #[test]
+  fn recursive_group_has_no_pattern() {
+    let cddl = cddl_from_str(
+      r#"
+        root = [ * a ]
+        a = (int, ? a)
+      "#,
+      true,
+    )
+    .unwrap();
+
+    if let crate::ast::Rule::Type { rule, .. } = &cddl.rules[0] {
+      if let Type2::Array { group, .. } = &rule.value.type_choices[0].type1.type2 {
+        assert!(ArrayPattern::from_group(lookup(&cddl), group).is_none());
+      }
+    }
+  }
+}
@@ -11,19 +11,29 @@ use crate::{
 
 use std::{
   borrow::Cow,
-  collections::HashMap,
+  collections::{HashMap, HashSet},
   convert::TryFrom,
   fmt::{self, Write},
 };
 
 use chrono::{TimeZone, Utc};
 use ciborium::value::Value;
+use serde::Serialize;
 use serde_json;
 
+#[cfg(feature = "additional-controls")]
+use crate::validator::array::match_joined_text;
+use crate::validator::array::{match_array_items, ArrayItem, ArrayMismatch, ArrayPattern};
+use crate::validator::compiled::{CompiledSchema, RuleLookup};
 #[cfg(feature = "additional-controls")]
 use crate::validator::control::{
-  abnf_from_complex_controller, cat_operation, plus_operation, validate_abnf,
+  abnf_from_complex_controller, cat_operation, decode_decimal, decode_text_conversion,
+  join_controller_group, plus_operation, printf_operation, validate_abnf,
 };
+use crate::validator::custom::{self, ControlValue, CustomControls};
+use crate::validator::number::ExactNumber;
+use crate::validator::raw::{self, FloatWidth, RawItem, RawKind};
+use crate::validator::tags::TagRegistry;
 
 /// cbor validation Result
 pub type Result<T> = std::result::Result<(), Error<T>>;
@@ -41,6 +51,9 @@ pub enum Error<T: std::fmt::Debug> {
   CDDLParsing(String),
   /// UTF8 parsing error,
   UTF8Parsing(std::str::Utf8Error),
+  /// Rule with the given name does not exist or cannot be used as the root
+  /// rule
+  MissingRule(String),
 }
 
 impl<T: std::fmt::Debug> fmt::Display for Error<T> {
@@ -57,6 +70,7 @@ impl<T: std::fmt::Debug> fmt::Display for Error<T> {
       Error::JSONParsing(error) => write!(f, "error parsing json string: {}", error),
       Error::CDDLParsing(error) => write!(f, "error parsing CDDL: {}", error),
       Error::UTF8Parsing(error) => write!(f, "error parsing utf8: {}", error),
+      Error::MissingRule(error) => write!(f, "error finding rule: {}", error),
     }
   }
 }
@@ -71,7 +85,7 @@ impl<T: std::fmt::Debug + 'static> std::error::Error for Error<T> {
 }
 
 /// cbor validation error
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct ValidationError {
   /// Error message
   pub reason: String,
@@ -127,7 +141,7 @@ impl<T: std::fmt::Debug> Error<T> {
       reason,
       is_multi_type_choice: cv.is_multi_type_choice,
       is_group_to_choice_enum: cv.is_group_to_choice_enum,
-      type_group_name_entry: cv.type_group_name_entry.map(|e| e.to_string()),
+      type_group_name_entry: cv.type_group_name_entry.as_ref().map(|e| e.to_string()),
       is_multi_group_choice: cv.is_multi_group_choice,
     }])
   }
@@ -138,6 +152,21 @@ impl<T: std::fmt::Debug> Error<T> {
 pub struct CBORValidator<'a> {
   cddl: &'a CDDL<'a>,
   cbor: Value,
+  // Encoding of the cbor value, if it was decoded with the raw decoder
+  raw: Option<RawItem>,
+  // Whether or not to report data items that do not use preferred
+  // serialization
+  is_preferred_serialization_required: bool,
+  // Whether or not to report data items that do not use core deterministic
+  // encoding
+  is_deterministic_encoding_required: bool,
+  // Compiled schema used for rule lookups, regular expressions and ABNF
+  // grammars, if any
+  compiled: Option<&'a CompiledSchema<'a>>,
+  // Custom control operators whose callbacks are invoked, if any
+  custom_controls: Option<&'a CustomControls>,
+  // Registry of semantic checks of tag content, if any
+  tag_registry: Option<&'a TagRegistry>,
   errors: Vec<ValidationError>,
   cddl_location: String,
   cbor_location: String,
@@ -155,7 +184,7 @@ pub struct CBORValidator<'a> {
   cut_value: Option<Type1<'a>>,
   // Validate the generic rule given by str ident in current state of AST
   // evaluation
-  eval_generic_rule: Option<&'a str>,
+  eval_generic_rule: Option<Cow<'a, str>>,
   // Aggregation of generic rules
   generic_rules: Vec<GenericRule<'a>>,
   // Control operator token detected in current state of AST evaluation
@@ -169,7 +198,7 @@ pub struct CBORValidator<'a> {
   is_multi_group_choice: bool,
   // Type/group name entry detected in current state of AST evaluation. Used
   // only for providing more verbose error messages
-  type_group_name_entry: Option<&'a str>,
+  type_group_name_entry: Option<Cow<'a, str>>,
   // Whether or not to advance to the next group entry if member key validation
   // fails as detected during the current state of AST evaluation
   advance_to_next_entry: bool,
@@ -178,8 +207,9 @@ pub struct CBORValidator<'a> {
   entry_counts: Option<Vec<EntryCount>>,
   // Collect map entry keys that have already been validated
   validated_keys: Option<Vec<Value>>,
-  // Collect map entry values that have yet to be validated
-  values_to_validate: Option<Vec<Value>>,
+  // Collect map entry values that have yet to be validated, along with the
+  // index of their entry
+  values_to_validate: Option<Vec<(usize, Value)>>,
   // Whether or not the validator is validating a map entry value
   validating_value: bool,
   // Collect valid array indices when entries are type choices
@@ -203,8 +233,8 @@ pub struct CBORValidator<'a> {
 
 #[derive(Clone, Debug)]
 struct GenericRule<'a> {
-  name: &'a str,
-  params: Vec<&'a str>,
+  name: Cow<'a, str>,
+  params: Vec<Cow<'a, str>>,
   args: Vec<Type1<'a>>,
 }
 
@@ -216,6 +246,12 @@ impl<'a> CBORValidator<'a> {
     CBORValidator {
       cddl,
       cbor,
+      raw: None,
+      compiled: None,
+      custom_controls: None,
+      tag_registry: None,
+      is_preferred_serialization_required: false,
+      is_deterministic_encoding_required: false,
       errors: Vec::default(),
       cddl_location: String::new(),
       cbor_location: String::new(),
@@ -255,6 +291,12 @@ impl<'a> CBORValidator<'a> {
     CBORValidator {
       cddl,
       cbor,
+      raw: None,
+      compiled: None,
+      custom_controls: None,
+      tag_registry: None,
+      is_preferred_serialization_required: false,
+      is_deterministic_encoding_required: false,
       errors: Vec::default(),
       cddl_location: String::new(),
       cbor_location: String::new(),
@@ -291,6 +333,12 @@ impl<'a> CBORValidator<'a> {
     CBORValidator {
       cddl,
       cbor,
+      raw: None,
+      compiled: None,
+      custom_controls: None,
+      tag_registry: None,
+      is_preferred_serialization_required: false,
+      is_deterministic_encoding_required: false,
       errors: Vec::default(),
       cddl_location: String::new(),
       cbor_location: String::new(),
@@ -330,6 +378,12 @@ impl<'a> CBORValidator<'a> {
     CBORValidator {
       cddl,
       cbor,
+      raw: None,
+      compiled: None,
+      custom_controls: None,
+      tag_registry: None,
+      is_preferred_serialization_required: false,
+      is_deterministic_encoding_required: false,
       errors: Vec::default(),
       cddl_location: String::new(),
       cbor_location: String::new(),
@@ -359,6 +413,373 @@ impl<'a> CBORValidator<'a> {
     }
   }
 
+  /// Encoding of the cbor value as decoded by [`raw::from_slice`]. Needed to
+  /// validate float widths (`#7.25`, `#7.26` and `#7.27`) and simple values
+  /// (`#7.n`), which are not retained by `ciborium::value::Value`
+  pub fn with_encoding(mut self, raw: RawItem) -> Self {
+    self.raw = Some(raw);
+    self
+  }
+
+  /// Report data items that do not use preferred serialization as defined by
+  /// RFC 8949 section 4.1, i.e. arguments and floats that are not encoded in
+  /// their shortest form. Requires the encoding given by
+  /// [`CBORValidator::with_encoding`]
+  pub fn with_preferred_serialization(mut self) -> Self {
+    self.is_preferred_serialization_required = true;
+    self
+  }
+
+  /// Report data items that do not use core deterministic encoding as defined
+  /// by RFC 8949 section 4.2.1, i.e. in addition to preferred serialization,
+  /// indefinite-length strings, arrays and maps and map keys that are not
+  /// sorted in the bytewise lexicographic order of their encodings. Requires
+  /// the encoding given by [`CBORValidator::with_encoding`]
+  pub fn with_deterministic_encoding(mut self) -> Self {
+    self.is_preferred_serialization_required = true;
+    self.is_deterministic_encoding_required = true;
+    self
+  }
+
+  /// Validate against the resolved CDDL AST of the given compiled schema,
+  /// instead of the CDDL AST the validator was created with, using its rule
+  /// index and the regular expressions and ABNF grammars it compiled
+  pub fn with_compiled_schema(mut self, compiled: &'a CompiledSchema<'a>) -> Self {
+    self.cddl = compiled.cddl();
+    self.compiled = Some(compiled);
+    self
+  }
+
+  /// Invoke the callbacks of the given custom control operators, which the
+  /// CDDL AST was parsed with, for the data items matching their target types
+  pub fn with_custom_controls(mut self, controls: &'a CustomControls) -> Self {
+    self.custom_controls = Some(controls);
+    self
+  }
+
+  /// Check the content of tagged data items matching `#6.n(...)` with the
+  /// checks of the given tag registry
+  pub fn with_tag_registry(mut self, registry: &'a TagRegistry) -> Self {
+    self.tag_registry = Some(registry);
+    self
+  }
+
+  /// Validate the cbor value, found at the given location of an enclosing data
+  /// item, with the given visit of the CDDL AST and return the resulting
+  /// validation errors. Used by the streaming validator to validate the data
+  /// items it decodes
+  pub(crate) fn validate_at(
+    mut self,
+    location: &str,
+    visit: impl FnOnce(&mut Self) -> visitor::Result<Error<std::io::Error>>,
+  ) -> std::result::Result<Vec<ValidationError>, Error<std::io::Error>> {
+    self.cbor_location = location.to_string();
+    self.validate_duplicate_keys();
+    self.validate_encoding();
+    visit(&mut self)?;
+
+    Ok(self.errors)
+  }
+
+  // Rule lookups, using the index of the compiled schema if any
+  fn lookup(&self) -> RuleLookup<'a> {
+    RuleLookup {
+      cddl: self.cddl,
+      compiled: self.compiled,
+    }
+  }
+
+  // Validate the target against the ABNF grammar, using the grammar of the
+  // compiled schema if any
+  #[cfg(feature = "additional-controls")]
+  fn validate_abnf(&self, abnf: &str, target: &str) -> std::result::Result<(), String> {
+    match self.compiled.and_then(|c| c.abnf_grammar(abnf)) {
+      Some(Some(grammar)) => grammar.validate(target),
+      Some(None) => Ok(()),
+      None => validate_abnf(abnf, target),
+    }
+  }
+
+  /// Validate the CBOR byte string data item against the controller of a
+  /// `.cbor` or `.cborseq` control operator, returning the validation errors.
+  /// Used for byte strings embedded in documents other than CBOR
+  pub(crate) fn validate_embedded(
+    &mut self,
+    ctrl: ControlOperator,
+    controller: &Type2<'a>,
+  ) -> std::result::Result<Vec<ValidationError>, Error<std::io::Error>> {
+    self.ctrl = Some(ctrl);
+    self.visit_type2(controller)?;
+    self.ctrl = None;
+
+    Ok(std::mem::take(&mut self.errors))
+  }
+
+  // Validate the JSON document decoded from a CBOR text string against the
+  // controller of a .json control operator
+  #[cfg(all(feature = "additional-controls", feature = "json"))]
+  fn validate_embedded_json(&mut self, value: serde_json::Value, controller: &Type2<'a>) {
+    #[cfg(target_arch = "wasm32")]
+    let mut jv = json::JSONValidator::new(self.cddl, value, self.enabled_features.clone());
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut jv = json::JSONValidator::new(self.cddl, value, self.enabled_features);
+    if let Some(compiled) = self.compiled {
+      jv = jv.with_compiled_schema(compiled);
+    }
+    if let Some(controls) = self.custom_controls {
+      jv = jv.with_custom_controls(controls);
+    }
+
+    match jv.validate_embedded(controller) {
+      Ok(errors) => {
+        for e in errors.into_iter() {
+          if e.json_location.is_empty() {
+            self.add_error(format!("embedded JSON: {}", e.reason));
+          } else {
+            self.add_error(format!(
+              "embedded JSON at {}: {}",
+              e.json_location, e.reason
+            ));
+          }
+        }
+      }
+      Err(e) => self.add_error(format!("error validating embedded JSON, {}", e)),
+    }
+  }
+
+  // Validate a tagged data item against a standard prelude type defined with
+  // the same tag, e.g. 2(h'0102') against biguint = #6.2(bstr), by validating
+  // the tag content against the tagged type of the prelude definition
+  fn validate_tagged_prelude_type<T: std::fmt::Debug + 'static>(
+    &mut self,
+    t: &Type<'a>,
+    tag: u64,
+    value: &Value,
+  ) -> visitor::Result<Error<T>>
+  where
+    cbor::Error<T>: From<cbor::Error<std::io::Error>>,
+  {
+    #[cfg(all(feature = "additional-controls", target_arch = "wasm32"))]
+    let mut cv = CBORValidator::new(self.cddl, value.clone(), self.enabled_features.clone());
+    #[cfg(all(feature = "additional-controls", not(target_arch = "wasm32")))]
+    let mut cv = CBORValidator::new(self.cddl, value.clone(), self.enabled_features);
+    #[cfg(not(feature = "additional-controls"))]
+    let mut cv = CBORValidator::new(self.cddl, value.clone());
+    cv.compiled = self.compiled;
+    cv.custom_controls = self.custom_controls;
+    cv.tag_registry = self.tag_registry;
+
+    cv.raw = self.raw.as_ref().and_then(|r| r.tagged_item()).cloned();
+    cv.cbor_location.push_str(&self.cbor_location);
+    cv.visit_type(t)?;
+
+    if cv.errors.is_empty() {
+      cv.validate_tag_content(tag, value, true);
+    }
+
+    self.errors.append(&mut cv.errors);
+
+    Ok(())
+  }
+
+  // Validate the content of a tag matching #6.n(...), or a standard prelude
+  // type defined with the tag, against the semantics of the tag
+  fn validate_tag_content(&mut self, tag: u64, value: &Value, is_prelude_type: bool) {
+    if let Err(e) = tags::validate_tag(self.tag_registry, tag, value, is_prelude_type) {
+      self.add_error(format!("invalid content of tag {}, {}", tag, e));
+    }
+  }
+
+  fn validate_duplicate_keys(&mut self) {
+    let mut errors = Vec::new();
+    duplicate_keys(
+      &self.cbor,
+      self.raw.as_ref(),
+      &self.cbor_location,
+      &mut errors,
+    );
+
+    let current_location = self.cbor_location.clone();
+    for (location, reason) in errors.into_iter() {
+      self.cbor_location = location;
+      self.add_error(reason);
+    }
+    self.cbor_location = current_location;
+  }
+
+  fn validate_encoding(&mut self) {
+    if !self.is_preferred_serialization_required {
+      return;
+    }
+
+    let mut errors = Vec::new();
+    if let Some(raw) = &self.raw {
+      encoding_violations(
+        &self.cbor,
+        raw,
+        &self.cbor_location,
+        self.is_deterministic_encoding_required,
+        &mut errors,
+      );
+    }
+
+    let current_location = self.cbor_location.clone();
+    for (location, reason) in errors.into_iter() {
+      self.cbor_location = location;
+      self.add_error(reason);
+    }
+    self.cbor_location = current_location;
+  }
+
+  fn raw_map_entry(&self, idx: usize) -> Option<(RawItem, RawItem)> {
+    self.raw.as_ref().and_then(|r| r.map_entry(idx)).cloned()
+  }
+
+  // Encoding of the given map value, preferring the entry of the most recently
+  // validated key since equal values may be encoded differently
+  fn raw_map_value(&self, value: &Value) -> Option<RawItem> {
+    let m = match &self.cbor {
+      Value::Map(m) => m,
+      _ => return None,
+    };
+
+    let last_key = self.validated_keys.as_ref().and_then(|keys| keys.last());
+    let idx = m
+      .iter()
+      .position(|(k, v)| Some(k) == last_key && v == value)
+      .or_else(|| m.iter().position(|(_, v)| v == value))?;
+
+    self.raw_map_entry(idx).map(|(_, v)| v)
+  }
+
+  // Debug representation of the cbor value including the encoding of floats and
+  // simple values, if known
+  fn cbor_with_encoding(&self) -> String {
+    match self.raw.as_ref().map(|r| &r.kind) {
+      Some(RawKind::Simple(s)) => format!("simple({})", s),
+      Some(RawKind::Float(w)) => format!("{:?} (#7.{})", self.cbor, w.additional_info()),
+      _ => format!("{:?}", self.cbor),
+    }
+  }
+
+  // Whether or not the cbor value is encoded as the given simple value. Without
+  // a known encoding, null and undefined are indistinguishable
+  fn is_raw_simple(&self, simple: u8) -> bool {
+    match self.raw.as_ref().map(|r| &r.kind) {
+      Some(RawKind::Simple(s)) => *s == simple,
+      _ => true,
+    }
+  }
+
+  /// Validate a float or simple value against a major type 7 constraint, i.e.
+  /// a float width (`#7.25`, `#7.26` and `#7.27`) or a simple value (`#7.n`).
+  /// Float widths are upper bounds, so that `#7.26` matches the same floats as
+  /// the `float32` prelude type defined with it
+  fn validate_major_type_7(&mut self, constraint: usize) {
+    let raw = self.raw.as_ref().map(|r| &r.kind);
+    let got = self.cbor_with_encoding();
+
+    match constraint {
+      25..=27 => {
+        let width = match constraint {
+          25 => FloatWidth::Half,
+          26 => FloatWidth::Single,
+          _ => FloatWidth::Double,
+        };
+
+        let is_valid = match (&self.cbor, raw) {
+          (Value::Float(_), Some(RawKind::Float(w))) => *w <= width,
+          // Without a known encoding, the float must at least be representable
+          // at the given width
+          (Value::Float(f), None) => raw::is_float_representable(*f, width),
+          _ => false,
+        };
+
+        if !is_valid {
+          self.add_error(format!(
+            "expected float type with constraint {} (#7.{}), got {}",
+            constraint, constraint, got
+          ));
+        }
+      }
+      0..=23 | 32..=255 => {
+        let is_valid = match (&self.cbor, raw) {
+          (_, Some(RawKind::Simple(s))) => *s as usize == constraint,
+          (Value::Bool(b), None) => constraint == 20 + *b as usize,
+          (Value::Null, None) => constraint == 22 || constraint == 23,
+          _ => false,
+        };
+
+        if !is_valid {
+          self.add_error(format!(
+            "expected simple value {} (#7.{}), got {}",
+            constraint, constraint, got
+          ));
+        }
+      }
+      _ => self.add_error(format!(
+        "invalid constraint for major type 7 (#7.{}), got {}",
+        constraint, got
+      )),
+    }
+  }
+
+  /// Validate the items of an array against an array group flattened into an
+  /// array pattern. Occurrence indicators are matched with backtracking, so
+  /// arrays like `[ * a: int, b: tstr, ? c: int ]` validate as expected
+  fn validate_array_pattern<T: std::fmt::Debug + 'static>(
+    &mut self,
+    pattern: &ArrayPattern<'a, '_>,
+    len: usize,
+  ) -> visitor::Result<Error<T>>
+  where
+    cbor::Error<T>: From<cbor::Error<std::io::Error>>,
+  {
+    let a = match &self.cbor {
+      Value::Array(a) => a,
+      _ => return Ok(()),
+    };
+
+    let result = match_array_items::<_, Error<T>, _>(pattern, len, |item, idx| {
+      #[cfg(all(feature = "additional-controls", target_arch = "wasm32"))]
+      let mut cv = CBORValidator::new(self.cddl, a[idx].clone(), self.enabled_features.clone());
+      #[cfg(all(feature = "additional-controls", not(target_arch = "wasm32")))]
+      let mut cv = CBORValidator::new(self.cddl, a[idx].clone(), self.enabled_features);
+      #[cfg(not(feature = "additional-controls"))]
+      let mut cv = CBORValidator::new(self.cddl, a[idx].clone());
+      cv.compiled = self.compiled;
+      cv.custom_controls = self.custom_controls;
+      cv.tag_registry = self.tag_registry;
+
+      cv.raw = self.raw.as_ref().and_then(|r| r.array_item(idx)).cloned();
+      cv.generic_rules = self.generic_rules.clone();
+      cv.eval_generic_rule = self.eval_generic_rule.clone();
+      let _ = write!(cv.cbor_location, "{}/{}", self.cbor_location, idx);
+
+      match item {
+        ArrayItem::Type(t) => cv.visit_type(t)?,
+        ArrayItem::Typename(entry) => cv.visit_type_groupname_entry(entry)?,
+      }
+
+      Ok(cv.errors)
+    })?;
+
+    match result {
+      Ok(()) => (),
+      Err(ArrayMismatch::Items(mut errors)) => self.errors.append(&mut errors),
+      Err(ArrayMismatch::UnexpectedItem(idx)) => {
+        let reason = format!("unexpected array item at index {}, got {:?}", idx, a[idx]);
+        self.add_error(reason);
+      }
+      Err(ArrayMismatch::MissingItems) => self.add_error(format!(
+        "array is missing required items, got {:?}",
+        self.cbor
+      )),
+    }
+
+    Ok(())
+  }
+
   fn validate_array_items<T: std::fmt::Debug + 'static>(
     &mut self,
     token: &ArrayItemToken,
@@ -392,9 +813,13 @@ impl<'a> CBORValidator<'a> {
               let mut cv = CBORValidator::new(self.cddl, v.clone(), self.enabled_features);
               #[cfg(not(feature = "additional-controls"))]
               let mut cv = CBORValidator::new(self.cddl, v.clone());
+              cv.compiled = self.compiled;
+              cv.custom_controls = self.custom_controls;
+              cv.tag_registry = self.tag_registry;
 
+              cv.raw = self.raw.as_ref().and_then(|r| r.array_item(idx)).cloned();
               cv.generic_rules = self.generic_rules.clone();
-              cv.eval_generic_rule = self.eval_generic_rule;
+              cv.eval_generic_rule = self.eval_generic_rule.clone();
               cv.ctrl = self.ctrl;
               cv.is_multi_type_choice = self.is_multi_type_choice;
               let _ = write!(cv.cbor_location, "{}/{}", self.cbor_location, idx);
@@ -445,9 +870,13 @@ impl<'a> CBORValidator<'a> {
                 let mut cv = CBORValidator::new(self.cddl, v.clone(), self.enabled_features);
                 #[cfg(not(feature = "additional-controls"))]
                 let mut cv = CBORValidator::new(self.cddl, v.clone());
+                cv.compiled = self.compiled;
+                cv.custom_controls = self.custom_controls;
+                cv.tag_registry = self.tag_registry;
 
+                cv.raw = self.raw.as_ref().and_then(|r| r.array_item(idx)).cloned();
                 cv.generic_rules = self.generic_rules.clone();
-                cv.eval_generic_rule = self.eval_generic_rule;
+                cv.eval_generic_rule = self.eval_generic_rule.clone();
                 cv.is_multi_type_choice = self.is_multi_type_choice;
                 cv.ctrl = self.ctrl;
                 let _ = write!(cv.cbor_location, "{}/{}", self.cbor_location, idx);
@@ -480,6 +909,35 @@ impl<'a> CBORValidator<'a> {
 
     Ok(())
   }
+
+  /// Validate the cbor value against the type or group rule with the given
+  /// name instead of the root type rule
+  pub fn validate_rule<T>(&mut self, rule: &str) -> std::result::Result<(), cbor::Error<T>>
+  where
+    T: std::fmt::Debug + 'static,
+    cbor::Error<T>: From<cbor::Error<std::io::Error>>,
+  {
+    let rule = root_rule_from_name(self.cddl, rule).map_err(Error::MissingRule)?;
+
+    self.validate_duplicate_keys();
+    self.validate_encoding();
+
+    self.is_root = true;
+    match rule {
+      Rule::Type { rule, .. } => self.visit_type_rule(rule)?,
+      Rule::Group { rule, .. } => {
+        let t2 = group_rule_as_type2(&rule.name, matches!(self.cbor, Value::Array(_)));
+        self.visit_type2(&t2)?;
+      }
+    }
+    self.is_root = false;
+
+    if !self.errors.is_empty() {
+      return Err(Error::Validation(self.errors.clone()));
+    }
+
+    Ok(())
+  }
 }
 
 impl<'a, 'b, T: std::fmt::Debug + 'static> Validator<'a, 'b, cbor::Error<T>> for CBORValidator<'a>
@@ -487,6 +945,9 @@ where
   cbor::Error<T>: From<cbor::Error<std::io::Error>>,
 {
   fn validate(&mut self) -> std::result::Result<(), cbor::Error<T>> {
+    self.validate_duplicate_keys();
+    self.validate_encoding();
+
     for r in self.cddl.rules.iter() {
       // First type rule is root
       if let Rule::Type { rule, .. } = r {
@@ -514,7 +975,7 @@ where
       is_multi_type_choice: self.is_multi_type_choice,
       is_multi_group_choice: self.is_multi_group_choice,
       is_group_to_choice_enum: self.is_group_to_choice_enum,
-      type_group_name_entry: self.type_group_name_entry.map(|e| e.to_string()),
+      type_group_name_entry: self.type_group_name_entry.as_ref().map(|e| e.to_string()),
     });
   }
 }
@@ -530,17 +991,17 @@ where
         .iter_mut()
         .find(|r| r.name == tr.name.ident)
       {
-        gr.params = gp.params.iter().map(|p| p.param.ident).collect();
+        gr.params = gp.params.iter().map(|p| p.param.ident.clone()).collect();
       } else {
         self.generic_rules.push(GenericRule {
-          name: tr.name.ident,
-          params: gp.params.iter().map(|p| p.param.ident).collect(),
+          name: tr.name.ident.clone(),
+          params: gp.params.iter().map(|p| p.param.ident.clone()).collect(),
           args: vec![],
         });
       }
     }
 
-    let type_choice_alternates = type_choice_alternates_from_ident(self.cddl, &tr.name);
+    let type_choice_alternates = self.lookup().type_choice_alternates_from_ident(&tr.name);
     if !type_choice_alternates.is_empty() {
       self.is_multi_type_choice = true;
     }
@@ -568,17 +1029,17 @@ where
         .iter_mut()
         .find(|r| r.name == gr.name.ident)
       {
-        gr.params = gp.params.iter().map(|p| p.param.ident).collect();
+        gr.params = gp.params.iter().map(|p| p.param.ident.clone()).collect();
       } else {
         self.generic_rules.push(GenericRule {
-          name: gr.name.ident,
-          params: gp.params.iter().map(|p| p.param.ident).collect(),
+          name: gr.name.ident.clone(),
+          params: gp.params.iter().map(|p| p.param.ident.clone()).collect(),
           args: vec![],
         });
       }
     }
 
-    let group_choice_alternates = group_choice_alternates_from_ident(self.cddl, &gr.name);
+    let group_choice_alternates = self.lookup().group_choice_alternates_from_ident(&gr.name);
     if !group_choice_alternates.is_empty() {
       self.is_multi_group_choice = true;
     }
@@ -690,7 +1151,7 @@ where
     if self.is_ctrl_map_equality {
       if let Some(t) = &self.ctrl {
         if let Value::Map(m) = &self.cbor {
-          let entry_counts = entry_counts_from_group(self.cddl, g);
+          let entry_counts = self.lookup().entry_counts_from_group(g);
           let len = m.len();
           if let ControlOperator::EQ | ControlOperator::NE = t {
             if !validate_entry_count(&entry_counts, len) {
@@ -740,7 +1201,7 @@ where
   fn visit_group_choice(&mut self, gc: &GroupChoice<'a>) -> visitor::Result<Error<T>> {
     if self.is_group_to_choice_enum {
       let initial_error_count = self.errors.len();
-      for tc in type_choices_from_group_choice(self.cddl, gc).iter() {
+      for tc in self.lookup().type_choices_from_group_choice(gc).iter() {
         let error_count = self.errors.len();
         self.visit_type_choice(tc)?;
         if self.errors.len() == error_count {
@@ -776,6 +1237,41 @@ where
       return self.validate_array_items(&ArrayItemToken::Range(lower, upper, is_inclusive));
     }
 
+    if let Value::Tag(tag @ 4..=5, value) = &self.cbor {
+      let (l, u) = match (
+        exact_number_from_type2(lower),
+        exact_number_from_type2(upper),
+      ) {
+        (Some(l), Some(u)) => (l, u),
+        _ => {
+          self.add_error(
+            "invalid cddl range. upper and lower values must be either integers or floats"
+              .to_string(),
+          );
+          return Ok(());
+        }
+      };
+
+      match exact_number_from_tagged(*tag, value) {
+        Ok(n) => {
+          if is_inclusive && (n < l || n > u) {
+            self.add_error(format!(
+              "expected number to be in range {} <= value <= {}, got {:?}",
+              lower, upper, self.cbor
+            ));
+          } else if !is_inclusive && (n <= l || n >= u) {
+            self.add_error(format!(
+              "expected number to be in range {} < value < {}, got {:?}",
+              lower, upper, self.cbor
+            ));
+          }
+        }
+        Err(e) => self.add_error(e),
+      }
+
+      return Ok(());
+    }
+
     match lower {
       Type2::IntValue { value: l, .. } => match upper {
         Type2::IntValue { value: u, .. } => {
@@ -909,6 +1405,30 @@ where
                 return Ok(());
               }
             },
+            Value::Bytes(b) => match self.ctrl {
+              Some(ControlOperator::SIZE) => {
+                let len = b.len();
+                if is_inclusive {
+                  if len < *l || len > *u {
+                    self.add_error(format!(
+                      "expected byte string length to be in the range {} <= value <= {}, got {}",
+                      l, u, len
+                    ));
+                  }
+                } else if len <= *l || len >= *u {
+                  self.add_error(format!(
+                    "expected byte string length to be in the range {} < value < {}, got {}",
+                    l, u, len
+                  ));
+                }
+
+                return Ok(());
+              }
+              _ => {
+                self.add_error("byte string value cannot be validated against a range without the .size control operator".to_string());
+                return Ok(());
+              }
+            },
             _ => {
               self.add_error(error_str);
               return Ok(());
@@ -995,12 +1515,12 @@ where
         ..
       } = controller
       {
-        if let Some(name) = self.eval_generic_rule {
+        if let Some(name) = &self.eval_generic_rule {
           if let Some(gr) = self
             .generic_rules
             .iter()
             .cloned()
-            .find(|gr| gr.name == name)
+            .find(|gr| gr.name == *name)
           {
             for (idx, gp) in gr.params.iter().enumerate() {
               if let Some(arg) = gr.args.get(idx) {
@@ -1019,12 +1539,12 @@ where
         }
       }
 
-      if let Some(name) = self.eval_generic_rule {
+      if let Some(name) = &self.eval_generic_rule {
         if let Some(gr) = self
           .generic_rules
           .iter()
           .cloned()
-          .find(|gr| gr.name == name)
+          .find(|gr| gr.name == *name)
         {
           for (idx, gp) in gr.params.iter().enumerate() {
             if let Some(arg) = gr.args.get(idx) {
@@ -1042,15 +1562,15 @@ where
       ControlOperator::EQ => {
         match target {
           Type2::Typename { ident, .. } => {
-            if is_ident_string_data_type(self.cddl, ident)
-              || is_ident_numeric_data_type(self.cddl, ident)
+            if self.lookup().is_ident_string_data_type(ident)
+              || self.lookup().is_ident_numeric_data_type(ident)
             {
               return self.visit_type2(controller);
             }
           }
           Type2::Array { group, .. } => {
             if let Value::Array(_) = &self.cbor {
-              let entry_counts = entry_counts_from_group(self.cddl, group);
+              let entry_counts = self.lookup().entry_counts_from_group(group);
               self.entry_counts = Some(entry_counts);
               self.visit_type2(controller)?;
               self.entry_counts = None;
@@ -1077,8 +1597,8 @@ where
       ControlOperator::NE => {
         match target {
           Type2::Typename { ident, .. } => {
-            if is_ident_string_data_type(self.cddl, ident)
-              || is_ident_numeric_data_type(self.cddl, ident)
+            if self.lookup().is_ident_string_data_type(ident)
+              || self.lookup().is_ident_numeric_data_type(ident)
             {
               self.ctrl = Some(ctrl);
               self.visit_type2(controller)?;
@@ -1113,7 +1633,7 @@ where
       }
       ControlOperator::LT | ControlOperator::GT | ControlOperator::GE | ControlOperator::LE => {
         match target {
-          Type2::Typename { ident, .. } if is_ident_numeric_data_type(self.cddl, ident) => {
+          Type2::Typename { ident, .. } if self.lookup().is_ident_numeric_data_type(ident) => {
             self.ctrl = Some(ctrl);
             self.visit_type2(controller)?;
             self.ctrl = None;
@@ -1130,9 +1650,9 @@ where
       }
       ControlOperator::SIZE => match target {
         Type2::Typename { ident, .. }
-          if is_ident_string_data_type(self.cddl, ident)
-            || is_ident_uint_data_type(self.cddl, ident)
-            || is_ident_byte_string_data_type(self.cddl, ident) =>
+          if self.lookup().is_ident_string_data_type(ident)
+            || self.lookup().is_ident_uint_data_type(ident)
+            || self.lookup().is_ident_byte_string_data_type(ident) =>
         {
           self.ctrl = Some(ctrl);
           self.visit_type2(controller)?;
@@ -1201,7 +1721,7 @@ where
       ControlOperator::REGEXP | ControlOperator::PCRE => {
         self.ctrl = Some(ctrl);
         match target {
-          Type2::Typename { ident, .. } if is_ident_string_data_type(self.cddl, ident) => {
+          Type2::Typename { ident, .. } if self.lookup().is_ident_string_data_type(ident) => {
             match self.cbor {
               Value::Text(_) | Value::Array(_) => self.visit_type2(controller)?,
               _ => self.add_error(format!(
@@ -1222,7 +1742,7 @@ where
       ControlOperator::CBOR | ControlOperator::CBORSEQ => {
         self.ctrl = Some(ctrl);
         match target {
-          Type2::Typename { ident, .. } if is_ident_byte_string_data_type(self.cddl, ident) => {
+          Type2::Typename { ident, .. } if self.lookup().is_ident_byte_string_data_type(ident) => {
             match &self.cbor {
               Value::Bytes(_) | Value::Array(_) => self.visit_type2(controller)?,
               _ => self.add_error(format!(
@@ -1244,8 +1764,8 @@ where
         self.ctrl = Some(ctrl);
         match target {
           Type2::Typename { ident, .. }
-            if is_ident_byte_string_data_type(self.cddl, ident)
-              || is_ident_uint_data_type(self.cddl, ident) =>
+            if self.lookup().is_ident_byte_string_data_type(ident)
+              || self.lookup().is_ident_uint_data_type(ident) =>
           {
             match &self.cbor {
               Value::Bytes(_) | Value::Array(_) => self.visit_type2(controller)?,
@@ -1356,7 +1876,7 @@ where
         self.ctrl = Some(ctrl);
 
         match target {
-          Type2::Typename { ident, .. } if is_ident_string_data_type(self.cddl, ident) => {
+          Type2::Typename { ident, .. } if self.lookup().is_ident_string_data_type(ident) => {
             match self.cbor {
               Value::Text(_) | Value::Array(_) => {
                 if let Type2::ParenthesizedType { pt, .. } = controller {
@@ -1404,7 +1924,7 @@ where
         self.ctrl = Some(ctrl);
 
         match target {
-          Type2::Typename { ident, .. } if is_ident_byte_string_data_type(self.cddl, ident) => {
+          Type2::Typename { ident, .. } if self.lookup().is_ident_byte_string_data_type(ident) => {
             match self.cbor {
               Value::Bytes(_) | Value::Array(_) => {
                 if let Type2::ParenthesizedType { pt, .. } = controller {
@@ -1448,100 +1968,343 @@ where
         Ok(())
       }
       #[cfg(feature = "additional-controls")]
-      #[cfg(not(target_arch = "wasm32"))]
-      ControlOperator::FEATURE => {
-        self.ctrl = Some(ctrl);
-
-        if let Some(ef) = self.enabled_features {
-          let tv = text_value_from_type2(self.cddl, controller);
-          if let Some(Type2::TextValue { value, .. }) = tv {
-            if ef.contains(&&**value) {
-              let err_count = self.errors.len();
-              self.visit_type2(target)?;
-              if self.errors.len() > err_count {
-                self.has_feature_errors = true;
-              }
-              self.ctrl = None;
-            } else {
-              self
-                .disabled_features
-                .get_or_insert(vec![value.to_string()])
-                .push(value.to_string());
-            }
-          } else if let Some(Type2::UTF8ByteString { value, .. }) = tv {
-            let value = std::str::from_utf8(value).map_err(Error::UTF8Parsing)?;
-            if ef.contains(&value) {
-              let err_count = self.errors.len();
-              self.visit_type2(target)?;
-              if self.errors.len() > err_count {
-                self.has_feature_errors = true;
-              }
-              self.ctrl = None;
-            } else {
-              self
-                .disabled_features
-                .get_or_insert(vec![value.to_string()])
-                .push(value.to_string());
+      ControlOperator::B64U
+      | ControlOperator::B64USLOPPY
+      | ControlOperator::B64C
+      | ControlOperator::B64CSLOPPY
+      | ControlOperator::B45
+      | ControlOperator::B32
+      | ControlOperator::H32
+      | ControlOperator::HEX
+      | ControlOperator::HEXLC
+      | ControlOperator::HEXUC => {
+        match target {
+          Type2::Typename { ident, .. } if self.lookup().is_ident_string_data_type(ident) => {
+            match &self.cbor {
+              Value::Text(s) => match decode_text_conversion(ctrl, s) {
+                Ok(b) => {
+                  #[cfg(target_arch = "wasm32")]
+                  let mut cv =
+                    CBORValidator::new(self.cddl, Value::Bytes(b), self.enabled_features.clone());
+                  #[cfg(not(target_arch = "wasm32"))]
+                  let mut cv =
+                    CBORValidator::new(self.cddl, Value::Bytes(b), self.enabled_features);
+                  cv.compiled = self.compiled;
+                  cv.custom_controls = self.custom_controls;
+                  cv.tag_registry = self.tag_registry;
+
+                  cv.generic_rules = self.generic_rules.clone();
+                  cv.eval_generic_rule = self.eval_generic_rule.clone();
+                  cv.is_multi_type_choice = self.is_multi_type_choice;
+                  cv.is_multi_group_choice = self.is_multi_group_choice;
+                  cv.cbor_location.push_str(&self.cbor_location);
+                  cv.type_group_name_entry = self.type_group_name_entry.clone();
+                  cv.visit_type2(controller)?;
+
+                  self.errors.append(&mut cv.errors);
+                }
+                Err(e) => self.add_error(format!(
+                  "expected text string {} encoding of a byte string, decoding error: {}",
+                  ctrl, e
+                )),
+              },
+              _ => self.add_error(format!(
+                "{} control can only be matched against a CBOR text string, got {:?}",
+                ctrl, self.cbor,
+              )),
             }
           }
+          _ => self.add_error(format!(
+            "{} control can only be matched against a text string data type, got {}",
+            ctrl, target,
+          )),
         }
 
-        self.ctrl = None;
-
         Ok(())
       }
       #[cfg(feature = "additional-controls")]
-      #[cfg(target_arch = "wasm32")]
-      ControlOperator::FEATURE => {
-        self.ctrl = Some(ctrl);
-
-        if let Some(ef) = &self.enabled_features {
-          let tv = text_value_from_type2(self.cddl, controller);
-          if let Some(Type2::TextValue { value, .. }) = tv {
-            if ef.contains(&JsValue::from(value.as_ref())) {
-              let err_count = self.errors.len();
-              self.visit_type2(target)?;
-              if self.errors.len() > err_count {
-                self.has_feature_errors = true;
-              }
-              self.ctrl = None;
-            } else {
-              self
-                .disabled_features
-                .get_or_insert(vec![value.to_string()])
-                .push(value.to_string());
-            }
-          } else if let Some(Type2::UTF8ByteString { value, .. }) = tv {
-            let value = std::str::from_utf8(value).map_err(Error::UTF8Parsing)?;
-            if ef.contains(&JsValue::from(value)) {
-              let err_count = self.errors.len();
-              self.visit_type2(target)?;
-              if self.errors.len() > err_count {
-                self.has_feature_errors = true;
-              }
-              self.ctrl = None;
-            } else {
-              self
-                .disabled_features
-                .get_or_insert(vec![value.to_string()])
-                .push(value.to_string());
+      ControlOperator::DECIMAL => {
+        match target {
+          Type2::Typename { ident, .. } if self.lookup().is_ident_string_data_type(ident) => {
+            match &self.cbor {
+              Value::Text(s) => match decode_decimal(s)
+                .and_then(|i| ciborium::value::Integer::try_from(i).map_err(|e| e.to_string()))
+              {
+                Ok(i) => {
+                  #[cfg(target_arch = "wasm32")]
+                  let mut cv =
+                    CBORValidator::new(self.cddl, Value::Integer(i), self.enabled_features.clone());
+                  #[cfg(not(target_arch = "wasm32"))]
+                  let mut cv =
+                    CBORValidator::new(self.cddl, Value::Integer(i), self.enabled_features);
+                  cv.compiled = self.compiled;
+                  cv.custom_controls = self.custom_controls;
+                  cv.tag_registry = self.tag_registry;
+
+                  cv.generic_rules = self.generic_rules.clone();
+                  cv.eval_generic_rule = self.eval_generic_rule.clone();
+                  cv.is_multi_type_choice = self.is_multi_type_choice;
+                  cv.is_multi_group_choice = self.is_multi_group_choice;
+                  cv.cbor_location.push_str(&self.cbor_location);
+                  cv.type_group_name_entry = self.type_group_name_entry.clone();
+                  cv.visit_type2(controller)?;
+
+                  self.errors.append(&mut cv.errors);
+                }
+                Err(e) => self.add_error(format!(
+                  "expected text string decimal representation of an integer, decoding error: {}",
+                  e
+                )),
+              },
+              _ => self.add_error(format!(
+                ".decimal control can only be matched against a CBOR text string, got {:?}",
+                self.cbor,
+              )),
             }
           }
+          _ => self.add_error(format!(
+            ".decimal control can only be matched against a text string data type, got {}",
+            target,
+          )),
         }
 
-        self.ctrl = None;
-
         Ok(())
       }
-    }
-  }
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::PRINTF => {
+        self.ctrl = Some(ctrl);
 
-  fn visit_type2(&mut self, t2: &Type2<'a>) -> visitor::Result<Error<T>> {
+        match printf_operation(self.cddl, target, controller) {
+          Ok(values) => {
+            let error_count = self.errors.len();
+            for v in values.iter() {
+              let cur_errors = self.errors.len();
+
+              self.visit_type2(v)?;
+
+              if self.errors.len() == cur_errors {
+                for _ in 0..self.errors.len() - error_count {
+                  self.errors.pop();
+                }
+
+                break;
+              }
+            }
+          }
+          Err(e) => self.add_error(e),
+        }
+
+        self.ctrl = None;
+
+        Ok(())
+      }
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::JSON => {
+        match target {
+          Type2::Typename { ident, .. } if self.lookup().is_ident_string_data_type(ident) => {
+            match &self.cbor {
+              #[cfg(feature = "json")]
+              Value::Text(s) => match serde_json::from_str::<serde_json::Value>(s) {
+                Ok(value) => self.validate_embedded_json(value, controller),
+                Err(e) => self.add_error(format!("error parsing embedded JSON, {}", e)),
+              },
+              #[cfg(not(feature = "json"))]
+              Value::Text(_) => self.add_error(
+                ".json control requires the json feature to validate embedded JSON".to_string(),
+              ),
+              _ => self.add_error(format!(
+                ".json control can only be matched against a CBOR text string, got {:?}",
+                self.cbor,
+              )),
+            }
+          }
+          _ => self.add_error(format!(
+            ".json control can only be matched against a text string data type, got {}",
+            target,
+          )),
+        }
+
+        Ok(())
+      }
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::JOIN => {
+        match target {
+          Type2::Typename { ident, .. } if self.lookup().is_ident_string_data_type(ident) => {
+            match &self.cbor {
+              Value::Text(s) => {
+                match join_controller_group(self.cddl, controller)
+                  .and_then(|group| ArrayPattern::from_group(self.lookup(), group))
+                {
+                  Some(pattern) => {
+                    let is_match = match_joined_text(&pattern, s, |item, piece| {
+                      #[cfg(target_arch = "wasm32")]
+                      let mut cv = CBORValidator::new(
+                        self.cddl,
+                        Value::Text(piece.to_string()),
+                        self.enabled_features.clone(),
+                      );
+                      #[cfg(not(target_arch = "wasm32"))]
+                      let mut cv = CBORValidator::new(
+                        self.cddl,
+                        Value::Text(piece.to_string()),
+                        self.enabled_features,
+                      );
+                      cv.compiled = self.compiled;
+                      cv.custom_controls = self.custom_controls;
+                      cv.tag_registry = self.tag_registry;
+
+                      cv.generic_rules = self.generic_rules.clone();
+                      cv.eval_generic_rule = self.eval_generic_rule.clone();
+                      cv.cbor_location.push_str(&self.cbor_location);
+
+                      match item {
+                        ArrayItem::Type(t) => cv.visit_type(t)?,
+                        ArrayItem::Typename(entry) => cv.visit_type_groupname_entry(entry)?,
+                      }
+
+                      Ok::<_, Error<T>>(cv.errors.is_empty())
+                    })?;
+
+                    if !is_match {
+                      self.add_error(format!(
+                        "expected text string joined from an array matching {}, got {:?}",
+                        controller, self.cbor
+                      ));
+                    }
+                  }
+                  None => self.add_error(format!(
+                    "invalid controller used for .join operation, got {}",
+                    controller
+                  )),
+                }
+              }
+              _ => self.add_error(format!(
+                ".join control can only be matched against a CBOR text string, got {:?}",
+                self.cbor,
+              )),
+            }
+          }
+          _ => self.add_error(format!(
+            ".join control can only be matched against a text string data type, got {}",
+            target,
+          )),
+        }
+
+        Ok(())
+      }
+      ControlOperator::CUSTOM(name) => {
+        // The callback is only invoked for data items matching the target
+        let error_count = self.errors.len();
+        self.visit_type2(target)?;
+
+        if self.errors.len() == error_count {
+          if let Err(e) = custom::validate_control(
+            self.custom_controls,
+            name,
+            ControlValue::CBOR(&self.cbor),
+            target,
+            controller,
+          ) {
+            self.add_error(e);
+          }
+        }
+
+        Ok(())
+      }
+      #[cfg(feature = "additional-controls")]
+      #[cfg(not(target_arch = "wasm32"))]
+      ControlOperator::FEATURE => {
+        self.ctrl = Some(ctrl);
+
+        if let Some(ef) = self.enabled_features {
+          let tv = self.lookup().text_value_from_type2(controller);
+          if let Some(Type2::TextValue { value, .. }) = tv {
+            if ef.contains(&&**value) {
+              let err_count = self.errors.len();
+              self.visit_type2(target)?;
+              if self.errors.len() > err_count {
+                self.has_feature_errors = true;
+              }
+              self.ctrl = None;
+            } else {
+              self
+                .disabled_features
+                .get_or_insert(vec![value.to_string()])
+                .push(value.to_string());
+            }
+          } else if let Some(Type2::UTF8ByteString { value, .. }) = tv {
+            let value = std::str::from_utf8(value).map_err(Error::UTF8Parsing)?;
+            if ef.contains(&value) {
+              let err_count = self.errors.len();
+              self.visit_type2(target)?;
+              if self.errors.len() > err_count {
+                self.has_feature_errors = true;
+              }
+              self.ctrl = None;
+            } else {
+              self
+                .disabled_features
+                .get_or_insert(vec![value.to_string()])
+                .push(value.to_string());
+            }
+          }
+        }
+
+        self.ctrl = None;
+
+        Ok(())
+      }
+      #[cfg(feature = "additional-controls")]
+      #[cfg(target_arch = "wasm32")]
+      ControlOperator::FEATURE => {
+        self.ctrl = Some(ctrl);
+
+        if let Some(ef) = &self.enabled_features {
+          let tv = self.lookup().text_value_from_type2(controller);
+          if let Some(Type2::TextValue { value, .. }) = tv {
+            if ef.contains(&JsValue::from(value.as_ref())) {
+              let err_count = self.errors.len();
+              self.visit_type2(target)?;
+              if self.errors.len() > err_count {
+                self.has_feature_errors = true;
+              }
+              self.ctrl = None;
+            } else {
+              self
+                .disabled_features
+                .get_or_insert(vec![value.to_string()])
+                .push(value.to_string());
+            }
+          } else if let Some(Type2::UTF8ByteString { value, .. }) = tv {
+            let value = std::str::from_utf8(value).map_err(Error::UTF8Parsing)?;
+            if ef.contains(&JsValue::from(value)) {
+              let err_count = self.errors.len();
+              self.visit_type2(target)?;
+              if self.errors.len() > err_count {
+                self.has_feature_errors = true;
+              }
+              self.ctrl = None;
+            } else {
+              self
+                .disabled_features
+                .get_or_insert(vec![value.to_string()])
+                .push(value.to_string());
+            }
+          }
+        }
+
+        self.ctrl = None;
+
+        Ok(())
+      }
+    }
+  }
+
+  fn visit_type2(&mut self, t2: &Type2<'a>) -> visitor::Result<Error<T>> {
     if matches!(self.ctrl, Some(ControlOperator::CBOR)) {
       if let Value::Bytes(b) = &self.cbor {
-        let value = ciborium::de::from_reader(&b[..]);
-        match value {
-          Ok(value) => {
+        match raw::from_slice(b) {
+          Ok((value, raw)) => {
             let current_location = self.cbor_location.clone();
 
             #[cfg(all(feature = "additional-controls", target_arch = "wasm32"))]
@@ -1551,13 +2314,17 @@ where
 
             #[cfg(not(feature = "additional-controls"))]
             let mut cv = CBORValidator::new(self.cddl, value);
+            cv.compiled = self.compiled;
+            cv.custom_controls = self.custom_controls;
+            cv.tag_registry = self.tag_registry;
 
+            cv.raw = Some(raw);
             cv.generic_rules = self.generic_rules.clone();
-            cv.eval_generic_rule = self.eval_generic_rule;
+            cv.eval_generic_rule = self.eval_generic_rule.clone();
             cv.is_multi_type_choice = self.is_multi_type_choice;
             cv.is_multi_group_choice = self.is_multi_group_choice;
             cv.cbor_location.push_str(&self.cbor_location);
-            cv.type_group_name_entry = self.type_group_name_entry;
+            cv.type_group_name_entry = self.type_group_name_entry.clone();
             cv.visit_type2(t2)?;
 
             if cv.errors.is_empty() {
@@ -1576,33 +2343,30 @@ where
       return Ok(());
     } else if matches!(self.ctrl, Some(ControlOperator::CBORSEQ)) {
       if let Value::Bytes(b) = &self.cbor {
-        let value = ciborium::de::from_reader(&b[..]);
-        match value {
-          Ok(Value::Array(_)) => {
+        // The data items of the sequence are matched against the controller
+        // as the items of an array
+        match raw::sequence_from_slice(b) {
+          Ok((value, raw)) => {
             let current_location = self.cbor_location.clone();
 
             #[cfg(all(feature = "additional-controls", target_arch = "wasm32"))]
-            let mut cv = CBORValidator::new(
-              self.cddl,
-              value.unwrap_or(Value::Null),
-              self.enabled_features.clone(),
-            );
+            let mut cv = CBORValidator::new(self.cddl, value, self.enabled_features.clone());
             #[cfg(all(feature = "additional-controls", not(target_arch = "wasm32")))]
-            let mut cv = CBORValidator::new(
-              self.cddl,
-              value.unwrap_or(Value::Null),
-              self.enabled_features,
-            );
+            let mut cv = CBORValidator::new(self.cddl, value, self.enabled_features);
 
             #[cfg(not(feature = "additional-controls"))]
-            let mut cv = CBORValidator::new(self.cddl, value.unwrap_or(Value::Null));
+            let mut cv = CBORValidator::new(self.cddl, value);
+            cv.compiled = self.compiled;
+            cv.custom_controls = self.custom_controls;
+            cv.tag_registry = self.tag_registry;
 
+            cv.raw = Some(raw);
             cv.generic_rules = self.generic_rules.clone();
-            cv.eval_generic_rule = self.eval_generic_rule;
+            cv.eval_generic_rule = self.eval_generic_rule.clone();
             cv.is_multi_type_choice = self.is_multi_type_choice;
             cv.is_multi_group_choice = self.is_multi_group_choice;
             cv.cbor_location.push_str(&self.cbor_location);
-            cv.type_group_name_entry = self.type_group_name_entry;
+            cv.type_group_name_entry = self.type_group_name_entry.clone();
             cv.visit_type2(t2)?;
 
             if cv.errors.is_empty() {
@@ -1613,12 +2377,8 @@ where
             self.errors.append(&mut cv.errors);
           }
           Err(e) => {
-            self.add_error(format!("error decoding embedded CBOR, {}", e));
+            self.add_error(format!("error decoding embedded CBOR sequence, {}", e));
           }
-          Ok(v) => self.add_error(format!(
-            "embedded CBOR must be a CBOR sequence, got {:?}",
-            v
-          )),
         }
       }
 
@@ -1627,12 +2387,15 @@ where
 
     match t2 {
       Type2::TextValue { value, .. } => self.visit_value(&token::Value::TEXT(value.clone())),
+      Type2::UTF8ByteString { .. } | Type2::B16ByteString { .. } | Type2::B64ByteString { .. } => {
+        walk_type2(self, t2)
+      }
       Type2::Map { group, .. } => match &self.cbor {
         Value::Map(m) => {
           if self.is_member_key {
             let current_location = self.cbor_location.clone();
 
-            for (k, v) in m.iter() {
+            for (idx, (k, v)) in m.iter().enumerate() {
               #[cfg(feature = "additional-controls")]
               #[cfg(all(feature = "additional-controls", target_arch = "wasm32"))]
               let mut cv = CBORValidator::new(self.cddl, k.clone(), self.enabled_features.clone());
@@ -1640,13 +2403,17 @@ where
               let mut cv = CBORValidator::new(self.cddl, k.clone(), self.enabled_features);
               #[cfg(not(feature = "additional-controls"))]
               let mut cv = CBORValidator::new(self.cddl, k.clone());
+              cv.compiled = self.compiled;
+              cv.custom_controls = self.custom_controls;
+              cv.tag_registry = self.tag_registry;
 
+              cv.raw = self.raw_map_entry(idx).map(|(k, _)| k);
               cv.generic_rules = self.generic_rules.clone();
-              cv.eval_generic_rule = self.eval_generic_rule;
+              cv.eval_generic_rule = self.eval_generic_rule.clone();
               cv.is_multi_type_choice = self.is_multi_type_choice;
               cv.is_multi_group_choice = self.is_multi_group_choice;
               cv.cbor_location.push_str(&self.cbor_location);
-              cv.type_group_name_entry = self.type_group_name_entry;
+              cv.type_group_name_entry = self.type_group_name_entry.clone();
               cv.visit_type2(t2)?;
 
               if cv.errors.is_empty() {
@@ -1705,7 +2472,13 @@ where
             return Ok(());
           }
 
-          let entry_counts = entry_counts_from_group(self.cddl, group);
+          if self.ctrl.is_none() {
+            if let Some(pattern) = ArrayPattern::from_group(self.lookup(), group) {
+              return self.validate_array_pattern(&pattern, a.len());
+            }
+          }
+
+          let entry_counts = self.lookup().entry_counts_from_group(group);
           self.entry_counts = Some(entry_counts);
           self.visit_group(group)?;
           self.entry_counts = None;
@@ -1730,24 +2503,28 @@ where
         Value::Map(m) if self.is_member_key => {
           let current_location = self.cbor_location.clone();
 
-          let entry_counts = entry_counts_from_group(self.cddl, group);
+          let entry_counts = self.lookup().entry_counts_from_group(group);
           self.entry_counts = Some(entry_counts);
 
-          for (k, v) in m.iter() {
+          for (idx, (k, v)) in m.iter().enumerate() {
             #[cfg(all(feature = "additional-controls", target_arch = "wasm32"))]
             let mut cv = CBORValidator::new(self.cddl, k.clone(), self.enabled_features.clone());
             #[cfg(all(feature = "additional-controls", not(target_arch = "wasm32")))]
             let mut cv = CBORValidator::new(self.cddl, k.clone(), self.enabled_features);
             #[cfg(not(feature = "additional-controls"))]
             let mut cv = CBORValidator::new(self.cddl, k.clone());
+            cv.compiled = self.compiled;
+            cv.custom_controls = self.custom_controls;
+            cv.tag_registry = self.tag_registry;
 
+            cv.raw = self.raw_map_entry(idx).map(|(k, _)| k);
             cv.generic_rules = self.generic_rules.clone();
             cv.entry_counts = self.entry_counts.clone();
-            cv.eval_generic_rule = self.eval_generic_rule;
+            cv.eval_generic_rule = self.eval_generic_rule.clone();
             cv.is_multi_type_choice = self.is_multi_type_choice;
             cv.is_multi_group_choice = self.is_multi_group_choice;
             cv.cbor_location.push_str(&self.cbor_location);
-            cv.type_group_name_entry = self.type_group_name_entry;
+            cv.type_group_name_entry = self.type_group_name_entry.clone();
             cv.visit_type2(t2)?;
 
             if cv.errors.is_empty() {
@@ -1778,7 +2555,7 @@ where
         ..
       } => {
         if let Some(ga) = generic_args {
-          if let Some(rule) = rule_from_ident(self.cddl, ident) {
+          if let Some(rule) = self.lookup().rule_from_ident(ident) {
             if let Some(gr) = self
               .generic_rules
               .iter_mut()
@@ -1789,7 +2566,7 @@ where
               }
             } else if let Some(params) = generic_params_from_rule(rule) {
               self.generic_rules.push(GenericRule {
-                name: ident.ident,
+                name: ident.ident.clone(),
                 params,
                 args: ga.args.iter().cloned().map(|arg| *arg.arg).collect(),
               });
@@ -1802,9 +2579,13 @@ where
             let mut cv = CBORValidator::new(self.cddl, self.cbor.clone(), self.enabled_features);
             #[cfg(not(feature = "additional-controls"))]
             let mut cv = CBORValidator::new(self.cddl, self.cbor.clone());
+            cv.compiled = self.compiled;
+            cv.custom_controls = self.custom_controls;
+            cv.tag_registry = self.tag_registry;
 
+            cv.raw = self.raw.clone();
             cv.generic_rules = self.generic_rules.clone();
-            cv.eval_generic_rule = Some(ident.ident);
+            cv.eval_generic_rule = Some(ident.ident.clone());
             cv.is_group_to_choice_enum = true;
             cv.is_multi_type_choice = self.is_multi_type_choice;
             cv.visit_rule(rule)?;
@@ -1815,7 +2596,7 @@ where
           }
         }
 
-        if group_rule_from_ident(self.cddl, ident).is_none() {
+        if self.lookup().group_rule_from_ident(ident).is_none() {
           self.add_error(format!(
             "rule {} must be a group rule to turn it into a choice",
             ident
@@ -1841,7 +2622,7 @@ where
         ..
       } => {
         if let Some(ga) = generic_args {
-          if let Some(rule) = rule_from_ident(self.cddl, ident) {
+          if let Some(rule) = self.lookup().rule_from_ident(ident) {
             if let Some(gr) = self
               .generic_rules
               .iter_mut()
@@ -1852,7 +2633,7 @@ where
               }
             } else if let Some(params) = generic_params_from_rule(rule) {
               self.generic_rules.push(GenericRule {
-                name: ident.ident,
+                name: ident.ident.clone(),
                 params,
                 args: ga.args.iter().cloned().map(|arg| *arg.arg).collect(),
               });
@@ -1865,9 +2646,13 @@ where
             let mut cv = CBORValidator::new(self.cddl, self.cbor.clone(), self.enabled_features);
             #[cfg(not(feature = "additional-controls"))]
             let mut cv = CBORValidator::new(self.cddl, self.cbor.clone());
+            cv.compiled = self.compiled;
+            cv.custom_controls = self.custom_controls;
+            cv.tag_registry = self.tag_registry;
 
+            cv.raw = self.raw.clone();
             cv.generic_rules = self.generic_rules.clone();
-            cv.eval_generic_rule = Some(ident.ident);
+            cv.eval_generic_rule = Some(ident.ident.clone());
             cv.is_multi_type_choice = self.is_multi_type_choice;
             cv.visit_rule(rule)?;
 
@@ -1877,7 +2662,7 @@ where
           }
         }
 
-        let type_choice_alternates = type_choice_alternates_from_ident(self.cddl, ident);
+        let type_choice_alternates = self.lookup().type_choice_alternates_from_ident(ident);
         if !type_choice_alternates.is_empty() {
           self.is_multi_type_choice = true;
         }
@@ -1909,12 +2694,12 @@ where
         // Per
         // https://github.com/w3c/did-spec-registries/pull/138#issuecomment-719739215,
         // strip tag and validate underlying type
-        if let Some(Type2::TaggedData { t, .. }) = tag_from_token(&lookup_ident(ident.ident)) {
+        if let Some(Type2::TaggedData { t, .. }) = tag_from_token(&lookup_ident(&ident.ident)) {
           return self.visit_type(&t);
         }
 
         if let Some(ga) = generic_args {
-          if let Some(rule) = unwrap_rule_from_ident(self.cddl, ident) {
+          if let Some(rule) = self.lookup().unwrap_rule_from_ident(ident) {
             if let Some(gr) = self
               .generic_rules
               .iter_mut()
@@ -1925,7 +2710,7 @@ where
               }
             } else if let Some(params) = generic_params_from_rule(rule) {
               self.generic_rules.push(GenericRule {
-                name: ident.ident,
+                name: ident.ident.clone(),
                 params,
                 args: ga.args.iter().cloned().map(|arg| *arg.arg).collect(),
               });
@@ -1938,9 +2723,13 @@ where
             let mut cv = CBORValidator::new(self.cddl, self.cbor.clone(), self.enabled_features);
             #[cfg(not(feature = "additional-controls"))]
             let mut cv = CBORValidator::new(self.cddl, self.cbor.clone());
+            cv.compiled = self.compiled;
+            cv.custom_controls = self.custom_controls;
+            cv.tag_registry = self.tag_registry;
 
+            cv.raw = self.raw.clone();
             cv.generic_rules = self.generic_rules.clone();
-            cv.eval_generic_rule = Some(ident.ident);
+            cv.eval_generic_rule = Some(ident.ident.clone());
             cv.is_multi_type_choice = self.is_multi_type_choice;
             cv.visit_rule(rule)?;
 
@@ -1950,7 +2739,7 @@ where
           }
         }
 
-        if let Some(rule) = unwrap_rule_from_ident(self.cddl, ident) {
+        if let Some(rule) = self.lookup().unwrap_rule_from_ident(ident) {
           return self.visit_rule(rule);
         }
 
@@ -1989,15 +2778,23 @@ where
           let mut cv = CBORValidator::new(self.cddl, value.as_ref().clone(), self.enabled_features);
           #[cfg(not(feature = "additional-controls"))]
           let mut cv = CBORValidator::new(self.cddl, value.as_ref().clone());
+          cv.compiled = self.compiled;
+          cv.custom_controls = self.custom_controls;
+          cv.tag_registry = self.tag_registry;
 
+          cv.raw = self.raw.as_ref().and_then(|r| r.tagged_item()).cloned();
           cv.generic_rules = self.generic_rules.clone();
-          cv.eval_generic_rule = self.eval_generic_rule;
+          cv.eval_generic_rule = self.eval_generic_rule.clone();
           cv.is_multi_type_choice = self.is_multi_type_choice;
           cv.is_multi_group_choice = self.is_multi_group_choice;
           cv.cbor_location.push_str(&self.cbor_location);
-          cv.type_group_name_entry = self.type_group_name_entry;
+          cv.type_group_name_entry = self.type_group_name_entry.clone();
           cv.visit_type(t)?;
 
+          if cv.errors.is_empty() && tag.is_some() {
+            cv.validate_tag_content(*actual_tag, value, false);
+          }
+
           self.errors.append(&mut cv.errors);
           Ok(())
         }
@@ -2138,12 +2935,13 @@ where
 
           Ok(())
         }
-        Value::Float(_f) => {
+        Value::Float(_) | Value::Bool(_) | Value::Null => {
           match mt {
-            7u8 => match constraint {
-              Some(_c) => unimplemented!(),
-              _ => return Ok(()),
-            },
+            7u8 => {
+              if let Some(c) = constraint {
+                self.validate_major_type_7(*c);
+              }
+            }
             _ => self.add_error(format!(
               "expected major type {} with constraint {:?}, got {:?}",
               mt, constraint, self.cbor
@@ -2169,23 +2967,16 @@ where
       Type2::Any { .. } => Ok(()),
       #[cfg(not(feature = "ast-span"))]
       Type2::Any {} => Ok(()),
-      _ => {
-        self.add_error(format!(
-          "unsupported data type for validating cbor, got {}",
-          t2
-        ));
-        Ok(())
-      }
     }
   }
 
   fn visit_identifier(&mut self, ident: &Identifier<'a>) -> visitor::Result<Error<T>> {
-    if let Some(name) = self.eval_generic_rule {
+    if let Some(name) = &self.eval_generic_rule {
       if let Some(gr) = self
         .generic_rules
         .iter()
         .cloned()
-        .find(|gr| gr.name == name)
+        .find(|gr| gr.name == *name)
       {
         for (idx, gp) in gr.params.iter().enumerate() {
           if *gp == ident.ident {
@@ -2200,24 +2991,31 @@ where
     // self.is_colon_shortcut_present is only true when the ident is part of a
     // member key
     if !self.is_colon_shortcut_present {
-      if let Some(r) = rule_from_ident(self.cddl, ident) {
+      if let Some(r) = self.lookup().rule_from_ident(ident) {
         return self.visit_rule(r);
       }
     }
 
-    if is_ident_any_type(self.cddl, ident) {
+    if self.lookup().is_ident_any_type(ident) {
       return Ok(());
     }
 
     match &self.cbor {
-      Value::Null if is_ident_null_data_type(self.cddl, ident) => Ok(()),
-      Value::Bytes(_) if is_ident_byte_string_data_type(self.cddl, ident) => Ok(()),
+      Value::Null if self.lookup().is_ident_null_data_type(ident) && self.is_raw_simple(22) => {
+        Ok(())
+      }
+      Value::Null
+        if matches!(lookup_ident(&ident.ident), Token::UNDEFINED) && self.is_raw_simple(23) =>
+      {
+        Ok(())
+      }
+      Value::Bytes(_) if self.lookup().is_ident_byte_string_data_type(ident) => Ok(()),
       Value::Bool(b) => {
-        if is_ident_bool_data_type(self.cddl, ident) {
+        if self.lookup().is_ident_bool_data_type(ident) {
           return Ok(());
         }
 
-        if ident_matches_bool_value(self.cddl, ident, *b) {
+        if self.lookup().ident_matches_bool_value(ident, *b) {
           return Ok(());
         }
 
@@ -2225,15 +3023,15 @@ where
         Ok(())
       }
       Value::Integer(i) => {
-        if is_ident_uint_data_type(self.cddl, ident) {
+        if self.lookup().is_ident_uint_data_type(ident) {
           if i128::from(*i).is_negative() {
             self.add_error(format!("expected type {}, got {:?}", ident, self.cbor));
           }
 
           Ok(())
-        } else if is_ident_integer_data_type(self.cddl, ident) {
+        } else if self.lookup().is_ident_integer_data_type(ident) {
           Ok(())
-        } else if is_ident_time_data_type(self.cddl, ident) {
+        } else if self.lookup().is_ident_time_data_type(ident) {
           if let chrono::LocalResult::None =
             Utc.timestamp_millis_opt((i128::from(*i) * 1000) as i64)
           {
@@ -2251,9 +3049,31 @@ where
         }
       }
       Value::Float(f) => {
-        if is_ident_float_data_type(self.cddl, ident) {
+        if self.lookup().is_ident_float_data_type(ident) {
+          // Floats may be encoded narrower than the width of the data type as
+          // long as the value is preserved, as is the case for preferred
+          // serialization. The same applies to the `#7.25` and `#7.26`
+          // constraints the types are defined with
+          let width = match lookup_ident(&ident.ident) {
+            Token::FLOAT16 => Some(FloatWidth::Half),
+            Token::FLOAT32 | Token::FLOAT1632 => Some(FloatWidth::Single),
+            _ => None,
+          };
+
+          if let Some(width) = width {
+            let is_valid = match self.raw.as_ref().map(|r| &r.kind) {
+              Some(RawKind::Float(w)) => *w <= width,
+              _ => raw::is_float_representable(*f, width),
+            };
+
+            if !is_valid {
+              let got = self.cbor_with_encoding();
+              self.add_error(format!("expected type {}, got {}", ident, got));
+            }
+          }
+
           Ok(())
-        } else if is_ident_time_data_type(self.cddl, ident) {
+        } else if self.lookup().is_ident_time_data_type(ident) {
           if let chrono::LocalResult::None = Utc.timestamp_millis_opt((*f * 1000f64) as i64) {
             let f = *f;
             self.add_error(format!(
@@ -2269,22 +3089,22 @@ where
         }
       }
       Value::Text(s) => {
-        if is_ident_uri_data_type(self.cddl, ident) {
+        if self.lookup().is_ident_uri_data_type(ident) {
           if let Err(e) = uriparse::URI::try_from(&**s) {
             self.add_error(format!("expected URI data type, decoding error: {}", e));
           }
-        } else if is_ident_b64url_data_type(self.cddl, ident) {
+        } else if self.lookup().is_ident_b64url_data_type(ident) {
           if let Err(e) = base64_url::decode(s) {
             self.add_error(format!(
               "expected base64 URL data type, decoding error: {}",
               e
             ));
           }
-        } else if is_ident_tdate_data_type(self.cddl, ident) {
+        } else if self.lookup().is_ident_tdate_data_type(ident) {
           if let Err(e) = chrono::DateTime::parse_from_rfc3339(s) {
             self.add_error(format!("expected tdate data type, decoding error: {}", e));
           }
-        } else if is_ident_string_data_type(self.cddl, ident) {
+        } else if self.lookup().is_ident_string_data_type(ident) {
           return Ok(());
         } else {
           self.add_error(format!("expected type {}, got {:?}", ident, self.cbor));
@@ -2297,7 +3117,7 @@ where
         match &self.occurrence {
           #[cfg(feature = "ast-span")]
           Some(Occur::Optional { .. }) | None => {
-            if is_ident_string_data_type(self.cddl, ident) && !self.validating_value {
+            if self.lookup().is_ident_string_data_type(ident) && !self.validating_value {
               if let Some((k, v)) = m.iter().find(|(k, _)| matches!(k, Value::Text(_))) {
                 self
                   .validated_keys
@@ -2311,7 +3131,7 @@ where
               return Ok(());
             }
 
-            if is_ident_integer_data_type(self.cddl, ident) && !self.validating_value {
+            if self.lookup().is_ident_integer_data_type(ident) && !self.validating_value {
               if let Some((k, v)) = m.iter().find(|(k, _)| matches!(k, Value::Integer(_))) {
                 self
                   .validated_keys
@@ -2325,7 +3145,7 @@ where
               return Ok(());
             }
 
-            if is_ident_bool_data_type(self.cddl, ident) && !self.validating_value {
+            if self.lookup().is_ident_bool_data_type(ident) && !self.validating_value {
               if let Some((k, v)) = m.iter().find(|(k, _)| matches!(k, Value::Bool(_))) {
                 self
                   .validated_keys
@@ -2339,7 +3159,7 @@ where
               return Ok(());
             }
 
-            if is_ident_null_data_type(self.cddl, ident) && !self.validating_value {
+            if self.lookup().is_ident_null_data_type(ident) && !self.validating_value {
               if let Some((k, v)) = m.iter().find(|(k, _)| matches!(k, Value::Null)) {
                 self
                   .validated_keys
@@ -2353,7 +3173,7 @@ where
               return Ok(());
             }
 
-            if is_ident_byte_string_data_type(self.cddl, ident) && !self.validating_value {
+            if self.lookup().is_ident_byte_string_data_type(ident) && !self.validating_value {
               if let Some((k, v)) = m.iter().find(|(k, _)| matches!(k, Value::Bytes(_))) {
                 self
                   .validated_keys
@@ -2367,7 +3187,7 @@ where
               return Ok(());
             }
 
-            if is_ident_float_data_type(self.cddl, ident) && !self.validating_value {
+            if self.lookup().is_ident_float_data_type(ident) && !self.validating_value {
               if let Some((k, v)) = m.iter().find(|(k, _)| matches!(k, Value::Null)) {
                 self
                   .validated_keys
@@ -2381,7 +3201,7 @@ where
               return Ok(());
             }
 
-            if token::lookup_ident(ident.ident)
+            if token::lookup_ident(&ident.ident)
               .in_standard_prelude()
               .is_some()
             {
@@ -2392,11 +3212,11 @@ where
               return Ok(());
             }
 
-            self.visit_value(&token::Value::TEXT(ident.ident.into()))
+            self.visit_value(&token::Value::TEXT(ident.ident.clone()))
           }
           #[cfg(not(feature = "ast-span"))]
           Some(Occur::Optional {}) | None => {
-            if is_ident_string_data_type(self.cddl, ident) && !self.validating_value {
+            if self.lookup().is_ident_string_data_type(ident) && !self.validating_value {
               if let Some((k, v)) = m.iter().find(|(k, _)| matches!(k, Value::Text(_))) {
                 self
                   .validated_keys
@@ -2411,7 +3231,7 @@ where
               return Ok(());
             }
 
-            if is_ident_integer_data_type(self.cddl, ident) && !self.validating_value {
+            if self.lookup().is_ident_integer_data_type(ident) && !self.validating_value {
               if let Some((k, v)) = m.iter().find(|(k, _)| matches!(k, Value::Integer(_))) {
                 self
                   .validated_keys
@@ -2425,7 +3245,7 @@ where
               return Ok(());
             }
 
-            if is_ident_bool_data_type(self.cddl, ident) && !self.validating_value {
+            if self.lookup().is_ident_bool_data_type(ident) && !self.validating_value {
               if let Some((k, v)) = m.iter().find(|(k, _)| matches!(k, Value::Bool(_))) {
                 self
                   .validated_keys
@@ -2439,7 +3259,7 @@ where
               return Ok(());
             }
 
-            if is_ident_null_data_type(self.cddl, ident) && !self.validating_value {
+            if self.lookup().is_ident_null_data_type(ident) && !self.validating_value {
               if let Some((k, v)) = m.iter().find(|(k, _)| matches!(k, Value::Null)) {
                 self
                   .validated_keys
@@ -2453,7 +3273,7 @@ where
               return Ok(());
             }
 
-            if is_ident_byte_string_data_type(self.cddl, ident) && !self.validating_value {
+            if self.lookup().is_ident_byte_string_data_type(ident) && !self.validating_value {
               if let Some((k, v)) = m.iter().find(|(k, _)| matches!(k, Value::Bytes(_))) {
                 self
                   .validated_keys
@@ -2467,7 +3287,7 @@ where
               return Ok(());
             }
 
-            if is_ident_float_data_type(self.cddl, ident) && !self.validating_value {
+            if self.lookup().is_ident_float_data_type(ident) && !self.validating_value {
               if let Some((k, v)) = m.iter().find(|(k, _)| matches!(k, Value::Null)) {
                 self
                   .validated_keys
@@ -2481,7 +3301,7 @@ where
               return Ok(());
             }
 
-            if token::lookup_ident(ident.ident)
+            if token::lookup_ident(&ident.ident)
               .in_standard_prelude()
               .is_some()
             {
@@ -2492,19 +3312,20 @@ where
               return Ok(());
             }
 
-            self.visit_value(&token::Value::TEXT(ident.ident.into()))
+            self.visit_value(&token::Value::TEXT(ident.ident.clone()))
           }
           Some(occur) => {
             let mut errors = Vec::new();
 
-            if is_ident_string_data_type(self.cddl, ident) {
+            if self.lookup().is_ident_string_data_type(ident) {
               let values_to_validate = m
                 .iter()
-                .filter_map(|(k, v)| {
+                .enumerate()
+                .filter_map(|(idx, (k, v))| {
                   if let Some(keys) = &self.validated_keys {
                     if !keys.contains(k) {
                       if matches!(k, Value::Text(_)) {
-                        Some(v.clone())
+                        Some((idx, v.clone()))
                       } else {
                         errors.push(format!("key of type {} required, got {:?}", ident, k));
                         None
@@ -2513,7 +3334,7 @@ where
                       None
                     }
                   } else if matches!(k, Value::Text(_)) {
-                    Some(v.clone())
+                    Some((idx, v.clone()))
                   } else {
                     errors.push(format!("key of type {} required, got {:?}", ident, k));
                     None
@@ -2524,15 +3345,16 @@ where
               self.values_to_validate = Some(values_to_validate);
             }
 
-            if is_ident_integer_data_type(self.cddl, ident) {
+            if self.lookup().is_ident_integer_data_type(ident) {
               let mut errors = Vec::new();
               let values_to_validate = m
                 .iter()
-                .filter_map(|(k, v)| {
+                .enumerate()
+                .filter_map(|(idx, (k, v))| {
                   if let Some(keys) = &self.validated_keys {
                     if !keys.contains(k) {
                       if matches!(k, Value::Integer(_)) {
-                        Some(v.clone())
+                        Some((idx, v.clone()))
                       } else {
                         errors.push(format!("key of type {} required, got {:?}", ident, k));
                         None
@@ -2541,7 +3363,7 @@ where
                       None
                     }
                   } else if matches!(k, Value::Integer(_)) {
-                    Some(v.clone())
+                    Some((idx, v.clone()))
                   } else {
                     errors.push(format!("key of type {} required, got {:?}", ident, k));
                     None
@@ -2552,15 +3374,16 @@ where
               self.values_to_validate = Some(values_to_validate);
             }
 
-            if is_ident_bool_data_type(self.cddl, ident) {
+            if self.lookup().is_ident_bool_data_type(ident) {
               let mut errors = Vec::new();
               let values_to_validate = m
                 .iter()
-                .filter_map(|(k, v)| {
+                .enumerate()
+                .filter_map(|(idx, (k, v))| {
                   if let Some(keys) = &self.validated_keys {
                     if !keys.contains(k) {
                       if matches!(k, Value::Bool(_)) {
-                        Some(v.clone())
+                        Some((idx, v.clone()))
                       } else {
                         errors.push(format!("key of type {} required, got {:?}", ident, k));
                         None
@@ -2569,7 +3392,7 @@ where
                       None
                     }
                   } else if matches!(k, Value::Bool(_)) {
-                    Some(v.clone())
+                    Some((idx, v.clone()))
                   } else {
                     errors.push(format!("key of type {} required, got {:?}", ident, k));
                     None
@@ -2580,15 +3403,16 @@ where
               self.values_to_validate = Some(values_to_validate);
             }
 
-            if is_ident_byte_string_data_type(self.cddl, ident) {
+            if self.lookup().is_ident_byte_string_data_type(ident) {
               let mut errors = Vec::new();
               let values_to_validate = m
                 .iter()
-                .filter_map(|(k, v)| {
+                .enumerate()
+                .filter_map(|(idx, (k, v))| {
                   if let Some(keys) = &self.validated_keys {
                     if !keys.contains(k) {
                       if matches!(k, Value::Bytes(_)) {
-                        Some(v.clone())
+                        Some((idx, v.clone()))
                       } else {
                         errors.push(format!("key of type {} required, got {:?}", ident, k));
                         None
@@ -2597,7 +3421,7 @@ where
                       None
                     }
                   } else if matches!(k, Value::Bytes(_)) {
-                    Some(v.clone())
+                    Some((idx, v.clone()))
                   } else {
                     errors.push(format!("key of type {} required, got {:?}", ident, k));
                     None
@@ -2608,15 +3432,16 @@ where
               self.values_to_validate = Some(values_to_validate);
             }
 
-            if is_ident_null_data_type(self.cddl, ident) {
+            if self.lookup().is_ident_null_data_type(ident) {
               let mut errors = Vec::new();
               let values_to_validate = m
                 .iter()
-                .filter_map(|(k, v)| {
+                .enumerate()
+                .filter_map(|(idx, (k, v))| {
                   if let Some(keys) = &self.validated_keys {
                     if !keys.contains(k) {
                       if matches!(k, Value::Null) {
-                        Some(v.clone())
+                        Some((idx, v.clone()))
                       } else {
                         errors.push(format!("key of type {} required, got {:?}", ident, k));
                         None
@@ -2625,7 +3450,7 @@ where
                       None
                     }
                   } else if matches!(k, Value::Null) {
-                    Some(v.clone())
+                    Some((idx, v.clone()))
                   } else {
                     errors.push(format!("key of type {} required, got {:?}", ident, k));
                     None
@@ -2636,15 +3461,16 @@ where
               self.values_to_validate = Some(values_to_validate);
             }
 
-            if is_ident_float_data_type(self.cddl, ident) {
+            if self.lookup().is_ident_float_data_type(ident) {
               let mut errors = Vec::new();
               let values_to_validate = m
                 .iter()
-                .filter_map(|(k, v)| {
+                .enumerate()
+                .filter_map(|(idx, (k, v))| {
                   if let Some(keys) = &self.validated_keys {
                     if !keys.contains(k) {
                       if matches!(k, Value::Float(_)) {
-                        Some(v.clone())
+                        Some((idx, v.clone()))
                       } else {
                         errors.push(format!("key of type {} required, got {:?}", ident, k));
                         None
@@ -2653,7 +3479,7 @@ where
                       None
                     }
                   } else if matches!(k, Value::Float(_)) {
-                    Some(v.clone())
+                    Some((idx, v.clone()))
                   } else {
                     errors.push(format!("key of type {} required, got {:?}", ident, k));
                     None
@@ -2787,7 +3613,7 @@ where
               }
             }
 
-            if is_ident_string_data_type(self.cddl, ident) && !self.validating_value {
+            if self.lookup().is_ident_string_data_type(ident) && !self.validating_value {
               if let Some((k, v)) = m.iter().find(|(k, _)| matches!(k, Value::Text(_))) {
                 self
                   .validated_keys
@@ -2804,7 +3630,7 @@ where
               return Ok(());
             }
 
-            if is_ident_integer_data_type(self.cddl, ident) && !self.validating_value {
+            if self.lookup().is_ident_integer_data_type(ident) && !self.validating_value {
               if let Some((k, v)) = m.iter().find(|(k, _)| matches!(k, Value::Integer(_))) {
                 self
                   .validated_keys
@@ -2820,7 +3646,7 @@ where
               return Ok(());
             }
 
-            if is_ident_bool_data_type(self.cddl, ident) && !self.validating_value {
+            if self.lookup().is_ident_bool_data_type(ident) && !self.validating_value {
               if let Some((k, v)) = m.iter().find(|(k, _)| matches!(k, Value::Bool(_))) {
                 self
                   .validated_keys
@@ -2836,7 +3662,7 @@ where
               return Ok(());
             }
 
-            if is_ident_null_data_type(self.cddl, ident) && !self.validating_value {
+            if self.lookup().is_ident_null_data_type(ident) && !self.validating_value {
               if let Some((k, v)) = m.iter().find(|(k, _)| matches!(k, Value::Null)) {
                 self
                   .validated_keys
@@ -2852,7 +3678,7 @@ where
               return Ok(());
             }
 
-            if is_ident_byte_string_data_type(self.cddl, ident) && !self.validating_value {
+            if self.lookup().is_ident_byte_string_data_type(ident) && !self.validating_value {
               if let Some((k, v)) = m.iter().find(|(k, _)| matches!(k, Value::Bytes(_))) {
                 self
                   .validated_keys
@@ -2868,7 +3694,7 @@ where
               return Ok(());
             }
 
-            if is_ident_float_data_type(self.cddl, ident) && !self.validating_value {
+            if self.lookup().is_ident_float_data_type(ident) && !self.validating_value {
               if let Some((k, v)) = m.iter().find(|(k, _)| matches!(k, Value::Null)) {
                 self
                   .validated_keys
@@ -2884,7 +3710,7 @@ where
               return Ok(());
             }
 
-            if token::lookup_ident(ident.ident)
+            if token::lookup_ident(&ident.ident)
               .in_standard_prelude()
               .is_some()
             {
@@ -2895,8 +3721,19 @@ where
               return Ok(());
             }
 
-            self.visit_value(&token::Value::TEXT(ident.ident.into()))
+            self.visit_value(&token::Value::TEXT(ident.ident.clone()))
+          }
+        }
+      }
+      Value::Tag(tag, value)
+        if tagged_prelude_type(&lookup_ident(&ident.ident), *tag).is_some() =>
+      {
+        match tagged_prelude_type(&lookup_ident(&ident.ident), *tag) {
+          Some(t) => {
+            let value = value.as_ref().clone();
+            self.validate_tagged_prelude_type(&t, *tag, &value)
           }
+          None => Ok(()),
         }
       }
       _ => {
@@ -2937,20 +3774,24 @@ where
     }
 
     if let Some(values) = &self.values_to_validate {
-      for v in values.iter() {
+      for (idx, v) in values.iter() {
         #[cfg(all(feature = "additional-controls", target_arch = "wasm32"))]
         let mut cv = CBORValidator::new(self.cddl, v.clone(), self.enabled_features.clone());
         #[cfg(all(feature = "additional-controls", not(target_arch = "wasm32")))]
         let mut cv = CBORValidator::new(self.cddl, v.clone(), self.enabled_features);
         #[cfg(not(feature = "additional-controls"))]
         let mut cv = CBORValidator::new(self.cddl, v.clone());
+        cv.compiled = self.compiled;
+        cv.custom_controls = self.custom_controls;
+        cv.tag_registry = self.tag_registry;
 
+        cv.raw = self.raw_map_entry(*idx).map(|(_, v)| v);
         cv.generic_rules = self.generic_rules.clone();
-        cv.eval_generic_rule = self.eval_generic_rule;
+        cv.eval_generic_rule = self.eval_generic_rule.clone();
         cv.is_multi_type_choice = self.is_multi_type_choice;
         cv.is_multi_group_choice = self.is_multi_group_choice;
         cv.cbor_location.push_str(&self.cbor_location);
-        cv.type_group_name_entry = self.type_group_name_entry;
+        cv.type_group_name_entry = self.type_group_name_entry.clone();
         cv.validating_value = true;
         cv.visit_type(&entry.entry_type)?;
 
@@ -2966,19 +3807,25 @@ where
     }
 
     if let Some(v) = self.object_value.take() {
+      let raw = self.raw_map_value(&v);
+
       #[cfg(all(feature = "additional-controls", target_arch = "wasm32"))]
       let mut cv = CBORValidator::new(self.cddl, v, self.enabled_features.clone());
       #[cfg(all(feature = "additional-controls", not(target_arch = "wasm32")))]
       let mut cv = CBORValidator::new(self.cddl, v, self.enabled_features);
       #[cfg(not(feature = "additional-controls"))]
       let mut cv = CBORValidator::new(self.cddl, v);
+      cv.compiled = self.compiled;
+      cv.custom_controls = self.custom_controls;
+      cv.tag_registry = self.tag_registry;
 
+      cv.raw = raw;
       cv.generic_rules = self.generic_rules.clone();
-      cv.eval_generic_rule = self.eval_generic_rule;
+      cv.eval_generic_rule = self.eval_generic_rule.clone();
       cv.is_multi_type_choice = self.is_multi_type_choice;
       cv.is_multi_group_choice = self.is_multi_group_choice;
       cv.cbor_location.push_str(&self.cbor_location);
-      cv.type_group_name_entry = self.type_group_name_entry;
+      cv.type_group_name_entry = self.type_group_name_entry.clone();
       cv.visit_type(&entry.entry_type)?;
 
       self.cbor_location = current_location;
@@ -3000,10 +3847,10 @@ where
     &mut self,
     entry: &TypeGroupnameEntry<'a>,
   ) -> visitor::Result<Error<T>> {
-    self.type_group_name_entry = Some(entry.name.ident);
+    self.type_group_name_entry = Some(entry.name.ident.clone());
 
     if let Some(ga) = &entry.generic_args {
-      if let Some(rule) = rule_from_ident(self.cddl, &entry.name) {
+      if let Some(rule) = self.lookup().rule_from_ident(&entry.name) {
         if let Some(gr) = self
           .generic_rules
           .iter_mut()
@@ -3014,7 +3861,7 @@ where
           }
         } else if let Some(params) = generic_params_from_rule(rule) {
           self.generic_rules.push(GenericRule {
-            name: entry.name.ident,
+            name: entry.name.ident.clone(),
             params,
             args: ga.args.iter().cloned().map(|arg| *arg.arg).collect(),
           });
@@ -3027,9 +3874,13 @@ where
         let mut cv = CBORValidator::new(self.cddl, self.cbor.clone(), self.enabled_features);
         #[cfg(not(feature = "additional-controls"))]
         let mut cv = CBORValidator::new(self.cddl, self.cbor.clone());
+        cv.compiled = self.compiled;
+        cv.custom_controls = self.custom_controls;
+        cv.tag_registry = self.tag_registry;
 
+        cv.raw = self.raw.clone();
         cv.generic_rules = self.generic_rules.clone();
-        cv.eval_generic_rule = Some(entry.name.ident);
+        cv.eval_generic_rule = Some(entry.name.ident.clone());
         cv.is_multi_type_choice = self.is_multi_type_choice;
         cv.visit_rule(rule)?;
 
@@ -3039,7 +3890,7 @@ where
       }
     }
 
-    let type_choice_alternates = type_choice_alternates_from_ident(self.cddl, &entry.name);
+    let type_choice_alternates = self.lookup().type_choice_alternates_from_ident(&entry.name);
     if !type_choice_alternates.is_empty() {
       self.is_multi_type_choice = true;
     }
@@ -3058,7 +3909,9 @@ where
     }
 
     let error_count = self.errors.len();
-    let group_choice_alternates = group_choice_alternates_from_ident(self.cddl, &entry.name);
+    let group_choice_alternates = self
+      .lookup()
+      .group_choice_alternates_from_ident(&entry.name);
     if !group_choice_alternates.is_empty() {
       self.is_multi_group_choice = true;
     }
@@ -3101,6 +3954,35 @@ where
 
   fn visit_value(&mut self, value: &token::Value<'a>) -> visitor::Result<Error<T>> {
     let error: Option<String> = match &self.cbor {
+      Value::Tag(tag @ 4..=5, v) => {
+        let expected = match value {
+          token::Value::INT(i) => Some(ExactNumber::from_int(*i as i128)),
+          token::Value::UINT(u) => Some(ExactNumber::from_int(*u as i128)),
+          token::Value::FLOAT(f) => ExactNumber::from_f64(*f),
+          _ => None,
+        };
+
+        match (exact_number_from_tagged(*tag, v), expected) {
+          (Err(e), _) => Some(e),
+          (Ok(n), Some(expected)) => match &self.ctrl {
+            Some(ControlOperator::NE) | Some(ControlOperator::DEFAULT) if n != expected => None,
+            Some(ControlOperator::LT) if n < expected => None,
+            Some(ControlOperator::LE) if n <= expected => None,
+            Some(ControlOperator::GT) if n > expected => None,
+            Some(ControlOperator::GE) if n >= expected => None,
+            #[cfg(feature = "additional-controls")]
+            None | Some(ControlOperator::FEATURE) if n == expected => None,
+            #[cfg(not(feature = "additional-controls"))]
+            None if n == expected => None,
+            Some(ctrl) => Some(format!(
+              "expected value {} {}, got {:?}",
+              ctrl, value, self.cbor
+            )),
+            None => Some(format!("expected value {}, got {:?}", value, self.cbor)),
+          },
+          (Ok(_), None) => Some(format!("expected {}, got {:?}", value, self.cbor)),
+        }
+      }
       Value::Integer(i) => match value {
         token::Value::INT(v) => match &self.ctrl {
           Some(ControlOperator::NE) | Some(ControlOperator::DEFAULT)
@@ -3256,18 +4138,25 @@ where
             }
           }
           Some(ControlOperator::REGEXP) | Some(ControlOperator::PCRE) => {
-            let re = regex::Regex::new(
-              &format_regex(
-                // Text strings must be JSON escaped per
-                // https://datatracker.ietf.org/doc/html/rfc8610#section-3.1
-                serde_json::from_str::<serde_json::Value>(&format!("\"{}\"", t))
-                  .map_err(Error::JSONParsing)?
-                  .as_str()
+            let built;
+            let re = match self.compiled.and_then(|c| c.regex(t)) {
+              Some(re) => re,
+              None => {
+                built = regex::Regex::new(
+                  &format_regex(
+                    // Text strings must be JSON escaped per
+                    // https://datatracker.ietf.org/doc/html/rfc8610#section-3.1
+                    serde_json::from_str::<serde_json::Value>(&format!("\"{}\"", t))
+                      .map_err(Error::JSONParsing)?
+                      .as_str()
+                      .ok_or_else(|| Error::from_validator(self, "malformed regex".to_string()))?,
+                  )
                   .ok_or_else(|| Error::from_validator(self, "malformed regex".to_string()))?,
-              )
-              .ok_or_else(|| Error::from_validator(self, "malformed regex".to_string()))?,
-            )
-            .map_err(|e| Error::from_validator(self, e.to_string()))?;
+                )
+                .map_err(|e| Error::from_validator(self, e.to_string()))?;
+                &built
+              }
+            };
 
             if re.is_match(s) {
               None
@@ -3276,7 +4165,8 @@ where
             }
           }
           #[cfg(feature = "additional-controls")]
-          Some(ControlOperator::ABNF) => validate_abnf(t, s)
+          Some(ControlOperator::ABNF) => self
+            .validate_abnf(t, s)
             .err()
             .map(|e| format!("\"{}\" is not valid against abnf: {}", s, e)),
           _ => {
@@ -3376,16 +4266,15 @@ where
         },
         #[cfg(feature = "additional-controls")]
         token::Value::TEXT(t) => match &self.ctrl {
-          Some(ControlOperator::ABNFB) => {
-            validate_abnf(t, std::str::from_utf8(b).map_err(Error::UTF8Parsing)?)
-              .err()
-              .map(|e| {
-                format!(
-                  "cbor bytes \"{:?}\" are not valid against abnf {}: {}",
-                  b, t, e
-                )
-              })
-          }
+          Some(ControlOperator::ABNFB) => self
+            .validate_abnf(t, std::str::from_utf8(b).map_err(Error::UTF8Parsing)?)
+            .err()
+            .map(|e| {
+              format!(
+                "cbor bytes \"{:?}\" are not valid against abnf {}: {}",
+                b, t, e
+              )
+            }),
           _ => Some(format!(
             "expected value {} {}, got {:?}",
             self.ctrl.unwrap(),
@@ -3393,6 +4282,7 @@ where
             b
           )),
         },
+        token::Value::BYTE(bv) if byte_value_bytes(bv).as_ref() == Some(b) => None,
         _ => Some(format!("expected {}, got {:?}", value, b)),
       },
       Value::Array(_) => {
@@ -3472,47 +4362,276 @@ where
   }
 }
 
-/// Converts a CDDL value type to ciborium::value::Value
-pub fn token_value_into_cbor_value(value: token::Value) -> ciborium::value::Value {
-  match value {
-    token::Value::UINT(i) => ciborium::value::Value::Integer(i.into()),
-    token::Value::INT(i) => ciborium::value::Value::Integer(i.into()),
-    token::Value::FLOAT(f) => ciborium::value::Value::Float(f),
-    token::Value::TEXT(t) => ciborium::value::Value::Text(t.to_string()),
-    token::Value::BYTE(b) => match b {
-      ByteValue::UTF8(b) | ByteValue::B16(b) | ByteValue::B64(b) => {
-        ciborium::value::Value::Bytes(b.into_owned())
+/// Type of the tagged data item of a standard prelude type with the given tag
+/// number, e.g. `bstr` for `biguint` with tag 2
+fn tagged_prelude_type<'a>(token: &Token, tag: u64) -> Option<Type<'a>> {
+  let tokens = match token {
+    Token::BIGINT | Token::INTEGER => vec![Token::BIGUINT, Token::BIGNINT],
+    Token::UNSIGNED => vec![Token::BIGUINT],
+    token => vec![token.clone()],
+  };
+
+  tokens.iter().find_map(|token| match tag_from_token(token) {
+    Some(Type2::TaggedData {
+      tag: Some(t),
+      t: tt,
+      ..
+    }) if t as u64 == tag => Some(tt),
+    _ => None,
+  })
+}
+
+/// Exact value of a decimal fraction (tag 4) or bigfloat (tag 5) data item
+fn exact_number_from_tagged(tag: u64, value: &Value) -> std::result::Result<ExactNumber, String> {
+  let name = if tag == 4 { "decfrac" } else { "bigfloat" };
+
+  let (exponent, mantissa) = match value {
+    Value::Array(a) if a.len() == 2 => (&a[0], &a[1]),
+    _ => {
+      return Err(format!(
+        "expected {} to be an array of an exponent and a mantissa, got {:?}",
+        name, value
+      ))
+    }
+  };
+
+  let exponent = match exponent {
+    Value::Integer(e) => i128::from(*e),
+    _ => {
+      return Err(format!(
+        "expected {} exponent of type int, got {:?}",
+        name, exponent
+      ))
+    }
+  };
+
+  let mantissa = match mantissa {
+    Value::Integer(m) => ExactNumber::from_int(i128::from(*m)),
+    Value::Tag(tag @ 2..=3, b) => match b.as_ref() {
+      Value::Bytes(b) => ExactNumber::from_bignum(b, *tag == 3),
+      _ => {
+        return Err(format!(
+          "expected {} bignum mantissa, got {:?}",
+          name, mantissa
+        ))
       }
     },
+    _ => {
+      return Err(format!(
+        "expected {} mantissa of type integer, got {:?}",
+        name, mantissa
+      ))
+    }
+  };
+
+  if tag == 4 {
+    Ok(mantissa.scaled_by_ten(exponent))
+  } else {
+    Ok(mantissa.scaled_by_two(exponent))
   }
 }
 
-#[cfg(test)]
-#[cfg(not(target_arch = "wasm32"))]
-mod tests {
-  use super::*;
-  use ciborium::cbor;
-  use indoc::indoc;
-
-  #[cfg(not(feature = "additional-controls"))]
-  #[test]
-  fn validate() -> std::result::Result<(), Box<dyn std::error::Error>> {
-    let cddl = indoc!(
-      r#"
-        tcpflagbytes = bstr .bits flags
-        flags = &(
-          fin: 8,
-          syn: 9,
-          rst: 10,
-          psh: 11,
-          ack: 12,
-          urg: 13,
-          ece: 14,
-          cwr: 15,
-          ns: 0,
-        ) / (4..7) ; data offset bits
-      "#
-    );
+/// Exact value of a numeric CDDL value or literal
+fn exact_number_from_type2(t2: &Type2) -> Option<ExactNumber> {
+  match t2 {
+    Type2::IntValue { value, .. } => Some(ExactNumber::from_int(*value as i128)),
+    Type2::UintValue { value, .. } => Some(ExactNumber::from_int(*value as i128)),
+    Type2::FloatValue { value, .. } => ExactNumber::from_f64(*value),
+    _ => None,
+  }
+}
+
+// Location of the value of the map entry with the given key
+pub(crate) fn map_key_location(location: &str, key: &Value) -> String {
+  match key {
+    Value::Text(k) => format!("{}/{}", location, k),
+    Value::Integer(k) => format!("{}/{}", location, i128::from(*k)),
+    _ => format!("{}/{:?}", location, key),
+  }
+}
+
+// Identity of a map key for detecting duplicate keys, i.e. its preferred
+// serialization, which is the same for equal keys whatever their encoding,
+// along with the simple value it is encoded as, if any, since simple values
+// other than false, true and null are decoded as null
+pub(crate) fn map_key_identity(key: &Value, raw: Option<&RawItem>) -> (Vec<u8>, Option<u8>) {
+  let mut encoded = Vec::new();
+  let _ = ciborium::ser::into_writer(key, &mut encoded);
+
+  let simple = match raw.map(|r| &r.kind) {
+    Some(RawKind::Simple(s)) => Some(*s),
+    _ => None,
+  };
+
+  (encoded, simple)
+}
+
+// Collect the location and reason of each map, including nested maps, with
+// duplicate keys. Keys are compared by value, except that simple values which
+// are decoded as null are only equal if their encodings are equal
+fn duplicate_keys(
+  value: &Value,
+  raw: Option<&RawItem>,
+  location: &str,
+  errors: &mut Vec<(String, String)>,
+) {
+  match value {
+    Value::Array(a) => {
+      for (idx, v) in a.iter().enumerate() {
+        let raw = raw.and_then(|r| r.array_item(idx));
+        duplicate_keys(v, raw, &format!("{}/{}", location, idx), errors);
+      }
+    }
+    Value::Map(m) => {
+      let mut keys = HashSet::with_capacity(m.len());
+      for (idx, (k, v)) in m.iter().enumerate() {
+        let entry = raw.and_then(|r| r.map_entry(idx));
+        let raw_k = entry.map(|(k, _)| k);
+
+        if !keys.insert(map_key_identity(k, raw_k)) {
+          errors.push((
+            location.to_string(),
+            format!("map has duplicate key {:?}", k),
+          ));
+        }
+
+        let location = map_key_location(location, k);
+        duplicate_keys(k, raw_k, &location, errors);
+        duplicate_keys(v, entry.map(|(_, v)| v), &location, errors);
+      }
+    }
+    Value::Tag(_, v) => duplicate_keys(v, raw.and_then(|r| r.tagged_item()), location, errors),
+    _ => (),
+  }
+}
+
+// Collect the location and reason of each data item, including nested data
+// items, that does not use preferred serialization or, if required, core
+// deterministic encoding
+fn encoding_violations(
+  value: &Value,
+  raw: &RawItem,
+  location: &str,
+  is_deterministic: bool,
+  errors: &mut Vec<(String, String)>,
+) {
+  if !raw.is_preferred() {
+    let preferred = raw.preferred_info();
+    let reason = match (raw.float_value(), raw.argument) {
+      (Some(f), _) => Some(format!(
+        "expected preferred serialization of float {:?} as #7.{}, got #7.{} at byte offset {}",
+        f, preferred, raw.info, raw.offset
+      )),
+      (None, Some(argument)) => Some(format!(
+        "expected preferred serialization of argument {} in {} byte(s), got {} byte(s) at byte offset {}",
+        argument,
+        raw::argument_len(preferred),
+        raw.argument_len(),
+        raw.offset
+      )),
+      (None, None) => None,
+    };
+
+    if let Some(reason) = reason {
+      errors.push((location.to_string(), reason));
+    }
+  }
+
+  if is_deterministic && raw.is_indefinite() {
+    errors.push((
+      location.to_string(),
+      format!(
+        "expected definite length encoding for deterministic encoding, got indefinite length at byte offset {}",
+        raw.offset
+      ),
+    ));
+  }
+
+  match (&raw.kind, value) {
+    (RawKind::Bytes(chunks), _) | (RawKind::Text(chunks), _) => {
+      for chunk in chunks.iter() {
+        encoding_violations(value, chunk, location, is_deterministic, errors);
+      }
+    }
+    (RawKind::Array(items), Value::Array(a)) => {
+      for (idx, (item, v)) in items.iter().zip(a.iter()).enumerate() {
+        let location = format!("{}/{}", location, idx);
+        encoding_violations(v, item, &location, is_deterministic, errors);
+      }
+    }
+    (
+      RawKind::Map {
+        entries,
+        unordered_keys,
+      },
+      Value::Map(m),
+    ) => {
+      for (idx, ((raw_k, raw_v), (k, v))) in entries.iter().zip(m.iter()).enumerate() {
+        let location = map_key_location(location, k);
+
+        if is_deterministic && unordered_keys.contains(&idx) {
+          errors.push((
+            location.clone(),
+            format!(
+              "expected map keys sorted in bytewise lexicographic order of their encodings for deterministic encoding, got key {:?} at byte offset {}",
+              k, raw_k.offset
+            ),
+          ));
+        }
+
+        encoding_violations(k, raw_k, &location, is_deterministic, errors);
+        encoding_violations(v, raw_v, &location, is_deterministic, errors);
+      }
+    }
+    (RawKind::Tag(item), Value::Tag(_, v)) => {
+      encoding_violations(v, item, location, is_deterministic, errors)
+    }
+    // Bignums that fit in 128 bits are decoded as integers
+    (RawKind::Tag(item), _) => encoding_violations(value, item, location, is_deterministic, errors),
+    _ => (),
+  }
+}
+
+/// Converts a CDDL value type to ciborium::value::Value
+pub fn token_value_into_cbor_value(value: token::Value) -> ciborium::value::Value {
+  match value {
+    token::Value::UINT(i) => ciborium::value::Value::Integer(i.into()),
+    token::Value::INT(i) => ciborium::value::Value::Integer(i.into()),
+    token::Value::FLOAT(f) => ciborium::value::Value::Float(f),
+    token::Value::TEXT(t) => ciborium::value::Value::Text(t.to_string()),
+    token::Value::BYTE(b) => match b {
+      ByteValue::UTF8(b) => ciborium::value::Value::Bytes(b.into_owned()),
+      b => ciborium::value::Value::Bytes(byte_value_bytes(&b).unwrap_or_default()),
+    },
+  }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+  use super::*;
+  use ciborium::cbor;
+  use indoc::indoc;
+
+  #[cfg(not(feature = "additional-controls"))]
+  #[test]
+  fn validate() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = indoc!(
+      r#"
+        tcpflagbytes = bstr .bits flags
+        flags = &(
+          fin: 8,
+          syn: 9,
+          rst: 10,
+          psh: 11,
+          ack: 12,
+          urg: 13,
+          ece: 14,
+          cwr: 15,
+          ns: 0,
+        ) / (4..7) ; data offset bits
+      "#
+    );
 
     let cbor = ciborium::value::Value::Bytes(vec![0x90, 0x6d]);
 
@@ -3554,6 +4673,50 @@ mod tests {
     Ok(())
   }
 
+  #[cfg(feature = "additional-controls")]
+  #[test]
+  fn validate_tagged_prelude_types() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = indoc!(
+      r#"
+        message = [biguint, bigint, tdate, uri]
+      "#
+    );
+
+    let cddl = cddl_from_str(cddl, true)?;
+
+    let cbor = ciborium::value::Value::Array(vec![
+      ciborium::value::Value::Tag(2, Box::new(ciborium::value::Value::Bytes(vec![1; 9]))),
+      ciborium::value::Value::Tag(3, Box::new(ciborium::value::Value::Bytes(vec![1; 9]))),
+      ciborium::value::Value::Tag(
+        0,
+        Box::new(ciborium::value::Value::Text(
+          "2013-03-21T20:04:00Z".to_string(),
+        )),
+      ),
+      ciborium::value::Value::Tag(
+        32,
+        Box::new(ciborium::value::Value::Text(
+          "https://example.com".to_string(),
+        )),
+      ),
+    ]);
+
+    let mut cv = CBORValidator::new(&cddl, cbor, None);
+    cv.validate()?;
+
+    let cbor = ciborium::value::Value::Array(vec![
+      ciborium::value::Value::Tag(3, Box::new(ciborium::value::Value::Bytes(vec![1; 9]))),
+      ciborium::value::Value::Tag(3, Box::new(ciborium::value::Value::Bytes(vec![1; 9]))),
+      ciborium::value::Value::Text("2013-03-21T20:04:00Z".to_string()),
+      ciborium::value::Value::Text("https://example.com".to_string()),
+    ]);
+
+    let mut cv = CBORValidator::new(&cddl, cbor, None);
+    assert!(cv.validate().is_err());
+
+    Ok(())
+  }
+
   #[cfg(feature = "additional-controls")]
   #[test]
   fn validate_feature() -> std::result::Result<(), Box<dyn std::error::Error>> {
@@ -3635,4 +4798,869 @@ mod tests {
 
     Ok(())
   }
+
+  #[test]
+  fn validate_array_with_multiple_occurrence_indicators(
+  ) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = indoc!(
+      r#"
+        tester = [ * a: int, b: tstr, ? c: int ]
+      "#
+    );
+
+    let cddl = cddl_from_str(cddl, true).map_err(json::Error::CDDLParsing)?;
+
+    for cbor in [
+      ciborium::cbor!(["b"]).unwrap(),
+      ciborium::cbor!([1, 2, "b"]).unwrap(),
+      ciborium::cbor!([1, "b", 3]).unwrap(),
+    ] {
+      let mut cv = CBORValidator::new(&cddl, cbor, None);
+      cv.validate()?;
+    }
+
+    for cbor in [
+      ciborium::cbor!([]).unwrap(),
+      ciborium::cbor!([1, 2]).unwrap(),
+      ciborium::cbor!(["b", 2, 3]).unwrap(),
+    ] {
+      let mut cv = CBORValidator::new(&cddl, cbor, None);
+      assert!(cv.validate().is_err());
+    }
+
+    Ok(())
+  }
+
+  #[test]
+  fn validate_against_named_rule() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = indoc!(
+      r#"
+        request = { id: uint, method: tstr }
+        response = [id: uint, result: any]
+        pair<K, V> = [K, V]
+      "#
+    );
+
+    let cddl = cddl_from_str(cddl, true).map_err(json::Error::CDDLParsing)?;
+
+    let mut cv = CBORValidator::new(&cddl, ciborium::cbor!([1, "a"]).unwrap(), None);
+    cv.validate_rule("response")?;
+
+    let mut cv = CBORValidator::new(&cddl, ciborium::cbor!([1, "a"]).unwrap(), None);
+    assert!(matches!(
+      cv.validate_rule("notification"),
+      Err(Error::MissingRule(_))
+    ));
+
+    let mut cv = CBORValidator::new(&cddl, ciborium::cbor!([1, "a"]).unwrap(), None);
+    assert!(matches!(
+      cv.validate_rule("pair"),
+      Err(Error::MissingRule(_))
+    ));
+
+    Ok(())
+  }
+
+  #[cfg(feature = "additional-controls")]
+  #[test]
+  fn validate_decfrac_and_bigfloat() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    use ciborium::value::Value;
+
+    let decfrac = |e: i64, m: Value| Value::Tag(4, Box::new(Value::Array(vec![e.into(), m])));
+    let bigfloat = |e: i64, m: Value| Value::Tag(5, Box::new(Value::Array(vec![e.into(), m])));
+
+    let cddl = indoc!(
+      r#"
+        prices = [
+          price: decfrac .lt 100,
+          discount: decfrac .ge 0.1,
+          temperature: bigfloat,
+          ratio: 0.0..1.0,
+        ]
+      "#
+    );
+
+    let cddl = cddl_from_str(cddl, true)?;
+
+    let valid = vec![
+      // 99.99
+      decfrac(-2, 9999.into()),
+      // 0.1
+      decfrac(-1, 1.into()),
+      // 2^64 * 2^-3
+      bigfloat(
+        -3,
+        Value::Tag(2, Box::new(Value::Bytes(vec![1, 0, 0, 0, 0, 0, 0, 0, 0]))),
+      ),
+      // 0.75
+      bigfloat(-2, 3.into()),
+    ];
+
+    let mut cv = CBORValidator::new(&cddl, Value::Array(valid.clone()), None);
+    cv.validate()?;
+
+    for (idx, invalid) in vec![
+      // 100.00
+      decfrac(-2, 10000.into()),
+      // 0.09
+      decfrac(-2, 9.into()),
+      bigfloat(1, "1".into()),
+      // 1.5
+      bigfloat(-1, 3.into()),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+      let mut cbor = valid.clone();
+      cbor[idx] = invalid;
+
+      let mut cv = CBORValidator::new(&cddl, Value::Array(cbor), None);
+      assert!(cv.validate().is_err());
+    }
+
+    Ok(())
+  }
+
+  #[cfg(feature = "additional-controls")]
+  #[test]
+  fn validate_float_widths_and_simple_values() -> std::result::Result<(), Box<dyn std::error::Error>>
+  {
+    use ciborium::value::Value;
+
+    let cddl = indoc!(
+      r#"
+        item = [
+          half: #7.25,
+          single: #7.26,
+          double: #7.27,
+          undef: #7.23,
+          unassigned: #7.16,
+          extended: #7.32,
+          nothing: undefined,
+          empty: null,
+          { key: #7.25 },
+        ]
+      "#
+    );
+
+    let valid: Vec<&[u8]> = vec![
+      &[0xf9, 0x3e, 0x00],
+      &[0xfa, 0x3f, 0xc0, 0x00, 0x00],
+      &[0xfb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0],
+      &[0xf7],
+      &[0xf0],
+      &[0xf8, 0x20],
+      &[0xf7],
+      &[0xf6],
+      &[0xa1, 0x63, b'k', b'e', b'y', 0xf9, 0x3e, 0x00],
+    ];
+
+    let encode = |items: &[&[u8]]| {
+      let mut cbor = vec![0x80 | items.len() as u8];
+      for item in items {
+        cbor.extend_from_slice(item);
+      }
+      cbor
+    };
+
+    validate_cbor_from_slice(cddl, &encode(&valid), None)?;
+
+    // Floats encoded narrower than the constraint match as well
+    let mut narrower = valid.clone();
+    narrower[1] = &[0xf9, 0x3e, 0x00];
+    narrower[2] = &[0xfa, 0x3f, 0xc0, 0x00, 0x00];
+    validate_cbor_from_slice(cddl, &encode(&narrower), None)?;
+
+    let invalid: Vec<&[u8]> = vec![
+      &[0xfa, 0x3f, 0xc0, 0x00, 0x00],
+      &[0xfb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0],
+      &[0x01],
+      &[0xf6],
+      &[0xf1],
+      &[0xf8, 0x21],
+      &[0xf6],
+      &[0xf7],
+      &[0xa1, 0x63, b'k', b'e', b'y', 0xfa, 0x3f, 0xc0, 0x00, 0x00],
+    ];
+
+    for (idx, invalid) in invalid.into_iter().enumerate() {
+      let mut items = valid.clone();
+      items[idx] = invalid;
+
+      assert!(validate_cbor_from_slice(cddl, &encode(&items), None).is_err());
+    }
+
+    // Without a known encoding, floats must be representable at the given
+    // width and null matches both null and undefined
+    let cddl = cddl_from_str("item = [#7.25, #7.26, #7.22, #7.23]", true)?;
+
+    let cbor = Value::Array(vec![1.5.into(), 0.25.into(), Value::Null, Value::Null]);
+    let mut cv = CBORValidator::new(&cddl, cbor, None);
+    cv.validate()?;
+
+    let cbor = Value::Array(vec![0.1.into(), 0.25.into(), Value::Null, Value::Null]);
+    let mut cv = CBORValidator::new(&cddl, cbor, None);
+    assert!(cv.validate().is_err());
+
+    let cddl = cddl_from_str("item = #7.24", true)?;
+    let mut cv = CBORValidator::new(&cddl, Value::Null, None);
+    assert!(cv.validate().is_err());
+
+    Ok(())
+  }
+
+  #[cfg(feature = "additional-controls")]
+  #[test]
+  fn validate_preferred_serialization() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = cddl_from_str("item = { * tstr => float16-32 / uint }", true)?;
+
+    // {"a": 1.5 as single, "b": 1 with a 1-byte argument, "c": 100000.0 as
+    // single}
+    let cbor = [
+      0xa3, 0x61, b'a', 0xfa, 0x3f, 0xc0, 0x00, 0x00, 0x61, b'b', 0x18, 0x01, 0x61, b'c', 0xfa,
+      0x47, 0xc3, 0x50, 0x00,
+    ];
+
+    let (value, raw) = raw::from_slice(&cbor)?;
+    let mut cv = CBORValidator::new(&cddl, value.clone(), None).with_encoding(raw.clone());
+    cv.validate()?;
+
+    let mut cv = CBORValidator::new(&cddl, value, None)
+      .with_encoding(raw)
+      .with_preferred_serialization();
+    match cv.validate() {
+      Err(Error::Validation(errors)) => {
+        assert_eq!(
+          errors
+            .iter()
+            .map(|e| e.cbor_location.as_str())
+            .collect::<Vec<_>>(),
+          vec!["/a", "/b"]
+        );
+      }
+      r => panic!("expected validation errors, got {:?}", r),
+    }
+
+    // Floats must fit the width of the data type
+    let cddl = cddl_from_str("item = [float16, float32]", true)?;
+    validate_cbor_from_slice(
+      "item = [float16, float32]",
+      &[0x82, 0xf9, 0x3e, 0x00, 0xf9, 0x3e, 0x00],
+      None,
+    )?;
+    assert!(validate_cbor_from_slice(
+      "item = [float16, float32]",
+      &[0x82, 0xfa, 0x3f, 0xc0, 0x00, 0x00, 0xf9, 0x3e, 0x00],
+      None
+    )
+    .is_err());
+
+    // Without a known encoding, floats must be representable at the given width
+    let mut cv = CBORValidator::new(&cddl, ciborium::cbor!([0.5, 0.1])?, None);
+    assert!(cv.validate().is_err());
+
+    Ok(())
+  }
+
+  #[cfg(feature = "additional-controls")]
+  #[test]
+  fn validate_deterministic_encoding() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = "item = { * (tstr / int) => [* uint] }";
+
+    // {1: [], "a": [1], -1: []}
+    let deterministic = [0xa3, 0x01, 0x80, 0x20, 0x80, 0x61, b'a', 0x81, 0x01];
+    validate_cbor_from_slice_deterministic(cddl, &deterministic, None, None)?;
+
+    // {"a": [_ 1], 1: [24], -1: []}, where "a" must sort after both integer
+    // keys
+    let cbor = [
+      0xa3, 0x61, b'a', 0x9f, 0x01, 0xff, 0x01, 0x81, 0x18, 0x18, 0x20, 0x80,
+    ];
+    validate_cbor_from_slice(cddl, &cbor, None)?;
+
+    match validate_cbor_from_slice_deterministic(cddl, &cbor, None, None) {
+      Err(Error::Validation(errors)) => {
+        let locations = errors
+          .iter()
+          .map(|e| e.cbor_location.as_str())
+          .collect::<Vec<_>>();
+
+        // Indefinite-length array and key 1, which sorts before the preceding
+        // key "a"
+        assert_eq!(locations, vec!["/a", "/1"]);
+      }
+      r => panic!("expected validation errors, got {:?}", r),
+    }
+
+    // {1: [24 as 0x19 0x00 0x18]}
+    let cbor = [0xa1, 0x01, 0x81, 0x19, 0x00, 0x18];
+    match validate_cbor_from_slice_deterministic(cddl, &cbor, None, None) {
+      Err(Error::Validation(errors)) => {
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].cbor_location, "/1/0");
+      }
+      r => panic!("expected validation errors, got {:?}", r),
+    }
+
+    Ok(())
+  }
+
+  #[cfg(feature = "additional-controls")]
+  #[test]
+  fn validate_duplicate_map_keys() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = "thing = { * any => any }";
+
+    // {1: {"a": 1, "a": 2}, 1 as 0x18 0x01: undefined, null: 1, undefined: 2}
+    let cbor = [
+      0xa4, 0x01, 0xa2, 0x61, b'a', 0x01, 0x61, b'a', 0x02, 0x18, 0x01, 0xf7, 0xf6, 0x01, 0xf7,
+      0x02,
+    ];
+
+    match validate_cbor_from_slice(cddl, &cbor, None) {
+      Err(Error::Validation(errors)) => {
+        // null and undefined are distinct keys even though both are decoded as
+        // null
+        assert_eq!(
+          errors
+            .iter()
+            .map(|e| e.cbor_location.as_str())
+            .collect::<Vec<_>>(),
+          vec!["/1", ""]
+        );
+      }
+      r => panic!("expected validation errors, got {:?}", r),
+    }
+
+    let cddl = cddl_from_str(cddl, true)?;
+    let mut cv = CBORValidator::new(
+      &cddl,
+      Value::Map(vec![(1.into(), 1.into()), (2.into(), 2.into())]),
+      None,
+    );
+    cv.validate()?;
+
+    Ok(())
+  }
+
+  #[cfg(feature = "additional-controls")]
+  #[test]
+  fn validate_embedded_cbor_sequence() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = indoc!(
+      r#"
+        thing = {
+          ? seq: bstr .cborseq [* int],
+          ? floats: bstr .cborseq [tstr, * float16],
+        }
+      "#
+    );
+    let cddl = cddl_from_str(cddl, true)?;
+
+    let validate = |key: &str, bytes: &[u8]| {
+      let value = Value::Map(vec![(
+        Value::Text(key.to_string()),
+        Value::Bytes(bytes.to_vec()),
+      )]);
+
+      let mut cv = CBORValidator::new(&cddl, value, None);
+      match cv.validate() {
+        Ok(()) => Vec::new(),
+        Err(Error::Validation(errors)) => errors.into_iter().map(|e| e.reason).collect(),
+        Err(e) => panic!("unexpected error {}", e),
+      }
+    };
+
+    // 1, 2
+    assert!(validate("seq", b"\x01\x02").is_empty());
+    assert!(validate("seq", b"").is_empty());
+    // [1, 2] is a single data item rather than a sequence of integers
+    assert_eq!(validate("seq", b"\x82\x01\x02").len(), 1);
+    // 1, "a"
+    assert_eq!(validate("seq", b"\x01\x61\x61").len(), 1);
+    // 1, truncated [1, 2]
+    assert!(
+      validate("seq", b"\x01\x82\x01")[0].starts_with("error decoding embedded CBOR sequence")
+    );
+
+    // "a", 1.5 as half, 1.5 as half
+    assert!(validate("floats", b"\x61\x61\xf9\x3e\x00\xf9\x3e\x00").is_empty());
+    // "a", 1.5 as half, 1.5 as single
+    assert_eq!(
+      validate("floats", b"\x61\x61\xf9\x3e\x00\xfa\x3f\xc0\x00\x00").len(),
+      1
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn validate_text_conversion_controls() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = indoc!(
+      r#"
+        thing = {
+          ? sig: text .b64u (bstr .size 4),
+          ? id: tstr .hexlc h'0102',
+          ? name: tstr .b45 'AB',
+          ? key: tstr .b64c-sloppy short,
+        }
+
+        short = bytes .size (1..2)
+      "#
+    );
+    let cddl = cddl_from_str(cddl, true)?;
+
+    let validate = |entries: &[(&str, &str)]| {
+      let value = Value::Map(
+        entries
+          .iter()
+          .map(|(k, v)| (Value::Text(k.to_string()), Value::Text(v.to_string())))
+          .collect(),
+      );
+
+      let mut cv = CBORValidator::new(&cddl, value, None);
+      match cv.validate() {
+        Ok(()) => Vec::new(),
+        Err(Error::Validation(errors)) => errors.into_iter().map(|e| e.cbor_location).collect(),
+        Err(e) => panic!("unexpected error {}", e),
+      }
+    };
+
+    assert!(validate(&[("sig", "AQIDBA"), ("id", "0102"), ("name", "BB8")]).is_empty());
+    assert!(validate(&[("key", "AQ==")]).is_empty());
+    assert!(validate(&[("key", "AR==")]).is_empty());
+    assert_eq!(validate(&[("sig", "AQIDBA==")]), vec!["/\"sig\""]);
+    assert_eq!(validate(&[("sig", "AQID")]), vec!["/\"sig\""]);
+    assert_eq!(validate(&[("id", "0A02")]), vec!["/\"id\""]);
+    assert_eq!(validate(&[("name", "BB9")]), vec!["/\"name\""]);
+    assert_eq!(validate(&[("key", "AQID")]), vec!["/\"key\""]);
+
+    Ok(())
+  }
+
+  #[cfg(feature = "additional-controls")]
+  #[test]
+  fn validate_decimal_and_printf_controls() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = indoc!(
+      r#"
+        thing = {
+          ? count: text .decimal (0..255),
+          ? offset: text .decimal int,
+          ? code: "0x%04x" .printf [4711],
+          ? label: "id-" .cat ("%02d" .printf [7]),
+        }
+      "#
+    );
+    let cddl = cddl_from_str(cddl, true)?;
+
+    let validate = |entries: &[(&str, &str)]| {
+      let value = Value::Map(
+        entries
+          .iter()
+          .map(|(k, v)| (Value::Text(k.to_string()), Value::Text(v.to_string())))
+          .collect(),
+      );
+
+      let mut cv = CBORValidator::new(&cddl, value, None);
+      match cv.validate() {
+        Ok(()) => Vec::new(),
+        Err(Error::Validation(errors)) => errors.into_iter().map(|e| e.cbor_location).collect(),
+        Err(e) => panic!("unexpected error {}", e),
+      }
+    };
+
+    assert!(validate(&[
+      ("count", "255"),
+      ("offset", "-12"),
+      ("code", "0x1267"),
+      ("label", "id-07")
+    ])
+    .is_empty());
+    assert_eq!(validate(&[("count", "256")]), vec!["/\"count\""]);
+    assert_eq!(validate(&[("count", "042")]), vec!["/\"count\""]);
+    assert_eq!(validate(&[("offset", "-0")]), vec!["/\"offset\""]);
+    assert_eq!(validate(&[("code", "0x1268")]), vec!["/\"code\""]);
+    assert_eq!(validate(&[("label", "id-7")]), vec!["/\"label\""]);
+
+    Ok(())
+  }
+
+  #[cfg(all(feature = "additional-controls", feature = "json"))]
+  #[test]
+  fn validate_json_and_join_controls() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = indoc!(
+      r#"
+        thing = {
+          ? doc: text .json { name: tstr, ? tags: [* tstr] },
+          ? address: text .join ip-address-elements,
+        }
+
+        ip-address-elements = [bytetext, ".", bytetext, ".", bytetext, ".", bytetext]
+        bytetext = text .decimal byte
+        byte = 0..255
+      "#
+    );
+    let cddl = cddl_from_str(cddl, true)?;
+
+    let validate = |entries: &[(&str, &str)]| {
+      let value = Value::Map(
+        entries
+          .iter()
+          .map(|(k, v)| (Value::Text(k.to_string()), Value::Text(v.to_string())))
+          .collect(),
+      );
+
+      let mut cv = CBORValidator::new(&cddl, value, None);
+      match cv.validate() {
+        Ok(()) => Vec::new(),
+        Err(Error::Validation(errors)) => errors.into_iter().map(|e| e.reason).collect(),
+        Err(e) => panic!("unexpected error {}", e),
+      }
+    };
+
+    assert!(validate(&[
+      ("doc", r#"{ "name": "foo", "tags": ["a", "b"] }"#),
+      ("address", "192.0.2.1")
+    ])
+    .is_empty());
+
+    let errors = validate(&[("doc", r#"{ "name": 1 }"#)]);
+    assert_eq!(errors.len(), 1);
+    assert!(
+      errors[0].starts_with("embedded JSON at /name: "),
+      "{}",
+      errors[0]
+    );
+    assert!(validate(&[("doc", "{ name: 1 }")])[0].starts_with("error parsing embedded JSON"));
+    assert_eq!(validate(&[("address", "192.0.2.256")]).len(), 1);
+    assert_eq!(validate(&[("address", "192.0.2")]).len(), 1);
+    assert_eq!(validate(&[("address", "192.0.02.1")]).len(), 1);
+
+    Ok(())
+  }
+
+  #[test]
+  fn validate_custom_control_operator() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let mut controls = CustomControls::new();
+    controls.register(".oid", |value, _, controller| match value {
+      ControlValue::CBOR(Value::Text(s)) => match controller {
+        Type2::TextValue { value: arc, .. } if s.starts_with(&format!("{}.", arc)) => Ok(()),
+        _ => Err(format!("expected OID under arc {}, got {}", controller, s)),
+      },
+      _ => Err(format!("expected OID, got {:?}", value)),
+    })?;
+
+    let cddl = indoc!(
+      r#"
+        thing = {
+          algorithm: tstr .oid "1.2.840",
+        }
+      "#
+    );
+    assert!(cddl_from_str(cddl, false).is_err());
+    let cddl = controls.cddl_from_str(cddl)?;
+
+    let validate = |oid: Value| {
+      let value = Value::Map(vec![(Value::Text("algorithm".to_string()), oid)]);
+
+      let mut cv = CBORValidator::new(&cddl, value, None).with_custom_controls(&controls);
+      match cv.validate() {
+        Ok(()) => Vec::new(),
+        Err(Error::Validation(errors)) => errors.into_iter().map(|e| e.reason).collect(),
+        Err(e) => panic!("unexpected error {}", e),
+      }
+    };
+
+    assert!(validate(Value::Text("1.2.840.10045".to_string())).is_empty());
+    assert_eq!(
+      validate(Value::Text("1.3.101".to_string())),
+      vec!["expected OID under arc \"1.2.840\", got 1.3.101"]
+    );
+    // The callback is not invoked for data items not matching the target
+    assert_eq!(validate(Value::Integer(1.into())).len(), 1);
+
+    Ok(())
+  }
+
+  #[test]
+  fn validate_tag_registry() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let mut registry = TagRegistry::iana();
+    registry.register(65000, |value| match value {
+      Value::Text(s) if s.chars().all(|c| c.is_ascii_uppercase()) => Ok(()),
+      _ => Err(format!("expected upper case text, got {:?}", value)),
+    });
+
+    let cddl = indoc!(
+      r#"
+        thing = {
+          ? id: #6.37(bstr),
+          ? ipv4: #6.52(bstr / [uint, bstr]),
+          ? ipv6: #6.54(bstr / [uint, bstr]),
+          ? code: #6.65000(tstr),
+          ? other: #6.37(any),
+          ? link: uri,
+        }
+      "#
+    );
+    let cddl = cddl_from_str(cddl, true)?;
+
+    let validate_with = |key: &str, value: Value, registry: Option<&TagRegistry>| {
+      let value = Value::Map(vec![(Value::Text(key.to_string()), value)]);
+
+      let mut cv = CBORValidator::new(&cddl, value, None);
+      if let Some(registry) = registry {
+        cv = cv.with_tag_registry(registry);
+      }
+
+      match cv.validate() {
+        Ok(()) => Vec::new(),
+        Err(Error::Validation(errors)) => errors.into_iter().map(|e| e.reason).collect(),
+        Err(e) => panic!("unexpected error {}", e),
+      }
+    };
+    let validate = |key: &str, value: Value| validate_with(key, value, Some(&registry));
+    let tagged = |tag: u64, value: Value| Value::Tag(tag, Box::new(value));
+
+    assert_eq!(
+      validate("id", tagged(37, Value::Bytes(vec![0; 16]))),
+      Vec::<String>::new()
+    );
+    assert_eq!(
+      validate("id", tagged(37, Value::Bytes(vec![0; 4]))),
+      vec!["invalid content of tag 37, expected UUID byte string of 16 bytes, got 4 bytes"]
+    );
+    assert_eq!(
+      validate("other", tagged(37, Value::Text("id".to_string()))).len(),
+      1
+    );
+
+    assert!(validate("ipv4", tagged(52, Value::Bytes(vec![192, 0, 2, 1]))).is_empty());
+    assert!(validate(
+      "ipv4",
+      tagged(
+        52,
+        Value::Array(vec![
+          Value::Integer(24.into()),
+          Value::Bytes(vec![192, 0, 2])
+        ])
+      )
+    )
+    .is_empty());
+    assert_eq!(validate("ipv6", tagged(54, Value::Bytes(vec![0x20, 0x01, 0x0d, 0xb8]))), vec!["invalid content of tag 54, expected IP address of 16 bytes, prefix or interface format, got Bytes([32, 1, 13, 184])"]);
+    assert!(validate(
+      "ipv6",
+      tagged(
+        54,
+        Value::Array(vec![
+          Value::Integer(32.into()),
+          Value::Bytes(vec![0x20, 0x01, 0x0d, 0xb8])
+        ])
+      )
+    )
+    .is_empty());
+    assert_eq!(
+      validate(
+        "ipv4",
+        tagged(
+          52,
+          Value::Array(vec![Value::Integer(8.into()), Value::Bytes(vec![192, 0])])
+        )
+      ),
+      vec!["invalid content of tag 52, expected prefix without trailing zero bytes"]
+    );
+    assert_eq!(
+      validate(
+        "ipv4",
+        tagged(
+          52,
+          Value::Array(vec![Value::Integer(33.into()), Value::Bytes(vec![192])])
+        )
+      ),
+      vec![
+        "invalid content of tag 52, expected prefix length of at most 32, got Integer(Integer(33))"
+      ]
+    );
+
+    assert!(validate("code", tagged(65000, Value::Text("ABC".to_string()))).is_empty());
+    assert_eq!(
+      validate("code", tagged(65000, Value::Text("abc".to_string()))),
+      vec!["invalid content of tag 65000, expected upper case text, got Text(\"abc\")"]
+    );
+
+    // Without a registry, the predefined checks of common IANA registered tags
+    // are used
+    assert_eq!(
+      validate_with("id", tagged(37, Value::Bytes(vec![0; 4])), None),
+      vec!["invalid content of tag 37, expected UUID byte string of 16 bytes, got 4 bytes"]
+    );
+    assert_eq!(
+      validate_with("other", tagged(37, Value::Integer(1.into())), None).len(),
+      1
+    );
+    assert_eq!(
+      validate_with("ipv4", tagged(52, Value::Bytes(vec![1, 2])), None).len(),
+      1
+    );
+    assert!(validate_with("code", tagged(65000, Value::Text("abc".to_string())), None).is_empty());
+
+    // With a registry without a check for the tag, the content of #6.n(...) is
+    // only constrained by the schema, while prelude types defined with tags
+    // are still checked with the predefined check of their tag
+    let empty = TagRegistry::new();
+    assert!(validate_with("id", tagged(37, Value::Bytes(vec![0; 4])), Some(&empty)).is_empty());
+    assert!(validate_with("other", tagged(37, Value::Integer(1.into())), Some(&empty)).is_empty());
+    assert!(validate_with(
+      "link",
+      tagged(32, Value::Text("https://example.com".to_string())),
+      Some(&empty)
+    )
+    .is_empty());
+    assert_eq!(
+      validate_with(
+        "link",
+        tagged(32, Value::Text("not a uri".to_string())),
+        Some(&empty)
+      )
+      .len(),
+      1
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn validate_date_tags() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = indoc!(
+      r#"
+        event = {
+          ? on: #6.1004(tstr),
+          ? days: #6.100(int),
+          ? at: #6.1001({ * (int / tstr) => any }),
+          ? since: #6.0(tstr),
+          ? until: #6.1(any),
+        }
+      "#
+    );
+    let cddl = cddl_from_str(cddl, true)?;
+
+    let validate = |key: &str, value: Value| {
+      let value = Value::Map(vec![(Value::Text(key.to_string()), value)]);
+
+      let mut cv = CBORValidator::new(&cddl, value, None);
+      match cv.validate() {
+        Ok(()) => Vec::new(),
+        Err(Error::Validation(errors)) => errors.into_iter().map(|e| e.reason).collect(),
+        Err(e) => panic!("unexpected error {}", e),
+      }
+    };
+    let tagged = |tag: u64, value: Value| Value::Tag(tag, Box::new(value));
+    let time = |entries: Vec<(i64, Value)>| {
+      tagged(
+        1001,
+        Value::Map(
+          entries
+            .into_iter()
+            .map(|(k, v)| (Value::Integer(k.into()), v))
+            .collect(),
+        ),
+      )
+    };
+
+    assert!(validate("on", tagged(1004, Value::Text("1985-04-12".to_string()))).is_empty());
+    assert_eq!(
+      validate("on", tagged(1004, Value::Text("1985-13-12".to_string()))),
+      vec!["invalid content of tag 1004, invalid full-date \"1985-13-12\", input is out of range"]
+    );
+    assert_eq!(
+      validate("on", tagged(1004, Value::Text("1985-04-12T00:00:00Z".to_string()))),
+      vec!["invalid content of tag 1004, expected full-date of the form YYYY-MM-DD, got \"1985-04-12T00:00:00Z\""]
+    );
+    assert_eq!(
+      validate("on", tagged(1004, Value::Text("2020-02-30".to_string()))).len(),
+      1
+    );
+
+    assert!(validate(
+      "since",
+      tagged(0, Value::Text("2013-03-21T20:04:00Z".to_string()))
+    )
+    .is_empty());
+    assert_eq!(
+      validate("since", tagged(0, Value::Text("nope".to_string()))).len(),
+      1
+    );
+    assert!(validate("until", tagged(1, Value::Integer(1363896240.into()))).is_empty());
+    assert_eq!(
+      validate("until", tagged(1, Value::Text("nope".to_string()))),
+      vec!["invalid content of tag 1, expected epoch-based date/time number, got Text(\"nope\")"]
+    );
+
+    assert!(validate("days", tagged(100, Value::Integer((-4000).into()))).is_empty());
+    assert_eq!(
+      validate("days", tagged(100, Value::Integer(i64::MAX.into()))),
+      vec![format!(
+        "invalid content of tag 100, days since epoch {:?} out of range",
+        ciborium::value::Integer::from(i64::MAX)
+      )]
+    );
+
+    assert!(validate("at", time(vec![(1, Value::Integer(1363896240.into()))])).is_empty());
+    assert!(validate(
+      "at",
+      time(vec![
+        (1, Value::Integer(1363896240.into())),
+        (-3, Value::Integer(500.into())),
+        (-1, Value::Integer(0.into())),
+      ])
+    )
+    .is_empty());
+    assert!(validate(
+      "at",
+      time(vec![(
+        4,
+        Value::Array(vec![
+          Value::Integer((-1).into()),
+          Value::Integer(13638962405i64.into())
+        ])
+      )])
+    )
+    .is_empty());
+    assert_eq!(
+      validate("at", time(vec![(-1, Value::Integer(0.into()))])),
+      vec!["invalid content of tag 1001, expected base time in key 1, 4 or 5"]
+    );
+    assert_eq!(
+      validate(
+        "at",
+        time(vec![
+          (1, Value::Integer(1363896240.into())),
+          (
+            5,
+            Value::Array(vec![Value::Integer(0.into()), Value::Integer(1.into())])
+          ),
+        ])
+      ),
+      vec!["invalid content of tag 1001, expected exactly one base time in key 1, 4 or 5"]
+    );
+    assert_eq!(
+      validate(
+        "at",
+        time(vec![
+          (1, Value::Integer(1363896240.into())),
+          (-3, Value::Integer(1000.into())),
+        ])
+      ),
+      vec!["invalid content of tag 1001, expected fractional time in key -3 less than 1000, got Integer(Integer(1000))"]
+    );
+    assert_eq!(
+      validate(
+        "at",
+        time(vec![
+          (1, Value::Float(1363896240.5)),
+          (-6, Value::Integer(500.into())),
+        ])
+      ),
+      vec!["invalid content of tag 1001, fractional time in key -6 requires an integer base time in key 1"]
+    );
+
+    Ok(())
+  }
 }
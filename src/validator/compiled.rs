@@ -0,0 +1,998 @@
+#![cfg(any(feature = "json", feature = "cbor"))]
+
+use super::*;
+use crate::{
+  ast::{
+    GenericArgs, MemberKey, NonMemberKey, Operator, OptionalComma, RangeCtlOp, Type1,
+    ValueMemberKeyEntry,
+  },
+  visitor,
+};
+
+#[cfg(feature = "additional-controls")]
+use super::control::{abnf_from_complex_controller, AbnfGrammar};
+
+use std::{
+  collections::{HashMap, HashSet},
+  marker::PhantomData,
+};
+
+/// Maximum number of instances of generic rules resolved when compiling a
+/// schema. Instantiations beyond it are resolved at validation time
+const MAX_GENERIC_INSTANCES: usize = 1024;
+
+/// Maximum depth of the instances of generic rules instantiated by other
+/// instances, such as those of generic rules instantiating themselves with
+/// ever different arguments. Instantiations beyond it are resolved at
+/// validation time
+const MAX_GENERIC_DEPTH: usize = 32;
+
+/// Intermediate representation of a CDDL AST for validating any number of
+/// documents against it. Compiling a schema resolves sockets and generics: the
+/// alternates of a rule (`/=` and `//=`) are merged into a single rule, and
+/// each instantiation of a generic rule, such as `message<tstr>`, is replaced
+/// by a rule named after it in which the generic parameters are substituted
+/// by the arguments. Rules are then indexed by name, so that validators
+/// resolve the rule names they encounter without scanning the AST, and the
+/// regular expressions and ABNF grammars of the `.regexp`, `.pcre`, `.abnf`
+/// and `.abnfb` control operators are compiled ahead of time. Validators
+/// given a compiled schema via their `with_compiled_schema` method validate
+/// against its resolved AST, instead of the AST they were created with
+///
+/// # Example
+///
+/// ```
+/// use cddl::{cddl_from_str, validator::{compiled::CompiledSchema, json::JSONValidator, Validator}};
+///
+/// let cddl = cddl_from_str(r#"code = tstr .regexp "[A-Z]{3}""#, true).unwrap();
+/// let compiled = CompiledSchema::new(&cddl);
+///
+/// for code in ["ABC", "DEF"] {
+///   let json = serde_json::Value::String(code.to_string());
+///   let mut jv = JSONValidator::new(compiled.cddl(), json, None).with_compiled_schema(&compiled);
+///   assert!(jv.validate().is_ok());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct CompiledSchema<'a> {
+  cddl: CDDL<'a>,
+  rules: HashMap<String, Vec<(Option<SocketPlug>, usize)>>,
+  regexes: HashMap<String, regex::Regex>,
+  #[cfg(feature = "additional-controls")]
+  abnf_grammars: HashMap<String, Option<AbnfGrammar>>,
+}
+
+impl<'a> CompiledSchema<'a> {
+  /// Compile the given CDDL AST. Regular expressions and ABNF grammars that
+  /// fail to compile are not included, so that the error is reported when
+  /// validating against them
+  pub fn new(cddl: &CDDL<'a>) -> Self {
+    let mut cddl = CDDL {
+      rules: merge_alternates(&cddl.rules),
+      #[cfg(feature = "ast-comments")]
+      comments: cddl.comments.clone(),
+    };
+    resolve_generics(&mut cddl);
+
+    let mut rules: HashMap<String, Vec<(Option<SocketPlug>, usize)>> = HashMap::new();
+    for (idx, rule) in cddl.rules.iter().enumerate() {
+      let name = match rule {
+        Rule::Type { rule, .. } => &rule.name,
+        Rule::Group { rule, .. } => &rule.name,
+      };
+
+      let entries = rules.entry(name.ident.to_string()).or_default();
+      if !entries.iter().any(|(socket, _)| *socket == name.socket) {
+        entries.push((name.socket, idx));
+      }
+    }
+
+    let mut controllers = ControllerCollector {
+      cddl: &cddl,
+      regexes: HashMap::new(),
+      #[cfg(feature = "additional-controls")]
+      abnf_grammars: HashMap::new(),
+    };
+    let _ = controllers.visit_cddl(&cddl);
+    let regexes = controllers.regexes;
+    #[cfg(feature = "additional-controls")]
+    let abnf_grammars = controllers.abnf_grammars;
+
+    CompiledSchema {
+      cddl,
+      rules,
+      regexes,
+      #[cfg(feature = "additional-controls")]
+      abnf_grammars,
+    }
+  }
+
+  /// Resolved CDDL AST
+  pub fn cddl(&self) -> &CDDL<'a> {
+    &self.cddl
+  }
+
+  fn rule(&self, ident: &Identifier) -> Option<&Rule<'a>> {
+    self
+      .rules
+      .get(ident.ident.as_ref())?
+      .iter()
+      .find(|(socket, _)| *socket == ident.socket)
+      .map(|(_, idx)| &self.cddl.rules[*idx])
+  }
+
+  /// Precompiled regular expression of the given `.regexp` or `.pcre`
+  /// controller
+  pub(crate) fn regex(&self, controller: &str) -> Option<&regex::Regex> {
+    self.regexes.get(controller)
+  }
+
+  /// Precompiled grammar of the given `.abnf` or `.abnfb` controller. `None`
+  /// if the controller was not compiled and `Some(None)` if it has no grammar
+  #[cfg(feature = "additional-controls")]
+  pub(crate) fn abnf_grammar(&self, controller: &str) -> Option<Option<&AbnfGrammar>> {
+    self.abnf_grammars.get(controller).map(|g| g.as_ref())
+  }
+}
+
+/// Merge the alternates of each rule into the first rule of the same name
+/// that is not an alternate, or the first alternate if there is none. Type
+/// choice alternates add their type choices and group choice alternates add
+/// their entries as group choices
+fn merge_alternates<'a>(rules: &[Rule<'a>]) -> Vec<Rule<'a>> {
+  let name = |rule: &Rule| match rule {
+    Rule::Type { rule, .. } => rule.name.to_string(),
+    Rule::Group { rule, .. } => rule.name.to_string(),
+  };
+
+  let mut bases = HashMap::new();
+  for (idx, rule) in rules.iter().enumerate() {
+    let base = bases.entry(name(rule)).or_insert(idx);
+    if rules[*base].is_choice_alternate() && !rule.is_choice_alternate() {
+      *base = idx;
+    }
+  }
+
+  let mut merged = Vec::with_capacity(rules.len());
+  let mut positions = HashMap::new();
+  let mut alternates: Vec<(usize, &Rule)> = Vec::new();
+  for (idx, rule) in rules.iter().enumerate() {
+    let name = name(rule);
+    if bases.get(&name) == Some(&idx) {
+      positions.insert(name, merged.len());
+      merged.push(rule.clone());
+    } else if rule.is_choice_alternate() {
+      alternates.push((idx, rule));
+    } else {
+      merged.push(rule.clone());
+    }
+  }
+
+  for (idx, alternate) in alternates {
+    let base = &mut merged[positions[&name(&rules[idx])]];
+    match (base, alternate) {
+      (Rule::Type { rule: base, .. }, Rule::Type { rule, .. }) => {
+        base
+          .value
+          .type_choices
+          .extend(rule.value.type_choices.iter().cloned());
+      }
+      (Rule::Group { rule: base, .. }, Rule::Group { rule, .. }) => {
+        let mut group_choices = group_choices_from_entry(&base.entry);
+        group_choices.extend(group_choices_from_entry(&rule.entry));
+        base.entry = GroupEntry::InlineGroup {
+          occur: None,
+          group: Group {
+            group_choices,
+            #[cfg(feature = "ast-span")]
+            span: Span::default(),
+          },
+          #[cfg(feature = "ast-span")]
+          span: Span::default(),
+          #[cfg(feature = "ast-comments")]
+          comments_before_group: None,
+          #[cfg(feature = "ast-comments")]
+          comments_after_group: None,
+        };
+      }
+      _ => (),
+    }
+  }
+
+  for rule in merged.iter_mut() {
+    match rule {
+      Rule::Type { rule, .. } => rule.is_type_choice_alternate = false,
+      Rule::Group { rule, .. } => rule.is_group_choice_alternate = false,
+    }
+  }
+
+  merged
+}
+
+/// Group choices matching the same data items as the given group entry
+fn group_choices_from_entry<'a>(entry: &GroupEntry<'a>) -> Vec<GroupChoice<'a>> {
+  match entry {
+    GroupEntry::InlineGroup {
+      occur: None, group, ..
+    } => group.group_choices.clone(),
+    entry => vec![GroupChoice {
+      group_entries: vec![(
+        entry.clone(),
+        OptionalComma {
+          optional_comma: false,
+          #[cfg(feature = "ast-comments")]
+          trailing_comments: None,
+          _a: PhantomData,
+        },
+      )],
+      #[cfg(feature = "ast-span")]
+      span: Span::default(),
+      #[cfg(feature = "ast-comments")]
+      comments_before_grpchoice: None,
+    }],
+  }
+}
+
+/// Replace the instantiations of generic rules by rules named after them, in
+/// which the generic parameters are substituted by the arguments
+fn resolve_generics(cddl: &mut CDDL) {
+  let mut resolver = GenericResolver::default();
+  for rule in cddl.rules.iter() {
+    match rule {
+      Rule::Type { rule: tr, .. } if tr.generic_params.is_some() => {
+        resolver
+          .generics
+          .insert(tr.name.ident.to_string(), rule.clone());
+      }
+      Rule::Group { rule: gr, .. } if gr.generic_params.is_some() => {
+        resolver
+          .generics
+          .insert(gr.name.ident.to_string(), rule.clone());
+      }
+      _ => (),
+    }
+  }
+
+  if resolver.generics.is_empty() {
+    return;
+  }
+
+  for rule in cddl.rules.iter_mut() {
+    let is_generic = match rule {
+      Rule::Type { rule, .. } => rule.generic_params.is_some(),
+      Rule::Group { rule, .. } => rule.generic_params.is_some(),
+    };
+    if !is_generic {
+      resolver.rule(rule);
+    }
+  }
+
+  while let Some((mut rule, bindings, depth)) = resolver.pending.pop() {
+    resolver.bindings = bindings;
+    resolver.depth = depth;
+    resolver.rule(&mut rule);
+    cddl.rules.push(rule);
+  }
+}
+
+#[derive(Default)]
+struct GenericResolver<'a> {
+  // Generic rules by name
+  generics: HashMap<String, Rule<'a>>,
+  // Names of the instances of generic rules
+  instances: HashSet<String>,
+  // Instances whose generic parameters are yet to be substituted, along with
+  // the arguments bound to them and their depth
+  pending: Vec<(Rule<'a>, HashMap<String, Type1<'a>>, usize)>,
+  // Arguments bound to the generic parameters of the instance being resolved
+  bindings: HashMap<String, Type1<'a>>,
+  // Depth of the instance being resolved, 0 for rules that are not instances
+  depth: usize,
+}
+
+impl<'a> GenericResolver<'a> {
+  fn rule(&mut self, rule: &mut Rule<'a>) {
+    match rule {
+      Rule::Type { rule, .. } => self.type_(&mut rule.value),
+      Rule::Group { rule, .. } => self.group_entry(&mut rule.entry),
+    }
+  }
+
+  fn type_(&mut self, t: &mut Type<'a>) {
+    for tc in t.type_choices.iter_mut() {
+      self.type1(&mut tc.type1);
+    }
+  }
+
+  fn type1(&mut self, t1: &mut Type1<'a>) {
+    self.type2(&mut t1.type2);
+    if let Some(Operator { type2, .. }) = &mut t1.operator {
+      self.type2(type2);
+    }
+  }
+
+  fn type2(&mut self, t2: &mut Type2<'a>) {
+    if let Type2::Typename {
+      ident,
+      generic_args: None,
+      ..
+    } = t2
+    {
+      if let Some(arg) = self.bound_arg(ident) {
+        *t2 = match arg.operator {
+          None => arg.type2,
+          Some(_) => Type2::ParenthesizedType {
+            pt: Type {
+              type_choices: vec![TypeChoice {
+                type1: arg,
+                #[cfg(feature = "ast-comments")]
+                comments_before_type: None,
+                #[cfg(feature = "ast-comments")]
+                comments_after_type: None,
+              }],
+              #[cfg(feature = "ast-span")]
+              span: Span::default(),
+            },
+            #[cfg(feature = "ast-span")]
+            span: Span::default(),
+            #[cfg(feature = "ast-comments")]
+            comments_before_type: None,
+            #[cfg(feature = "ast-comments")]
+            comments_after_type: None,
+          },
+        };
+
+        return;
+      }
+    }
+
+    match t2 {
+      Type2::Typename {
+        ident,
+        generic_args,
+        ..
+      }
+      | Type2::Unwrap {
+        ident,
+        generic_args,
+        ..
+      }
+      | Type2::ChoiceFromGroup {
+        ident,
+        generic_args,
+        ..
+      } => self.rule_name(ident, generic_args),
+      Type2::ParenthesizedType { pt: t, .. } | Type2::TaggedData { t, .. } => self.type_(t),
+      Type2::Map { group, .. }
+      | Type2::Array { group, .. }
+      | Type2::ChoiceFromInlineGroup { group, .. } => self.group(group),
+      _ => (),
+    }
+  }
+
+  fn group(&mut self, group: &mut Group<'a>) {
+    for gc in group.group_choices.iter_mut() {
+      for (ge, _) in gc.group_entries.iter_mut() {
+        self.group_entry(ge);
+      }
+    }
+  }
+
+  fn group_entry(&mut self, ge: &mut GroupEntry<'a>) {
+    match ge {
+      GroupEntry::ValueMemberKey { ge: vmke, .. } => {
+        match &mut vmke.member_key {
+          Some(MemberKey::Type1 { t1, .. }) => self.type1(t1),
+          Some(MemberKey::NonMemberKey {
+            non_member_key: NonMemberKey::Group(group),
+            ..
+          }) => self.group(group),
+          Some(MemberKey::NonMemberKey {
+            non_member_key: NonMemberKey::Type(t),
+            ..
+          }) => self.type_(t),
+          _ => (),
+        }
+
+        self.type_(&mut vmke.entry_type);
+      }
+      GroupEntry::TypeGroupname {
+        ge: tge,
+        #[cfg(feature = "ast-span")]
+        span,
+        #[cfg(feature = "ast-comments")]
+        leading_comments,
+        #[cfg(feature = "ast-comments")]
+        trailing_comments,
+      } => {
+        if tge.generic_args.is_none() {
+          if let Some(arg) = self.bound_arg(&tge.name) {
+            // A generic parameter used as a group entry stands for either a
+            // group name or a type
+            if arg.operator.is_none() {
+              if let Type2::Typename {
+                ident,
+                generic_args,
+                ..
+              } = arg.type2
+              {
+                tge.name = ident;
+                tge.generic_args = generic_args;
+                return;
+              }
+            }
+
+            *ge = GroupEntry::ValueMemberKey {
+              ge: Box::new(ValueMemberKeyEntry {
+                occur: tge.occur.take(),
+                member_key: None,
+                entry_type: Type {
+                  type_choices: vec![TypeChoice {
+                    type1: arg,
+                    #[cfg(feature = "ast-comments")]
+                    comments_before_type: None,
+                    #[cfg(feature = "ast-comments")]
+                    comments_after_type: None,
+                  }],
+                  #[cfg(feature = "ast-span")]
+                  span: *span,
+                },
+              }),
+              #[cfg(feature = "ast-span")]
+              span: *span,
+              #[cfg(feature = "ast-comments")]
+              leading_comments: leading_comments.take(),
+              #[cfg(feature = "ast-comments")]
+              trailing_comments: trailing_comments.take(),
+            };
+
+            return;
+          }
+        }
+
+        self.rule_name(&mut tge.name, &mut tge.generic_args);
+      }
+      GroupEntry::InlineGroup { group, .. } => self.group(group),
+    }
+  }
+
+  // Argument bound to the generic parameter of the given name, if any
+  fn bound_arg(&self, ident: &Identifier) -> Option<Type1<'a>> {
+    if ident.socket.is_some() {
+      return None;
+    }
+
+    self.bindings.get(ident.ident.as_ref()).cloned()
+  }
+
+  // Replace an instantiation of a generic rule by the name of its instance
+  fn rule_name(&mut self, ident: &mut Identifier<'a>, generic_args: &mut Option<GenericArgs<'a>>) {
+    let args = match generic_args {
+      Some(args) => args,
+      None => return,
+    };
+
+    for arg in args.args.iter_mut() {
+      self.type1(&mut arg.arg);
+    }
+
+    let generic = match self.generics.get(ident.ident.as_ref()) {
+      Some(generic) => generic,
+      None => return,
+    };
+    let params = match generic {
+      Rule::Type { rule, .. } => rule.generic_params.as_ref(),
+      Rule::Group { rule, .. } => rule.generic_params.as_ref(),
+    };
+    let params = match params {
+      Some(params) if params.params.len() == args.args.len() => params,
+      _ => return,
+    };
+
+    let name = format!("{}{}", ident.ident, args);
+    if !self.instances.contains(&name) {
+      if self.instances.len() == MAX_GENERIC_INSTANCES || self.depth == MAX_GENERIC_DEPTH {
+        return;
+      }
+
+      let bindings = params
+        .params
+        .iter()
+        .zip(args.args.iter())
+        .map(|(param, arg)| (param.param.ident.to_string(), (*arg.arg).clone()))
+        .collect();
+
+      let mut instance = generic.clone();
+      match &mut instance {
+        Rule::Type { rule, .. } => {
+          rule.name.ident = Cow::Owned(name.clone());
+          rule.generic_params = None;
+        }
+        Rule::Group { rule, .. } => {
+          rule.name.ident = Cow::Owned(name.clone());
+          rule.generic_params = None;
+        }
+      }
+
+      self.instances.insert(name.clone());
+      self.pending.push((instance, bindings, self.depth + 1));
+    }
+
+    ident.ident = Cow::Owned(name);
+    *generic_args = None;
+  }
+}
+
+/// Compile the regular expression of a `.regexp` or `.pcre` controller, whose
+/// text is JSON escaped per
+/// https://datatracker.ietf.org/doc/html/rfc8610#section-3.1
+fn compile_regex(controller: &str) -> Option<regex::Regex> {
+  let pattern = serde_json::from_str::<serde_json::Value>(&format!("\"{}\"", controller)).ok()?;
+  regex::Regex::new(&format_regex(pattern.as_str()?)?).ok()
+}
+
+// Collects the controllers of control operators that are compiled ahead of
+// time
+struct ControllerCollector<'c, 'a> {
+  cddl: &'c CDDL<'a>,
+  regexes: HashMap<String, regex::Regex>,
+  #[cfg(feature = "additional-controls")]
+  abnf_grammars: HashMap<String, Option<AbnfGrammar>>,
+}
+
+impl<'c, 'a, 'b> visitor::Visitor<'a, 'b, std::fmt::Error> for ControllerCollector<'c, 'a> {
+  fn visit_operator(
+    &mut self,
+    target: &'b Type1<'a>,
+    o: &'b Operator<'a>,
+  ) -> visitor::Result<std::fmt::Error> {
+    if let RangeCtlOp::CtlOp { ctrl, .. } = o.operator {
+      match ctrl {
+        ControlOperator::REGEXP | ControlOperator::PCRE => {
+          if let Some(Type2::TextValue { value, .. }) = text_value_from_type2(self.cddl, &o.type2) {
+            if !self.regexes.contains_key(value.as_ref()) {
+              if let Some(re) = compile_regex(value) {
+                self.regexes.insert(value.to_string(), re);
+              }
+            }
+          }
+        }
+        #[cfg(feature = "additional-controls")]
+        ControlOperator::ABNF | ControlOperator::ABNFB => {
+          let controllers = match &o.type2 {
+            Type2::ParenthesizedType { pt, .. } => {
+              abnf_from_complex_controller(self.cddl, pt).unwrap_or_default()
+            }
+            controller => text_value_from_type2(self.cddl, controller)
+              .cloned()
+              .into_iter()
+              .collect(),
+          };
+
+          for controller in controllers {
+            if let Type2::TextValue { value, .. } = controller {
+              if !self.abnf_grammars.contains_key(value.as_ref()) {
+                if let Ok(grammar) = AbnfGrammar::compile(&value) {
+                  self.abnf_grammars.insert(value.to_string(), grammar);
+                }
+              }
+            }
+          }
+        }
+        _ => (),
+      }
+    }
+
+    visitor::walk_operator(self, target, o)
+  }
+}
+
+/// Rule lookups of a validator, using the index of its compiled schema if any
+/// and otherwise scanning the rules of the CDDL AST. The CDDL AST of a
+/// validator with a compiled schema is the resolved AST of the schema, which
+/// has no alternates
+#[derive(Clone, Copy)]
+pub(crate) struct RuleLookup<'a> {
+  pub(crate) cddl: &'a CDDL<'a>,
+  pub(crate) compiled: Option<&'a CompiledSchema<'a>>,
+}
+
+impl<'a> RuleLookup<'a> {
+  /// See [`unwrap_rule_from_ident`]
+  pub(crate) fn unwrap_rule_from_ident(&self, ident: &Identifier) -> Option<&'a Rule<'a>> {
+    if self.compiled.is_none() {
+      return unwrap_rule_from_ident(self.cddl, ident);
+    }
+
+    let rule = self.rule_from_ident(ident)?;
+    let type_choices = match rule {
+      Rule::Type { rule: tr, .. } => &tr.value.type_choices,
+      _ => return None,
+    };
+
+    if type_choices.iter().any(|tc| {
+      matches!(
+        tc.type1.type2,
+        Type2::Map { .. } | Type2::Array { .. } | Type2::TaggedData { .. }
+      )
+    }) {
+      return Some(rule);
+    }
+
+    type_choices
+      .iter()
+      .find_map(|tc| match &tc.type1.type2 {
+        Type2::Typename {
+          ident,
+          generic_args: None,
+          ..
+        } => Some(ident),
+        _ => None,
+      })
+      .and_then(|ident| self.unwrap_rule_from_ident(ident))
+  }
+}
+
+impl<'a> Lookup<'a> for RuleLookup<'a> {
+  fn rule_from_ident(&self, ident: &Identifier) -> Option<&'a Rule<'a>> {
+    match self.compiled {
+      Some(c) => c.rule(ident),
+      None => rule_from_ident(self.cddl, ident),
+    }
+  }
+
+  fn group_rule_from_ident(&self, ident: &Identifier) -> Option<&'a GroupRule<'a>> {
+    match self.compiled {
+      Some(c) => match c.rule(ident)? {
+        Rule::Group { rule, .. } => Some(rule.as_ref()),
+        _ => None,
+      },
+      None => group_rule_from_ident(self.cddl, ident),
+    }
+  }
+
+  fn type_choice_alternates_from_ident(&self, ident: &Identifier) -> Vec<&'a Type<'a>> {
+    match self.compiled {
+      Some(_) => Vec::new(),
+      None => type_choice_alternates_from_ident(self.cddl, ident),
+    }
+  }
+
+  fn group_choice_alternates_from_ident(&self, ident: &Identifier) -> Vec<&'a GroupEntry<'a>> {
+    match self.compiled {
+      Some(_) => Vec::new(),
+      None => group_choice_alternates_from_ident(self.cddl, ident),
+    }
+  }
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "json", feature = "cbor", feature = "additional-controls"))]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+  use super::*;
+  use crate::{
+    cddl_from_str,
+    validator::{cbor::CBORValidator, json::JSONValidator},
+  };
+  use indoc::indoc;
+
+  fn json_reasons<'a>(
+    cddl: &'a CDDL<'a>,
+    compiled: Option<&'a CompiledSchema<'a>>,
+    json: &str,
+  ) -> Vec<String> {
+    let json = serde_json::from_str(json).unwrap();
+    let mut jv = JSONValidator::new(cddl, json, None);
+    if let Some(compiled) = compiled {
+      jv = jv.with_compiled_schema(compiled);
+    }
+
+    match jv.validate() {
+      Ok(()) => Vec::new(),
+      Err(json::Error::Validation(errors)) => errors.into_iter().map(|e| e.reason).collect(),
+      Err(e) => vec![e.to_string()],
+    }
+  }
+
+  fn cbor_reasons<'a>(
+    cddl: &'a CDDL<'a>,
+    compiled: Option<&'a CompiledSchema<'a>>,
+    json: &str,
+  ) -> Vec<String> {
+    let json: serde_json::Value = serde_json::from_str(json).unwrap();
+    let cbor = ciborium::value::Value::serialized(&json).unwrap();
+    let mut cv = CBORValidator::new(cddl, cbor, None);
+    if let Some(compiled) = compiled {
+      cv = cv.with_compiled_schema(compiled);
+    }
+
+    match cv.validate() {
+      Ok(()) => Vec::new(),
+      Err(cbor::Error::Validation(errors)) => errors.into_iter().map(|e| e.reason).collect(),
+      Err(e) => vec![e.to_string()],
+    }
+  }
+
+  fn rule_names(cddl: &CDDL) -> Vec<String> {
+    cddl.rules.iter().map(|r| r.name()).collect()
+  }
+
+  #[test]
+  fn compiled_schema_matches_uncompiled() {
+    let cddl = cddl_from_str(
+      indoc!(
+        r#"
+          thing = {
+            code: tstr .regexp "[A-Z]{3}",
+            ? digits: tstr .abnf ("digits" .det rules),
+            ? kind: $kind,
+            ? link: link,
+            * $$ext,
+          }
+
+          link = tstr .pcre "^https?://"
+
+          $kind /= "a"
+          $kind /= "b"
+
+          $$ext //= ( ? other: int )
+          $$ext //= ( ? on: bool )
+
+          rules = '
+            digits = 1*digit
+            digit = %x30-39
+          '
+        "#
+      ),
+      true,
+    )
+    .unwrap();
+    let compiled = CompiledSchema::new(&cddl);
+
+    assert!(compiled.regex("[A-Z]{3}").is_some());
+    assert!(compiled.regex("^https?://").is_some());
+    assert_eq!(compiled.abnf_grammars.len(), 1);
+
+    // Alternates are merged into a single rule
+    assert_eq!(
+      rule_names(compiled.cddl()),
+      vec!["thing", "link", "$kind", "$$ext", "rules"]
+    );
+    assert!(!compiled
+      .cddl()
+      .rules
+      .iter()
+      .any(|r| r.is_choice_alternate()));
+    assert!(RuleLookup {
+      cddl: compiled.cddl(),
+      compiled: Some(&compiled),
+    }
+    .type_choice_alternates_from_ident(&"$kind".into())
+    .is_empty());
+
+    for (json, is_valid) in [
+      (r#"{ "code": "ABC" }"#, true),
+      (
+        r#"{ "code": "ABC", "digits": "123", "kind": "b", "other": 1 }"#,
+        true,
+      ),
+      (r#"{ "code": "ABC", "kind": "a" }"#, true),
+      (r#"{ "code": "ABC", "link": "https://example.com" }"#, true),
+      (r#"{ "code": "abc" }"#, false),
+      (r#"{ "code": "ABC", "digits": "a12" }"#, false),
+      (r#"{ "code": "ABC", "kind": "c" }"#, false),
+      (r#"{ "code": "ABC", "link": "ftp://example.com" }"#, false),
+    ] {
+      for reasons in [
+        json_reasons(&cddl, None, json),
+        json_reasons(&cddl, Some(&compiled), json),
+      ] {
+        assert_eq!(reasons.is_empty(), is_valid, "{}: {:?}", json, reasons);
+      }
+      assert_eq!(
+        cbor_reasons(&cddl, Some(&compiled), json).is_empty(),
+        cbor_reasons(&cddl, None, json).is_empty(),
+        "{}",
+        json
+      );
+    }
+
+    for json in [
+      r#"{ "code": "abc" }"#,
+      r#"{ "code": "ABC", "digits": "a12" }"#,
+      r#"{ "code": "ABC", "on": true }"#,
+      r#"{ "code": "ABC", "other": "1" }"#,
+    ] {
+      assert_eq!(
+        json_reasons(&cddl, Some(&compiled), json),
+        json_reasons(&cddl, None, json),
+      );
+    }
+
+    // Validators given a compiled schema validate against its resolved AST
+    let other = cddl_from_str("other = int", true).unwrap();
+    assert!(json_reasons(&other, Some(&compiled), r#"{ "code": "ABC" }"#).is_empty());
+  }
+
+  #[test]
+  fn compiled_schema_resolves_generics() {
+    let cddl = cddl_from_str(
+      indoc!(
+        r#"
+          message = {
+            header: header<tstr>,
+            body: pair<code, uint .le 10>,
+            ? nested: pair<pair<int, bool>, tstr>,
+            ? list: [* entries<code>],
+            ? tagged: #6.32(pair<tstr, code>),
+          }
+
+          header<t> = { version: t, fields<t> }
+          fields<f> = ( * tstr => f )
+          pair<k, v> = [k, v]
+          entries<e> = ( name: e )
+          code = tstr .regexp "[a-z]+"
+        "#
+      ),
+      true,
+    )
+    .unwrap();
+    let compiled = CompiledSchema::new(&cddl);
+
+    let names = rule_names(compiled.cddl());
+    for name in [
+      "header<tstr>",
+      "fields<tstr>",
+      "pair<code, uint .le 10>",
+      "pair<int, bool>",
+      "pair<pair<int, bool>, tstr>",
+      "entries<code>",
+      "pair<tstr, code>",
+    ] {
+      assert!(names.iter().any(|n| n == name), "{:?}", names);
+    }
+
+    // Instantiations refer to the instances of the generic rules
+    let message = match &compiled.cddl().rules[0] {
+      Rule::Type { rule, .. } => rule.value.to_string(),
+      _ => unreachable!(),
+    };
+    assert!(message.contains("header: header<tstr>"), "{}", message);
+    assert!(compiled.regex("[a-z]+").is_some());
+
+    for (json, is_valid) in [
+      (
+        r#"{ "header": { "version": "1", "x": "y" }, "body": ["abc", 3] }"#,
+        true,
+      ),
+      (
+        r#"{ "header": { "version": "1" }, "body": ["abc", 3], "nested": [[1, true], "a"], "list": ["a", "b"] }"#,
+        true,
+      ),
+      (
+        r#"{ "header": { "version": 1 }, "body": ["abc", 3] }"#,
+        false,
+      ),
+      (
+        r#"{ "header": { "version": "1", "x": 2 }, "body": ["abc", 3] }"#,
+        false,
+      ),
+      (
+        r#"{ "header": { "version": "1" }, "body": ["ABC", 3] }"#,
+        false,
+      ),
+      (
+        r#"{ "header": { "version": "1" }, "body": ["abc", 11] }"#,
+        false,
+      ),
+      (
+        r#"{ "header": { "version": "1" }, "body": ["abc", 3], "nested": [[1, 2], "a"] }"#,
+        false,
+      ),
+      (
+        r#"{ "header": { "version": "1" }, "body": ["abc", 3], "list": ["a", "B"] }"#,
+        false,
+      ),
+    ] {
+      assert_eq!(
+        json_reasons(&cddl, Some(&compiled), json).is_empty(),
+        is_valid,
+        "{}",
+        json
+      );
+      assert_eq!(
+        cbor_reasons(&cddl, Some(&compiled), json).is_empty(),
+        is_valid,
+        "{}",
+        json
+      );
+    }
+
+    // Generic rules instantiating themselves with ever different arguments are
+    // only resolved up to a limit
+    let cddl = cddl_from_str("nest = deep<int>\ndeep<t> = [t, ? deep<[t]>]", true).unwrap();
+    let compiled = CompiledSchema::new(&cddl);
+    assert_eq!(compiled.cddl().rules.len(), 2 + MAX_GENERIC_DEPTH);
+    assert!(json_reasons(&cddl, Some(&compiled), "[1, [[2]]]").is_empty());
+  }
+
+  #[test]
+  fn rule_lookup_matches_ast_scan() {
+    let cddl = cddl_from_str(
+      indoc!(
+        r#"
+          thing = [id, name, flag, $kind, ? pair]
+          id = my-uint
+          my-uint = uint
+          name = my-text / id
+          my-text = tstr
+          flag = true
+          $kind /= float16
+          $kind /= "a"
+          pair = [count, "b"]
+          count = ( 1, id )
+        "#
+      ),
+      true,
+    )
+    .unwrap();
+    let compiled = CompiledSchema::new(&cddl);
+
+    let scan = &cddl;
+    let lookup = RuleLookup {
+      cddl: compiled.cddl(),
+      compiled: Some(&compiled),
+    };
+    let idents = cddl
+      .rules
+      .iter()
+      .map(|r| match r {
+        Rule::Type { rule, .. } => (rule.name.to_string(), &rule.name),
+        Rule::Group { rule, .. } => (rule.name.to_string(), &rule.name),
+      })
+      .collect::<HashMap<_, _>>();
+    for (name, ident) in idents.iter() {
+      assert_eq!(
+        lookup.is_ident_uint_data_type(ident),
+        scan.is_ident_uint_data_type(ident),
+        "{}",
+        name
+      );
+      assert_eq!(
+        lookup.is_ident_string_data_type(ident),
+        scan.is_ident_string_data_type(ident),
+        "{}",
+        name
+      );
+      assert_eq!(
+        lookup.is_ident_float_data_type(ident),
+        scan.is_ident_float_data_type(ident),
+        "{}",
+        name
+      );
+      assert_eq!(
+        lookup.ident_matches_bool_value(ident, true),
+        scan.ident_matches_bool_value(ident, true),
+        "{}",
+        name
+      );
+    }
+    assert!(lookup.is_ident_uint_data_type(idents["name"]));
+    assert!(lookup.is_ident_float_data_type(idents["$kind"]));
+    assert!(lookup.ident_matches_bool_value(idents["flag"], true));
+    assert!(!lookup.is_ident_string_data_type(idents["thing"]));
+
+    let group = match &compiled.cddl().rules[0] {
+      Rule::Type { rule, .. } => match &rule.value.type_choices[0].type1.type2 {
+        Type2::Array { group, .. } => group,
+        _ => unreachable!(),
+      },
+      _ => unreachable!(),
+    };
+    assert_eq!(
+      format!("{:?}", lookup.entry_counts_from_group(group)),
+      format!("{:?}", scan.entry_counts_from_group(group))
+    );
+  }
+}
@@ -6,12 +6,19 @@ use crate::{
   token::ControlOperator,
 };
 
+#[cfg(all(feature = "additional-controls", feature = "ast-span"))]
+use crate::ast::Span;
 #[cfg(feature = "additional-controls")]
-use crate::{ast::Type, validator::ByteValue};
+use crate::{
+  ast::{Group, GroupEntry, Type},
+  validator::ByteValue,
+};
 #[cfg(feature = "additional-controls")]
 use itertools::Itertools;
 #[cfg(feature = "additional-controls")]
 use pest_meta;
+#[cfg(feature = "additional-controls")]
+use std::convert::TryFrom;
 
 /// Retrieve all text strings and byte string literals from a given rule
 /// identifier. Used for proposed .cat control operator.
@@ -163,13 +170,28 @@ pub fn cat_operation<'a>(
       // "testing" .cat ( "123" / "1234" )
       Type2::ParenthesizedType { pt: controller, .. } => {
         for controller in controller.type_choices.iter() {
-          if controller.type1.operator.is_none() {
-            literals.append(&mut cat_operation(
+          match &controller.type1.operator {
+            None => literals.append(&mut cat_operation(
               cddl,
               target,
               &controller.type1.type2,
               is_dedent,
-            )?);
+            )?),
+            Some(Operator {
+              operator:
+                RangeCtlOp::CtlOp {
+                  ctrl: ControlOperator::PRINTF,
+                  ..
+                },
+              type2: args,
+              ..
+            }) => {
+              for v in printf_operation(cddl, &controller.type1.type2, args)?.iter() {
+                literals.append(&mut cat_operation(cddl, target, v, is_dedent)?);
+              }
+            }
+            // Ignore other nested operators
+            _ => continue,
           }
         }
       }
@@ -190,18 +212,35 @@ pub fn cat_operation<'a>(
       // Only grab the first type choice literal from the target per
       // https://github.com/cbor-wg/cddl-control/issues/2#issuecomment-729253368
       if let Some(tc) = target.type_choices.first() {
-        // Ignore nested operator
-        if tc.type1.operator.is_none() {
-          literals.append(&mut cat_operation(
+        match &tc.type1.operator {
+          None => literals.append(&mut cat_operation(
             cddl,
             &tc.type1.type2,
             controller,
             is_dedent,
-          )?);
+          )?),
+          // ( "%d" .printf [123] ) .cat "456"
+          Some(Operator {
+            operator:
+              RangeCtlOp::CtlOp {
+                ctrl: ControlOperator::PRINTF,
+                ..
+              },
+            type2: args,
+            ..
+          }) => {
+            for v in printf_operation(cddl, &tc.type1.type2, args)?.iter() {
+              literals.append(&mut cat_operation(cddl, v, controller, is_dedent)?);
+            }
+          }
+          // Ignore other nested operators
+          _ => (),
         }
       }
 
-      return Err(format!("invalid target type in {} control operator", ctrl));
+      if literals.is_empty() {
+        return Err(format!("invalid target type in {} control operator", ctrl));
+      }
     }
     Type2::UTF8ByteString { value, .. } => match std::str::from_utf8(value) {
       Ok(value) => match controller {
@@ -289,13 +328,28 @@ pub fn cat_operation<'a>(
         // 'testing' .cat ( "123" / "1234" )
         Type2::ParenthesizedType { pt: controller, .. } => {
           for controller in controller.type_choices.iter() {
-            if controller.type1.operator.is_none() {
-              literals.append(&mut cat_operation(
+            match &controller.type1.operator {
+              None => literals.append(&mut cat_operation(
                 cddl,
                 target,
                 &controller.type1.type2,
                 is_dedent,
-              )?);
+              )?),
+              Some(Operator {
+                operator:
+                  RangeCtlOp::CtlOp {
+                    ctrl: ControlOperator::PRINTF,
+                    ..
+                  },
+                type2: args,
+                ..
+              }) => {
+                for v in printf_operation(cddl, &controller.type1.type2, args)?.iter() {
+                  literals.append(&mut cat_operation(cddl, target, v, is_dedent)?);
+                }
+              }
+              // Ignore other nested operators
+              _ => continue,
             }
           }
         }
@@ -405,13 +459,28 @@ pub fn cat_operation<'a>(
       // h'74657374696E67' .cat ( "123" / "1234" )
       Type2::ParenthesizedType { pt: controller, .. } => {
         for controller in controller.type_choices.iter() {
-          if controller.type1.operator.is_none() {
-            literals.append(&mut cat_operation(
+          match &controller.type1.operator {
+            None => literals.append(&mut cat_operation(
               cddl,
               target,
               &controller.type1.type2,
               is_dedent,
-            )?);
+            )?),
+            Some(Operator {
+              operator:
+                RangeCtlOp::CtlOp {
+                  ctrl: ControlOperator::PRINTF,
+                  ..
+                },
+              type2: args,
+              ..
+            }) => {
+              for v in printf_operation(cddl, &controller.type1.type2, args)?.iter() {
+                literals.append(&mut cat_operation(cddl, target, v, is_dedent)?);
+              }
+            }
+            // Ignore other nested operators
+            _ => continue,
           }
         }
       }
@@ -542,13 +611,28 @@ pub fn cat_operation<'a>(
       // b64'dGVzdGluZw==' .cat ( "123" / "1234" )
       Type2::ParenthesizedType { pt: controller, .. } => {
         for controller in controller.type_choices.iter() {
-          if controller.type1.operator.is_none() {
-            literals.append(&mut cat_operation(
+          match &controller.type1.operator {
+            None => literals.append(&mut cat_operation(
               cddl,
               target,
               &controller.type1.type2,
               is_dedent,
-            )?);
+            )?),
+            Some(Operator {
+              operator:
+                RangeCtlOp::CtlOp {
+                  ctrl: ControlOperator::PRINTF,
+                  ..
+                },
+              type2: args,
+              ..
+            }) => {
+              for v in printf_operation(cddl, &controller.type1.type2, args)?.iter() {
+                literals.append(&mut cat_operation(cddl, target, v, is_dedent)?);
+              }
+            }
+            // Ignore other nested operators
+            _ => continue,
           }
         }
       }
@@ -797,10 +881,331 @@ pub fn plus_operation<'a>(
   Ok(values)
 }
 
+/// Format the arguments in the controller array according to the format string
+/// in the target. The Vec return type is for consistency with the .cat
+/// operation so that the result can be composed with it
 #[cfg(feature = "additional-controls")]
-pub fn validate_abnf(abnf: &str, target: &str) -> Result<(), String> {
-  if let Some(idx) = abnf.find('\n') {
-    let (rule, abnf) = abnf.split_at(idx);
+pub fn printf_operation<'a>(
+  cddl: &'a CDDL<'a>,
+  target: &Type2,
+  controller: &Type2,
+) -> Result<Vec<Type2<'a>>, String> {
+  match target {
+    // "%d" .printf [4711]
+    Type2::TextValue { value, .. } => {
+      let args = printf_arguments(cddl, controller)?;
+
+      Ok(vec![format_printf(value, &args)?.into()])
+    }
+    // a .printf [4711]
+    Type2::Typename { ident, .. } => match string_literals_from_ident(cddl, ident).first() {
+      Some(value) => printf_operation(cddl, value, controller),
+      None => Err(format!(
+        "target of type rule {} is not a text string literal",
+        ident
+      )),
+    },
+    // ( "%d" ) .printf [4711]
+    Type2::ParenthesizedType { pt: target, .. } => match target.type_choices.first() {
+      Some(tc) if tc.type1.operator.is_none() => {
+        printf_operation(cddl, &tc.type1.type2, controller)
+      }
+      _ => Err("invalid target type in .printf control operator".to_string()),
+    },
+    _ => Err(format!(
+      "invalid target used for .printf operation, got {}",
+      target
+    )),
+  }
+}
+
+#[cfg(feature = "additional-controls")]
+enum PrintfArgument {
+  Integer(i128),
+  Float(f64),
+  Text(String),
+}
+
+#[cfg(feature = "additional-controls")]
+impl std::fmt::Display for PrintfArgument {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    match self {
+      PrintfArgument::Integer(i) => write!(f, "{}", i),
+      PrintfArgument::Float(n) => write!(f, "{}", n),
+      PrintfArgument::Text(s) => write!(f, "\"{}\"", s),
+    }
+  }
+}
+
+// Retrieve the literal arguments from the array controller of a .printf
+// operation
+#[cfg(feature = "additional-controls")]
+fn printf_arguments(cddl: &CDDL, controller: &Type2) -> Result<Vec<PrintfArgument>, String> {
+  match controller {
+    Type2::Array { group, .. } => {
+      let mut args = Vec::new();
+      for gc in group.group_choices.iter().take(1) {
+        for (ge, _) in gc.group_entries.iter() {
+          match ge {
+            GroupEntry::ValueMemberKey { ge, .. }
+              if ge.member_key.is_none() && ge.entry_type.type_choices.len() == 1 =>
+            {
+              let type1 = &ge.entry_type.type_choices[0].type1;
+              if type1.operator.is_some() {
+                return Err(format!("invalid .printf argument {}", type1));
+              }
+              args.push(printf_argument(cddl, &type1.type2)?);
+            }
+            GroupEntry::TypeGroupname { ge, .. } => args.push(printf_argument(
+              cddl,
+              &Type2::Typename {
+                ident: ge.name.clone(),
+                generic_args: None,
+                #[cfg(feature = "ast-span")]
+                span: Span::default(),
+              },
+            )?),
+            _ => return Err(format!("invalid .printf argument {}", ge)),
+          }
+        }
+      }
+
+      Ok(args)
+    }
+    Type2::Typename { ident, .. } => {
+      for r in cddl.rules.iter() {
+        if let Rule::Type { rule, .. } = r {
+          if rule.name == *ident {
+            if let Some(tc) = rule.value.type_choices.first() {
+              return printf_arguments(cddl, &tc.type1.type2);
+            }
+          }
+        }
+      }
+
+      Err(format!(
+        "controller of type rule {} is not an array of arguments",
+        ident
+      ))
+    }
+    _ => Err(format!(
+      "controller of .printf operation must be an array of arguments, got {}",
+      controller
+    )),
+  }
+}
+
+#[cfg(feature = "additional-controls")]
+fn printf_argument(cddl: &CDDL, arg: &Type2) -> Result<PrintfArgument, String> {
+  match arg {
+    Type2::UintValue { value, .. } => Ok(PrintfArgument::Integer(*value as i128)),
+    Type2::IntValue { value, .. } => Ok(PrintfArgument::Integer(*value as i128)),
+    Type2::FloatValue { value, .. } => Ok(PrintfArgument::Float(*value)),
+    Type2::TextValue { value, .. } => Ok(PrintfArgument::Text(value.to_string())),
+    Type2::Typename { ident, .. } => {
+      if let Some(value) = numeric_values_from_ident(cddl, ident).first() {
+        printf_argument(cddl, value)
+      } else if let Some(value) = string_literals_from_ident(cddl, ident).first() {
+        printf_argument(cddl, value)
+      } else {
+        Err(format!(
+          "argument of type rule {} is not a numeric or text string literal",
+          ident
+        ))
+      }
+    }
+    _ => Err(format!("invalid .printf argument {}", arg)),
+  }
+}
+
+// Format the arguments using the subset of the C printf conversions
+// (%d, %i, %x, %X, %o, %b, %c, %s, %f and %%) and flags (-, 0, + and #), with
+// optional field width and precision
+#[cfg(feature = "additional-controls")]
+fn format_printf(format: &str, args: &[PrintfArgument]) -> Result<String, String> {
+  let mut output = String::new();
+  let mut args = args.iter();
+  let mut chars = format.chars().peekable();
+
+  while let Some(c) = chars.next() {
+    if c != '%' {
+      output.push(c);
+      continue;
+    }
+
+    if chars.peek() == Some(&'%') {
+      chars.next();
+      output.push('%');
+      continue;
+    }
+
+    let (mut is_left, mut is_zero, mut is_plus, mut is_alternate) = (false, false, false, false);
+    while let Some(flag) = chars.peek() {
+      match flag {
+        '-' => is_left = true,
+        '0' => is_zero = true,
+        '+' => is_plus = true,
+        '#' => is_alternate = true,
+        _ => break,
+      }
+      chars.next();
+    }
+
+    let mut width = 0;
+    while let Some(d) = chars.peek().and_then(|c| c.to_digit(10)) {
+      width = width * 10 + d as usize;
+      chars.next();
+    }
+
+    let mut precision = None;
+    if chars.peek() == Some(&'.') {
+      chars.next();
+      let mut p = 0;
+      while let Some(d) = chars.peek().and_then(|c| c.to_digit(10)) {
+        p = p * 10 + d as usize;
+        chars.next();
+      }
+      precision = Some(p);
+    }
+
+    let conversion = chars
+      .next()
+      .ok_or_else(|| format!("incomplete conversion specification in {:?}", format))?;
+    let arg = args
+      .next()
+      .ok_or_else(|| format!("missing argument for conversion %{}", conversion))?;
+
+    let sign = |is_negative: bool| match (is_negative, is_plus) {
+      (true, _) => "-",
+      (false, true) => "+",
+      (false, false) => "",
+    };
+    let alternate = |prefix: &'static str| if is_alternate { prefix } else { "" };
+
+    let (prefix, mut body, is_numeric) = match (conversion, arg) {
+      ('d', PrintfArgument::Integer(i)) | ('i', PrintfArgument::Integer(i)) => {
+        (sign(*i < 0), i.unsigned_abs().to_string(), true)
+      }
+      ('x', PrintfArgument::Integer(i)) if *i >= 0 => (alternate("0x"), format!("{:x}", i), true),
+      ('X', PrintfArgument::Integer(i)) if *i >= 0 => (alternate("0X"), format!("{:X}", i), true),
+      ('o', PrintfArgument::Integer(i)) if *i >= 0 => (alternate("0"), format!("{:o}", i), true),
+      ('b', PrintfArgument::Integer(i)) if *i >= 0 => (alternate("0b"), format!("{:b}", i), true),
+      ('c', PrintfArgument::Integer(i)) => match u32::try_from(*i).ok().and_then(char::from_u32) {
+        Some(c) => ("", c.to_string(), false),
+        None => return Err(format!("argument {} is not a valid character", i)),
+      },
+      ('f', PrintfArgument::Float(n)) => (
+        sign(n.is_sign_negative()),
+        format!("{:.*}", precision.unwrap_or(6), n.abs()),
+        true,
+      ),
+      ('f', PrintfArgument::Integer(i)) => (
+        sign(*i < 0),
+        format!("{:.*}", precision.unwrap_or(6), i.unsigned_abs() as f64),
+        true,
+      ),
+      ('s', PrintfArgument::Text(s)) => match precision {
+        Some(p) => ("", s.chars().take(p).collect(), false),
+        None => ("", s.clone(), false),
+      },
+      _ => {
+        return Err(format!(
+          "argument {} cannot be formatted with conversion %{}",
+          arg, conversion
+        ))
+      }
+    };
+
+    // The precision of integer conversions is the minimum number of digits
+    if let (Some(p), PrintfArgument::Integer(_)) = (precision, arg) {
+      if is_numeric && body.len() < p {
+        body = format!("{}{}", "0".repeat(p - body.len()), body);
+      }
+    }
+
+    let padding = width.saturating_sub(prefix.chars().count() + body.chars().count());
+    if is_left {
+      output.push_str(&format!("{}{}{}", prefix, body, " ".repeat(padding)));
+    } else if is_zero && is_numeric {
+      output.push_str(&format!("{}{}{}", prefix, "0".repeat(padding), body));
+    } else {
+      output.push_str(&format!("{}{}{}", " ".repeat(padding), prefix, body));
+    }
+  }
+
+  if args.next().is_some() {
+    return Err(format!("too many arguments for format string {:?}", format));
+  }
+
+  Ok(output)
+}
+
+/// Retrieve the array group of the controller of a .join control operator,
+/// which is either an array or the name of a type rule defining an array
+#[cfg(feature = "additional-controls")]
+pub fn join_controller_group<'a, 'b>(
+  cddl: &'b CDDL<'a>,
+  controller: &'b Type2<'a>,
+) -> Option<&'b Group<'a>> {
+  match controller {
+    Type2::Array { group, .. } => Some(group),
+    Type2::Typename { ident, .. } => cddl.rules.iter().find_map(|r| match r {
+      Rule::Type { rule, .. } if rule.name == *ident => match rule.value.type_choices.first() {
+        Some(tc) if rule.value.type_choices.len() == 1 && tc.type1.operator.is_none() => {
+          join_controller_group(cddl, &tc.type1.type2)
+        }
+        _ => None,
+      },
+      _ => None,
+    }),
+    Type2::ParenthesizedType { pt, .. } => match pt.type_choices.first() {
+      Some(tc) if pt.type_choices.len() == 1 && tc.type1.operator.is_none() => {
+        join_controller_group(cddl, &tc.type1.type2)
+      }
+      _ => None,
+    },
+    _ => None,
+  }
+}
+
+/// Decode the text string targeted by a .decimal control operator into the
+/// integer it represents. Leading zeros, a leading plus sign and negative zero
+/// are not permitted
+#[cfg(feature = "additional-controls")]
+pub fn decode_decimal(text: &str) -> Result<i128, String> {
+  let digits = text.strip_prefix('-').unwrap_or(text);
+  if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+    return Err(format!("{:?} is not a decimal integer", text));
+  }
+
+  if digits.len() > 1 && digits.starts_with('0') {
+    return Err(format!("unexpected leading zero in {:?}", text));
+  }
+
+  if text == "-0" {
+    return Err("unexpected negative zero".to_string());
+  }
+
+  text.parse::<i128>().map_err(|e| e.to_string())
+}
+
+/// ABNF grammar compiled from the controller of an .abnf or .abnfb control
+/// operator, whose first line names the rule to match
+#[cfg(feature = "additional-controls")]
+pub struct AbnfGrammar {
+  rule: String,
+  vm: pest_vm::Vm,
+}
+
+#[cfg(feature = "additional-controls")]
+impl AbnfGrammar {
+  /// Compile the given ABNF controller. Controllers without a rule name
+  /// followed by a grammar yield no grammar, since any target matches them
+  pub fn compile(abnf: &str) -> Result<Option<Self>, String> {
+    let (rule, abnf) = match abnf.find('\n') {
+      Some(idx) => abnf.split_at(idx),
+      None => return Ok(None),
+    };
 
     let rules = abnf_to_pest::parse_abnf(abnf).map_err(|e| e.to_string())?;
     let mut w = Vec::new();
@@ -814,13 +1219,37 @@ pub fn validate_abnf(abnf: &str, target: &str) -> Result<(), String> {
 
     let ast = pest_meta::parser::consume_rules(pairs).unwrap();
 
-    let vm = pest_vm::Vm::new(pest_meta::optimizer::optimize(ast));
+    Ok(Some(AbnfGrammar {
+      rule: rule.replace('-', "_"),
+      vm: pest_vm::Vm::new(pest_meta::optimizer::optimize(ast)),
+    }))
+  }
+
+  /// Match the target against the rule of the grammar
+  pub fn validate(&self, target: &str) -> Result<(), String> {
+    self
+      .vm
+      .parse(&self.rule, target)
+      .map(|_| ())
+      .map_err(|e| e.to_string())
+  }
+}
 
-    let rule = rule.replace('-', "_");
-    let _ = vm.parse(&rule, target).map_err(|e| e.to_string())?;
+#[cfg(feature = "additional-controls")]
+impl std::fmt::Debug for AbnfGrammar {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    f.debug_struct("AbnfGrammar")
+      .field("rule", &self.rule)
+      .finish()
   }
+}
 
-  Ok(())
+#[cfg(feature = "additional-controls")]
+pub fn validate_abnf(abnf: &str, target: &str) -> Result<(), String> {
+  match AbnfGrammar::compile(abnf)? {
+    Some(grammar) => grammar.validate(target),
+    None => Ok(()),
+  }
 }
 
 /// If the controller for an .abnf/.abnfb control operator is a parenthesized
@@ -850,6 +1279,143 @@ pub fn abnf_from_complex_controller<'a>(
   Err("invalid controller".to_string())
 }
 
+pub(crate) const BASE64URL_ALPHABET: &[u8] =
+  b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+pub(crate) const BASE64_ALPHABET: &[u8] =
+  b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+pub(crate) const BASE32_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+pub(crate) const BASE32HEX_ALPHABET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUV";
+pub(crate) const BASE16_ALPHABET: &[u8] = b"0123456789ABCDEF";
+#[cfg(feature = "additional-controls")]
+const BASE45_ALPHABET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+/// Decode the text string targeted by one of the text conversion control
+/// operators of RFC 9741 into the bytes that are matched against the
+/// controller. Only the canonical encoding of the bytes is accepted, except
+/// that the `-sloppy` variants of `.b64u` and `.b64c` allow the unused bits of
+/// the last character to be non-zero
+#[cfg(feature = "additional-controls")]
+pub fn decode_text_conversion(ctrl: ControlOperator, text: &str) -> Result<Vec<u8>, String> {
+  match ctrl {
+    ControlOperator::B64U => decode_base2n(text, BASE64URL_ALPHABET, 6, false, false),
+    ControlOperator::B64USLOPPY => decode_base2n(text, BASE64URL_ALPHABET, 6, false, true),
+    ControlOperator::B64C => decode_base2n(text, BASE64_ALPHABET, 6, true, false),
+    ControlOperator::B64CSLOPPY => decode_base2n(text, BASE64_ALPHABET, 6, true, true),
+    ControlOperator::B32 => decode_base2n(text, BASE32_ALPHABET, 5, false, false),
+    ControlOperator::H32 => decode_base2n(text, BASE32HEX_ALPHABET, 5, false, false),
+    ControlOperator::B45 => decode_base45(text),
+    ControlOperator::HEX | ControlOperator::HEXLC | ControlOperator::HEXUC => {
+      if let Some(c) = text.chars().find(|c| {
+        (matches!(ctrl, ControlOperator::HEXLC) && c.is_ascii_uppercase())
+          || (matches!(ctrl, ControlOperator::HEXUC) && c.is_ascii_lowercase())
+      }) {
+        return Err(format!("unexpected character {:?} for {}", c, ctrl));
+      }
+
+      base16::decode(text).map_err(|e| e.to_string())
+    }
+    _ => Err(format!(
+      "{} is not a text conversion control operator",
+      ctrl
+    )),
+  }
+}
+
+// Decode text encoded with an alphabet of 2^bits characters, as per RFC 4648.
+// The text must have the minimal number of characters for the bytes it
+// encodes, followed by the padding required for a whole number of blocks if
+// padded. Shared with the EDN parser for its prefixed byte string literals
+pub(crate) fn decode_base2n(
+  text: &str,
+  alphabet: &[u8],
+  bits: usize,
+  is_padded: bool,
+  is_sloppy: bool,
+) -> Result<Vec<u8>, String> {
+  let mut data = text.as_bytes();
+  if is_padded {
+    // Number of characters in a block that encodes a whole number of bytes
+    let block = if bits == 6 { 4 } else { 8 };
+    if data.len() % block != 0 {
+      return Err(format!(
+        "expected padding to a multiple of {} characters, got {} characters",
+        block,
+        data.len()
+      ));
+    }
+
+    let padding = data.iter().rev().take_while(|&&c| c == b'=').count();
+    data = &data[..data.len() - padding];
+    if padding >= block || (padding > 0 && data.len() % block + padding != block) {
+      return Err(format!("unexpected padding length {}", padding));
+    }
+  }
+
+  let mut bytes = Vec::with_capacity(data.len() * bits / 8);
+  let (mut acc, mut acc_bits) = (0u32, 0);
+  for (idx, c) in data.iter().enumerate() {
+    let value = alphabet
+      .iter()
+      .position(|a| a == c)
+      .ok_or_else(|| format!("invalid character {:?} at offset {}", *c as char, idx))?;
+
+    acc = (acc << bits) | value as u32;
+    acc_bits += bits;
+    if acc_bits >= 8 {
+      acc_bits -= 8;
+      bytes.push((acc >> acc_bits) as u8);
+      acc &= (1 << acc_bits) - 1;
+    }
+  }
+
+  if acc_bits >= bits || (bytes.len() * 8 + bits - 1) / bits != data.len() {
+    return Err(format!("invalid length of {} characters", data.len()));
+  }
+
+  if acc != 0 && !is_sloppy {
+    return Err("unexpected non-zero trailing bits".to_string());
+  }
+
+  Ok(bytes)
+}
+
+// Decode base45 encoded text, as per RFC 9285
+#[cfg(feature = "additional-controls")]
+fn decode_base45(text: &str) -> Result<Vec<u8>, String> {
+  let data = text.as_bytes();
+  if data.len() % 3 == 1 {
+    return Err(format!("invalid length of {} characters", data.len()));
+  }
+
+  let mut bytes = Vec::with_capacity(data.len() * 2 / 3);
+  for (chunk_idx, chunk) in data.chunks(3).enumerate() {
+    let mut n = 0u32;
+    for (idx, c) in chunk.iter().enumerate().rev() {
+      let value = BASE45_ALPHABET.iter().position(|a| a == c).ok_or_else(|| {
+        format!(
+          "invalid character {:?} at offset {}",
+          *c as char,
+          chunk_idx * 3 + idx
+        )
+      })?;
+      n = n * 45 + value as u32;
+    }
+
+    match chunk.len() {
+      3 if n <= 0xffff => bytes.extend_from_slice(&(n as u16).to_be_bytes()),
+      2 if n <= 0xff => bytes.push(n as u8),
+      _ => {
+        return Err(format!(
+          "invalid character group at offset {}",
+          chunk_idx * 3
+        ))
+      }
+    }
+  }
+
+  Ok(bytes)
+}
+
 #[cfg(test)]
 #[cfg(not(target_arch = "wasm32"))]
 mod tests {
@@ -969,4 +1535,133 @@ mod tests {
 
     Ok(())
   }
+
+  #[cfg(feature = "additional-controls")]
+  #[test]
+  fn test_decode_text_conversion() {
+    let decode = decode_text_conversion;
+
+    assert_eq!(decode(ControlOperator::B64U, "-_8"), Ok(vec![0xfb, 0xff]));
+    assert!(decode(ControlOperator::B64U, "-_8=").is_err());
+    assert!(decode(ControlOperator::B64U, "-_9").is_err());
+    assert_eq!(
+      decode(ControlOperator::B64USLOPPY, "-_9"),
+      Ok(vec![0xfb, 0xff])
+    );
+    assert!(decode(ControlOperator::B64U, "AAAAA").is_err());
+
+    assert_eq!(decode(ControlOperator::B64C, "+/8="), Ok(vec![0xfb, 0xff]));
+    assert_eq!(
+      decode(ControlOperator::B64C, "Zm9vYg=="),
+      Ok(b"foob".to_vec())
+    );
+    assert!(decode(ControlOperator::B64C, "+/8").is_err());
+    assert!(decode(ControlOperator::B64C, "Zm9vYg=").is_err());
+    assert!(decode(ControlOperator::B64C, "Zm9vYh==").is_err());
+    assert_eq!(
+      decode(ControlOperator::B64CSLOPPY, "Zm9vYh=="),
+      Ok(b"foob".to_vec())
+    );
+    assert!(decode(ControlOperator::B64C, "Zm9v====").is_err());
+
+    // Test vectors of RFC 4648 section 10 without padding
+    assert_eq!(
+      decode(ControlOperator::B32, "MZXW6YTBOI"),
+      Ok(b"foobar".to_vec())
+    );
+    assert_eq!(
+      decode(ControlOperator::H32, "CPNMUOJ1E8"),
+      Ok(b"foobar".to_vec())
+    );
+    assert_eq!(
+      decode(ControlOperator::B32, "MZXW6YQ"),
+      Ok(b"foob".to_vec())
+    );
+    assert!(decode(ControlOperator::B32, "MZXW6YQ=").is_err());
+    assert!(decode(ControlOperator::B32, "mzxw6yq").is_err());
+    assert!(decode(ControlOperator::B32, "MZXW6YR").is_err());
+    assert!(decode(ControlOperator::B32, "MZX").is_err());
+
+    // Test vectors of RFC 9285 section 4.3
+    assert_eq!(decode(ControlOperator::B45, "BB8"), Ok(b"AB".to_vec()));
+    assert_eq!(
+      decode(ControlOperator::B45, "%69 VD92EX0"),
+      Ok(b"Hello!!".to_vec())
+    );
+    assert_eq!(
+      decode(ControlOperator::B45, "UJCLQE7W581"),
+      Ok(b"base-45".to_vec())
+    );
+    assert!(decode(ControlOperator::B45, "GGW").is_err());
+    assert!(decode(ControlOperator::B45, "BB8A").is_err());
+
+    assert_eq!(decode(ControlOperator::HEX, "aBcD"), Ok(vec![0xab, 0xcd]));
+    assert_eq!(decode(ControlOperator::HEXLC, "abcd"), Ok(vec![0xab, 0xcd]));
+    assert!(decode(ControlOperator::HEXLC, "ABCD").is_err());
+    assert_eq!(decode(ControlOperator::HEXUC, "ABCD"), Ok(vec![0xab, 0xcd]));
+    assert!(decode(ControlOperator::HEXUC, "abcd").is_err());
+    assert!(decode(ControlOperator::HEX, "abc").is_err());
+  }
+
+  #[cfg(feature = "additional-controls")]
+  #[test]
+  fn test_printf() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl_str = indoc!(
+      r#"
+        a = "0x%04x-%s" .printf [4711, "foo"]
+        b = "%+d|%-4d|%5.2f|%%|%c" .printf [7, -3, 2.5, 65]
+        c = "id-" .cat ("%03d" .printf [5])
+        d = "%d" .printf ["foo"]
+      "#
+    );
+
+    let cddl = cddl_from_str(cddl_str, true)?;
+
+    let printf = |idx: usize| match &cddl.rules[idx] {
+      Rule::Type { rule, .. } => {
+        let operator = rule.value.type_choices[0].type1.operator.as_ref().unwrap();
+        match operator.operator {
+          RangeCtlOp::CtlOp {
+            ctrl: ControlOperator::CAT,
+            ..
+          } => cat_operation(
+            &cddl,
+            &rule.value.type_choices[0].type1.type2,
+            &operator.type2,
+            false,
+          ),
+          _ => printf_operation(
+            &cddl,
+            &rule.value.type_choices[0].type1.type2,
+            &operator.type2,
+          ),
+        }
+      }
+      _ => unreachable!(),
+    };
+
+    assert_eq!(printf(0)?, vec![Type2::from("0x1267-foo".to_string())]);
+    assert_eq!(
+      printf(1)?,
+      vec![Type2::from("+7|-3  | 2.50|%|A".to_string())]
+    );
+    assert_eq!(printf(2)?, vec![Type2::from("id-005".to_string())]);
+    assert!(printf(3).is_err());
+
+    Ok(())
+  }
+
+  #[cfg(feature = "additional-controls")]
+  #[test]
+  fn test_decode_decimal() {
+    assert_eq!(decode_decimal("0"), Ok(0));
+    assert_eq!(decode_decimal("4711"), Ok(4711));
+    assert_eq!(decode_decimal("-42"), Ok(-42));
+    assert!(decode_decimal("").is_err());
+    assert!(decode_decimal("-").is_err());
+    assert!(decode_decimal("-0").is_err());
+    assert!(decode_decimal("007").is_err());
+    assert!(decode_decimal("+7").is_err());
+    assert!(decode_decimal("1e3").is_err());
+  }
 }
@@ -0,0 +1,171 @@
+#![cfg(any(feature = "json", feature = "cbor"))]
+
+use crate::{
+  ast::{Type2, CDDL},
+  lexer::Lexer,
+  parser, token,
+};
+
+use std::{fmt, sync::Arc};
+
+/// Data item targeted by a custom control operator
+#[derive(Debug, Clone, Copy)]
+pub enum ControlValue<'v> {
+  /// CBOR data item
+  #[cfg(feature = "cbor")]
+  CBOR(&'v ciborium::value::Value),
+  /// JSON value
+  #[cfg(feature = "json")]
+  JSON(&'v serde_json::Value),
+}
+
+/// Callback of a custom control operator. It receives the data item being
+/// validated, the target type and the controller type of the control
+/// operator, and returns the reason the data item does not match
+pub type ControlCallback = dyn Fn(ControlValue, &Type2, &Type2) -> Result<(), String> + Send + Sync;
+
+#[derive(Clone)]
+struct CustomControl {
+  name: &'static str,
+  callback: Arc<ControlCallback>,
+}
+
+/// Set of custom control operators, each with a name and a callback. CDDL is
+/// parsed with [`CustomControls::cddl_from_str`] for the control operators of
+/// the set to be recognized, and validators invoke their callbacks once given
+/// the set with `with_custom_controls`. Control operators of one set have no
+/// effect on the parsing and validation of CDDL with another set or without
+/// one.
+///
+/// # Example
+///
+/// ```
+/// use cddl::validator::{
+///   custom::{ControlValue, CustomControls},
+///   json::JSONValidator,
+///   Validator,
+/// };
+///
+/// let mut controls = CustomControls::new();
+/// controls
+///   .register(".semver", |value, _, _| match value {
+///     ControlValue::JSON(serde_json::Value::String(s))
+///       if s.split('.').count() == 3 && s.split('.').all(|n| n.parse::<u64>().is_ok()) =>
+///     {
+///       Ok(())
+///     }
+///     _ => Err(format!("expected semantic version, got {:?}", value)),
+///   })
+///   .unwrap();
+///
+/// let cddl = controls
+///   .cddl_from_str(r#"version = tstr .semver "2.0""#)
+///   .unwrap();
+///
+/// let validate = |json: serde_json::Value| {
+///   let jv = JSONValidator::new(&cddl, json, None);
+///   jv.with_custom_controls(&controls).validate()
+/// };
+/// assert!(validate(serde_json::json!("1.2.3")).is_ok());
+/// assert!(validate(serde_json::json!("1.2")).is_err());
+/// ```
+#[derive(Clone, Default)]
+pub struct CustomControls {
+  controls: Vec<CustomControl>,
+}
+
+impl fmt::Debug for CustomControls {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_set().entries(self.names()).finish()
+  }
+}
+
+impl CustomControls {
+  /// Empty set of custom control operators
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Add a custom control operator with the given name, including the leading
+  /// `.`. Errors returned by the callback are reported as validation errors of
+  /// the data items that match the target type of the control operator.
+  /// Adding a control operator with the name of one already in the set
+  /// replaces its callback. Predefined control operators cannot be replaced
+  pub fn register<F>(&mut self, name: &'static str, callback: F) -> Result<(), String>
+  where
+    F: Fn(ControlValue, &Type2, &Type2) -> Result<(), String> + Send + Sync + 'static,
+  {
+    let is_valid_name = match name.strip_prefix('.') {
+      Some(ident) => {
+        ident.starts_with(|c: char| c.is_ascii_alphabetic() || c == '@' || c == '_' || c == '$')
+          && ident
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '@' | '_' | '$' | '-' | '.'))
+      }
+      None => false,
+    };
+    if !is_valid_name {
+      return Err(format!("invalid control operator name {:?}", name));
+    }
+
+    if token::lookup_control_from_str(name).is_some() {
+      return Err(format!(
+        "{} is a predefined control operator and cannot be registered",
+        name
+      ));
+    }
+
+    let callback: Arc<ControlCallback> = Arc::new(callback);
+    match self.controls.iter_mut().find(|c| c.name == name) {
+      Some(control) => control.callback = callback,
+      None => self.controls.push(CustomControl { name, callback }),
+    }
+
+    Ok(())
+  }
+
+  /// Remove the custom control operator with the given name. Returns whether
+  /// it was in the set
+  pub fn remove(&mut self, name: &str) -> bool {
+    let len = self.controls.len();
+    self.controls.retain(|c| c.name != name);
+
+    self.controls.len() != len
+  }
+
+  /// Names of the custom control operators of the set
+  pub fn names(&self) -> Vec<&'static str> {
+    self.controls.iter().map(|c| c.name).collect()
+  }
+
+  /// Parse CDDL from the given input, recognizing the custom control operators
+  /// of the set in addition to the predefined ones
+  pub fn cddl_from_str<'a>(&self, input: &'a str) -> Result<CDDL<'a>, String> {
+    let lexer = Lexer::new(input).with_custom_controls(&self.names());
+    let mut p = parser::Parser::new(input, Box::new(lexer.iter())).map_err(|e| e.to_string())?;
+
+    match p.parse_cddl() {
+      Ok(c) => Ok(c),
+      Err(parser::Error::INCREMENTAL) => match p.report_errors(false) {
+        Ok(Some(e)) => Err(e),
+        _ => Err(parser::Error::INCREMENTAL.to_string()),
+      },
+      Err(e) => Err(e.to_string()),
+    }
+  }
+}
+
+/// Invoke the callback of the custom control operator with the given name from
+/// the given set, if any
+pub(crate) fn validate_control(
+  controls: Option<&CustomControls>,
+  name: &str,
+  value: ControlValue,
+  target: &Type2,
+  controller: &Type2,
+) -> Result<(), String> {
+  match controls.and_then(|controls| controls.controls.iter().find(|c| c.name == name)) {
+    Some(control) => (control.callback)(value, target, controller),
+    None => Err(format!("control operator {} is not registered", name)),
+  }
+}
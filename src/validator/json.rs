@@ -11,16 +11,29 @@ use crate::{
 
 use std::{
   borrow::Cow,
-  collections::HashMap,
+  cell::RefCell,
+  collections::{HashMap, HashSet},
   convert::TryFrom,
   fmt::{self, Write},
 };
 
 use chrono::{TimeZone, Utc};
+use serde::{
+  de::{DeserializeSeed, MapAccess, SeqAccess, Visitor as DeVisitor},
+  Deserializer, Serialize,
+};
 use serde_json::Value;
 
 #[cfg(feature = "additional-controls")]
-use control::{abnf_from_complex_controller, cat_operation, plus_operation, validate_abnf};
+use array::match_joined_text;
+use array::{match_array_items, ArrayItem, ArrayMismatch, ArrayPattern};
+use compiled::{CompiledSchema, RuleLookup};
+#[cfg(feature = "additional-controls")]
+use control::{
+  abnf_from_complex_controller, cat_operation, decode_decimal, decode_text_conversion,
+  join_controller_group, plus_operation, printf_operation, validate_abnf,
+};
+use custom::{ControlValue, CustomControls};
 
 /// JSON validation Result
 pub type Result = std::result::Result<(), Error>;
@@ -38,6 +51,9 @@ pub enum Error {
   UTF8Parsing(std::str::Utf8Error),
   /// Disabled feature
   DisabledFeature(String),
+  /// Rule with the given name does not exist or cannot be used as the root
+  /// rule
+  MissingRule(String),
 }
 
 impl fmt::Display for Error {
@@ -54,6 +70,7 @@ impl fmt::Display for Error {
       Error::CDDLParsing(error) => write!(f, "error parsing CDDL: {}", error),
       Error::UTF8Parsing(error) => write!(f, "error pasing utf8: {}", error),
       Error::DisabledFeature(feature) => write!(f, "feature {} is not enabled", feature),
+      Error::MissingRule(error) => write!(f, "error finding rule: {}", error),
     }
   }
 }
@@ -75,14 +92,14 @@ impl Error {
       reason,
       is_multi_type_choice: jv.is_multi_type_choice,
       is_group_to_choice_enum: jv.is_group_to_choice_enum,
-      type_group_name_entry: jv.type_group_name_entry.map(|e| e.to_string()),
+      type_group_name_entry: jv.type_group_name_entry.as_ref().map(|e| e.to_string()),
       is_multi_group_choice: jv.is_multi_group_choice,
     }])
   }
 }
 
 /// JSON validation error
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct ValidationError {
   /// Error message
   pub reason: String,
@@ -146,17 +163,192 @@ impl ValidationError {
       reason,
       is_multi_type_choice: jv.is_multi_type_choice,
       is_group_to_choice_enum: jv.is_group_to_choice_enum,
-      type_group_name_entry: jv.type_group_name_entry.map(|e| e.to_string()),
+      type_group_name_entry: jv.type_group_name_entry.as_ref().map(|e| e.to_string()),
       is_multi_group_choice: jv.is_multi_group_choice,
     }
   }
 }
 
+/// Object member whose name duplicates the name of a preceding member of the
+/// same object
+#[derive(Clone, Debug, PartialEq)]
+pub struct DuplicateKey {
+  /// Location of the object in the JSON document
+  pub location: String,
+  /// Name of the duplicate member
+  pub key: String,
+}
+
+/// Parse a JSON document, additionally collecting duplicate object member
+/// names. `serde_json` silently keeps only the last of the duplicate members,
+/// so they cannot be detected once the document has been parsed
+pub fn from_str_with_duplicate_keys(
+  json: &str,
+) -> std::result::Result<(Value, Vec<DuplicateKey>), serde_json::Error> {
+  let duplicates = RefCell::new(Vec::new());
+
+  let mut deserializer = serde_json::Deserializer::from_str(json);
+  let value = DuplicateKeySeed {
+    location: String::new(),
+    duplicates: &duplicates,
+  }
+  .deserialize(&mut deserializer)?;
+  deserializer.end()?;
+
+  Ok((value, duplicates.into_inner()))
+}
+
+// Deserializes a JSON value at the given location, collecting duplicate object
+// member names of the value and its nested values
+struct DuplicateKeySeed<'d> {
+  location: String,
+  duplicates: &'d RefCell<Vec<DuplicateKey>>,
+}
+
+impl<'de, 'd> DeserializeSeed<'de> for DuplicateKeySeed<'d> {
+  type Value = Value;
+
+  fn deserialize<D: Deserializer<'de>>(
+    self,
+    deserializer: D,
+  ) -> std::result::Result<Value, D::Error> {
+    deserializer.deserialize_any(self)
+  }
+}
+
+impl<'de, 'd> DeVisitor<'de> for DuplicateKeySeed<'d> {
+  type Value = Value;
+
+  fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    write!(formatter, "a JSON value")
+  }
+
+  fn visit_bool<E>(self, v: bool) -> std::result::Result<Value, E> {
+    Ok(Value::Bool(v))
+  }
+
+  fn visit_i64<E>(self, v: i64) -> std::result::Result<Value, E> {
+    Ok(Value::Number(v.into()))
+  }
+
+  fn visit_u64<E>(self, v: u64) -> std::result::Result<Value, E> {
+    Ok(Value::Number(v.into()))
+  }
+
+  fn visit_f64<E>(self, v: f64) -> std::result::Result<Value, E> {
+    Ok(serde_json::Number::from_f64(v).map_or(Value::Null, Value::Number))
+  }
+
+  fn visit_str<E>(self, v: &str) -> std::result::Result<Value, E> {
+    Ok(Value::String(v.to_string()))
+  }
+
+  fn visit_string<E>(self, v: String) -> std::result::Result<Value, E> {
+    Ok(Value::String(v))
+  }
+
+  fn visit_unit<E>(self) -> std::result::Result<Value, E> {
+    Ok(Value::Null)
+  }
+
+  fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Value, A::Error> {
+    let mut values = Vec::new();
+    while let Some(value) = seq.next_element_seed(DuplicateKeySeed {
+      location: format!("{}/{}", self.location, values.len()),
+      duplicates: self.duplicates,
+    })? {
+      values.push(value);
+    }
+
+    Ok(Value::Array(values))
+  }
+
+  fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<Value, A::Error> {
+    let mut object = serde_json::Map::new();
+    let mut keys = HashSet::new();
+    while let Some(key) = map.next_key::<String>()? {
+      if !keys.insert(key.clone()) {
+        self.duplicates.borrow_mut().push(DuplicateKey {
+          location: self.location.clone(),
+          key: key.clone(),
+        });
+      }
+
+      let value = map.next_value_seed(DuplicateKeySeed {
+        location: format!("{}/{}", self.location, key),
+        duplicates: self.duplicates,
+      })?;
+      object.insert(key, value);
+    }
+
+    Ok(Value::Object(object))
+  }
+}
+
+/// Encoding of byte strings represented as JSON strings
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteStringEncoding {
+  /// base64url encoding without padding, as per the convention of RFC 8610
+  /// Appendix E
+  Base64Url,
+  /// base16 (hex) encoding
+  Base16,
+}
+
+impl Default for ByteStringEncoding {
+  fn default() -> Self {
+    ByteStringEncoding::Base64Url
+  }
+}
+
+impl fmt::Display for ByteStringEncoding {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ByteStringEncoding::Base64Url => write!(f, "base64url"),
+      ByteStringEncoding::Base16 => write!(f, "base16"),
+    }
+  }
+}
+
+impl ByteStringEncoding {
+  /// Decode the bytes of a byte string represented as a JSON string
+  pub fn decode(&self, s: &str) -> std::result::Result<Vec<u8>, String> {
+    match self {
+      ByteStringEncoding::Base64Url => {
+        base64::decode_config(s.trim_end_matches('='), base64::URL_SAFE_NO_PAD)
+          .map_err(|e| e.to_string())
+      }
+      ByteStringEncoding::Base16 => base16::decode(s).map_err(|e| e.to_string()),
+    }
+  }
+
+  /// Encode bytes as a JSON string
+  pub fn encode(&self, b: &[u8]) -> String {
+    match self {
+      ByteStringEncoding::Base64Url => base64::encode_config(b, base64::URL_SAFE_NO_PAD),
+      ByteStringEncoding::Base16 => base16::encode_lower(b),
+    }
+  }
+}
+
 /// JSON validator type
 #[derive(Clone)]
 pub struct JSONValidator<'a> {
   cddl: &'a CDDL<'a>,
   json: Value,
+  // Duplicate object member names of the JSON document, if it was parsed with
+  // from_str_with_duplicate_keys
+  duplicate_keys: Vec<DuplicateKey>,
+  // Encoding of JSON strings validated against byte string data types
+  byte_string_encoding: ByteStringEncoding,
+  // Compiled schema used for rule lookups, regular expressions and ABNF
+  // grammars, if any
+  compiled: Option<&'a CompiledSchema<'a>>,
+  // Custom control operators whose callbacks are invoked, if any
+  custom_controls: Option<&'a CustomControls>,
+  // Decoded bytes of the JSON string validated against the controller of a
+  // byte string control operator in current state of AST evaluation
+  byte_string: Option<Vec<u8>>,
   errors: Vec<ValidationError>,
   cddl_location: String,
   json_location: String,
@@ -174,7 +366,7 @@ pub struct JSONValidator<'a> {
   cut_value: Option<Cow<'a, str>>,
   // Validate the generic rule given by str ident in current state of AST
   // evaluation
-  eval_generic_rule: Option<&'a str>,
+  eval_generic_rule: Option<Cow<'a, str>>,
   // Aggregation of generic rules
   generic_rules: Vec<GenericRule<'a>>,
   // Control operator token detected in current state of AST evaluation
@@ -188,7 +380,7 @@ pub struct JSONValidator<'a> {
   is_multi_group_choice: bool,
   // Type/group name entry detected in current state of AST evaluation. Used
   // only for providing more verbose error messages
-  type_group_name_entry: Option<&'a str>,
+  type_group_name_entry: Option<Cow<'a, str>>,
   // Whether or not to advance to the next group entry if member key validation
   // fails as detected during the current state of AST evaluation
   advance_to_next_entry: bool,
@@ -219,8 +411,8 @@ pub struct JSONValidator<'a> {
 
 #[derive(Clone, Debug)]
 struct GenericRule<'a> {
-  name: &'a str,
-  params: Vec<&'a str>,
+  name: Cow<'a, str>,
+  params: Vec<Cow<'a, str>>,
   args: Vec<Type1<'a>>,
 }
 
@@ -232,6 +424,11 @@ impl<'a> JSONValidator<'a> {
     JSONValidator {
       cddl,
       json,
+      duplicate_keys: Vec::new(),
+      byte_string_encoding: ByteStringEncoding::default(),
+      compiled: None,
+      custom_controls: None,
+      byte_string: None,
       errors: Vec::default(),
       cddl_location: String::new(),
       json_location: String::new(),
@@ -270,6 +467,11 @@ impl<'a> JSONValidator<'a> {
     JSONValidator {
       cddl,
       json,
+      duplicate_keys: Vec::new(),
+      byte_string_encoding: ByteStringEncoding::default(),
+      compiled: None,
+      custom_controls: None,
+      byte_string: None,
       errors: Vec::default(),
       cddl_location: String::new(),
       json_location: String::new(),
@@ -305,6 +507,11 @@ impl<'a> JSONValidator<'a> {
     JSONValidator {
       cddl,
       json,
+      duplicate_keys: Vec::new(),
+      byte_string_encoding: ByteStringEncoding::default(),
+      compiled: None,
+      custom_controls: None,
+      byte_string: None,
       errors: Vec::default(),
       cddl_location: String::new(),
       json_location: String::new(),
@@ -343,6 +550,11 @@ impl<'a> JSONValidator<'a> {
     JSONValidator {
       cddl,
       json,
+      duplicate_keys: Vec::new(),
+      byte_string_encoding: ByteStringEncoding::default(),
+      compiled: None,
+      custom_controls: None,
+      byte_string: None,
       errors: Vec::default(),
       cddl_location: String::new(),
       json_location: String::new(),
@@ -371,6 +583,248 @@ impl<'a> JSONValidator<'a> {
     }
   }
 
+  /// Duplicate object member names of the JSON document as collected by
+  /// [`from_str_with_duplicate_keys`], which are reported as validation errors
+  pub fn with_duplicate_keys(mut self, duplicate_keys: Vec<DuplicateKey>) -> Self {
+    self.duplicate_keys = duplicate_keys;
+    self
+  }
+
+  /// Encoding of the JSON strings validated against byte string data types,
+  /// which defaults to [`ByteStringEncoding::Base64Url`]
+  pub fn with_byte_string_encoding(mut self, encoding: ByteStringEncoding) -> Self {
+    self.byte_string_encoding = encoding;
+    self
+  }
+
+  /// Validate against the resolved CDDL AST of the given compiled schema,
+  /// instead of the CDDL AST the validator was created with, using its rule
+  /// index and the regular expressions and ABNF grammars it compiled
+  pub fn with_compiled_schema(mut self, compiled: &'a CompiledSchema<'a>) -> Self {
+    self.cddl = compiled.cddl();
+    self.compiled = Some(compiled);
+    self
+  }
+
+  /// Invoke the callbacks of the given custom control operators, which the
+  /// CDDL AST was parsed with, for the data items matching their target types
+  pub fn with_custom_controls(mut self, controls: &'a CustomControls) -> Self {
+    self.custom_controls = Some(controls);
+    self
+  }
+
+  // Rule lookups, using the index of the compiled schema if any
+  fn lookup(&self) -> RuleLookup<'a> {
+    RuleLookup {
+      cddl: self.cddl,
+      compiled: self.compiled,
+    }
+  }
+
+  // Validate the target against the ABNF grammar, using the grammar of the
+  // compiled schema if any
+  #[cfg(feature = "additional-controls")]
+  fn validate_abnf(&self, abnf: &str, target: &str) -> std::result::Result<(), String> {
+    match self.compiled.and_then(|c| c.abnf_grammar(abnf)) {
+      Some(Some(grammar)) => grammar.validate(target),
+      Some(None) => Ok(()),
+      None => validate_abnf(abnf, target),
+    }
+  }
+
+  // Validate the bytes decoded from a JSON string against the controller of a
+  // control operator whose target is a byte string data type
+  fn visit_byte_string_controller(&mut self, controller: &Type2<'a>) -> visitor::Result<Error> {
+    match &self.json {
+      Value::String(s) => match self.byte_string_encoding.decode(s) {
+        #[cfg(feature = "cbor")]
+        Ok(b)
+          if matches!(
+            self.ctrl,
+            Some(ControlOperator::CBOR) | Some(ControlOperator::CBORSEQ)
+          ) =>
+        {
+          self.validate_embedded_cbor(b, controller)
+        }
+        #[cfg(feature = "additional-controls")]
+        Ok(b) if matches!(self.ctrl, Some(ControlOperator::ABNFB)) => {
+          self.byte_string = Some(b);
+          self.visit_abnf_controller(controller)?;
+          self.byte_string = None;
+        }
+        Ok(b) => {
+          self.byte_string = Some(b);
+          self.visit_type2(controller)?;
+          self.byte_string = None;
+        }
+        Err(e) => self.add_error(format!(
+          "expected {} encoded byte string data type, decoding error: {}",
+          self.byte_string_encoding, e
+        )),
+      },
+      _ => self.add_error(format!(
+        "{} control can only be matched against a JSON string, got {}",
+        self.ctrl.map_or_else(String::new, |ctrl| ctrl.to_string()),
+        self.json,
+      )),
+    }
+
+    Ok(())
+  }
+
+  /// Validate the JSON document decoded from a text string against the
+  /// controller of a `.json` control operator, returning the validation
+  /// errors. Used for text strings embedded in documents other than JSON
+  #[cfg(all(feature = "additional-controls", feature = "cbor"))]
+  pub(crate) fn validate_embedded(
+    &mut self,
+    controller: &Type2<'a>,
+  ) -> std::result::Result<Vec<ValidationError>, Error> {
+    self.visit_type2(controller)?;
+
+    Ok(std::mem::take(&mut self.errors))
+  }
+
+  // Validate the CBOR embedded in the bytes decoded from a JSON string against
+  // the controller of a .cbor or .cborseq control operator
+  #[cfg(feature = "cbor")]
+  fn validate_embedded_cbor(&mut self, bytes: Vec<u8>, controller: &Type2<'a>) {
+    let ctrl = match self.ctrl {
+      Some(ctrl) => ctrl,
+      None => return,
+    };
+
+    #[cfg(all(feature = "additional-controls", target_arch = "wasm32"))]
+    let mut cv = cbor::CBORValidator::new(
+      self.cddl,
+      ciborium::value::Value::Bytes(bytes),
+      self.enabled_features.clone(),
+    );
+    #[cfg(all(feature = "additional-controls", not(target_arch = "wasm32")))]
+    let mut cv = cbor::CBORValidator::new(
+      self.cddl,
+      ciborium::value::Value::Bytes(bytes),
+      self.enabled_features,
+    );
+    #[cfg(not(feature = "additional-controls"))]
+    let mut cv = cbor::CBORValidator::new(self.cddl, ciborium::value::Value::Bytes(bytes));
+    if let Some(compiled) = self.compiled {
+      cv = cv.with_compiled_schema(compiled);
+    }
+    if let Some(controls) = self.custom_controls {
+      cv = cv.with_custom_controls(controls);
+    }
+
+    match cv.validate_embedded(ctrl, controller) {
+      Ok(errors) => {
+        for e in errors.into_iter() {
+          if e.cbor_location.is_empty() {
+            self.add_error(format!("embedded CBOR: {}", e.reason));
+          } else {
+            self.add_error(format!(
+              "embedded CBOR at {}: {}",
+              e.cbor_location, e.reason
+            ));
+          }
+        }
+      }
+      Err(e) => self.add_error(format!("error validating embedded CBOR, {}", e)),
+    }
+  }
+
+  // Validate a JSON string against the controller of an .abnf or .abnfb
+  // control operator, which may be a complex controller with a .cat or .det
+  // control operator
+  #[cfg(feature = "additional-controls")]
+  fn visit_abnf_controller(&mut self, controller: &Type2<'a>) -> visitor::Result<Error> {
+    if let Type2::ParenthesizedType { pt, .. } = controller {
+      match abnf_from_complex_controller(self.cddl, pt) {
+        Ok(values) => {
+          let error_count = self.errors.len();
+          for v in values.iter() {
+            let cur_errors = self.errors.len();
+
+            self.visit_type2(v)?;
+
+            if self.errors.len() == cur_errors {
+              for _ in 0..self.errors.len() - error_count {
+                self.errors.pop();
+              }
+
+              break;
+            }
+          }
+        }
+        Err(e) => self.add_error(e),
+      }
+
+      Ok(())
+    } else {
+      self.visit_type2(controller)
+    }
+  }
+
+  fn validate_duplicate_keys(&mut self) {
+    let current_location = self.json_location.clone();
+    for dk in std::mem::take(&mut self.duplicate_keys).into_iter() {
+      self.json_location = format!("{}{}", current_location, dk.location);
+      self.add_error(format!("object has duplicate member {:?}", dk.key));
+    }
+    self.json_location = current_location;
+  }
+
+  /// Validate the items of an array against an array group flattened into an
+  /// array pattern. Occurrence indicators are matched with backtracking, so
+  /// arrays like `[ * a: int, b: tstr, ? c: int ]` validate as expected
+  fn validate_array_pattern(
+    &mut self,
+    pattern: &ArrayPattern<'a, '_>,
+    len: usize,
+  ) -> visitor::Result<Error> {
+    let a = match &self.json {
+      Value::Array(a) => a,
+      _ => return Ok(()),
+    };
+
+    let result = match_array_items(pattern, len, |item, idx| {
+      #[cfg(all(feature = "additional-controls", target_arch = "wasm32"))]
+      let mut jv = JSONValidator::new(self.cddl, a[idx].clone(), self.enabled_features.clone());
+      #[cfg(all(feature = "additional-controls", not(target_arch = "wasm32")))]
+      let mut jv = JSONValidator::new(self.cddl, a[idx].clone(), self.enabled_features);
+      #[cfg(not(feature = "additional-controls"))]
+      let mut jv = JSONValidator::new(self.cddl, a[idx].clone());
+      jv.compiled = self.compiled;
+      jv.custom_controls = self.custom_controls;
+
+      jv.generic_rules = self.generic_rules.clone();
+      jv.byte_string_encoding = self.byte_string_encoding;
+      jv.eval_generic_rule = self.eval_generic_rule.clone();
+      let _ = write!(jv.json_location, "{}/{}", self.json_location, idx);
+
+      match item {
+        ArrayItem::Type(t) => jv.visit_type(t)?,
+        ArrayItem::Typename(entry) => jv.visit_type_groupname_entry(entry)?,
+      }
+
+      Ok(jv.errors)
+    })?;
+
+    match result {
+      Ok(()) => (),
+      Err(ArrayMismatch::Items(mut errors)) => self.errors.append(&mut errors),
+      Err(ArrayMismatch::UnexpectedItem(idx)) => {
+        let reason = format!("unexpected array item at index {}, got {}", idx, a[idx]);
+        self.add_error(reason);
+      }
+      Err(ArrayMismatch::MissingItems) => self.add_error(format!(
+        "array is missing required items, got {}",
+        self.json
+      )),
+    }
+
+    Ok(())
+  }
+
   fn validate_array_items(&mut self, token: &ArrayItemToken) -> visitor::Result<Error> {
     if let Value::Array(a) = &self.json {
       // Member keys are annotation only in an array context
@@ -398,9 +852,12 @@ impl<'a> JSONValidator<'a> {
               let mut jv = JSONValidator::new(self.cddl, v.clone(), self.enabled_features);
               #[cfg(not(feature = "additional-controls"))]
               let mut jv = JSONValidator::new(self.cddl, v.clone());
+              jv.compiled = self.compiled;
+              jv.custom_controls = self.custom_controls;
 
               jv.generic_rules = self.generic_rules.clone();
-              jv.eval_generic_rule = self.eval_generic_rule;
+              jv.byte_string_encoding = self.byte_string_encoding;
+              jv.eval_generic_rule = self.eval_generic_rule.clone();
               jv.is_multi_type_choice = self.is_multi_type_choice;
               jv.ctrl = self.ctrl;
               let _ = write!(jv.json_location, "{}/{}", self.json_location, idx);
@@ -443,9 +900,12 @@ impl<'a> JSONValidator<'a> {
               let mut jv = JSONValidator::new(self.cddl, v.clone(), self.enabled_features);
               #[cfg(not(feature = "additional-controls"))]
               let mut jv = JSONValidator::new(self.cddl, v.clone());
+              jv.compiled = self.compiled;
+              jv.custom_controls = self.custom_controls;
 
               jv.generic_rules = self.generic_rules.clone();
-              jv.eval_generic_rule = self.eval_generic_rule;
+              jv.byte_string_encoding = self.byte_string_encoding;
+              jv.eval_generic_rule = self.eval_generic_rule.clone();
               jv.is_multi_type_choice = self.is_multi_type_choice;
               jv.ctrl = self.ctrl;
               let _ = write!(jv.json_location, "{}/{}", self.json_location, idx);
@@ -545,11 +1005,37 @@ impl<'a> JSONValidator<'a> {
 
     Ok(())
   }
+
+  /// Validate the JSON value against the type or group rule with the given
+  /// name instead of the root type rule
+  pub fn validate_rule(&mut self, rule: &str) -> std::result::Result<(), Error> {
+    let rule = root_rule_from_name(self.cddl, rule).map_err(Error::MissingRule)?;
+
+    self.validate_duplicate_keys();
+
+    self.is_root = true;
+    match rule {
+      Rule::Type { rule, .. } => self.visit_type_rule(rule)?,
+      Rule::Group { rule, .. } => {
+        let t2 = group_rule_as_type2(&rule.name, matches!(self.json, Value::Array(_)));
+        self.visit_type2(&t2)?;
+      }
+    }
+    self.is_root = false;
+
+    if !self.errors.is_empty() {
+      return Err(Error::Validation(self.errors.clone()));
+    }
+
+    Ok(())
+  }
 }
 
 impl<'a, 'b> Validator<'a, 'b, Error> for JSONValidator<'a> {
   /// Validate
   fn validate(&mut self) -> std::result::Result<(), Error> {
+    self.validate_duplicate_keys();
+
     for r in self.cddl.rules.iter() {
       // First type rule is root
       if let Rule::Type { rule, .. } = r {
@@ -577,7 +1063,7 @@ impl<'a, 'b> Validator<'a, 'b, Error> for JSONValidator<'a> {
       is_multi_type_choice: self.is_multi_type_choice,
       is_multi_group_choice: self.is_multi_group_choice,
       is_group_to_choice_enum: self.is_group_to_choice_enum,
-      type_group_name_entry: self.type_group_name_entry.map(|e| e.to_string()),
+      type_group_name_entry: self.type_group_name_entry.as_ref().map(|e| e.to_string()),
     });
   }
 }
@@ -590,17 +1076,17 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
         .iter_mut()
         .find(|r| r.name == tr.name.ident)
       {
-        gr.params = gp.params.iter().map(|p| p.param.ident).collect();
+        gr.params = gp.params.iter().map(|p| p.param.ident.clone()).collect();
       } else {
         self.generic_rules.push(GenericRule {
-          name: tr.name.ident,
-          params: gp.params.iter().map(|p| p.param.ident).collect(),
+          name: tr.name.ident.clone(),
+          params: gp.params.iter().map(|p| p.param.ident.clone()).collect(),
           args: vec![],
         });
       }
     }
 
-    let type_choice_alternates = type_choice_alternates_from_ident(self.cddl, &tr.name);
+    let type_choice_alternates = self.lookup().type_choice_alternates_from_ident(&tr.name);
     if !type_choice_alternates.is_empty() {
       self.is_multi_type_choice = true;
     }
@@ -628,17 +1114,17 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
         .iter_mut()
         .find(|r| r.name == gr.name.ident)
       {
-        gr.params = gp.params.iter().map(|p| p.param.ident).collect();
+        gr.params = gp.params.iter().map(|p| p.param.ident.clone()).collect();
       } else {
         self.generic_rules.push(GenericRule {
-          name: gr.name.ident,
-          params: gp.params.iter().map(|p| p.param.ident).collect(),
+          name: gr.name.ident.clone(),
+          params: gp.params.iter().map(|p| p.param.ident.clone()).collect(),
           args: vec![],
         });
       }
     }
 
-    let group_choice_alternates = group_choice_alternates_from_ident(self.cddl, &gr.name);
+    let group_choice_alternates = self.lookup().group_choice_alternates_from_ident(&gr.name);
     if !group_choice_alternates.is_empty() {
       self.is_multi_group_choice = true;
     }
@@ -750,7 +1236,7 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
     if self.is_ctrl_map_equality {
       if let Some(t) = &self.ctrl {
         if let Value::Object(o) = &self.json {
-          let entry_counts = entry_counts_from_group(self.cddl, g);
+          let entry_counts = self.lookup().entry_counts_from_group(g);
 
           let len = o.len();
           if let ControlOperator::EQ = t {
@@ -818,7 +1304,7 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
   fn visit_group_choice(&mut self, gc: &GroupChoice<'a>) -> visitor::Result<Error> {
     if self.is_group_to_choice_enum {
       let initial_error_count = self.errors.len();
-      for tc in type_choices_from_group_choice(self.cddl, gc).iter() {
+      for tc in self.lookup().type_choices_from_group_choice(gc).iter() {
         let error_count = self.errors.len();
         self.visit_type_choice(tc)?;
         if self.errors.len() == error_count {
@@ -984,10 +1470,10 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
             }
             Value::String(s) => match self.ctrl {
               Some(ControlOperator::SIZE) => {
-                let len = s.len();
+                let len = self.byte_string.as_ref().map_or(s.len(), |b| b.len());
                 let s = s.clone();
                 if is_inclusive {
-                  if s.len() < *l || s.len() > *u {
+                  if len < *l || len > *u {
                     self.add_error(format!(
                       "expected \"{}\" string length to be in the range {} <= value <= {}, got {}",
                       s, l, u, len
@@ -995,7 +1481,7 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
                   }
 
                   return Ok(());
-                } else if s.len() <= *l || s.len() >= *u {
+                } else if len <= *l || len >= *u {
                   self.add_error(format!(
                     "expected \"{}\" string length to be in the range {} < value < {}, got {}",
                     s, l, u, len
@@ -1099,12 +1585,12 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
         ..
       } = controller
       {
-        if let Some(name) = self.eval_generic_rule {
+        if let Some(name) = &self.eval_generic_rule {
           if let Some(gr) = self
             .generic_rules
             .iter()
             .cloned()
-            .find(|gr| gr.name == name)
+            .find(|gr| gr.name == *name)
           {
             for (idx, gp) in gr.params.iter().enumerate() {
               if let Some(arg) = gr.args.get(idx) {
@@ -1123,12 +1609,12 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
         }
       }
 
-      if let Some(name) = self.eval_generic_rule {
+      if let Some(name) = &self.eval_generic_rule {
         if let Some(gr) = self
           .generic_rules
           .iter()
           .cloned()
-          .find(|gr| gr.name == name)
+          .find(|gr| gr.name == *name)
         {
           for (idx, gp) in gr.params.iter().enumerate() {
             if let Some(arg) = gr.args.get(idx) {
@@ -1145,15 +1631,15 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
     match ctrl {
       ControlOperator::EQ => match target {
         Type2::Typename { ident, .. } => {
-          if is_ident_string_data_type(self.cddl, ident)
-            || is_ident_numeric_data_type(self.cddl, ident)
+          if self.lookup().is_ident_string_data_type(ident)
+            || self.lookup().is_ident_numeric_data_type(ident)
           {
             return self.visit_type2(controller);
           }
         }
         Type2::Array { group, .. } => {
           if let Value::Array(_) = &self.json {
-            let entry_counts = entry_counts_from_group(self.cddl, group);
+            let entry_counts = self.lookup().entry_counts_from_group(group);
             self.entry_counts = Some(entry_counts);
             self.visit_type2(controller)?;
             self.entry_counts = None;
@@ -1177,8 +1663,8 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
       },
       ControlOperator::NE => match target {
         Type2::Typename { ident, .. } => {
-          if is_ident_string_data_type(self.cddl, ident)
-            || is_ident_numeric_data_type(self.cddl, ident)
+          if self.lookup().is_ident_string_data_type(ident)
+            || self.lookup().is_ident_numeric_data_type(ident)
           {
             self.ctrl = Some(ctrl);
             self.visit_type2(controller)?;
@@ -1211,7 +1697,7 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
       },
       ControlOperator::LT | ControlOperator::GT | ControlOperator::GE | ControlOperator::LE => {
         match target {
-          Type2::Typename { ident, .. } if is_ident_numeric_data_type(self.cddl, ident) => {
+          Type2::Typename { ident, .. } if self.lookup().is_ident_numeric_data_type(ident) => {
             self.ctrl = Some(ctrl);
             self.visit_type2(controller)?;
             self.ctrl = None;
@@ -1225,9 +1711,14 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
         }
       }
       ControlOperator::SIZE => match target {
+        Type2::Typename { ident, .. } if self.lookup().is_ident_byte_string_data_type(ident) => {
+          self.ctrl = Some(ctrl);
+          self.visit_byte_string_controller(controller)?;
+          self.ctrl = None;
+        }
         Type2::Typename { ident, .. }
-          if is_ident_string_data_type(self.cddl, ident)
-            || is_ident_uint_data_type(self.cddl, ident) =>
+          if self.lookup().is_ident_string_data_type(ident)
+            || self.lookup().is_ident_uint_data_type(ident) =>
         {
           self.ctrl = Some(ctrl);
           self.visit_type2(controller)?;
@@ -1240,6 +1731,42 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
           ));
         }
       },
+      ControlOperator::BITS => {
+        self.ctrl = Some(ctrl);
+        match target {
+          Type2::Typename { ident, .. } if self.lookup().is_ident_byte_string_data_type(ident) => {
+            self.visit_byte_string_controller(controller)?
+          }
+          Type2::Typename { ident, .. } if self.lookup().is_ident_uint_data_type(ident) => {
+            match &self.json {
+              Value::Number(n) if n.is_u64() => self.visit_type2(controller)?,
+              _ => self.add_error(format!(
+                "{} control can only be matched against a JSON string or uint, got {}",
+                ctrl, self.json,
+              )),
+            }
+          }
+          _ => self.add_error(format!(
+            ".bits control can only be matched against a byte string data type, got {}",
+            target
+          )),
+        }
+        self.ctrl = None;
+      }
+      #[cfg(feature = "cbor")]
+      ControlOperator::CBOR | ControlOperator::CBORSEQ => {
+        self.ctrl = Some(ctrl);
+        match target {
+          Type2::Typename { ident, .. } if self.lookup().is_ident_byte_string_data_type(ident) => {
+            self.visit_byte_string_controller(controller)?
+          }
+          _ => self.add_error(format!(
+            ".cbor control can only be matched against a byte string data type, got {}",
+            target
+          )),
+        }
+        self.ctrl = None;
+      }
       ControlOperator::AND => {
         self.ctrl = Some(ctrl);
         self.visit_type2(target)?;
@@ -1290,7 +1817,7 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
       ControlOperator::REGEXP | ControlOperator::PCRE => {
         self.ctrl = Some(ctrl);
         match target {
-          Type2::Typename { ident, .. } if is_ident_string_data_type(self.cddl, ident) => {
+          Type2::Typename { ident, .. } if self.lookup().is_ident_string_data_type(ident) => {
             match self.json {
               Value::String(_) | Value::Array(_) => self.visit_type2(controller)?,
               _ => self.add_error(format!(
@@ -1391,33 +1918,9 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
         self.ctrl = Some(ctrl);
 
         match target {
-          Type2::Typename { ident, .. } if is_ident_string_data_type(self.cddl, ident) => {
+          Type2::Typename { ident, .. } if self.lookup().is_ident_string_data_type(ident) => {
             match self.json {
-              Value::String(_) | Value::Array(_) => {
-                if let Type2::ParenthesizedType { pt, .. } = controller {
-                  match abnf_from_complex_controller(self.cddl, pt) {
-                    Ok(values) => {
-                      let error_count = self.errors.len();
-                      for v in values.iter() {
-                        let cur_errors = self.errors.len();
-
-                        self.visit_type2(v)?;
-
-                        if self.errors.len() == cur_errors {
-                          for _ in 0..self.errors.len() - error_count {
-                            self.errors.pop();
-                          }
-
-                          break;
-                        }
-                      }
-                    }
-                    Err(e) => self.add_error(e),
-                  }
-                } else {
-                  self.visit_type2(controller)?
-                }
-              }
+              Value::String(_) | Value::Array(_) => self.visit_abnf_controller(controller)?,
               _ => self.add_error(format!(
                 ".abnf control can only be matched against a JSON string, got {}",
                 self.json,
@@ -1433,53 +1936,311 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
         self.ctrl = None;
       }
       #[cfg(feature = "additional-controls")]
-      #[cfg(not(target_arch = "wasm32"))]
-      ControlOperator::FEATURE => {
+      ControlOperator::ABNFB => {
         self.ctrl = Some(ctrl);
 
-        if let Some(ef) = self.enabled_features {
-          let tv = text_value_from_type2(self.cddl, controller);
-          if let Some(Type2::TextValue { value, .. }) = tv {
-            if ef.contains(&&**value) {
-              let err_count = self.errors.len();
-              self.visit_type2(target)?;
-              if self.errors.len() > err_count {
-                self.has_feature_errors = true;
-              }
-              self.ctrl = None;
-            } else {
-              self
-                .disabled_features
-                .get_or_insert(vec![value.to_string()])
-                .push(value.to_string());
-            }
-          } else if let Some(Type2::UTF8ByteString { value, .. }) = tv {
-            let value = std::str::from_utf8(value).map_err(Error::UTF8Parsing)?;
-            if ef.contains(&value) {
-              let err_count = self.errors.len();
-              self.visit_type2(target)?;
-              if self.errors.len() > err_count {
-                self.has_feature_errors = true;
-              }
-              self.ctrl = None;
-            } else {
-              self
-                .disabled_features
-                .get_or_insert(vec![value.to_string()])
-                .push(value.to_string());
-            }
+        match target {
+          Type2::Typename { ident, .. } if self.lookup().is_ident_byte_string_data_type(ident) => {
+            self.visit_byte_string_controller(controller)?
           }
+          _ => self.add_error(format!(
+            ".abnfb can only be matched against byte string target data type, got {}",
+            target,
+          )),
         }
 
         self.ctrl = None;
       }
       #[cfg(feature = "additional-controls")]
-      #[cfg(target_arch = "wasm32")]
-      ControlOperator::FEATURE => {
+      ControlOperator::B64U
+      | ControlOperator::B64USLOPPY
+      | ControlOperator::B64C
+      | ControlOperator::B64CSLOPPY
+      | ControlOperator::B45
+      | ControlOperator::B32
+      | ControlOperator::H32
+      | ControlOperator::HEX
+      | ControlOperator::HEXLC
+      | ControlOperator::HEXUC => match target {
+        Type2::Typename { ident, .. } if self.lookup().is_ident_string_data_type(ident) => {
+          match &self.json {
+            // The decoded bytes are validated against the controller as a
+            // byte string represented in the validator's byte string encoding
+            Value::String(s) => match decode_text_conversion(ctrl, s) {
+              Ok(b) => {
+                let value = Value::String(self.byte_string_encoding.encode(&b));
+
+                #[cfg(target_arch = "wasm32")]
+                let mut jv = JSONValidator::new(self.cddl, value, self.enabled_features.clone());
+                #[cfg(not(target_arch = "wasm32"))]
+                let mut jv = JSONValidator::new(self.cddl, value, self.enabled_features);
+                jv.compiled = self.compiled;
+                jv.custom_controls = self.custom_controls;
+
+                jv.generic_rules = self.generic_rules.clone();
+                jv.byte_string_encoding = self.byte_string_encoding;
+                jv.eval_generic_rule = self.eval_generic_rule.clone();
+                jv.is_multi_type_choice = self.is_multi_type_choice;
+                jv.is_multi_group_choice = self.is_multi_group_choice;
+                jv.json_location.push_str(&self.json_location);
+                jv.type_group_name_entry = self.type_group_name_entry.clone();
+                jv.visit_type2(controller)?;
+
+                self.errors.append(&mut jv.errors);
+              }
+              Err(e) => self.add_error(format!(
+                "expected text string {} encoding of a byte string, decoding error: {}",
+                ctrl, e
+              )),
+            },
+            _ => self.add_error(format!(
+              "{} control can only be matched against a JSON string, got {}",
+              ctrl, self.json,
+            )),
+          }
+        }
+        _ => self.add_error(format!(
+          "{} control can only be matched against a text string data type, got {}",
+          ctrl, target,
+        )),
+      },
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::DECIMAL => match target {
+        Type2::Typename { ident, .. } if self.lookup().is_ident_string_data_type(ident) => {
+          match &self.json {
+            Value::String(s) => match decode_decimal(s).and_then(|i| {
+              u64::try_from(i)
+                .map(serde_json::Number::from)
+                .or_else(|_| i64::try_from(i).map(serde_json::Number::from))
+                .map_err(|_| format!("{} is out of range of a JSON number", i))
+            }) {
+              Ok(n) => {
+                #[cfg(target_arch = "wasm32")]
+                let mut jv =
+                  JSONValidator::new(self.cddl, Value::Number(n), self.enabled_features.clone());
+                #[cfg(not(target_arch = "wasm32"))]
+                let mut jv = JSONValidator::new(self.cddl, Value::Number(n), self.enabled_features);
+                jv.compiled = self.compiled;
+                jv.custom_controls = self.custom_controls;
+
+                jv.generic_rules = self.generic_rules.clone();
+                jv.byte_string_encoding = self.byte_string_encoding;
+                jv.eval_generic_rule = self.eval_generic_rule.clone();
+                jv.is_multi_type_choice = self.is_multi_type_choice;
+                jv.is_multi_group_choice = self.is_multi_group_choice;
+                jv.json_location.push_str(&self.json_location);
+                jv.type_group_name_entry = self.type_group_name_entry.clone();
+                jv.visit_type2(controller)?;
+
+                self.errors.append(&mut jv.errors);
+              }
+              Err(e) => self.add_error(format!(
+                "expected text string decimal representation of an integer, decoding error: {}",
+                e
+              )),
+            },
+            _ => self.add_error(format!(
+              ".decimal control can only be matched against a JSON string, got {}",
+              self.json,
+            )),
+          }
+        }
+        _ => self.add_error(format!(
+          ".decimal control can only be matched against a text string data type, got {}",
+          target,
+        )),
+      },
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::PRINTF => {
         self.ctrl = Some(ctrl);
 
-        if let Some(ef) = &self.enabled_features {
-          let tv = text_value_from_type2(self.cddl, controller);
+        match printf_operation(self.cddl, target, controller) {
+          Ok(values) => {
+            let error_count = self.errors.len();
+
+            for v in values.iter() {
+              let cur_errors = self.errors.len();
+
+              self.visit_type2(v)?;
+
+              if self.errors.len() == cur_errors {
+                for _ in 0..self.errors.len() - error_count {
+                  self.errors.pop();
+                }
+
+                break;
+              }
+            }
+          }
+          Err(e) => self.add_error(e),
+        }
+
+        self.ctrl = None;
+      }
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::JSON => match target {
+        Type2::Typename { ident, .. } if self.lookup().is_ident_string_data_type(ident) => {
+          match &self.json {
+            Value::String(s) => match serde_json::from_str::<Value>(s) {
+              Ok(value) => {
+                #[cfg(target_arch = "wasm32")]
+                let mut jv = JSONValidator::new(self.cddl, value, self.enabled_features.clone());
+                #[cfg(not(target_arch = "wasm32"))]
+                let mut jv = JSONValidator::new(self.cddl, value, self.enabled_features);
+                jv.compiled = self.compiled;
+                jv.custom_controls = self.custom_controls;
+
+                jv.generic_rules = self.generic_rules.clone();
+                jv.byte_string_encoding = self.byte_string_encoding;
+                jv.eval_generic_rule = self.eval_generic_rule.clone();
+                jv.is_multi_type_choice = self.is_multi_type_choice;
+                jv.is_multi_group_choice = self.is_multi_group_choice;
+                jv.json_location.push_str(&self.json_location);
+                jv.type_group_name_entry = self.type_group_name_entry.clone();
+                jv.visit_type2(controller)?;
+
+                self.errors.append(&mut jv.errors);
+              }
+              Err(e) => self.add_error(format!("error parsing embedded JSON, {}", e)),
+            },
+            _ => self.add_error(format!(
+              ".json control can only be matched against a JSON string, got {}",
+              self.json,
+            )),
+          }
+        }
+        _ => self.add_error(format!(
+          ".json control can only be matched against a text string data type, got {}",
+          target,
+        )),
+      },
+      #[cfg(feature = "additional-controls")]
+      ControlOperator::JOIN => match target {
+        Type2::Typename { ident, .. } if self.lookup().is_ident_string_data_type(ident) => {
+          match &self.json {
+            Value::String(s) => {
+              match join_controller_group(self.cddl, controller)
+                .and_then(|group| ArrayPattern::from_group(self.lookup(), group))
+              {
+                Some(pattern) => {
+                  let is_match = match_joined_text(&pattern, s, |item, piece| {
+                    #[cfg(target_arch = "wasm32")]
+                    let mut jv = JSONValidator::new(
+                      self.cddl,
+                      Value::String(piece.to_string()),
+                      self.enabled_features.clone(),
+                    );
+                    #[cfg(not(target_arch = "wasm32"))]
+                    let mut jv = JSONValidator::new(
+                      self.cddl,
+                      Value::String(piece.to_string()),
+                      self.enabled_features,
+                    );
+                    jv.compiled = self.compiled;
+                    jv.custom_controls = self.custom_controls;
+
+                    jv.generic_rules = self.generic_rules.clone();
+                    jv.byte_string_encoding = self.byte_string_encoding;
+                    jv.eval_generic_rule = self.eval_generic_rule.clone();
+                    jv.json_location.push_str(&self.json_location);
+
+                    match item {
+                      ArrayItem::Type(t) => jv.visit_type(t)?,
+                      ArrayItem::Typename(entry) => jv.visit_type_groupname_entry(entry)?,
+                    }
+
+                    Ok::<_, Error>(jv.errors.is_empty())
+                  })?;
+
+                  if !is_match {
+                    self.add_error(format!(
+                      "expected text string joined from an array matching {}, got {}",
+                      controller, self.json
+                    ));
+                  }
+                }
+                None => self.add_error(format!(
+                  "invalid controller used for .join operation, got {}",
+                  controller
+                )),
+              }
+            }
+            _ => self.add_error(format!(
+              ".join control can only be matched against a JSON string, got {}",
+              self.json,
+            )),
+          }
+        }
+        _ => self.add_error(format!(
+          ".join control can only be matched against a text string data type, got {}",
+          target,
+        )),
+      },
+      ControlOperator::CUSTOM(name) => {
+        // The callback is only invoked for data items matching the target
+        let error_count = self.errors.len();
+        self.visit_type2(target)?;
+
+        if self.errors.len() == error_count {
+          if let Err(e) = custom::validate_control(
+            self.custom_controls,
+            name,
+            ControlValue::JSON(&self.json),
+            target,
+            controller,
+          ) {
+            self.add_error(e);
+          }
+        }
+      }
+      #[cfg(feature = "additional-controls")]
+      #[cfg(not(target_arch = "wasm32"))]
+      ControlOperator::FEATURE => {
+        self.ctrl = Some(ctrl);
+
+        if let Some(ef) = self.enabled_features {
+          let tv = self.lookup().text_value_from_type2(controller);
+          if let Some(Type2::TextValue { value, .. }) = tv {
+            if ef.contains(&&**value) {
+              let err_count = self.errors.len();
+              self.visit_type2(target)?;
+              if self.errors.len() > err_count {
+                self.has_feature_errors = true;
+              }
+              self.ctrl = None;
+            } else {
+              self
+                .disabled_features
+                .get_or_insert(vec![value.to_string()])
+                .push(value.to_string());
+            }
+          } else if let Some(Type2::UTF8ByteString { value, .. }) = tv {
+            let value = std::str::from_utf8(value).map_err(Error::UTF8Parsing)?;
+            if ef.contains(&value) {
+              let err_count = self.errors.len();
+              self.visit_type2(target)?;
+              if self.errors.len() > err_count {
+                self.has_feature_errors = true;
+              }
+              self.ctrl = None;
+            } else {
+              self
+                .disabled_features
+                .get_or_insert(vec![value.to_string()])
+                .push(value.to_string());
+            }
+          }
+        }
+
+        self.ctrl = None;
+      }
+      #[cfg(feature = "additional-controls")]
+      #[cfg(target_arch = "wasm32")]
+      ControlOperator::FEATURE => {
+        self.ctrl = Some(ctrl);
+
+        if let Some(ef) = &self.enabled_features {
+          let tv = self.lookup().text_value_from_type2(controller);
           if let Some(Type2::TextValue { value, .. }) = tv {
             if ef.contains(&JsValue::from(value.as_ref())) {
               let err_count = self.errors.len();
@@ -1514,6 +2275,8 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
 
         self.ctrl = None;
       }
+      // Reachable when the cbor or additional-controls features are disabled
+      #[allow(unreachable_patterns)]
       _ => {
         self.add_error(format!("unsupported control operator {}", ctrl));
       }
@@ -1525,6 +2288,9 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
   fn visit_type2(&mut self, t2: &Type2<'a>) -> visitor::Result<Error> {
     match t2 {
       Type2::TextValue { value, .. } => self.visit_value(&token::Value::TEXT(value.clone())),
+      Type2::UTF8ByteString { .. } | Type2::B16ByteString { .. } | Type2::B64ByteString { .. } => {
+        walk_type2(self, t2)
+      }
       Type2::Map { group, .. } => match &self.json {
         Value::Object(o) => {
           #[allow(clippy::needless_collect)]
@@ -1566,7 +2332,13 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
             return Ok(());
           }
 
-          let entry_counts = entry_counts_from_group(self.cddl, group);
+          if self.ctrl.is_none() {
+            if let Some(pattern) = ArrayPattern::from_group(self.lookup(), group) {
+              return self.validate_array_pattern(&pattern, a.len());
+            }
+          }
+
+          let entry_counts = self.lookup().entry_counts_from_group(group);
           self.entry_counts = Some(entry_counts);
           self.visit_group(group)?;
           self.entry_counts = None;
@@ -1599,7 +2371,7 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
         ..
       } => {
         if let Some(ga) = generic_args {
-          if let Some(rule) = rule_from_ident(self.cddl, ident) {
+          if let Some(rule) = self.lookup().rule_from_ident(ident) {
             if let Some(gr) = self
               .generic_rules
               .iter_mut()
@@ -1610,7 +2382,7 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
               }
             } else if let Some(params) = generic_params_from_rule(rule) {
               self.generic_rules.push(GenericRule {
-                name: ident.ident,
+                name: ident.ident.clone(),
                 params,
                 args: ga.args.iter().cloned().map(|arg| *arg.arg).collect(),
               });
@@ -1623,9 +2395,12 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
             let mut jv = JSONValidator::new(self.cddl, self.json.clone(), self.enabled_features);
             #[cfg(not(feature = "additional-controls"))]
             let mut jv = JSONValidator::new(self.cddl, self.json.clone());
+            jv.compiled = self.compiled;
+            jv.custom_controls = self.custom_controls;
 
             jv.generic_rules = self.generic_rules.clone();
-            jv.eval_generic_rule = Some(ident.ident);
+            jv.byte_string_encoding = self.byte_string_encoding;
+            jv.eval_generic_rule = Some(ident.ident.clone());
             jv.is_group_to_choice_enum = true;
             jv.is_multi_type_choice = self.is_multi_type_choice;
             jv.visit_rule(rule)?;
@@ -1636,7 +2411,7 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
           }
         }
 
-        if group_rule_from_ident(self.cddl, ident).is_none() {
+        if self.lookup().group_rule_from_ident(ident).is_none() {
           self.add_error(format!(
             "rule {} must be a group rule to turn it into a choice",
             ident
@@ -1662,7 +2437,7 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
         ..
       } => {
         if let Some(ga) = generic_args {
-          if let Some(rule) = rule_from_ident(self.cddl, ident) {
+          if let Some(rule) = self.lookup().rule_from_ident(ident) {
             if let Some(gr) = self
               .generic_rules
               .iter_mut()
@@ -1673,7 +2448,7 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
               }
             } else if let Some(params) = generic_params_from_rule(rule) {
               self.generic_rules.push(GenericRule {
-                name: ident.ident,
+                name: ident.ident.clone(),
                 params,
                 args: ga.args.iter().cloned().map(|arg| *arg.arg).collect(),
               });
@@ -1686,9 +2461,12 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
             let mut jv = JSONValidator::new(self.cddl, self.json.clone(), self.enabled_features);
             #[cfg(not(feature = "additional-controls"))]
             let mut jv = JSONValidator::new(self.cddl, self.json.clone());
+            jv.compiled = self.compiled;
+            jv.custom_controls = self.custom_controls;
 
             jv.generic_rules = self.generic_rules.clone();
-            jv.eval_generic_rule = Some(ident.ident);
+            jv.byte_string_encoding = self.byte_string_encoding;
+            jv.eval_generic_rule = Some(ident.ident.clone());
             jv.is_multi_type_choice = self.is_multi_type_choice;
             jv.visit_rule(rule)?;
 
@@ -1698,7 +2476,7 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
           }
         }
 
-        let type_choice_alternates = type_choice_alternates_from_ident(self.cddl, ident);
+        let type_choice_alternates = self.lookup().type_choice_alternates_from_ident(ident);
         if !type_choice_alternates.is_empty() {
           self.is_multi_type_choice = true;
         }
@@ -1730,12 +2508,12 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
         // Per
         // https://github.com/w3c/did-spec-registries/pull/138#issuecomment-719739215,
         // strip tag and validate underlying type
-        if let Some(Type2::TaggedData { t, .. }) = tag_from_token(&lookup_ident(ident.ident)) {
+        if let Some(Type2::TaggedData { t, .. }) = tag_from_token(&lookup_ident(&ident.ident)) {
           return self.visit_type(&t);
         }
 
         if let Some(ga) = generic_args {
-          if let Some(rule) = unwrap_rule_from_ident(self.cddl, ident) {
+          if let Some(rule) = self.lookup().unwrap_rule_from_ident(ident) {
             if let Some(gr) = self
               .generic_rules
               .iter_mut()
@@ -1746,7 +2524,7 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
               }
             } else if let Some(params) = generic_params_from_rule(rule) {
               self.generic_rules.push(GenericRule {
-                name: ident.ident,
+                name: ident.ident.clone(),
                 params,
                 args: ga.args.iter().cloned().map(|arg| *arg.arg).collect(),
               });
@@ -1759,9 +2537,12 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
             let mut jv = JSONValidator::new(self.cddl, self.json.clone(), self.enabled_features);
             #[cfg(not(feature = "additional-controls"))]
             let mut jv = JSONValidator::new(self.cddl, self.json.clone());
+            jv.compiled = self.compiled;
+            jv.custom_controls = self.custom_controls;
 
             jv.generic_rules = self.generic_rules.clone();
-            jv.eval_generic_rule = Some(ident.ident);
+            jv.byte_string_encoding = self.byte_string_encoding;
+            jv.eval_generic_rule = Some(ident.ident.clone());
             jv.is_multi_type_choice = self.is_multi_type_choice;
             jv.visit_rule(rule)?;
 
@@ -1771,7 +2552,7 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
           }
         }
 
-        if let Some(rule) = unwrap_rule_from_ident(self.cddl, ident) {
+        if let Some(rule) = self.lookup().unwrap_rule_from_ident(ident) {
           return self.visit_rule(rule);
         }
 
@@ -1782,6 +2563,35 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
 
         Ok(())
       }
+      // JSON has no tags, so the date/time tags of RFC 8949 and RFC 8943 are
+      // validated against their untagged representation
+      Type2::TaggedData {
+        tag: Some(tag @ (0 | 1 | 100 | 1004)),
+        t,
+        ..
+      } => {
+        let error_count = self.errors.len();
+        self.visit_type(t)?;
+        if self.errors.len() != error_count {
+          return Ok(());
+        }
+
+        if let Value::String(s) = &self.json {
+          let result = match tag {
+            0 => chrono::DateTime::parse_from_rfc3339(s)
+              .map(|_| ())
+              .map_err(|e| format!("invalid standard date/time string {:?}, {}", s, e)),
+            1004 => validate_full_date(s),
+            _ => Ok(()),
+          };
+
+          if let Err(e) = result {
+            self.add_error(format!("invalid content of tag {}, {}", tag, e));
+          }
+        }
+
+        Ok(())
+      }
       #[cfg(feature = "ast-span")]
       Type2::Any { .. } => Ok(()),
       #[cfg(not(feature = "ast-span"))]
@@ -1797,12 +2607,12 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
   }
 
   fn visit_identifier(&mut self, ident: &Identifier<'a>) -> visitor::Result<Error> {
-    if let Some(name) = self.eval_generic_rule {
+    if let Some(name) = &self.eval_generic_rule {
       if let Some(gr) = self
         .generic_rules
         .iter()
         .cloned()
-        .find(|gr| gr.name == name)
+        .find(|gr| gr.name == *name)
       {
         for (idx, gp) in gr.params.iter().enumerate() {
           if *gp == ident.ident {
@@ -1817,23 +2627,23 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
     // self.is_colon_shortcut_present is only true when the ident is part of a
     // member key
     if !self.is_colon_shortcut_present {
-      if let Some(r) = rule_from_ident(self.cddl, ident) {
+      if let Some(r) = self.lookup().rule_from_ident(ident) {
         return self.visit_rule(r);
       }
     }
 
-    if is_ident_any_type(self.cddl, ident) {
+    if self.lookup().is_ident_any_type(ident) {
       return Ok(());
     }
 
     match &self.json {
-      Value::Null if is_ident_null_data_type(self.cddl, ident) => Ok(()),
+      Value::Null if self.lookup().is_ident_null_data_type(ident) => Ok(()),
       Value::Bool(b) => {
-        if is_ident_bool_data_type(self.cddl, ident) {
+        if self.lookup().is_ident_bool_data_type(ident) {
           return Ok(());
         }
 
-        if ident_matches_bool_value(self.cddl, ident, *b) {
+        if self.lookup().ident_matches_bool_value(ident, *b) {
           return Ok(());
         }
 
@@ -1841,15 +2651,15 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
         Ok(())
       }
       Value::Number(n) => {
-        if is_ident_uint_data_type(self.cddl, ident) && n.is_u64() {
+        if self.lookup().is_ident_uint_data_type(ident) && n.is_u64() {
           return Ok(());
-        } else if is_ident_nint_data_type(self.cddl, ident) {
+        } else if self.lookup().is_ident_nint_data_type(ident) {
           if let Some(n) = n.as_i64() {
             if n.is_negative() {
               return Ok(());
             }
           }
-        } else if is_ident_time_data_type(self.cddl, ident) {
+        } else if self.lookup().is_ident_time_data_type(ident) {
           if let Some(n) = n.as_i64() {
             if let chrono::LocalResult::None = Utc.timestamp_millis_opt(n * 1000) {
               self.add_error(format!(
@@ -1868,8 +2678,8 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
               ));
             }
           }
-        } else if (is_ident_integer_data_type(self.cddl, ident) && n.is_i64())
-          || (is_ident_float_data_type(self.cddl, ident) && n.is_f64())
+        } else if (self.lookup().is_ident_integer_data_type(ident) && n.is_i64())
+          || (self.lookup().is_ident_float_data_type(ident) && n.is_f64())
         {
           return Ok(());
         }
@@ -1878,22 +2688,29 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
         Ok(())
       }
       Value::String(s) => {
-        if is_ident_uri_data_type(self.cddl, ident) {
+        if self.lookup().is_ident_uri_data_type(ident) {
           if let Err(e) = uriparse::URI::try_from(&**s) {
             self.add_error(format!("expected URI data type, decoding error: {}", e));
           }
-        } else if is_ident_b64url_data_type(self.cddl, ident) {
+        } else if self.lookup().is_ident_b64url_data_type(ident) {
           if let Err(e) = base64_url::decode(s) {
             self.add_error(format!(
               "expected base64 URL data type, decoding error: {}",
               e
             ));
           }
-        } else if is_ident_tdate_data_type(self.cddl, ident) {
+        } else if self.lookup().is_ident_tdate_data_type(ident) {
           if let Err(e) = chrono::DateTime::parse_from_rfc3339(s) {
             self.add_error(format!("expected tdate data type, decoding error: {}", e));
           }
-        } else if is_ident_string_data_type(self.cddl, ident) {
+        } else if self.lookup().is_ident_byte_string_data_type(ident) {
+          if let Err(e) = self.byte_string_encoding.decode(s) {
+            self.add_error(format!(
+              "expected {} encoded byte string data type, decoding error: {}",
+              self.byte_string_encoding, e
+            ));
+          }
+        } else if self.lookup().is_ident_string_data_type(ident) {
           return Ok(());
         } else {
           self.add_error(format!("expected type {}, got {}", ident, self.json));
@@ -1905,7 +2722,7 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
       Value::Object(o) => match &self.occurrence {
         #[cfg(feature = "ast-span")]
         Some(Occur::Optional { .. }) | None => {
-          if token::lookup_ident(ident.ident)
+          if token::lookup_ident(&ident.ident)
             .in_standard_prelude()
             .is_some()
           {
@@ -1916,11 +2733,11 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
             return Ok(());
           }
 
-          self.visit_value(&token::Value::TEXT(ident.ident.into()))
+          self.visit_value(&token::Value::TEXT(ident.ident.clone()))
         }
         #[cfg(not(feature = "ast-span"))]
         Some(Occur::Optional {}) | None => {
-          if token::lookup_ident(ident.ident)
+          if token::lookup_ident(&ident.ident)
             .in_standard_prelude()
             .is_some()
           {
@@ -1931,10 +2748,10 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
             return Ok(());
           }
 
-          self.visit_value(&token::Value::TEXT(ident.ident.into()))
+          self.visit_value(&token::Value::TEXT(ident.ident.clone()))
         }
         Some(occur) => {
-          if is_ident_string_data_type(self.cddl, ident) {
+          if self.lookup().is_ident_string_data_type(ident) {
             let values_to_validate = o
               .iter()
               .filter_map(|(k, v)| match &self.validated_keys {
@@ -2109,13 +2926,16 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
         let mut jv = JSONValidator::new(self.cddl, v.clone(), self.enabled_features);
         #[cfg(not(feature = "additional-controls"))]
         let mut jv = JSONValidator::new(self.cddl, v.clone());
+        jv.compiled = self.compiled;
+        jv.custom_controls = self.custom_controls;
 
         jv.generic_rules = self.generic_rules.clone();
-        jv.eval_generic_rule = self.eval_generic_rule;
+        jv.byte_string_encoding = self.byte_string_encoding;
+        jv.eval_generic_rule = self.eval_generic_rule.clone();
         jv.is_multi_type_choice = self.is_multi_type_choice;
         jv.is_multi_group_choice = self.is_multi_group_choice;
         jv.json_location.push_str(&self.json_location);
-        jv.type_group_name_entry = self.type_group_name_entry;
+        jv.type_group_name_entry = self.type_group_name_entry.clone();
         jv.visit_type(&entry.entry_type)?;
 
         self.json_location = current_location.clone();
@@ -2136,13 +2956,16 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
       let mut jv = JSONValidator::new(self.cddl, v, self.enabled_features);
       #[cfg(not(feature = "additional-controls"))]
       let mut jv = JSONValidator::new(self.cddl, v);
+      jv.compiled = self.compiled;
+      jv.custom_controls = self.custom_controls;
 
       jv.generic_rules = self.generic_rules.clone();
-      jv.eval_generic_rule = self.eval_generic_rule;
+      jv.byte_string_encoding = self.byte_string_encoding;
+      jv.eval_generic_rule = self.eval_generic_rule.clone();
       jv.is_multi_type_choice = self.is_multi_type_choice;
       jv.is_multi_group_choice = self.is_multi_group_choice;
       jv.json_location.push_str(&self.json_location);
-      jv.type_group_name_entry = self.type_group_name_entry;
+      jv.type_group_name_entry = self.type_group_name_entry.clone();
       jv.visit_type(&entry.entry_type)?;
 
       self.json_location = current_location;
@@ -2164,10 +2987,10 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
     &mut self,
     entry: &TypeGroupnameEntry<'a>,
   ) -> visitor::Result<Error> {
-    self.type_group_name_entry = Some(entry.name.ident);
+    self.type_group_name_entry = Some(entry.name.ident.clone());
 
     if let Some(ga) = &entry.generic_args {
-      if let Some(rule) = rule_from_ident(self.cddl, &entry.name) {
+      if let Some(rule) = self.lookup().rule_from_ident(&entry.name) {
         if let Some(gr) = self
           .generic_rules
           .iter_mut()
@@ -2178,7 +3001,7 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
           }
         } else if let Some(params) = generic_params_from_rule(rule) {
           self.generic_rules.push(GenericRule {
-            name: entry.name.ident,
+            name: entry.name.ident.clone(),
             params,
             args: ga.args.iter().cloned().map(|arg| *arg.arg).collect(),
           });
@@ -2191,9 +3014,12 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
         let mut jv = JSONValidator::new(self.cddl, self.json.clone(), self.enabled_features);
         #[cfg(not(feature = "additional-controls"))]
         let mut jv = JSONValidator::new(self.cddl, self.json.clone());
+        jv.compiled = self.compiled;
+        jv.custom_controls = self.custom_controls;
 
         jv.generic_rules = self.generic_rules.clone();
-        jv.eval_generic_rule = Some(entry.name.ident);
+        jv.byte_string_encoding = self.byte_string_encoding;
+        jv.eval_generic_rule = Some(entry.name.ident.clone());
         jv.is_multi_type_choice = self.is_multi_type_choice;
         jv.visit_rule(rule)?;
 
@@ -2203,7 +3029,7 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
       }
     }
 
-    let type_choice_alternates = type_choice_alternates_from_ident(self.cddl, &entry.name);
+    let type_choice_alternates = self.lookup().type_choice_alternates_from_ident(&entry.name);
     if !type_choice_alternates.is_empty() {
       self.is_multi_type_choice = true;
     }
@@ -2222,7 +3048,9 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
     }
 
     let error_count = self.errors.len();
-    let group_choice_alternates = group_choice_alternates_from_ident(self.cddl, &entry.name);
+    let group_choice_alternates = self
+      .lookup()
+      .group_choice_alternates_from_ident(&entry.name);
     if !group_choice_alternates.is_empty() {
       self.is_multi_group_choice = true;
     }
@@ -2330,6 +3158,10 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
               Some(n) if (i as u128) < n => None,
               _ => Some(format!("expected value .size {}, got {}", v, n)),
             },
+            Some(ControlOperator::BITS) => match 1u64.checked_shl(*v as u32) {
+              Some(sv) if i & sv != 0 => None,
+              _ => Some(format!("expected uint .bits {}, got {}", v, n)),
+            },
             #[cfg(feature = "additional-controls")]
             Some(ControlOperator::PLUS) => {
               if i == *v as u64 {
@@ -2363,8 +3195,21 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
           },
           None => Some(format!("{} cannot be represented as a u64", n)),
         },
-        Value::String(s) => match &self.ctrl {
-          Some(ControlOperator::SIZE) => {
+        Value::String(s) => match (&self.ctrl, &self.byte_string) {
+          (Some(ControlOperator::SIZE), Some(b)) => {
+            if b.len() == *v {
+              None
+            } else {
+              Some(format!("expected \"{}\" .size {}, got {}", s, v, b.len()))
+            }
+          }
+          (Some(ControlOperator::BITS), Some(b)) => {
+            match (b.get(v >> 3), 1u8.checked_shl(*v as u32 & 7)) {
+              (Some(byte), Some(bit)) if byte & bit != 0 => None,
+              _ => Some(format!("expected \"{}\" .bits {}, got {:?}", s, v, b)),
+            }
+          }
+          (Some(ControlOperator::SIZE), None) => {
             if s.len() == *v {
               None
             } else {
@@ -2432,18 +3277,25 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
             }
           }
           Some(ControlOperator::REGEXP) | Some(ControlOperator::PCRE) => {
-            let re = regex::Regex::new(
-              &format_regex(
-                // Text strings must be JSON escaped per
-                // https://datatracker.ietf.org/doc/html/rfc8610#section-3.1
-                serde_json::from_str::<Value>(&format!("\"{}\"", t))
-                  .map_err(Error::JSONParsing)?
-                  .as_str()
+            let built;
+            let re = match self.compiled.and_then(|c| c.regex(t)) {
+              Some(re) => re,
+              None => {
+                built = regex::Regex::new(
+                  &format_regex(
+                    // Text strings must be JSON escaped per
+                    // https://datatracker.ietf.org/doc/html/rfc8610#section-3.1
+                    serde_json::from_str::<Value>(&format!("\"{}\"", t))
+                      .map_err(Error::JSONParsing)?
+                      .as_str()
+                      .ok_or_else(|| Error::from_validator(self, "malformed regex".to_string()))?,
+                  )
                   .ok_or_else(|| Error::from_validator(self, "malformed regex".to_string()))?,
-              )
-              .ok_or_else(|| Error::from_validator(self, "malformed regex".to_string()))?,
-            )
-            .map_err(|e| Error::from_validator(self, e.to_string()))?;
+                )
+                .map_err(|e| Error::from_validator(self, e.to_string()))?;
+                &built
+              }
+            };
 
             if re.is_match(s) {
               None
@@ -2452,9 +3304,23 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
             }
           }
           #[cfg(feature = "additional-controls")]
-          Some(ControlOperator::ABNF) => validate_abnf(t, s)
+          Some(ControlOperator::ABNF) => self
+            .validate_abnf(t, s)
             .err()
             .map(|e| format!("\"{}\" is not valid against abnf: {}", s, e)),
+          #[cfg(feature = "additional-controls")]
+          Some(ControlOperator::ABNFB) => match &self.byte_string {
+            Some(b) => self
+              .validate_abnf(t, std::str::from_utf8(b).map_err(Error::UTF8Parsing)?)
+              .err()
+              .map(|e| format!("bytes \"{}\" are not valid against abnf {}: {}", s, t, e)),
+            None => Some(format!(
+              "expected value {} {}, got \"{}\"",
+              ControlOperator::ABNFB,
+              value,
+              s
+            )),
+          },
 
           _ => {
             #[cfg(feature = "additional-controls")]
@@ -2483,17 +3349,12 @@ impl<'a, 'b> Visitor<'a, 'b, Error> for JSONValidator<'a> {
         },
         _ => Some(format!("expected value {}, got {}", t, self.json)),
       },
-      token::Value::BYTE(token::ByteValue::UTF8(b)) => match &self.json {
-        Value::String(s) if s.as_bytes() == b.as_ref() => None,
-        _ => Some(format!("expected byte value {:?}, got {}", b, self.json)),
-      },
-      token::Value::BYTE(token::ByteValue::B16(b)) => match &self.json {
-        Value::String(s) if s.as_bytes() == b.as_ref() => None,
-        _ => Some(format!("expected byte value {:?}, got {}", b, self.json)),
-      },
-      token::Value::BYTE(token::ByteValue::B64(b)) => match &self.json {
-        Value::String(s) if s.as_bytes() == b.as_ref() => None,
-        _ => Some(format!("expected byte value {:?}, got {}", b, self.json)),
+      token::Value::BYTE(b) => match &self.json {
+        Value::String(s) => match self.byte_string_encoding.decode(s) {
+          Ok(bytes) if byte_value_bytes(b).as_ref() == Some(&bytes) => None,
+          _ => Some(format!("expected byte value {}, got {}", b, self.json)),
+        },
+        _ => Some(format!("expected byte value {}, got {}", b, self.json)),
       },
     };
 
@@ -2785,4 +3646,602 @@ mod tests {
 
     Ok(())
   }
+
+  #[test]
+  fn validate_array_with_multiple_occurrence_indicators(
+  ) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = indoc!(
+      r#"
+        tester = [ * a: int, b: tstr, ? c: int ]
+      "#
+    );
+
+    let cddl = cddl_from_str(cddl, true).map_err(json::Error::CDDLParsing)?;
+
+    for json in [r#"["b"]"#, r#"[1, 2, "b"]"#, r#"[1, "b", 3]"#] {
+      let json =
+        serde_json::from_str::<serde_json::Value>(json).map_err(json::Error::JSONParsing)?;
+
+      let mut jv = JSONValidator::new(&cddl, json, None);
+      jv.validate()?;
+    }
+
+    for json in [r#"[]"#, r#"[1, 2]"#, r#"["b", 2, 3]"#, r#"[1, "b", "c"]"#] {
+      let json =
+        serde_json::from_str::<serde_json::Value>(json).map_err(json::Error::JSONParsing)?;
+
+      let mut jv = JSONValidator::new(&cddl, json, None);
+      assert!(jv.validate().is_err());
+    }
+
+    Ok(())
+  }
+
+  #[test]
+  fn validate_array_with_occurrence_on_groups(
+  ) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = indoc!(
+      r#"
+        tester = [ + pair, ? tstr, * int ]
+        pair = (key: tstr, value: int)
+      "#
+    );
+
+    let cddl = cddl_from_str(cddl, true).map_err(json::Error::CDDLParsing)?;
+
+    for json in [
+      r#"["a", 1]"#,
+      r#"["a", 1, "b", 2, "c"]"#,
+      r#"["a", 1, "b", 2, 3, 4]"#,
+    ] {
+      let json =
+        serde_json::from_str::<serde_json::Value>(json).map_err(json::Error::JSONParsing)?;
+
+      let mut jv = JSONValidator::new(&cddl, json, None);
+      jv.validate()?;
+    }
+
+    let json = serde_json::from_str::<serde_json::Value>(r#"["a", 1, 2, "b"]"#)
+      .map_err(json::Error::JSONParsing)?;
+
+    let mut jv = JSONValidator::new(&cddl, json, None);
+    if let Err(json::Error::Validation(errors)) = jv.validate() {
+      assert_eq!(errors.len(), 1);
+    } else {
+      panic!("expected array validation error");
+    }
+
+    Ok(())
+  }
+
+  #[test]
+  fn validate_rfc8610_array_examples() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let examples = [
+      // RFC 8610, Section 3.4
+      (
+        indoc!(
+          r#"
+            Geography = [
+              city           : tstr,
+              gpsCoordinates : GpsCoordinates,
+            ]
+
+            GpsCoordinates = {
+              longitude      : uint,            ; degrees, scaled by 10^7
+              latitude       : uint,            ; degrees, scaled by 10^7
+            }
+          "#
+        ),
+        &[r#"["Vienna", { "longitude": 163738950, "latitude": 482083330 }]"#][..],
+        &[
+          r#"[{ "longitude": 163738950, "latitude": 482083330 }, "Vienna"]"#,
+          r#"["Vienna"]"#,
+          r#"["Vienna", { "longitude": 163738950, "latitude": 482083330 }, "Austria"]"#,
+        ][..],
+      ),
+      // RFC 8610, Section 3.7
+      (
+        indoc!(
+          r#"
+            advanced-header = [
+              ~basic-header,
+              field3: bytes,
+              field4: ~time,
+            ]
+
+            basic-header = [
+              field1: int,
+              field2: text,
+            ]
+          "#
+        ),
+        &[r#"[1, "a", "AQI", 1]"#, r#"[1, "a", "AQI", 1.5]"#][..],
+        &[
+          r#"[[1, "a"], "AQI", 1]"#,
+          r#"[1, "a", "AQI"]"#,
+          r#"["a", 1, "AQI", 1]"#,
+          r#"[1, "a", "AQI", "now"]"#,
+        ][..],
+      ),
+      // RFC 8610, Appendix H
+      (
+        include_str!("../../tests/fixtures/cddl/reputon.cddl"),
+        &[
+          r#"{ "application": "conformance", "reputons": [] }"#,
+          r#"{
+            "application": "conformance",
+            "reputons": [
+              { "rater": "a", "assertion": "b", "rated": "c", "rating": 0.5 },
+              { "rater": "d", "assertion": "e", "rated": "f", "rating": 0.25, "sample-size": 2 }
+            ]
+          }"#,
+        ][..],
+        &[
+          r#"{ "application": "conformance", "reputons": [{ "rater": "a" }] }"#,
+          r#"{ "application": "conformance", "reputons": [1] }"#,
+        ][..],
+      ),
+    ];
+
+    for (cddl, valid, invalid) in examples.iter() {
+      let cddl = cddl_from_str(cddl, true).map_err(json::Error::CDDLParsing)?;
+
+      for json in valid.iter() {
+        let json =
+          serde_json::from_str::<serde_json::Value>(json).map_err(json::Error::JSONParsing)?;
+
+        let mut jv = JSONValidator::new(&cddl, json, None);
+        jv.validate()?;
+      }
+
+      for json in invalid.iter() {
+        let json =
+          serde_json::from_str::<serde_json::Value>(json).map_err(json::Error::JSONParsing)?;
+
+        let mut jv = JSONValidator::new(&cddl, json, None);
+        assert!(jv.validate().is_err());
+      }
+    }
+
+    Ok(())
+  }
+
+  #[test]
+  fn validate_against_named_rule() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = indoc!(
+      r#"
+        request = { id: uint, method: tstr }
+        response = { id: uint, result: any }
+        header = (version: uint, kind: tstr)
+        pair<K, V> = [K, V]
+      "#
+    );
+
+    let cddl = cddl_from_str(cddl, true).map_err(json::Error::CDDLParsing)?;
+
+    let json = serde_json::from_str::<serde_json::Value>(r#"{ "id": 1, "result": null }"#)
+      .map_err(json::Error::JSONParsing)?;
+
+    let mut jv = JSONValidator::new(&cddl, json.clone(), None);
+    assert!(jv.validate().is_err());
+
+    let mut jv = JSONValidator::new(&cddl, json, None);
+    jv.validate_rule("response")?;
+
+    for json in [r#"{ "version": 1, "kind": "a" }"#, r#"[1, "a"]"#] {
+      let json =
+        serde_json::from_str::<serde_json::Value>(json).map_err(json::Error::JSONParsing)?;
+
+      let mut jv = JSONValidator::new(&cddl, json, None);
+      jv.validate_rule("header")?;
+    }
+
+    let json =
+      serde_json::from_str::<serde_json::Value>(r#"["a", 1]"#).map_err(json::Error::JSONParsing)?;
+
+    let mut jv = JSONValidator::new(&cddl, json.clone(), None);
+    assert!(jv.validate_rule("header").is_err());
+
+    let mut jv = JSONValidator::new(&cddl, json.clone(), None);
+    assert!(matches!(
+      jv.validate_rule("notification"),
+      Err(json::Error::MissingRule(_))
+    ));
+
+    let mut jv = JSONValidator::new(&cddl, json, None);
+    assert!(matches!(
+      jv.validate_rule("pair"),
+      Err(json::Error::MissingRule(_))
+    ));
+
+    Ok(())
+  }
+
+  #[test]
+  fn serialize_validation_errors() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = indoc!(
+      r#"
+        person = { name: tstr, age: uint }
+      "#
+    );
+
+    let cddl = cddl_from_str(cddl, true).map_err(json::Error::CDDLParsing)?;
+    let json = serde_json::from_str::<serde_json::Value>(r#"{ "name": "John", "age": "fifty" }"#)
+      .map_err(json::Error::JSONParsing)?;
+
+    let mut jv = JSONValidator::new(&cddl, json, None);
+    if let Err(json::Error::Validation(errors)) = jv.validate() {
+      let errors = serde_json::to_value(&errors)?;
+      assert_eq!(errors[0]["json_location"], "/age");
+      assert_eq!(errors[0]["is_multi_type_choice"], false);
+    } else {
+      panic!("expected validation error");
+    }
+
+    Ok(())
+  }
+
+  #[test]
+  fn validate_duplicate_object_members() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = "thing = { * tstr => any }";
+
+    validate_json_from_str(cddl, r#"{ "a": 1, "b": { "a": 2 } }"#, None)?;
+
+    let json = r#"{ "a": 1, "b": { "c": 2, "c": 3 }, "a": [{ "d": 4, "d": 5 }] }"#;
+    let (value, duplicate_keys) = from_str_with_duplicate_keys(json)?;
+    assert_eq!(value, serde_json::from_str::<Value>(json)?);
+    assert_eq!(
+      duplicate_keys
+        .iter()
+        .map(|dk| (dk.location.as_str(), dk.key.as_str()))
+        .collect::<Vec<_>>(),
+      vec![("/b", "c"), ("", "a"), ("/a/0", "d")]
+    );
+
+    match validate_json_from_str(cddl, json, None) {
+      Err(Error::Validation(errors)) => assert_eq!(errors.len(), 3),
+      r => panic!("expected validation errors, got {:?}", r),
+    }
+
+    Ok(())
+  }
+
+  #[test]
+  fn validate_byte_strings() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = indoc!(
+      r#"
+        thing = {
+          id: bstr .size 4,
+          ? flags: bytes .bits (0 / 9),
+          ? salt: bstr .size (1..4),
+          ? kid: h'0102' / b64'AwQ' / 'kid',
+        }
+      "#
+    );
+    let cddl = cddl_from_str(cddl, true)?;
+
+    let validate = |json: &str, encoding| -> Vec<String> {
+      #[cfg(feature = "additional-controls")]
+      let jv = JSONValidator::new(&cddl, serde_json::from_str(json).unwrap(), None);
+      #[cfg(not(feature = "additional-controls"))]
+      let jv = JSONValidator::new(&cddl, serde_json::from_str(json).unwrap());
+
+      match jv.with_byte_string_encoding(encoding).validate() {
+        Ok(()) => Vec::new(),
+        Err(Error::Validation(errors)) => errors.into_iter().map(|e| e.json_location).collect(),
+        Err(e) => panic!("unexpected error {}", e),
+      }
+    };
+
+    let b64 = ByteStringEncoding::Base64Url;
+    assert!(validate(r#"{ "id": "AQIDBA" }"#, b64).is_empty());
+    assert!(validate(r#"{ "id": "AQIDBA==" }"#, b64).is_empty());
+    assert!(validate(r#"{ "id": "AQIDBA", "flags": "AQ", "kid": "AQI" }"#, b64).is_empty());
+    assert!(validate(r#"{ "id": "AQIDBA", "flags": "AAI", "kid": "AwQ" }"#, b64).is_empty());
+    assert!(validate(r#"{ "id": "AQIDBA", "salt": "AQ", "kid": "a2lk" }"#, b64).is_empty());
+    assert_eq!(validate(r#"{ "id": "AQID" }"#, b64), vec!["/id"]);
+    assert_eq!(validate(r#"{ "id": "01020304" }"#, b64), vec!["/id"]);
+    assert_eq!(validate(r#"{ "id": "AQ+DBA" }"#, b64), vec!["/id"]);
+    assert_eq!(
+      validate(r#"{ "id": "AQIDBA", "flags": "Ag" }"#, b64),
+      vec!["/flags", "/flags"]
+    );
+    assert_eq!(
+      validate(r#"{ "id": "AQIDBA", "salt": "AQIDBAU" }"#, b64),
+      vec!["/salt"]
+    );
+    assert!(!validate(r#"{ "id": "AQIDBA", "kid": "kid" }"#, b64).is_empty());
+
+    let b16 = ByteStringEncoding::Base16;
+    assert!(validate(r#"{ "id": "01020304", "kid": "0102" }"#, b16).is_empty());
+    assert!(validate(r#"{ "id": "0A0b0C0d", "flags": "0002" }"#, b16).is_empty());
+    assert_eq!(validate(r#"{ "id": "AQIDBA" }"#, b16), vec!["/id"]);
+
+    Ok(())
+  }
+
+  #[cfg(feature = "additional-controls")]
+  #[test]
+  fn validate_embedded_cbor_and_abnfb() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = indoc!(
+      r#"
+        thing = {
+          ? data: bstr .cbor inner,
+          ? seq: bstr .cborseq [* int],
+          ? code: bytes .abnfb ("code" .det rules),
+        }
+
+        inner = [int, tstr]
+
+        rules = '
+          code = 1*digit
+          digit = %x30-39
+        '
+      "#
+    );
+
+    // [1, "a"]
+    validate_json_from_str(cddl, r#"{ "data": "ggFhYQ" }"#, None)?;
+    // 1, 2
+    validate_json_from_str(cddl, r#"{ "seq": "AQI" }"#, None)?;
+    // empty sequence
+    validate_json_from_str(cddl, r#"{ "seq": "" }"#, None)?;
+    validate_json_from_str(cddl, r#"{ "code": "MTIz" }"#, None)?;
+
+    let errors = |json: &str| match validate_json_from_str(cddl, json, None) {
+      Err(Error::Validation(errors)) => errors,
+      r => panic!("expected validation errors, got {:?}", r),
+    };
+
+    // [1, 2]
+    let e = errors(r#"{ "data": "ggEC" }"#);
+    assert_eq!(e.len(), 1);
+    assert_eq!(e[0].json_location, "/data");
+    assert!(e[0].reason.starts_with("embedded CBOR at /1: "));
+    // truncated [1, "a"]
+    assert!(errors(r#"{ "data": "ggFh" }"#)[0]
+      .reason
+      .contains("error decoding embedded CBOR"));
+    // [1, "a"]
+    assert_eq!(errors(r#"{ "seq": "ggFhYQ" }"#)[0].json_location, "/seq");
+    // [1, 2], a single array rather than a sequence of integers
+    assert_eq!(errors(r#"{ "seq": "ggEC" }"#)[0].json_location, "/seq");
+    // 1, truncated [1, 2]
+    assert!(errors(r#"{ "seq": "AYIB" }"#)[0]
+      .reason
+      .contains("error decoding embedded CBOR sequence"));
+    // "abc"
+    assert_eq!(errors(r#"{ "code": "YWJj" }"#)[0].json_location, "/code");
+    assert_eq!(errors(r#"{ "data": 1 }"#)[0].json_location, "/data");
+
+    Ok(())
+  }
+
+  #[cfg(feature = "additional-controls")]
+  #[test]
+  fn validate_text_conversion_controls() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = indoc!(
+      r#"
+        thing = {
+          ? sig: text .b64u (bstr .size 4),
+          ? id: tstr .hexlc h'0102',
+          ? name: tstr .b45 'AB',
+          ? key: tstr .b64c-sloppy short,
+        }
+
+        short = bytes .size (1..2)
+      "#
+    );
+
+    validate_json_from_str(
+      cddl,
+      r#"{ "sig": "AQIDBA", "id": "0102", "name": "BB8", "key": "AR==" }"#,
+      None,
+    )?;
+
+    for json in [
+      r#"{ "sig": "AQIDBA==" }"#,
+      r#"{ "sig": "AQID" }"#,
+      r#"{ "id": "0A02" }"#,
+      r#"{ "name": "BB9" }"#,
+      r#"{ "key": "AQID" }"#,
+    ]
+    .iter()
+    {
+      match validate_json_from_str(cddl, json, None) {
+        Err(Error::Validation(errors)) => assert_eq!(errors.len(), 1, "{}", json),
+        r => panic!("expected validation error for {}, got {:?}", json, r),
+      }
+    }
+
+    Ok(())
+  }
+
+  #[cfg(feature = "additional-controls")]
+  #[test]
+  fn validate_decimal_and_printf_controls() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = indoc!(
+      r#"
+        thing = {
+          ? count: text .decimal (0..255),
+          ? offset: text .decimal int,
+          ? code: "0x%04x" .printf [4711],
+          ? label: "id-" .cat ("%02d" .printf [7]),
+        }
+      "#
+    );
+
+    validate_json_from_str(
+      cddl,
+      r#"{ "count": "255", "offset": "-12", "code": "0x1267", "label": "id-07" }"#,
+      None,
+    )?;
+
+    for json in [
+      r#"{ "count": "256" }"#,
+      r#"{ "count": "042" }"#,
+      r#"{ "offset": "-0" }"#,
+      r#"{ "code": "0x1268" }"#,
+      r#"{ "label": "id-7" }"#,
+    ]
+    .iter()
+    {
+      match validate_json_from_str(cddl, json, None) {
+        Err(Error::Validation(errors)) => assert_eq!(errors.len(), 1, "{}", json),
+        r => panic!("expected validation error for {}, got {:?}", json, r),
+      }
+    }
+
+    Ok(())
+  }
+
+  #[cfg(feature = "additional-controls")]
+  #[test]
+  fn validate_json_and_join_controls() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = indoc!(
+      r#"
+        thing = {
+          ? doc: text .json { name: tstr, ? tags: [* tstr] },
+          ? address: text .join ip-address-elements,
+        }
+
+        ip-address-elements = [bytetext, ".", bytetext, ".", bytetext, ".", bytetext]
+        bytetext = text .decimal byte
+        byte = 0..255
+      "#
+    );
+
+    validate_json_from_str(
+      cddl,
+      r#"{ "doc": "{ \"name\": \"foo\", \"tags\": [\"a\"] }", "address": "192.0.2.1" }"#,
+      None,
+    )?;
+
+    for json in [
+      r#"{ "doc": "{ \"name\": 1 }" }"#,
+      r#"{ "doc": "{ name: 1 }" }"#,
+      r#"{ "address": "192.0.2.256" }"#,
+      r#"{ "address": "192.0.2" }"#,
+      r#"{ "address": "192.0.02.1" }"#,
+    ]
+    .iter()
+    {
+      match validate_json_from_str(cddl, json, None) {
+        Err(Error::Validation(errors)) => assert_eq!(errors.len(), 1, "{}", json),
+        r => panic!("expected validation error for {}, got {:?}", json, r),
+      }
+    }
+
+    Ok(())
+  }
+
+  #[test]
+  fn validate_custom_control_operator() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let mut controls = CustomControls::new();
+    controls.register(".oid", |value, _, controller| match value {
+      ControlValue::JSON(Value::String(s)) => match controller {
+        Type2::TextValue { value: arc, .. } if s.starts_with(&format!("{}.", arc)) => Ok(()),
+        _ => Err(format!("expected OID under arc {}, got {}", controller, s)),
+      },
+      _ => Err(format!("expected OID, got {:?}", value)),
+    })?;
+
+    let cddl = indoc!(
+      r#"
+        thing = {
+          algorithm: tstr .oid "1.2.840",
+        }
+      "#
+    );
+    let cddl = controls.cddl_from_str(cddl)?;
+
+    let validate = |json: &str, controls: &CustomControls| {
+      #[cfg(feature = "additional-controls")]
+      let jv = JSONValidator::new(&cddl, serde_json::from_str(json).unwrap(), None);
+      #[cfg(not(feature = "additional-controls"))]
+      let jv = JSONValidator::new(&cddl, serde_json::from_str(json).unwrap());
+
+      match jv.with_custom_controls(controls).validate() {
+        Ok(()) => Vec::new(),
+        Err(Error::Validation(errors)) => errors.into_iter().map(|e| e.reason).collect(),
+        Err(e) => panic!("unexpected error {}", e),
+      }
+    };
+
+    assert!(validate(r#"{ "algorithm": "1.2.840.10045" }"#, &controls).is_empty());
+    assert_eq!(
+      validate(r#"{ "algorithm": "1.3.101" }"#, &controls),
+      vec!["expected OID under arc \"1.2.840\", got 1.3.101"]
+    );
+    // The callback is not invoked for data items not matching the target
+    assert_eq!(validate(r#"{ "algorithm": 1 }"#, &controls).len(), 1);
+
+    // Removed control operators are no longer validated
+    let mut removed = controls.clone();
+    assert!(removed.remove(".oid"));
+    assert_eq!(
+      validate(r#"{ "algorithm": "1.2.840.10045" }"#, &removed),
+      vec!["control operator .oid is not registered"]
+    );
+    assert!(cddl_from_str(r#"algorithm = tstr .oid "1.2.840""#, false).is_err());
+
+    // Predefined control operators cannot be replaced
+    assert!(controls.register(".size", |_, _, _| Ok(())).is_err());
+    assert!(controls.register("oid", |_, _, _| Ok(())).is_err());
+
+    Ok(())
+  }
+
+  #[test]
+  fn validate_date_tags() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let cddl = indoc!(
+      r#"
+        person = {
+          birthday: #6.1004(tstr),
+          ? registered: #6.100(int),
+          ? updated: #6.0(tstr),
+        }
+      "#
+    );
+
+    validate_json_from_str(
+      cddl,
+      r#"{ "birthday": "1985-04-12", "registered": 19000, "updated": "2022-01-02T03:04:05Z" }"#,
+      None,
+    )?;
+
+    for (json, reason) in [
+      (
+        r#"{ "birthday": "1985-4-12" }"#,
+        "invalid content of tag 1004, expected full-date of the form YYYY-MM-DD, got \"1985-4-12\"",
+      ),
+      (
+        r#"{ "birthday": "1985-02-30" }"#,
+        "invalid content of tag 1004, invalid full-date \"1985-02-30\", input is out of range",
+      ),
+      (
+        r#"{ "birthday": "1985-04-12", "updated": "2022-01-02" }"#,
+        "invalid content of tag 0, invalid standard date/time string \"2022-01-02\", premature end of input",
+      ),
+    ] {
+      match validate_json_from_str(cddl, json, None) {
+        Err(Error::Validation(errors)) => {
+          assert_eq!(errors.len(), 1);
+          assert_eq!(errors[0].reason, reason);
+        }
+        r => panic!("expected validation error, got {:?}", r),
+      }
+    }
+
+    match validate_json_from_str(
+      cddl,
+      r#"{ "birthday": "1985-04-12", "registered": "19000" }"#,
+      None,
+    ) {
+      Err(Error::Validation(errors)) => assert_eq!(errors.len(), 1),
+      r => panic!("expected validation error, got {:?}", r),
+    }
+
+    Ok(())
+  }
 }
@@ -0,0 +1,329 @@
+#![cfg(feature = "json")]
+#![cfg(not(feature = "lsp"))]
+#![cfg(not(target_arch = "wasm32"))]
+
+use super::*;
+use crate::ast::CDDL;
+
+use std::{
+  fmt::{self, Write},
+  io::BufRead,
+};
+
+use serde_json::Value;
+
+use super::compiled::CompiledSchema;
+
+/// Record separator preceding each JSON text of a JSON text sequence
+const RS: u8 = 0x1e;
+
+/// Format of a file or stream holding multiple JSON texts, each of which is
+/// validated as a separate record
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+  /// JSON Lines, also known as newline-delimited JSON (NDJSON), with one JSON
+  /// text per line. Blank lines are ignored
+  Lines,
+  /// JSON text sequence (RFC 7464), with each JSON text preceded by the record
+  /// separator 0x1E and usually followed by a line feed
+  TextSequence,
+}
+
+/// Error of a single record of a JSON Lines file or JSON text sequence
+#[derive(Debug)]
+pub struct RecordError {
+  /// Number of the record, starting at 1
+  pub record: usize,
+  /// Line on which the record starts, starting at 1
+  pub line: usize,
+  /// Error parsing or validating the record. The locations of validation
+  /// errors are relative to the record
+  pub error: json::Error,
+}
+
+impl fmt::Display for RecordError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "record {} (line {}): {}",
+      self.record,
+      self.line,
+      self.error.to_string().trim_end()
+    )
+  }
+}
+
+/// Error validating a JSON Lines file or JSON text sequence
+#[derive(Debug)]
+pub enum Error {
+  /// Records failing to parse or validate
+  Records(Vec<RecordError>),
+  /// Error not specific to a record, such as a CDDL parsing error or a
+  /// missing rule
+  JSON(json::Error),
+  /// Error reading the input
+  Io(std::io::Error),
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Error::Records(errors) => {
+        let mut error_str = String::new();
+        for e in errors.iter() {
+          let _ = writeln!(error_str, "{}", e);
+        }
+        write!(f, "{}", error_str)
+      }
+      Error::JSON(error) => write!(f, "{}", error),
+      Error::Io(error) => write!(f, "error reading records: {}", error),
+    }
+  }
+}
+
+impl std::error::Error for Error {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Error::JSON(error) => Some(error),
+      Error::Io(error) => Some(error),
+      _ => None,
+    }
+  }
+}
+
+/// Result of validating a JSON Lines file or JSON text sequence
+pub type Result = std::result::Result<(), Error>;
+
+/// Validate each record read from the given reader against the type or group
+/// rule with the given name, if any, instead of the root type rule. Records
+/// are validated independently of each other, so validation continues past
+/// records that fail to parse or validate, and the errors of all failing
+/// records are returned along with their record and line numbers
+///
+/// # Example
+///
+/// ```
+/// use cddl::{cddl_from_str, validator::json_seq::{validate_records, Error, Format}};
+///
+/// let cddl = cddl_from_str("entry = { level: tstr, msg: tstr }", true).unwrap();
+/// let lines = "{ \"level\": \"info\", \"msg\": \"a\" }\n{ \"level\": 1, \"msg\": \"b\" }\n";
+///
+/// match validate_records(&cddl, lines.as_bytes(), Format::Lines, None, None) {
+///   Err(Error::Records(errors)) => assert_eq!((errors[0].record, errors[0].line), (2, 2)),
+///   r => panic!("expected record errors, got {:?}", r),
+/// }
+/// ```
+pub fn validate_records<R: BufRead>(
+  cddl: &CDDL,
+  reader: R,
+  format: Format,
+  rule: Option<&str>,
+  #[cfg(feature = "additional-controls")] enabled_features: Option<&[&str]>,
+) -> Result {
+  if let Some(rule) = rule {
+    root_rule_from_name(cddl, rule).map_err(|e| Error::JSON(json::Error::MissingRule(e)))?;
+  }
+
+  let compiled = CompiledSchema::new(cddl);
+
+  let mut errors = Vec::new();
+  for record in Records::new(reader, format) {
+    let record = record.map_err(Error::Io)?;
+
+    let result = record.parse(format).and_then(|(json, duplicate_keys)| {
+      #[cfg(feature = "additional-controls")]
+      let jv = JSONValidator::new(cddl, json, enabled_features);
+      #[cfg(not(feature = "additional-controls"))]
+      let jv = JSONValidator::new(cddl, json);
+
+      let mut jv = jv
+        .with_duplicate_keys(duplicate_keys)
+        .with_compiled_schema(&compiled);
+      match rule {
+        Some(rule) => jv.validate_rule(rule),
+        None => jv.validate(),
+      }
+    });
+
+    if let Err(error) = result {
+      errors.push(RecordError {
+        record: record.number,
+        line: record.line,
+        error,
+      });
+    }
+  }
+
+  if errors.is_empty() {
+    Ok(())
+  } else {
+    Err(Error::Records(errors))
+  }
+}
+
+// JSON text read from a JSON Lines file or JSON text sequence
+struct Record {
+  number: usize,
+  line: usize,
+  data: Vec<u8>,
+  // Whether the JSON text of a JSON text sequence is not preceded by the
+  // record separator
+  is_unseparated: bool,
+}
+
+impl Record {
+  fn parse(
+    &self,
+    format: Format,
+  ) -> std::result::Result<(Value, Vec<json::DuplicateKey>), json::Error> {
+    if self.is_unseparated {
+      return Err(parsing_error(
+        "JSON text is not preceded by a record separator (0x1E)",
+      ));
+    }
+
+    let text = std::str::from_utf8(&self.data).map_err(json::Error::UTF8Parsing)?;
+    let (json, duplicate_keys) =
+      json::from_str_with_duplicate_keys(text).map_err(json::Error::JSONParsing)?;
+
+    // A number, boolean or null not followed by whitespace may have been
+    // truncated (RFC 7464 section 2.4)
+    if format == Format::TextSequence
+      && matches!(json, Value::Number(_) | Value::Bool(_) | Value::Null)
+      && !matches!(self.data.last(), Some(b) if b.is_ascii_whitespace())
+    {
+      return Err(parsing_error("JSON text is possibly truncated"));
+    }
+
+    Ok((json, duplicate_keys))
+  }
+}
+
+fn parsing_error(msg: &str) -> json::Error {
+  json::Error::JSONParsing(<serde_json::Error as serde::de::Error>::custom(msg))
+}
+
+// Iterator over the non-blank records of a JSON Lines file or JSON text
+// sequence
+struct Records<R> {
+  reader: R,
+  format: Format,
+  line: usize,
+  number: usize,
+  is_separated: bool,
+}
+
+impl<R: BufRead> Records<R> {
+  fn new(reader: R, format: Format) -> Self {
+    Records {
+      reader,
+      format,
+      line: 1,
+      number: 0,
+      is_separated: false,
+    }
+  }
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+  type Item = std::io::Result<Record>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let delimiter = match self.format {
+      Format::Lines => b'\n',
+      Format::TextSequence => RS,
+    };
+
+    loop {
+      let mut data = Vec::new();
+      match self.reader.read_until(delimiter, &mut data) {
+        Ok(0) => return None,
+        Ok(_) => (),
+        Err(e) => return Some(Err(e)),
+      }
+
+      let line = self.line;
+      self.line += data.iter().filter(|b| **b == b'\n').count();
+
+      // The JSON text of a text sequence runs until the next record separator,
+      // which precedes the following JSON text
+      let is_separated = self.is_separated;
+      if data.last() == Some(&delimiter) {
+        data.pop();
+        self.is_separated = true;
+      }
+
+      if data.iter().all(|b| b.is_ascii_whitespace()) {
+        continue;
+      }
+
+      self.number += 1;
+
+      return Some(Ok(Record {
+        number: self.number,
+        line,
+        data,
+        is_unseparated: self.format == Format::TextSequence && !is_separated,
+      }));
+    }
+  }
+}
+
+#[cfg(test)]
+#[cfg(feature = "additional-controls")]
+mod tests {
+  use super::*;
+  use crate::cddl_from_str;
+
+  fn record_errors(input: &[u8], format: Format) -> Vec<(usize, usize, String)> {
+    let cddl = cddl_from_str("entry = { level: tstr, ? msg: tstr } / uint", true).unwrap();
+
+    match validate_records(&cddl, input, format, None, None) {
+      Ok(()) => Vec::new(),
+      Err(Error::Records(errors)) => errors
+        .into_iter()
+        .map(|e| {
+          let location = match e.error {
+            json::Error::Validation(errors) => errors[0].json_location.clone(),
+            e => e.to_string(),
+          };
+          (e.record, e.line, location)
+        })
+        .collect(),
+      Err(e) => panic!("unexpected error {}", e),
+    }
+  }
+
+  #[test]
+  fn validate_json_lines() {
+    let lines = b"{ \"level\": \"info\" }\n\n{ \"level\": 1 }\r\n{ \"level\": \"debug\", \"msg\": 2 }\nnot json\n7";
+    let errors = record_errors(lines, Format::Lines);
+
+    assert_eq!(errors.len(), 3);
+    assert_eq!((errors[0].0, errors[0].1), (2, 3));
+    assert_eq!((errors[1].0, errors[1].1), (3, 4));
+    assert_eq!(errors[1].2, "/msg");
+    assert_eq!((errors[2].0, errors[2].1), (4, 5));
+    assert!(errors[2].2.contains("error parsing JSON"));
+  }
+
+  #[test]
+  fn validate_json_text_sequence() {
+    let seq = b"\x1e{ \"level\": \"info\" }\n\x1e\x1e{\n  \"level\": 1\n}\n\x1e42\n\x1e7";
+    let errors = record_errors(seq, Format::TextSequence);
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!((errors[0].0, errors[0].1), (2, 2));
+    assert_eq!((errors[1].0, errors[1].1), (4, 6));
+    assert!(errors[1].2.contains("truncated"));
+
+    let errors = record_errors(b"{ \"level\": \"info\" }\n", Format::TextSequence);
+    assert!(errors[0].2.contains("record separator"));
+
+    let cddl = cddl_from_str("entry = uint", true).unwrap();
+    assert!(matches!(
+      validate_records(&cddl, &seq[..], Format::TextSequence, Some("missing"), None),
+      Err(Error::JSON(json::Error::MissingRule(_)))
+    ));
+  }
+}
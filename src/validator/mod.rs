@@ -4,19 +4,38 @@
 pub mod cbor;
 /// JSON validation implementation
 pub mod json;
-
-mod control;
+/// Validation of JSON Lines and JSON text sequences
+pub mod json_seq;
+
+mod array;
+/// Compiled schemas for fast repeated validation
+pub mod compiled;
+pub(crate) mod control;
+/// Custom control operators
+pub mod custom;
+mod number;
+/// Decoding of CBOR data items that retains their encoding
+pub mod raw;
+/// Parsed CDDL schemas for validating JSON and CBOR
+pub mod schema;
+/// Streaming CBOR validation
+pub mod stream;
+/// CBOR tag registry
+pub mod tags;
 
 use crate::{
   ast::{
     Group, GroupChoice, GroupEntry, GroupRule, Identifier, Occur, Rule, Type, Type2, TypeChoice,
-    TypeRule, CDDL,
+    TypeGroupnameEntry, TypeRule, CDDL,
   },
   token::*,
   visitor::Visitor,
 };
 
-use std::error::Error;
+#[cfg(feature = "ast-span")]
+use crate::ast::Span;
+
+use std::{borrow::Cow, error::Error};
 
 #[cfg(feature = "cbor")]
 use cbor::CBORValidator;
@@ -67,23 +86,57 @@ impl CDDL<'_> {
     #[cfg(target_arch = "wasm32")]
     enabled_features: Option<Box<[JsValue]>>,
   ) -> Result<(), Box<dyn Error>> {
-    if std::str::from_utf8(document).is_ok() {
-      let json =
-        serde_json::from_slice::<serde_json::Value>(document).map_err(json::Error::JSONParsing)?;
+    if let Ok(document) = std::str::from_utf8(document) {
+      let (json, duplicate_keys) =
+        json::from_str_with_duplicate_keys(document).map_err(json::Error::JSONParsing)?;
 
       #[cfg(feature = "additional-controls")]
-      let mut jv = JSONValidator::new(self, json, enabled_features);
+      let mut jv =
+        JSONValidator::new(self, json, enabled_features).with_duplicate_keys(duplicate_keys);
       #[cfg(not(feature = "additional-controls"))]
-      let mut jv = JSONValidator::new(&cddl, json);
+      let mut jv = JSONValidator::new(&cddl, json).with_duplicate_keys(duplicate_keys);
 
       return jv.validate().map_err(|e| e.into());
     }
 
-    let cbor: ciborium::value::Value = ciborium::de::from_reader(document)?;
+    let (cbor, raw) = raw::from_slice(document)?;
 
-    let mut cv = CBORValidator::new(self, cbor, enabled_features);
+    let mut cv = CBORValidator::new(self, cbor, enabled_features).with_encoding(raw);
     cv.validate().map_err(|e| e.into())
   }
+
+  /// Validate the given document against the type or group rule with the given
+  /// name
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn validate_rule(
+    &self,
+    document: &[u8],
+    rule: &str,
+    #[cfg(feature = "additional-controls")] enabled_features: Option<&[&str]>,
+  ) -> Result<(), Box<dyn Error>> {
+    if let Ok(document) = std::str::from_utf8(document) {
+      let (json, duplicate_keys) =
+        json::from_str_with_duplicate_keys(document).map_err(json::Error::JSONParsing)?;
+
+      #[cfg(feature = "additional-controls")]
+      let mut jv =
+        JSONValidator::new(self, json, enabled_features).with_duplicate_keys(duplicate_keys);
+      #[cfg(not(feature = "additional-controls"))]
+      let mut jv = JSONValidator::new(self, json).with_duplicate_keys(duplicate_keys);
+
+      return jv.validate_rule(rule).map_err(|e| e.into());
+    }
+
+    let (cbor, raw) = raw::from_slice(document)?;
+
+    #[cfg(feature = "additional-controls")]
+    let mut cv = CBORValidator::new(self, cbor, enabled_features).with_encoding(raw);
+    #[cfg(not(feature = "additional-controls"))]
+    let mut cv = CBORValidator::new(self, cbor).with_encoding(raw);
+
+    let r: cbor::Result<std::io::Error> = cv.validate_rule(rule);
+    r.map_err(|e| e.into())
+  }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -95,16 +148,65 @@ pub fn validate_json_from_str(
   #[cfg(feature = "additional-controls")] enabled_features: Option<&[&str]>,
 ) -> json::Result {
   let cddl = cddl_from_str(cddl, true).map_err(json::Error::CDDLParsing)?;
-  let json = serde_json::from_str::<serde_json::Value>(json).map_err(json::Error::JSONParsing)?;
+  let (json, duplicate_keys) =
+    json::from_str_with_duplicate_keys(json).map_err(json::Error::JSONParsing)?;
 
   #[cfg(feature = "additional-controls")]
-  let mut jv = JSONValidator::new(&cddl, json, enabled_features);
+  let mut jv =
+    JSONValidator::new(&cddl, json, enabled_features).with_duplicate_keys(duplicate_keys);
   #[cfg(not(feature = "additional-controls"))]
-  let mut jv = JSONValidator::new(&cddl, json);
+  let mut jv = JSONValidator::new(&cddl, json).with_duplicate_keys(duplicate_keys);
 
   jv.validate()
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(feature = "json")]
+/// Validate JSON string from a given CDDL document string against the type or
+/// group rule with the given name
+pub fn validate_json_from_str_with_rule(
+  cddl: &str,
+  json: &str,
+  rule: &str,
+  #[cfg(feature = "additional-controls")] enabled_features: Option<&[&str]>,
+) -> json::Result {
+  let cddl = cddl_from_str(cddl, true).map_err(json::Error::CDDLParsing)?;
+  let (json, duplicate_keys) =
+    json::from_str_with_duplicate_keys(json).map_err(json::Error::JSONParsing)?;
+
+  #[cfg(feature = "additional-controls")]
+  let mut jv =
+    JSONValidator::new(&cddl, json, enabled_features).with_duplicate_keys(duplicate_keys);
+  #[cfg(not(feature = "additional-controls"))]
+  let mut jv = JSONValidator::new(&cddl, json).with_duplicate_keys(duplicate_keys);
+
+  jv.validate_rule(rule)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(feature = "json")]
+/// Validate each record of the JSON Lines file or JSON text sequence (RFC
+/// 7464) read from the given reader against a given CDDL document string,
+/// using the type or group rule with the given name, if any, instead of the
+/// root type rule. See [`json_seq::validate_records`]
+pub fn validate_json_records_from_reader<R: std::io::BufRead>(
+  cddl: &str,
+  reader: R,
+  format: json_seq::Format,
+  rule: Option<&str>,
+  #[cfg(feature = "additional-controls")] enabled_features: Option<&[&str]>,
+) -> json_seq::Result {
+  let cddl =
+    cddl_from_str(cddl, true).map_err(|e| json_seq::Error::JSON(json::Error::CDDLParsing(e)))?;
+
+  #[cfg(feature = "additional-controls")]
+  let r = json_seq::validate_records(&cddl, reader, format, rule, enabled_features);
+  #[cfg(not(feature = "additional-controls"))]
+  let r = json_seq::validate_records(&cddl, reader, format, rule);
+
+  r
+}
+
 #[cfg(target_arch = "wasm32")]
 #[cfg(feature = "additional-controls")]
 #[cfg(feature = "json")]
@@ -139,10 +241,10 @@ pub fn validate_json_from_str(
     );
   }
 
-  let json =
-    serde_json::from_str::<serde_json::Value>(json).map_err(|e| JsValue::from(e.to_string()))?;
+  let (json, duplicate_keys) =
+    json::from_str_with_duplicate_keys(json).map_err(|e| JsValue::from(e.to_string()))?;
 
-  let mut jv = JSONValidator::new(&c, json, enabled_features);
+  let mut jv = JSONValidator::new(&c, json, enabled_features).with_duplicate_keys(duplicate_keys);
   jv.validate()
     .map_err(|e| JsValue::from(e.to_string()))
     .map(|_| JsValue::default())
@@ -178,10 +280,10 @@ pub fn validate_json_from_str(cddl: &str, json: &str) -> std::result::Result<JsV
     );
   }
 
-  let json =
-    serde_json::from_str::<serde_json::Value>(json).map_err(|e| JsValue::from(e.to_string()))?;
+  let (json, duplicate_keys) =
+    json::from_str_with_duplicate_keys(json).map_err(|e| JsValue::from(e.to_string()))?;
 
-  let mut jv = JSONValidator::new(&c, json);
+  let mut jv = JSONValidator::new(&c, json).with_duplicate_keys(duplicate_keys);
   jv.validate()
     .map_err(|e| JsValue::from(e.to_string()))
     .map(|_| JsValue::default())
@@ -198,13 +300,108 @@ pub fn validate_cbor_from_slice(
 ) -> cbor::Result<std::io::Error> {
   let cddl = cddl_from_str(cddl, true).map_err(cbor::Error::CDDLParsing)?;
 
-  let cbor: ciborium::value::Value =
-    ciborium::de::from_reader(cbor_slice).map_err(cbor::Error::CBORParsing)?;
+  let (cbor, raw) = raw::from_slice(cbor_slice).map_err(cbor::Error::CBORParsing)?;
 
-  let mut cv = CBORValidator::new(&cddl, cbor, enabled_features);
+  let mut cv = CBORValidator::new(&cddl, cbor, enabled_features).with_encoding(raw);
   cv.validate()
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(feature = "cbor")]
+/// Validate CBOR slice from a given CDDL document string against the type or
+/// group rule with the given name
+pub fn validate_cbor_from_slice_with_rule(
+  cddl: &str,
+  cbor_slice: &[u8],
+  rule: &str,
+  #[cfg(feature = "additional-controls")] enabled_features: Option<&[&str]>,
+) -> cbor::Result<std::io::Error> {
+  let cddl = cddl_from_str(cddl, true).map_err(cbor::Error::CDDLParsing)?;
+
+  let (cbor, raw) = raw::from_slice(cbor_slice).map_err(cbor::Error::CBORParsing)?;
+
+  #[cfg(feature = "additional-controls")]
+  let mut cv = CBORValidator::new(&cddl, cbor, enabled_features).with_encoding(raw);
+  #[cfg(not(feature = "additional-controls"))]
+  let mut cv = CBORValidator::new(&cddl, cbor).with_encoding(raw);
+
+  cv.validate_rule(rule)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(feature = "cbor")]
+/// Validate the CBOR data item read from the given reader against a given CDDL
+/// document string, validating arrays and maps entry by entry as they are
+/// decoded. See [`stream::StreamValidator`]
+pub fn validate_cbor_from_reader<R: std::io::Read>(
+  cddl: &str,
+  reader: R,
+  #[cfg(feature = "additional-controls")] enabled_features: Option<&[&str]>,
+) -> cbor::Result<std::io::Error> {
+  let cddl = cddl_from_str(cddl, true).map_err(cbor::Error::CDDLParsing)?;
+
+  #[cfg(feature = "additional-controls")]
+  let mut sv = stream::StreamValidator::new(&cddl, enabled_features);
+  #[cfg(not(feature = "additional-controls"))]
+  let mut sv = stream::StreamValidator::new(&cddl);
+
+  sv.validate(reader)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(feature = "cbor")]
+/// Validate the CBOR sequence (RFC 8742) read from the given reader until the
+/// end of its input against a given CDDL document string. The data items of
+/// the sequence are matched against the group or array type rule with the
+/// given name, if any, instead of the first rule, with errors located at the
+/// index of each data item. See [`stream::StreamValidator::validate_sequence`]
+pub fn validate_cbor_seq_from_reader<R: std::io::Read>(
+  cddl: &str,
+  reader: R,
+  rule: Option<&str>,
+  #[cfg(feature = "additional-controls")] enabled_features: Option<&[&str]>,
+) -> cbor::Result<std::io::Error> {
+  let cddl = cddl_from_str(cddl, true).map_err(cbor::Error::CDDLParsing)?;
+
+  #[cfg(feature = "additional-controls")]
+  let mut sv = stream::StreamValidator::new(&cddl, enabled_features);
+  #[cfg(not(feature = "additional-controls"))]
+  let mut sv = stream::StreamValidator::new(&cddl);
+
+  match rule {
+    Some(rule) => sv.validate_sequence_rule(reader, rule),
+    None => sv.validate_sequence(reader),
+  }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(feature = "cbor")]
+/// Validate CBOR slice from a given CDDL document string, additionally
+/// requiring core deterministic encoding as defined by RFC 8949 section 4.2.1.
+/// Validates against the type or group rule with the given name, if any,
+/// instead of the root type rule
+pub fn validate_cbor_from_slice_deterministic(
+  cddl: &str,
+  cbor_slice: &[u8],
+  rule: Option<&str>,
+  #[cfg(feature = "additional-controls")] enabled_features: Option<&[&str]>,
+) -> cbor::Result<std::io::Error> {
+  let cddl = cddl_from_str(cddl, true).map_err(cbor::Error::CDDLParsing)?;
+
+  let (cbor, raw) = raw::from_slice(cbor_slice).map_err(cbor::Error::CBORParsing)?;
+
+  #[cfg(feature = "additional-controls")]
+  let cv = CBORValidator::new(&cddl, cbor, enabled_features);
+  #[cfg(not(feature = "additional-controls"))]
+  let cv = CBORValidator::new(&cddl, cbor);
+
+  let mut cv = cv.with_encoding(raw).with_deterministic_encoding();
+  match rule {
+    Some(rule) => cv.validate_rule(rule),
+    None => cv.validate(),
+  }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(feature = "cbor")]
 #[cfg(not(feature = "additional-controls"))]
@@ -212,10 +409,9 @@ pub fn validate_cbor_from_slice(
 pub fn validate_cbor_from_slice(cddl: &str, cbor_slice: &[u8]) -> cbor::Result<std::io::Error> {
   let mut lexer = lexer_from_str(cddl);
   let cddl = cddl_from_str(&mut lexer, cddl, true).map_err(cbor::Error::CDDLParsing)?;
-  let cbor: ciborium::value::Value =
-    ciborium::de::from_reader(cbor_slice).map_err(cbor::Error::CBORParsing)?;
+  let (cbor, raw) = raw::from_slice(cbor_slice).map_err(cbor::Error::CBORParsing)?;
 
-  let mut cv = CBORValidator::new(&cddl, cbor);
+  let mut cv = CBORValidator::new(&cddl, cbor).with_encoding(raw);
   cv.validate()
 }
 
@@ -253,10 +449,9 @@ pub fn validate_cbor_from_slice(
     );
   }
 
-  let cbor: ciborium::value::Value =
-    ciborium::de::from_reader(cbor_slice).map_err(|e| JsValue::from(e.to_string()))?;
+  let (cbor, raw) = raw::from_slice(cbor_slice).map_err(|e| JsValue::from(e.to_string()))?;
 
-  let mut cv = CBORValidator::new(&c, cbor, enabled_features);
+  let mut cv = CBORValidator::new(&c, cbor, enabled_features).with_encoding(raw);
   cv.validate()
     .map_err(|e| JsValue::from(e.to_string()))
     .map(|_| JsValue::default())
@@ -295,15 +490,84 @@ pub fn validate_cbor_from_slice(
     );
   }
 
-  let cbor: ciborium::value::Value =
-    ciborium::de::from_reader(cbor_slice).map_err(|e| JsValue::from(e.to_string()))?;
+  let (cbor, raw) = raw::from_slice(cbor_slice).map_err(|e| JsValue::from(e.to_string()))?;
 
-  let mut cv = CBORValidator::new(&c, cbor);
+  let mut cv = CBORValidator::new(&c, cbor).with_encoding(raw);
   cv.validate()
     .map_err(|e| JsValue::from(e.to_string()))
     .map(|_| JsValue::default())
 }
 
+/// Find the non-choice alternate type or group rule with the given name to use
+/// as the root rule for validation. Generic rules cannot be used as the root
+/// since there are no arguments to bind to their generic parameters
+pub fn root_rule_from_name<'a>(
+  cddl: &'a CDDL<'a>,
+  name: &str,
+) -> std::result::Result<&'a Rule<'a>, String> {
+  let rule = cddl
+    .rules
+    .iter()
+    .find(|r| match r {
+      Rule::Type { rule, .. } => rule.name.ident == name && !rule.is_type_choice_alternate,
+      Rule::Group { rule, .. } => rule.name.ident == name && !rule.is_group_choice_alternate,
+    })
+    .ok_or_else(|| format!("no rule named {} found in the CDDL definition", name))?;
+
+  let is_generic = match rule {
+    Rule::Type { rule, .. } => rule.generic_params.is_some(),
+    Rule::Group { rule, .. } => rule.generic_params.is_some(),
+  };
+  if is_generic {
+    return Err(format!(
+      "rule {} is generic and cannot be used as the root rule without generic arguments",
+      name
+    ));
+  }
+
+  Ok(rule)
+}
+
+/// Map or array type referencing the group rule with the given name, used to
+/// validate a document against a group rule
+fn group_rule_as_type2<'a>(name: &Identifier<'a>, is_array: bool) -> Type2<'a> {
+  let group = Group::from(GroupEntry::TypeGroupname {
+    ge: TypeGroupnameEntry {
+      occur: None,
+      name: name.clone(),
+      generic_args: None,
+    },
+    #[cfg(feature = "ast-span")]
+    span: Span::default(),
+    #[cfg(feature = "ast-comments")]
+    leading_comments: None,
+    #[cfg(feature = "ast-comments")]
+    trailing_comments: None,
+  });
+
+  if is_array {
+    Type2::Array {
+      group,
+      #[cfg(feature = "ast-span")]
+      span: Span::default(),
+      #[cfg(feature = "ast-comments")]
+      comments_before_group: None,
+      #[cfg(feature = "ast-comments")]
+      comments_after_group: None,
+    }
+  } else {
+    Type2::Map {
+      group,
+      #[cfg(feature = "ast-span")]
+      span: Span::default(),
+      #[cfg(feature = "ast-comments")]
+      comments_before_group: None,
+      #[cfg(feature = "ast-comments")]
+      comments_after_group: None,
+    }
+  }
+}
+
 /// Find non-choice alternate rule from a given identifier
 pub fn rule_from_ident<'a>(cddl: &'a CDDL, ident: &Identifier) -> Option<&'a Rule<'a>> {
   cddl.rules.iter().find(|r| match r {
@@ -315,70 +579,12 @@ pub fn rule_from_ident<'a>(cddl: &'a CDDL, ident: &Identifier) -> Option<&'a Rul
 
 /// Find text values from a given identifier
 pub fn text_value_from_ident<'a>(cddl: &'a CDDL, ident: &Identifier) -> Option<&'a Type2<'a>> {
-  cddl.rules.iter().find_map(|r| match r {
-    Rule::Type { rule, .. } if rule.name == *ident => {
-      rule.value.type_choices.iter().find_map(|tc| {
-        if tc.type1.operator.is_none() {
-          match &tc.type1.type2 {
-            Type2::TextValue { .. } | Type2::UTF8ByteString { .. } => Some(&tc.type1.type2),
-            Type2::Typename { ident, .. } => text_value_from_ident(cddl, ident),
-            Type2::ParenthesizedType { pt, .. } => pt.type_choices.iter().find_map(|tc| {
-              if tc.type1.operator.is_none() {
-                text_value_from_type2(cddl, &tc.type1.type2)
-              } else {
-                None
-              }
-            }),
-            _ => None,
-          }
-        } else {
-          None
-        }
-      })
-    }
-    _ => None,
-  })
+  Lookup::text_value_from_ident(&cddl, ident)
 }
 
 /// Find text values from a given Type2
 pub fn text_value_from_type2<'a>(cddl: &'a CDDL, t2: &'a Type2<'a>) -> Option<&'a Type2<'a>> {
-  match t2 {
-    Type2::TextValue { .. } | Type2::UTF8ByteString { .. } => Some(t2),
-    Type2::Typename { ident, .. } => text_value_from_ident(cddl, ident),
-    Type2::Array { group, .. } => group.group_choices.iter().find_map(|gc| {
-      if gc.group_entries.len() == 2 {
-        if let Some(ge) = gc.group_entries.first() {
-          if let GroupEntry::ValueMemberKey { ge, .. } = &ge.0 {
-            if ge.member_key.is_none() {
-              ge.entry_type.type_choices.iter().find_map(|tc| {
-                if tc.type1.operator.is_none() {
-                  text_value_from_type2(cddl, &tc.type1.type2)
-                } else {
-                  None
-                }
-              })
-            } else {
-              None
-            }
-          } else {
-            None
-          }
-        } else {
-          None
-        }
-      } else {
-        None
-      }
-    }),
-    Type2::ParenthesizedType { pt, .. } => pt.type_choices.iter().find_map(|tc| {
-      if tc.type1.operator.is_none() {
-        text_value_from_type2(cddl, &tc.type1.type2)
-      } else {
-        None
-      }
-    }),
-    _ => None,
-  }
+  Lookup::text_value_from_type2(&cddl, t2)
 }
 
 /// Unwrap array, map or tag type rule from ident
@@ -443,16 +649,16 @@ pub fn type_rule_from_ident<'a>(cddl: &'a CDDL, ident: &Identifier) -> Option<&'
 }
 
 /// Retrieve the list of generic parameters for a given rule
-pub fn generic_params_from_rule<'a>(rule: &Rule<'a>) -> Option<Vec<&'a str>> {
+pub fn generic_params_from_rule<'a>(rule: &Rule<'a>) -> Option<Vec<Cow<'a, str>>> {
   match rule {
     Rule::Type { rule, .. } => rule
       .generic_params
       .as_ref()
-      .map(|gp| gp.params.iter().map(|gp| gp.param.ident).collect()),
+      .map(|gp| gp.params.iter().map(|gp| gp.param.ident.clone()).collect()),
     Rule::Group { rule, .. } => rule
       .generic_params
       .as_ref()
-      .map(|gp| gp.params.iter().map(|gp| gp.param.ident).collect()),
+      .map(|gp| gp.params.iter().map(|gp| gp.param.ident.clone()).collect()),
   }
 }
 
@@ -495,331 +701,474 @@ pub fn type_choices_from_group_choice<'a>(
   cddl: &'a CDDL,
   grpchoice: &GroupChoice<'a>,
 ) -> Vec<TypeChoice<'a>> {
-  let mut type_choices = Vec::new();
-  for ge in grpchoice.group_entries.iter() {
-    match &ge.0 {
-      GroupEntry::ValueMemberKey { ge, .. } => {
-        type_choices.append(&mut ge.entry_type.type_choices.clone());
-      }
-      GroupEntry::TypeGroupname { ge, .. } => {
-        // TODO: parse generic args
-        if let Some(r) = rule_from_ident(cddl, &ge.name) {
-          match r {
-            Rule::Type { rule, .. } => type_choices.append(&mut rule.value.type_choices.clone()),
-            Rule::Group { rule, .. } => type_choices.append(&mut type_choices_from_group_choice(
-              cddl,
-              &GroupChoice::new(vec![rule.entry.clone()]),
-            )),
-          }
-        }
-      }
-      GroupEntry::InlineGroup { group, .. } => {
-        for gc in group.group_choices.iter() {
-          type_choices.append(&mut type_choices_from_group_choice(cddl, gc));
-        }
-      }
-    }
-  }
-
-  type_choices
+  Lookup::type_choices_from_group_choice(&cddl, grpchoice)
 }
 
 /// Is the given identifier associated with a null data type
 pub fn is_ident_null_data_type(cddl: &CDDL, ident: &Identifier) -> bool {
-  if let Token::NULL | Token::NIL = lookup_ident(ident.ident) {
-    return true;
-  }
-
-  cddl.rules.iter().any(|r| match r {
-    Rule::Type { rule, .. } if &rule.name == ident => rule.value.type_choices.iter().any(|tc| {
-      if let Type2::Typename { ident, .. } = &tc.type1.type2 {
-        is_ident_null_data_type(cddl, ident)
-      } else {
-        false
-      }
-    }),
-    _ => false,
-  })
+  Lookup::is_ident_null_data_type(&cddl, ident)
 }
 
 /// Is the given identifier associated with a boolean data type
 pub fn is_ident_bool_data_type(cddl: &CDDL, ident: &Identifier) -> bool {
-  if let Token::BOOL = lookup_ident(ident.ident) {
-    return true;
-  }
-
-  cddl.rules.iter().any(|r| match r {
-    Rule::Type { rule, .. } if &rule.name == ident => rule.value.type_choices.iter().any(|tc| {
-      if let Type2::Typename { ident, .. } = &tc.type1.type2 {
-        is_ident_bool_data_type(cddl, ident)
-      } else {
-        false
-      }
-    }),
-    _ => false,
-  })
+  Lookup::is_ident_bool_data_type(&cddl, ident)
 }
 
 /// Does the given boolean identifier match the boolean value
 pub fn ident_matches_bool_value(cddl: &CDDL, ident: &Identifier, value: bool) -> bool {
-  if let Token::TRUE = lookup_ident(ident.ident) {
-    if value {
-      return true;
-    }
-  }
-
-  if let Token::FALSE = lookup_ident(ident.ident) {
-    if !value {
-      return true;
-    }
-  }
-
-  cddl.rules.iter().any(|r| match r {
-    Rule::Type { rule, .. } if &rule.name == ident => rule.value.type_choices.iter().any(|tc| {
-      if let Type2::Typename { ident, .. } = &tc.type1.type2 {
-        ident_matches_bool_value(cddl, ident, value)
-      } else {
-        false
-      }
-    }),
-    _ => false,
-  })
+  Lookup::ident_matches_bool_value(&cddl, ident, value)
 }
 
 /// Is the given identifier associated with a URI data type
 pub fn is_ident_uri_data_type(cddl: &CDDL, ident: &Identifier) -> bool {
-  if let Token::URI = lookup_ident(ident.ident) {
-    return true;
-  }
-
-  cddl.rules.iter().any(|r| match r {
-    Rule::Type { rule, .. } if &rule.name == ident => rule.value.type_choices.iter().any(|tc| {
-      if let Type2::Typename { ident, .. } = &tc.type1.type2 {
-        is_ident_uri_data_type(cddl, ident)
-      } else {
-        false
-      }
-    }),
-    _ => false,
-  })
+  Lookup::is_ident_uri_data_type(&cddl, ident)
 }
 
 /// Is the given identifier associated with a b64url data type
 pub fn is_ident_b64url_data_type(cddl: &CDDL, ident: &Identifier) -> bool {
-  if let Token::B64URL = lookup_ident(ident.ident) {
-    return true;
-  }
-
-  cddl.rules.iter().any(|r| match r {
-    Rule::Type { rule, .. } if &rule.name == ident => rule.value.type_choices.iter().any(|tc| {
-      if let Type2::Typename { ident, .. } = &tc.type1.type2 {
-        is_ident_b64url_data_type(cddl, ident)
-      } else {
-        false
-      }
-    }),
-    _ => false,
-  })
+  Lookup::is_ident_b64url_data_type(&cddl, ident)
 }
 
 /// Is the given identifier associated with a tdate data type
 pub fn is_ident_tdate_data_type(cddl: &CDDL, ident: &Identifier) -> bool {
-  if let Token::TDATE = lookup_ident(ident.ident) {
-    return true;
+  Lookup::is_ident_tdate_data_type(&cddl, ident)
+}
+
+/// Validate an RFC 3339 full-date string, e.g. `1985-04-12`, as used by the
+/// date tag 1004 defined in RFC 8943
+pub fn validate_full_date(s: &str) -> std::result::Result<(), String> {
+  let is_full_date_format = s.len() == 10
+    && s.char_indices().all(|(idx, c)| match idx {
+      4 | 7 => c == '-',
+      _ => c.is_ascii_digit(),
+    });
+  if !is_full_date_format {
+    return Err(format!(
+      "expected full-date of the form YYYY-MM-DD, got {:?}",
+      s
+    ));
   }
 
-  cddl.rules.iter().any(|r| match r {
-    Rule::Type { rule, .. } if &rule.name == ident => rule.value.type_choices.iter().any(|tc| {
-      if let Type2::Typename { ident, .. } = &tc.type1.type2 {
-        is_ident_tdate_data_type(cddl, ident)
-      } else {
-        false
-      }
-    }),
-    _ => false,
-  })
+  chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")
+    .map(|_| ())
+    .map_err(|e| format!("invalid full-date {:?}, {}", s, e))
 }
 
 /// Is the given identifier associated with a time data type
 pub fn is_ident_time_data_type(cddl: &CDDL, ident: &Identifier) -> bool {
-  if let Token::TIME = lookup_ident(ident.ident) {
-    return true;
-  }
-
-  cddl.rules.iter().any(|r| match r {
-    Rule::Type { rule, .. } if &rule.name == ident => rule.value.type_choices.iter().any(|tc| {
-      if let Type2::Typename { ident, .. } = &tc.type1.type2 {
-        is_ident_time_data_type(cddl, ident)
-      } else {
-        false
-      }
-    }),
-    _ => false,
-  })
+  Lookup::is_ident_time_data_type(&cddl, ident)
 }
 
 /// Is the given identifier associated with a numeric data type
 pub fn is_ident_numeric_data_type(cddl: &CDDL, ident: &Identifier) -> bool {
-  if let Token::UINT
-  | Token::NINT
-  | Token::INTEGER
-  | Token::INT
-  | Token::NUMBER
-  | Token::FLOAT
-  | Token::FLOAT16
-  | Token::FLOAT32
-  | Token::FLOAT64
-  | Token::FLOAT1632
-  | Token::FLOAT3264
-  | Token::UNSIGNED = lookup_ident(ident.ident)
-  {
-    return true;
-  }
-
-  cddl.rules.iter().any(|r| match r {
-    Rule::Type { rule, .. } if rule.name == *ident => rule.value.type_choices.iter().any(|tc| {
-      if let Type2::Typename { ident, .. } = &tc.type1.type2 {
-        is_ident_numeric_data_type(cddl, ident)
-      } else {
-        false
-      }
-    }),
-    _ => false,
-  })
+  Lookup::is_ident_numeric_data_type(&cddl, ident)
 }
 
 /// Is the given identifier associated with a uint data type
 pub fn is_ident_uint_data_type(cddl: &CDDL, ident: &Identifier) -> bool {
-  if let Token::UINT = lookup_ident(ident.ident) {
-    return true;
-  }
-
-  cddl.rules.iter().any(|r| match r {
-    Rule::Type { rule, .. } if rule.name == *ident => rule.value.type_choices.iter().any(|tc| {
-      if let Type2::Typename { ident, .. } = &tc.type1.type2 {
-        is_ident_uint_data_type(cddl, ident)
-      } else {
-        false
-      }
-    }),
-    _ => false,
-  })
+  Lookup::is_ident_uint_data_type(&cddl, ident)
 }
 
 /// Is the given identifier associated with a nint data type
 pub fn is_ident_nint_data_type(cddl: &CDDL, ident: &Identifier) -> bool {
-  if let Token::NINT = lookup_ident(ident.ident) {
-    return true;
-  }
-
-  cddl.rules.iter().any(|r| match r {
-    Rule::Type { rule, .. } if rule.name == *ident => rule.value.type_choices.iter().any(|tc| {
-      if let Type2::Typename { ident, .. } = &tc.type1.type2 {
-        is_ident_nint_data_type(cddl, ident)
-      } else {
-        false
-      }
-    }),
-    _ => false,
-  })
+  Lookup::is_ident_nint_data_type(&cddl, ident)
 }
 
 /// Is the given identifier associated with an integer data type
 pub fn is_ident_integer_data_type(cddl: &CDDL, ident: &Identifier) -> bool {
-  if let Token::INT | Token::INTEGER | Token::NINT | Token::UINT | Token::NUMBER | Token::UNSIGNED =
-    lookup_ident(ident.ident)
-  {
-    return true;
-  }
-
-  cddl.rules.iter().any(|r| match r {
-    Rule::Type { rule, .. } if rule.name == *ident => rule.value.type_choices.iter().any(|tc| {
-      if let Type2::Typename { ident, .. } = &tc.type1.type2 {
-        is_ident_integer_data_type(cddl, ident)
-      } else {
-        false
-      }
-    }),
-    _ => false,
-  })
+  Lookup::is_ident_integer_data_type(&cddl, ident)
 }
 
 /// Is the given identifier associated with a float data type
 pub fn is_ident_float_data_type(cddl: &CDDL, ident: &Identifier) -> bool {
-  if let Token::FLOAT
-  | Token::FLOAT16
-  | Token::FLOAT1632
-  | Token::FLOAT32
-  | Token::FLOAT3264
-  | Token::FLOAT64 = lookup_ident(ident.ident)
-  {
-    return true;
-  }
-
-  cddl.rules.iter().any(|r| match r {
-    Rule::Type { rule, .. } if rule.name == *ident => rule.value.type_choices.iter().any(|tc| {
-      if let Type2::Typename { ident, .. } = &tc.type1.type2 {
-        is_ident_float_data_type(cddl, ident)
-      } else {
-        false
-      }
-    }),
-    _ => false,
-  })
+  Lookup::is_ident_float_data_type(&cddl, ident)
 }
 
 /// Is the given identifier associated with a string data type
 pub fn is_ident_string_data_type(cddl: &CDDL, ident: &Identifier) -> bool {
-  if let Token::TEXT | Token::TSTR = lookup_ident(ident.ident) {
-    return true;
-  }
-
-  cddl.rules.iter().any(|r| match r {
-    Rule::Type { rule, .. } if rule.name == *ident => rule.value.type_choices.iter().any(|tc| {
-      if let Type2::Typename { ident, .. } = &tc.type1.type2 {
-        is_ident_string_data_type(cddl, ident)
-      } else {
-        false
-      }
-    }),
-    _ => false,
-  })
+  Lookup::is_ident_string_data_type(&cddl, ident)
 }
 
 /// Is the given identifier associated with the any type
 pub fn is_ident_any_type(cddl: &CDDL, ident: &Identifier) -> bool {
-  if let Token::ANY = lookup_ident(ident.ident) {
-    return true;
-  }
-
-  cddl.rules.iter().any(|r| match r {
-    Rule::Type { rule, .. } if rule.name == *ident => rule.value.type_choices.iter().any(|tc| {
-      if let Type2::Typename { ident, .. } = &tc.type1.type2 {
-        is_ident_any_type(cddl, ident)
-      } else {
-        false
-      }
-    }),
-    _ => false,
-  })
+  Lookup::is_ident_any_type(&cddl, ident)
 }
 
 /// Is the given identifier associated with a byte string data type
 pub fn is_ident_byte_string_data_type(cddl: &CDDL, ident: &Identifier) -> bool {
-  if let Token::BSTR | Token::BYTES = lookup_ident(ident.ident) {
-    return true;
+  Lookup::is_ident_byte_string_data_type(&cddl, ident)
+}
+
+/// Lookup of the rules referred to by name while validating, either by
+/// scanning the rules of a CDDL AST or through the index of a compiled schema.
+/// The functions of this module taking a CDDL AST scan its rules
+pub(crate) trait Lookup<'a> {
+  /// See [`rule_from_ident`]
+  fn rule_from_ident(&self, ident: &Identifier) -> Option<&'a Rule<'a>>;
+
+  /// See [`group_rule_from_ident`]
+  fn group_rule_from_ident(&self, ident: &Identifier) -> Option<&'a GroupRule<'a>>;
+
+  /// See [`type_choice_alternates_from_ident`]
+  fn type_choice_alternates_from_ident(&self, ident: &Identifier) -> Vec<&'a Type<'a>>;
+
+  /// See [`group_choice_alternates_from_ident`]
+  fn group_choice_alternates_from_ident(&self, ident: &Identifier) -> Vec<&'a GroupEntry<'a>>;
+
+  /// See [`text_value_from_ident`]
+  fn text_value_from_ident(&self, ident: &Identifier) -> Option<&'a Type2<'a>> {
+    types_from_ident(self, ident).find_map(|t| {
+      t.type_choices.iter().find_map(|tc| {
+        if tc.type1.operator.is_none() {
+          match &tc.type1.type2 {
+            Type2::TextValue { .. } | Type2::UTF8ByteString { .. } => Some(&tc.type1.type2),
+            Type2::Typename { ident, .. } => self.text_value_from_ident(ident),
+            Type2::ParenthesizedType { pt, .. } => pt.type_choices.iter().find_map(|tc| {
+              if tc.type1.operator.is_none() {
+                self.text_value_from_type2(&tc.type1.type2)
+              } else {
+                None
+              }
+            }),
+            _ => None,
+          }
+        } else {
+          None
+        }
+      })
+    })
   }
 
-  cddl.rules.iter().any(|r| match r {
-    Rule::Type { rule, .. } if rule.name == *ident => rule.value.type_choices.iter().any(|tc| {
-      if let Type2::Typename { ident, .. } = &tc.type1.type2 {
-        is_ident_byte_string_data_type(cddl, ident)
-      } else {
-        false
+  /// See [`text_value_from_type2`]
+  fn text_value_from_type2<'b>(&self, t2: &'b Type2<'a>) -> Option<&'b Type2<'a>>
+  where
+    'a: 'b,
+  {
+    match t2 {
+      Type2::TextValue { .. } | Type2::UTF8ByteString { .. } => Some(t2),
+      Type2::Typename { ident, .. } => self.text_value_from_ident(ident),
+      Type2::Array { group, .. } => group.group_choices.iter().find_map(|gc| {
+        if gc.group_entries.len() == 2 {
+          if let Some(ge) = gc.group_entries.first() {
+            if let GroupEntry::ValueMemberKey { ge, .. } = &ge.0 {
+              if ge.member_key.is_none() {
+                ge.entry_type.type_choices.iter().find_map(|tc| {
+                  if tc.type1.operator.is_none() {
+                    self.text_value_from_type2(&tc.type1.type2)
+                  } else {
+                    None
+                  }
+                })
+              } else {
+                None
+              }
+            } else {
+              None
+            }
+          } else {
+            None
+          }
+        } else {
+          None
+        }
+      }),
+      Type2::ParenthesizedType { pt, .. } => pt.type_choices.iter().find_map(|tc| {
+        if tc.type1.operator.is_none() {
+          self.text_value_from_type2(&tc.type1.type2)
+        } else {
+          None
+        }
+      }),
+      _ => None,
+    }
+  }
+
+  /// See [`type_choices_from_group_choice`]
+  fn type_choices_from_group_choice(&self, grpchoice: &GroupChoice<'a>) -> Vec<TypeChoice<'a>> {
+    let mut type_choices = Vec::new();
+    for ge in grpchoice.group_entries.iter() {
+      match &ge.0 {
+        GroupEntry::ValueMemberKey { ge, .. } => {
+          type_choices.append(&mut ge.entry_type.type_choices.clone());
+        }
+        GroupEntry::TypeGroupname { ge, .. } => {
+          // TODO: parse generic args
+          if let Some(r) = self.rule_from_ident(&ge.name) {
+            match r {
+              Rule::Type { rule, .. } => type_choices.append(&mut rule.value.type_choices.clone()),
+              Rule::Group { rule, .. } => type_choices.append(
+                &mut self
+                  .type_choices_from_group_choice(&GroupChoice::new(vec![rule.entry.clone()])),
+              ),
+            }
+          }
+        }
+        GroupEntry::InlineGroup { group, .. } => {
+          for gc in group.group_choices.iter() {
+            type_choices.append(&mut self.type_choices_from_group_choice(gc));
+          }
+        }
       }
-    }),
-    _ => false,
-  })
+    }
+
+    type_choices
+  }
+
+  /// See [`entry_counts_from_group`]
+  fn entry_counts_from_group(&self, group: &Group<'a>) -> Vec<EntryCount> {
+    // Each EntryCount is associated with a group choice in the given group
+    let mut entry_counts = Vec::new();
+
+    for gc in group.group_choices.iter() {
+      let mut count = 0;
+      let mut entry_occurrence = None;
+
+      for (idx, ge) in gc.group_entries.iter().enumerate() {
+        match &ge.0 {
+          GroupEntry::ValueMemberKey { ge, .. } => {
+            if idx == 1 {
+              if let Some(occur) = &ge.occur {
+                entry_occurrence = Some(occur.occur)
+              }
+            }
+
+            count += 1;
+          }
+          GroupEntry::InlineGroup { group, occur, .. } => {
+            if idx == 1 {
+              if let Some(occur) = occur {
+                entry_occurrence = Some(occur.occur)
+              }
+            }
+
+            entry_counts = self.entry_counts_from_group(group);
+          }
+          GroupEntry::TypeGroupname { ge, .. } => {
+            if idx == 1 {
+              if let Some(occur) = &ge.occur {
+                entry_occurrence = Some(occur.occur)
+              }
+            }
+
+            if let Some(gr) = self.group_rule_from_ident(&ge.name) {
+              if let GroupEntry::InlineGroup { group, .. } = &gr.entry {
+                if group.group_choices.len() == 1 {
+                  count += if let Some(ec) = self.entry_counts_from_group(group).first() {
+                    ec.count
+                  } else {
+                    0
+                  };
+                } else {
+                  entry_counts.append(&mut self.entry_counts_from_group(group));
+                }
+              } else {
+                entry_counts.append(&mut self.entry_counts_from_group(&gr.entry.clone().into()));
+              }
+            } else if self.group_choice_alternates_from_ident(&ge.name).is_empty() {
+              count += 1;
+            } else {
+              for ge in self
+                .group_choice_alternates_from_ident(&ge.name)
+                .into_iter()
+              {
+                entry_counts.append(&mut self.entry_counts_from_group(&ge.clone().into()));
+              }
+            }
+          }
+        }
+      }
+
+      entry_counts.push(EntryCount {
+        count,
+        entry_occurrence,
+      });
+    }
+
+    entry_counts
+  }
+
+  /// Whether or not the given identifier is a prelude type for which the
+  /// given predicate holds, or the name of a type rule with a type choice that
+  /// is
+  fn is_ident_data_type(&self, ident: &Identifier, is_data_type: &dyn Fn(&Token) -> bool) -> bool {
+    is_data_type(&lookup_ident(&ident.ident))
+      || types_from_ident(self, ident).any(|t| {
+        t.type_choices.iter().any(|tc| match &tc.type1.type2 {
+          Type2::Typename { ident, .. } => self.is_ident_data_type(ident, is_data_type),
+          _ => false,
+        })
+      })
+  }
+
+  /// See [`is_ident_null_data_type`]
+  fn is_ident_null_data_type(&self, ident: &Identifier) -> bool {
+    self.is_ident_data_type(ident, &|t| matches!(t, Token::NULL | Token::NIL))
+  }
+
+  /// See [`is_ident_bool_data_type`]
+  fn is_ident_bool_data_type(&self, ident: &Identifier) -> bool {
+    self.is_ident_data_type(ident, &|t| matches!(t, Token::BOOL))
+  }
+
+  /// See [`ident_matches_bool_value`]
+  fn ident_matches_bool_value(&self, ident: &Identifier, value: bool) -> bool {
+    self.is_ident_data_type(ident, &|t| match t {
+      Token::TRUE => value,
+      Token::FALSE => !value,
+      _ => false,
+    })
+  }
+
+  /// See [`is_ident_uri_data_type`]
+  fn is_ident_uri_data_type(&self, ident: &Identifier) -> bool {
+    self.is_ident_data_type(ident, &|t| matches!(t, Token::URI))
+  }
+
+  /// See [`is_ident_b64url_data_type`]
+  fn is_ident_b64url_data_type(&self, ident: &Identifier) -> bool {
+    self.is_ident_data_type(ident, &|t| matches!(t, Token::B64URL))
+  }
+
+  /// See [`is_ident_tdate_data_type`]
+  fn is_ident_tdate_data_type(&self, ident: &Identifier) -> bool {
+    self.is_ident_data_type(ident, &|t| matches!(t, Token::TDATE))
+  }
+
+  /// See [`is_ident_time_data_type`]
+  fn is_ident_time_data_type(&self, ident: &Identifier) -> bool {
+    self.is_ident_data_type(ident, &|t| matches!(t, Token::TIME))
+  }
+
+  /// See [`is_ident_numeric_data_type`]
+  fn is_ident_numeric_data_type(&self, ident: &Identifier) -> bool {
+    self.is_ident_data_type(ident, &|t| {
+      matches!(
+        t,
+        Token::UINT
+          | Token::NINT
+          | Token::INTEGER
+          | Token::INT
+          | Token::NUMBER
+          | Token::FLOAT
+          | Token::FLOAT16
+          | Token::FLOAT32
+          | Token::FLOAT64
+          | Token::FLOAT1632
+          | Token::FLOAT3264
+          | Token::UNSIGNED
+          | Token::DECFRAC
+          | Token::BIGFLOAT
+      )
+    })
+  }
+
+  /// See [`is_ident_uint_data_type`]
+  fn is_ident_uint_data_type(&self, ident: &Identifier) -> bool {
+    self.is_ident_data_type(ident, &|t| matches!(t, Token::UINT))
+  }
+
+  /// See [`is_ident_nint_data_type`]
+  fn is_ident_nint_data_type(&self, ident: &Identifier) -> bool {
+    self.is_ident_data_type(ident, &|t| matches!(t, Token::NINT))
+  }
+
+  /// See [`is_ident_integer_data_type`]
+  fn is_ident_integer_data_type(&self, ident: &Identifier) -> bool {
+    self.is_ident_data_type(ident, &|t| {
+      matches!(
+        t,
+        Token::INT | Token::INTEGER | Token::NINT | Token::UINT | Token::NUMBER | Token::UNSIGNED
+      )
+    })
+  }
+
+  /// See [`is_ident_float_data_type`]
+  fn is_ident_float_data_type(&self, ident: &Identifier) -> bool {
+    self.is_ident_data_type(ident, &|t| {
+      matches!(
+        t,
+        Token::FLOAT
+          | Token::FLOAT16
+          | Token::FLOAT1632
+          | Token::FLOAT32
+          | Token::FLOAT3264
+          | Token::FLOAT64
+          | Token::NUMBER
+      )
+    })
+  }
+
+  /// See [`is_ident_string_data_type`]
+  fn is_ident_string_data_type(&self, ident: &Identifier) -> bool {
+    self.is_ident_data_type(ident, &|t| matches!(t, Token::TEXT | Token::TSTR))
+  }
+
+  /// See [`is_ident_any_type`]
+  fn is_ident_any_type(&self, ident: &Identifier) -> bool {
+    self.is_ident_data_type(ident, &|t| matches!(t, Token::ANY))
+  }
+
+  /// See [`is_ident_byte_string_data_type`]
+  fn is_ident_byte_string_data_type(&self, ident: &Identifier) -> bool {
+    self.is_ident_data_type(ident, &|t| matches!(t, Token::BSTR | Token::BYTES))
+  }
+}
+
+impl<'a> Lookup<'a> for &'a CDDL<'a> {
+  fn rule_from_ident(&self, ident: &Identifier) -> Option<&'a Rule<'a>> {
+    rule_from_ident(self, ident)
+  }
+
+  fn group_rule_from_ident(&self, ident: &Identifier) -> Option<&'a GroupRule<'a>> {
+    group_rule_from_ident(self, ident)
+  }
+
+  fn type_choice_alternates_from_ident(&self, ident: &Identifier) -> Vec<&'a Type<'a>> {
+    type_choice_alternates_from_ident(self, ident)
+  }
+
+  fn group_choice_alternates_from_ident(&self, ident: &Identifier) -> Vec<&'a GroupEntry<'a>> {
+    group_choice_alternates_from_ident(self, ident)
+  }
+}
+
+/// Types of the type rule with the given name and of its type choice
+/// alternates
+fn types_from_ident<'a, L: Lookup<'a> + ?Sized>(
+  lookup: &L,
+  ident: &Identifier,
+) -> impl Iterator<Item = &'a Type<'a>> {
+  let rule = match lookup.rule_from_ident(ident) {
+    Some(Rule::Type { rule, .. }) => Some(&rule.value),
+    _ => None,
+  };
+
+  rule
+    .into_iter()
+    .chain(lookup.type_choice_alternates_from_ident(ident))
+}
+
+/// Bytes of a byte string literal, decoding prefixed base16 and base64 byte
+/// strings
+pub fn byte_value_bytes(value: &ByteValue) -> Option<Vec<u8>> {
+  match value {
+    ByteValue::UTF8(b) => Some(b.to_vec()),
+    ByteValue::B16(b) => base16::decode(
+      &b.iter()
+        .filter(|b| !b.is_ascii_whitespace())
+        .copied()
+        .collect::<Vec<_>>(),
+    )
+    .ok(),
+    ByteValue::B64(b) => base64::decode_config(
+      b.iter()
+        .filter(|b| !b.is_ascii_whitespace())
+        .copied()
+        .collect::<Vec<_>>(),
+      base64::URL_SAFE,
+    )
+    .ok(),
+  }
 }
 
 /// Validate array length and \[non\]homogeneity based on a given optional
@@ -946,72 +1295,7 @@ pub fn entry_counts_from_group<'a, 'b: 'a>(
   cddl: &'a CDDL,
   group: &'b Group<'a>,
 ) -> Vec<EntryCount> {
-  // Each EntryCount is associated with a group choice in the given group
-  let mut entry_counts = Vec::new();
-
-  for gc in group.group_choices.iter() {
-    let mut count = 0;
-    let mut entry_occurrence = None;
-
-    for (idx, ge) in gc.group_entries.iter().enumerate() {
-      match &ge.0 {
-        GroupEntry::ValueMemberKey { ge, .. } => {
-          if idx == 1 {
-            if let Some(occur) = &ge.occur {
-              entry_occurrence = Some(occur.occur)
-            }
-          }
-
-          count += 1;
-        }
-        GroupEntry::InlineGroup { group, occur, .. } => {
-          if idx == 1 {
-            if let Some(occur) = occur {
-              entry_occurrence = Some(occur.occur)
-            }
-          }
-
-          entry_counts = entry_counts_from_group(cddl, group);
-        }
-        GroupEntry::TypeGroupname { ge, .. } => {
-          if idx == 1 {
-            if let Some(occur) = &ge.occur {
-              entry_occurrence = Some(occur.occur)
-            }
-          }
-
-          if let Some(gr) = group_rule_from_ident(cddl, &ge.name) {
-            if let GroupEntry::InlineGroup { group, .. } = &gr.entry {
-              if group.group_choices.len() == 1 {
-                count += if let Some(ec) = entry_counts_from_group(cddl, group).first() {
-                  ec.count
-                } else {
-                  0
-                };
-              } else {
-                entry_counts.append(&mut entry_counts_from_group(cddl, group));
-              }
-            } else {
-              entry_counts.append(&mut entry_counts_from_group(cddl, &gr.entry.clone().into()));
-            }
-          } else if group_choice_alternates_from_ident(cddl, &ge.name).is_empty() {
-            count += 1;
-          } else {
-            for ge in group_choice_alternates_from_ident(cddl, &ge.name).into_iter() {
-              entry_counts.append(&mut entry_counts_from_group(cddl, &ge.clone().into()));
-            }
-          }
-        }
-      }
-    }
-
-    entry_counts.push(EntryCount {
-      count,
-      entry_occurrence,
-    });
-  }
-
-  entry_counts
+  Lookup::entry_counts_from_group(&cddl, group)
 }
 
 /// Validate the number of entries given an array of possible valid entry counts
@@ -0,0 +1,335 @@
+#![cfg(feature = "cbor")]
+#![cfg(not(feature = "lsp"))]
+
+use std::cmp::Ordering;
+
+/// Largest power of two or five a mantissa is scaled by when comparing two
+/// numbers exactly. Numbers that would need to be scaled further are compared
+/// by their approximate binary magnitude instead
+const MAX_SCALE: i128 = 4096;
+
+/// log2(5), used to estimate the binary magnitude of a power of five
+const LOG2_5: f64 = 2.321_928_094_887_362;
+
+/// Arbitrary precision unsigned integer stored as little-endian 32-bit limbs
+/// without trailing zero limbs
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Magnitude(Vec<u32>);
+
+impl Magnitude {
+  fn from_be_bytes(bytes: &[u8]) -> Self {
+    let limbs = bytes
+      .rchunks(4)
+      .map(|chunk| chunk.iter().fold(0u32, |acc, b| (acc << 8) | *b as u32))
+      .collect();
+
+    Magnitude(limbs).normalized()
+  }
+
+  fn from_u128(mut n: u128) -> Self {
+    let mut limbs = Vec::new();
+    while n > 0 {
+      limbs.push(n as u32);
+      n >>= 32;
+    }
+
+    Magnitude(limbs)
+  }
+
+  fn normalized(mut self) -> Self {
+    while self.0.last() == Some(&0) {
+      self.0.pop();
+    }
+
+    self
+  }
+
+  fn is_zero(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  fn bits(&self) -> u64 {
+    match self.0.last() {
+      Some(limb) => (self.0.len() as u64 - 1) * 32 + (32 - limb.leading_zeros()) as u64,
+      None => 0,
+    }
+  }
+
+  fn add_small(&mut self, n: u32) {
+    let mut carry = n as u64;
+    for limb in self.0.iter_mut() {
+      if carry == 0 {
+        return;
+      }
+
+      let v = *limb as u64 + carry;
+      *limb = v as u32;
+      carry = v >> 32;
+    }
+
+    if carry > 0 {
+      self.0.push(carry as u32);
+    }
+  }
+
+  fn mul_small(&mut self, n: u32) {
+    let mut carry = 0u64;
+    for limb in self.0.iter_mut() {
+      let v = *limb as u64 * n as u64 + carry;
+      *limb = v as u32;
+      carry = v >> 32;
+    }
+
+    if carry > 0 {
+      self.0.push(carry as u32);
+    }
+
+    if n == 0 {
+      self.0.clear();
+    }
+  }
+
+  fn mul_pow2(&mut self, exp: u64) {
+    if self.is_zero() {
+      return;
+    }
+
+    let shift = (exp % 32) as u32;
+    if shift > 0 {
+      let mut carry = 0u32;
+      for limb in self.0.iter_mut() {
+        let v = ((*limb as u64) << shift) | carry as u64;
+        *limb = v as u32;
+        carry = (v >> 32) as u32;
+      }
+
+      if carry > 0 {
+        self.0.push(carry);
+      }
+    }
+
+    self.0.splice(0..0, vec![0; (exp / 32) as usize]);
+  }
+
+  fn mul_pow5(&mut self, mut exp: u64) {
+    // 5^13 is the largest power of five that fits in a limb
+    while exp >= 13 {
+      self.mul_small(1_220_703_125);
+      exp -= 13;
+    }
+
+    self.mul_small(5u32.pow(exp as u32));
+  }
+}
+
+impl Ord for Magnitude {
+  fn cmp(&self, other: &Self) -> Ordering {
+    self
+      .0
+      .len()
+      .cmp(&other.0.len())
+      .then_with(|| self.0.iter().rev().cmp(other.0.iter().rev()))
+  }
+}
+
+impl PartialOrd for Magnitude {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+/// Exact value of a number of the form `mantissa * 2^e2 * 5^e5`. Used to
+/// compare decimal fractions and bigfloats, whose mantissas may be bignums,
+/// against integer and floating point values without loss of precision
+#[derive(Debug, Clone)]
+pub struct ExactNumber {
+  negative: bool,
+  mantissa: Magnitude,
+  e2: i128,
+  e5: i128,
+}
+
+impl ExactNumber {
+  /// Exact value of an integer
+  pub fn from_int(i: i128) -> Self {
+    ExactNumber {
+      negative: i < 0,
+      mantissa: Magnitude::from_u128(i.unsigned_abs()),
+      e2: 0,
+      e5: 0,
+    }
+  }
+
+  /// Exact value of the content of a bignum tag. The value of a negative
+  /// bignum is -1 minus the given unsigned integer
+  pub fn from_bignum(bytes: &[u8], negative: bool) -> Self {
+    let mut mantissa = Magnitude::from_be_bytes(bytes);
+    if negative {
+      mantissa.add_small(1);
+    }
+
+    ExactNumber {
+      negative,
+      mantissa,
+      e2: 0,
+      e5: 0,
+    }
+  }
+
+  /// Decimal value of a floating point number as it is written, i.e. the
+  /// shortest decimal representation that round-trips to the same float. `None`
+  /// if the number is not finite
+  pub fn from_f64(f: f64) -> Option<Self> {
+    if !f.is_finite() {
+      return None;
+    }
+
+    let s = format!("{:e}", f.abs());
+    let (digits, exponent) = s.split_once('e')?;
+    let exponent = exponent.parse::<i128>().ok()?;
+
+    let mut mantissa = Magnitude(Vec::new());
+    let mut fraction_digits = 0;
+    let mut is_fraction = false;
+    for c in digits.chars() {
+      if c == '.' {
+        is_fraction = true;
+        continue;
+      }
+
+      mantissa.mul_small(10);
+      mantissa.add_small(c.to_digit(10)?);
+      if is_fraction {
+        fraction_digits += 1;
+      }
+    }
+
+    Some(
+      ExactNumber {
+        negative: f.is_sign_negative(),
+        mantissa,
+        e2: 0,
+        e5: 0,
+      }
+      .scaled_by_ten(exponent - fraction_digits),
+    )
+  }
+
+  /// Number multiplied by 10^exponent, i.e. the value of a decimal fraction
+  /// with this number as its mantissa
+  pub fn scaled_by_ten(mut self, exponent: i128) -> Self {
+    self.e2 = self.e2.saturating_add(exponent);
+    self.e5 = self.e5.saturating_add(exponent);
+    self
+  }
+
+  /// Number multiplied by 2^exponent, i.e. the value of a bigfloat with this
+  /// number as its mantissa
+  pub fn scaled_by_two(mut self, exponent: i128) -> Self {
+    self.e2 = self.e2.saturating_add(exponent);
+    self
+  }
+
+  fn signum(&self) -> i8 {
+    if self.mantissa.is_zero() {
+      0
+    } else if self.negative {
+      -1
+    } else {
+      1
+    }
+  }
+
+  /// Approximate binary logarithm of the absolute value. The actual value lies
+  /// within one of the estimate
+  fn log2(&self) -> f64 {
+    self.mantissa.bits() as f64 + self.e2 as f64 + self.e5 as f64 * LOG2_5
+  }
+
+  fn cmp_abs(&self, other: &Self) -> Ordering {
+    let (a, b) = (self.log2(), other.log2());
+    let margin = 2.0 + a.abs().max(b.abs()) * f64::EPSILON * 4.0;
+    if (a - b).abs() > margin {
+      return a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+    }
+
+    let e2 = self.e2.min(other.e2);
+    let e5 = self.e5.min(other.e5);
+    let scales = [self.e2 - e2, self.e5 - e5, other.e2 - e2, other.e5 - e5];
+    if scales.iter().any(|s| *s > MAX_SCALE) {
+      return a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+    }
+
+    let mut x = self.mantissa.clone();
+    x.mul_pow2(scales[0] as u64);
+    x.mul_pow5(scales[1] as u64);
+
+    let mut y = other.mantissa.clone();
+    y.mul_pow2(scales[2] as u64);
+    y.mul_pow5(scales[3] as u64);
+
+    x.cmp(&y)
+  }
+}
+
+impl Ord for ExactNumber {
+  fn cmp(&self, other: &Self) -> Ordering {
+    match self.signum().cmp(&other.signum()) {
+      Ordering::Equal => match self.signum() {
+        0 => Ordering::Equal,
+        1 => self.cmp_abs(other),
+        _ => other.cmp_abs(self),
+      },
+      ordering => ordering,
+    }
+  }
+}
+
+impl PartialOrd for ExactNumber {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl PartialEq for ExactNumber {
+  fn eq(&self, other: &Self) -> bool {
+    self.cmp(other) == Ordering::Equal
+  }
+}
+
+impl Eq for ExactNumber {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn compare_decimal_fractions_and_bigfloats() {
+    // 27315 * 10^-2 == 273.15
+    let decfrac = ExactNumber::from_int(27315).scaled_by_ten(-2);
+    assert_eq!(decfrac, ExactNumber::from_f64(273.15).unwrap());
+    assert!(decfrac < ExactNumber::from_int(274));
+    assert!(decfrac > ExactNumber::from_int(273));
+
+    // 3 * 2^-1 == 1.5
+    let bigfloat = ExactNumber::from_int(3).scaled_by_two(-1);
+    assert_eq!(bigfloat, ExactNumber::from_f64(1.5).unwrap());
+    assert!(bigfloat < decfrac);
+
+    // 1 * 10^-1 is not representable as a float but is equal to 0.1 as written
+    let tenth = ExactNumber::from_int(1).scaled_by_ten(-1);
+    assert_eq!(tenth, ExactNumber::from_f64(0.1).unwrap());
+    assert!(ExactNumber::from_int(-1).scaled_by_ten(-1) < tenth);
+
+    // -1 - 2^64 as a negative bignum
+    let bignint = ExactNumber::from_bignum(&[1, 0, 0, 0, 0, 0, 0, 0, 0], true);
+    assert_eq!(bignint, ExactNumber::from_int(-(1 << 64) - 1));
+    assert!(bignint < ExactNumber::from_int(i64::MIN as i128));
+
+    assert!(ExactNumber::from_int(1).scaled_by_ten(1_000_000) > ExactNumber::from_int(i128::MAX));
+    assert_eq!(
+      ExactNumber::from_int(0).scaled_by_ten(5),
+      ExactNumber::from_int(0)
+    );
+  }
+}
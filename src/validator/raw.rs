@@ -0,0 +1,658 @@
+#![cfg(feature = "cbor")]
+#![cfg(not(feature = "lsp"))]
+
+use std::convert::TryFrom;
+
+use ciborium::value::{Integer, Value};
+
+/// Error decoding a CBOR data item
+pub type Error = ciborium::de::Error<std::io::Error>;
+
+/// Maximum nesting depth of arrays, maps and tags, matching the recursion limit
+/// of ciborium
+pub(crate) const RECURSION_LIMIT: usize = 256;
+
+/// Encoded width of a floating-point number, ordered from narrowest to widest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FloatWidth {
+  /// IEEE 754 half-precision, i.e. `#7.25`
+  Half,
+  /// IEEE 754 single-precision, i.e. `#7.26`
+  Single,
+  /// IEEE 754 double-precision, i.e. `#7.27`
+  Double,
+}
+
+impl FloatWidth {
+  /// Additional information of the initial byte of a float with this width
+  pub fn additional_info(&self) -> u8 {
+    match self {
+      FloatWidth::Half => 25,
+      FloatWidth::Single => 26,
+      FloatWidth::Double => 27,
+    }
+  }
+}
+
+/// Encoding of a CBOR data item. `ciborium::value::Value` does not retain the
+/// width of floating-point numbers, unassigned simple values, the length of
+/// arguments nor whether strings, arrays and maps are of indefinite length, all
+/// of which are needed to validate major type 7 constraints and preferred
+/// serialization
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawItem {
+  /// Byte offset of the initial byte of the data item
+  pub offset: usize,
+  /// Additional information of the initial byte, i.e. the argument itself if
+  /// below 24, the length of the argument that follows (24 to 27) or an
+  /// indefinite length (31)
+  pub info: u8,
+  /// Argument of the head of the data item. `None` if of indefinite length
+  pub argument: Option<u64>,
+  /// Kind of the data item, including the encoding of any nested data items
+  pub kind: RawKind,
+}
+
+/// Kind of an encoded CBOR data item
+#[derive(Debug, Clone, PartialEq)]
+pub enum RawKind {
+  /// Unsigned or negative integer (major types 0 and 1)
+  Integer,
+  /// Byte string (major type 2) and its chunks if of indefinite length
+  Bytes(Vec<RawItem>),
+  /// Text string (major type 3) and its chunks if of indefinite length
+  Text(Vec<RawItem>),
+  /// Array (major type 4) and its items
+  Array(Vec<RawItem>),
+  /// Map (major type 5)
+  Map {
+    /// Encoding of the entries of the map
+    entries: Vec<(RawItem, RawItem)>,
+    /// Indices of the keys whose encoding does not sort bytewise after the
+    /// encoding of the preceding key, as required by core deterministic
+    /// encoding (RFC 8949 section 4.2.1). Includes duplicate keys
+    unordered_keys: Vec<usize>,
+  },
+  /// Tag (major type 6) and the tagged data item
+  Tag(Box<RawItem>),
+  /// Floating-point number (major type 7)
+  Float(FloatWidth),
+  /// Simple value (major type 7), including false (20), true (21), null (22)
+  /// and undefined (23)
+  Simple(u8),
+}
+
+impl RawItem {
+  /// Encoding of the array item at the given index
+  pub fn array_item(&self, idx: usize) -> Option<&RawItem> {
+    match &self.kind {
+      RawKind::Array(items) => items.get(idx),
+      _ => None,
+    }
+  }
+
+  /// Encoding of the map entry at the given index
+  pub fn map_entry(&self, idx: usize) -> Option<&(RawItem, RawItem)> {
+    match &self.kind {
+      RawKind::Map { entries, .. } => entries.get(idx),
+      _ => None,
+    }
+  }
+
+  /// Encoding of the tagged data item
+  pub fn tagged_item(&self) -> Option<&RawItem> {
+    match &self.kind {
+      RawKind::Tag(item) => Some(item),
+      _ => None,
+    }
+  }
+
+  /// Whether or not the data item is a string, array or map of indefinite
+  /// length
+  pub fn is_indefinite(&self) -> bool {
+    self.argument.is_none()
+  }
+
+  /// Number of bytes following the initial byte that encode the argument
+  pub fn argument_len(&self) -> usize {
+    argument_len(self.info)
+  }
+
+  /// Value of a floating-point number
+  pub fn float_value(&self) -> Option<f64> {
+    match (&self.kind, self.argument) {
+      (RawKind::Float(FloatWidth::Half), Some(bits)) => Some(f16_to_f64(bits as u16)),
+      (RawKind::Float(FloatWidth::Single), Some(bits)) => Some(f32::from_bits(bits as u32) as f64),
+      (RawKind::Float(FloatWidth::Double), Some(bits)) => Some(f64::from_bits(bits)),
+      _ => None,
+    }
+  }
+
+  /// Additional information of the preferred serialization of the head of the
+  /// data item as defined by RFC 8949 section 4.1, i.e. the shortest argument
+  /// or, for floating-point numbers, the narrowest width that preserves the
+  /// value. Nested data items are not taken into account
+  pub fn preferred_info(&self) -> u8 {
+    if let Some(f) = self.float_value() {
+      return [FloatWidth::Half, FloatWidth::Single, FloatWidth::Double]
+        .iter()
+        .find(|w| is_float_representable(f, **w))
+        .map(|w| w.additional_info())
+        .unwrap_or(self.info);
+    }
+
+    match (&self.kind, self.argument) {
+      // Simple values are always encoded in the shortest form
+      (RawKind::Simple(_), _) | (_, None) => self.info,
+      (_, Some(a)) if a < 24 => a as u8,
+      (_, Some(a)) if a <= u8::MAX as u64 => 24,
+      (_, Some(a)) if a <= u16::MAX as u64 => 25,
+      (_, Some(a)) if a <= u32::MAX as u64 => 26,
+      _ => 27,
+    }
+  }
+
+  /// Whether or not the head of the data item uses preferred serialization.
+  /// Nested data items are not taken into account
+  pub fn is_preferred(&self) -> bool {
+    self.info == self.preferred_info()
+  }
+}
+
+/// Number of bytes following an initial byte with the given additional
+/// information that encode the argument
+pub fn argument_len(info: u8) -> usize {
+  match info {
+    24 => 1,
+    25 => 2,
+    26 => 4,
+    27 => 8,
+    _ => 0,
+  }
+}
+
+/// Decode a single CBOR data item from the given slice, retaining its encoding.
+/// Bytes following the data item are ignored. Simple values other than false,
+/// true, null and undefined cannot be represented by `Value` and are decoded as
+/// `Value::Null`
+pub fn from_slice(bytes: &[u8]) -> Result<(Value, RawItem), Error> {
+  Decoder {
+    bytes,
+    offset: 0,
+    depth: 0,
+  }
+  .item()
+}
+
+/// Decode a CBOR sequence (RFC 8742), i.e. zero or more concatenated data
+/// items, from the given slice. The data items are returned as an array so that
+/// they can be matched against the array controller of a `.cborseq` control,
+/// along with the encoding of each data item. The head of the array is not part
+/// of the input and is given its preferred serialization
+pub fn sequence_from_slice(bytes: &[u8]) -> Result<(Value, RawItem), Error> {
+  let mut decoder = Decoder {
+    bytes,
+    offset: 0,
+    depth: 0,
+  };
+
+  let mut values = Vec::new();
+  let mut items = Vec::new();
+  while decoder.remaining() > 0 {
+    let (value, item) = decoder.item()?;
+    values.push(value);
+    items.push(item);
+  }
+
+  let mut raw = RawItem {
+    offset: 0,
+    info: 0,
+    argument: Some(values.len() as u64),
+    kind: RawKind::Array(items),
+  };
+  raw.info = raw.preferred_info();
+
+  Ok((Value::Array(values), raw))
+}
+
+struct Decoder<'b> {
+  bytes: &'b [u8],
+  offset: usize,
+  depth: usize,
+}
+
+/// Argument of the head of a data item. `None` for indefinite-length items and
+/// the break stop code
+type Argument = Option<u64>;
+
+impl<'b> Decoder<'b> {
+  fn read(&mut self, len: usize) -> Result<&'b [u8], Error> {
+    match self.bytes.get(self.offset..self.offset.saturating_add(len)) {
+      Some(b) if b.len() == len => {
+        self.offset += len;
+        Ok(b)
+      }
+      _ => Err(Error::Io(std::io::ErrorKind::UnexpectedEof.into())),
+    }
+  }
+
+  fn remaining(&self) -> usize {
+    self.bytes.len() - self.offset
+  }
+
+  fn is_break(&self) -> bool {
+    self.bytes.get(self.offset) == Some(&0xff)
+  }
+
+  fn head(&mut self) -> Result<(u8, u8, Argument), Error> {
+    let offset = self.offset;
+    let initial = self.read(1)?[0];
+    let (major, info) = (initial >> 5, initial & 0x1f);
+
+    let argument = match info {
+      0..=23 => Some(info as u64),
+      24 => Some(self.read(1)?[0] as u64),
+      25 => Some(u16::from_be_bytes([self.read(1)?[0], self.read(1)?[0]]) as u64),
+      26 => {
+        let mut b = [0; 4];
+        b.copy_from_slice(self.read(4)?);
+        Some(u32::from_be_bytes(b) as u64)
+      }
+      27 => {
+        let mut b = [0; 8];
+        b.copy_from_slice(self.read(8)?);
+        Some(u64::from_be_bytes(b))
+      }
+      31 if matches!(major, 2..=5 | 7) => None,
+      _ => return Err(Error::Syntax(offset)),
+    };
+
+    Ok((major, info, argument))
+  }
+
+  fn len(&self, argument: u64, offset: usize) -> Result<usize, Error> {
+    // Every byte, array item or map entry takes up at least one byte, so a
+    // length larger than the remaining input is truncated
+    match usize::try_from(argument) {
+      Ok(len) if len <= self.remaining() => Ok(len),
+      Ok(_) => Err(Error::Io(std::io::ErrorKind::UnexpectedEof.into())),
+      Err(_) => Err(Error::Syntax(offset)),
+    }
+  }
+
+  fn nested<V>(&mut self, f: impl FnOnce(&mut Self) -> Result<V, Error>) -> Result<V, Error> {
+    if self.depth == RECURSION_LIMIT {
+      return Err(Error::RecursionLimitExceeded);
+    }
+
+    self.depth += 1;
+    let result = f(self);
+    self.depth -= 1;
+
+    result
+  }
+
+  /// Content of a definite or indefinite length byte or text string and the
+  /// encoding of its chunks
+  fn string(
+    &mut self,
+    major: u8,
+    argument: Argument,
+    offset: usize,
+  ) -> Result<(Vec<u8>, Vec<RawItem>), Error> {
+    if let Some(argument) = argument {
+      let len = self.len(argument, offset)?;
+      return Ok((self.read(len)?.to_vec(), Vec::new()));
+    }
+
+    let mut content = Vec::new();
+    let mut chunks = Vec::new();
+    while !self.is_break() {
+      let chunk_offset = self.offset;
+      match self.head()? {
+        (m, info, Some(argument)) if m == major => {
+          let len = self.len(argument, chunk_offset)?;
+          content.extend_from_slice(self.read(len)?);
+          chunks.push(RawItem {
+            offset: chunk_offset,
+            info,
+            argument: Some(argument),
+            kind: if major == 2 {
+              RawKind::Bytes(Vec::new())
+            } else {
+              RawKind::Text(Vec::new())
+            },
+          });
+        }
+        _ => return Err(Error::Syntax(chunk_offset)),
+      }
+    }
+    self.read(1)?;
+
+    Ok((content, chunks))
+  }
+
+  fn item(&mut self) -> Result<(Value, RawItem), Error> {
+    let offset = self.offset;
+    let (major, info, argument) = self.head()?;
+
+    let (value, kind) = match (major, argument) {
+      (0, Some(argument)) => (Value::Integer(argument.into()), RawKind::Integer),
+      (1, Some(argument)) => {
+        let i = Integer::try_from(-1 - argument as i128).map_err(|_| Error::Syntax(offset))?;
+
+        (Value::Integer(i), RawKind::Integer)
+      }
+      (2, _) => {
+        let (content, chunks) = self.string(major, argument, offset)?;
+
+        (Value::Bytes(content), RawKind::Bytes(chunks))
+      }
+      (3, _) => {
+        let (content, chunks) = self.string(major, argument, offset)?;
+        let text =
+          String::from_utf8(content).map_err(|e| Error::semantic(offset, e.to_string()))?;
+
+        (Value::Text(text), RawKind::Text(chunks))
+      }
+      (4, _) => self.nested(|d| {
+        let mut values = Vec::new();
+        let mut items = Vec::new();
+        match argument {
+          Some(argument) => {
+            for _ in 0..d.len(argument, offset)? {
+              let (value, item) = d.item()?;
+              values.push(value);
+              items.push(item);
+            }
+          }
+          None => {
+            while !d.is_break() {
+              let (value, item) = d.item()?;
+              values.push(value);
+              items.push(item);
+            }
+            d.read(1)?;
+          }
+        }
+
+        Ok((Value::Array(values), RawKind::Array(items)))
+      })?,
+      (5, _) => self.nested(|d| {
+        let len = argument.map(|a| d.len(a, offset)).transpose()?;
+        let bytes = d.bytes;
+
+        let mut values = Vec::new();
+        let mut entries = Vec::new();
+        let mut unordered_keys = Vec::new();
+        let mut previous_key: Option<&[u8]> = None;
+        while len.map_or(!d.is_break(), |len| values.len() < len) {
+          let key_offset = d.offset;
+          let (k, raw_k) = d.item()?;
+          let key = &bytes[key_offset..d.offset];
+          if matches!(previous_key, Some(p) if key <= p) {
+            unordered_keys.push(values.len());
+          }
+          previous_key = Some(key);
+
+          let (v, raw_v) = d.item()?;
+          values.push((k, v));
+          entries.push((raw_k, raw_v));
+        }
+
+        if len.is_none() {
+          d.read(1)?;
+        }
+
+        Ok((
+          Value::Map(values),
+          RawKind::Map {
+            entries,
+            unordered_keys,
+          },
+        ))
+      })?,
+      (6, Some(tag)) => self.nested(|d| {
+        let is_definite_bytes =
+          matches!(d.bytes.get(d.offset), Some(b) if b >> 5 == 2 && b & 0x1f != 31);
+        let (value, item) = d.item()?;
+        let kind = RawKind::Tag(Box::new(item));
+
+        // Like ciborium, bignums that fit in 128 bits are decoded as integers
+        match (tag, value) {
+          (2..=3, Value::Bytes(b)) if is_definite_bytes && b.len() <= 16 => {
+            let n = b.iter().fold(0u128, |n, b| (n << 8) | *b as u128);
+            let value = if tag == 2 {
+              Value::from(n)
+            } else {
+              Value::from(
+                i128::try_from(n)
+                  .map(|n| n ^ !0)
+                  .map_err(|_| Error::semantic(offset, "integer too large"))?,
+              )
+            };
+
+            Ok((value, kind))
+          }
+          (_, value) => Ok((Value::Tag(tag, Box::new(value)), kind)),
+        }
+      })?,
+      (7, Some(argument)) => match info {
+        0..=24 => {
+          // Simple values below 32 must be encoded in the initial byte
+          if info == 24 && argument < 32 {
+            return Err(Error::Syntax(offset));
+          }
+
+          let value = match argument {
+            20 => Value::Bool(false),
+            21 => Value::Bool(true),
+            _ => Value::Null,
+          };
+
+          (value, RawKind::Simple(argument as u8))
+        }
+        25 => (
+          Value::Float(f16_to_f64(argument as u16)),
+          RawKind::Float(FloatWidth::Half),
+        ),
+        26 => (
+          Value::Float(f32::from_bits(argument as u32) as f64),
+          RawKind::Float(FloatWidth::Single),
+        ),
+        _ => (
+          Value::Float(f64::from_bits(argument)),
+          RawKind::Float(FloatWidth::Double),
+        ),
+      },
+      _ => return Err(Error::Syntax(offset)),
+    };
+
+    Ok((
+      value,
+      RawItem {
+        offset,
+        info,
+        argument,
+        kind,
+      },
+    ))
+  }
+}
+
+/// Value of an IEEE 754 half-precision float
+pub(crate) fn f16_to_f64(bits: u16) -> f64 {
+  let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+  let exponent = ((bits >> 10) & 0x1f) as i32;
+  let fraction = (bits & 0x3ff) as f64;
+
+  match exponent {
+    0 => sign * fraction * 2f64.powi(-24),
+    31 if fraction == 0.0 => sign * f64::INFINITY,
+    31 => f64::NAN,
+    _ => sign * (1.0 + fraction / 1024.0) * 2f64.powi(exponent - 15),
+  }
+}
+
+/// Whether or not the given float can be represented without loss of precision
+/// by a float of the given width
+pub fn is_float_representable(f: f64, width: FloatWidth) -> bool {
+  match width {
+    FloatWidth::Double => true,
+    FloatWidth::Single => f.is_nan() || (f as f32) as f64 == f,
+    FloatWidth::Half => f.is_nan() || f16_from_f64(f).map(f16_to_f64) == Some(f),
+  }
+}
+
+/// Bits of the IEEE 754 half-precision float with the same value as the given
+/// float, if there is one
+pub(crate) fn f16_from_f64(f: f64) -> Option<u16> {
+  let sign = if f.is_sign_negative() { 0x8000 } else { 0 };
+  if f.is_infinite() {
+    return Some(sign | 0x7c00);
+  }
+  if f == 0.0 {
+    return Some(sign);
+  }
+
+  let abs = f.abs();
+  let exponent = abs.log2().floor() as i32;
+  if exponent < -14 {
+    // Subnormal half-precision floats are multiples of 2^-24
+    let fraction = abs / 2f64.powi(-24);
+    if fraction.fract() != 0.0 || fraction >= 1024.0 {
+      return None;
+    }
+
+    return Some(sign | fraction as u16);
+  }
+  if exponent > 15 {
+    return None;
+  }
+
+  let fraction = (abs / 2f64.powi(exponent) - 1.0) * 1024.0;
+  if fraction.fract() != 0.0 || !(0.0..1024.0).contains(&fraction) {
+    return None;
+  }
+
+  Some(sign | (((exponent + 15) as u16) << 10) | fraction as u16)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn decode_floats_and_simple_values() -> Result<(), Error> {
+    // [1.5 as half, 1.5 as single, 1.5 as double, undefined, simple(16),
+    // simple(255)]
+    let bytes = [
+      0x86, 0xf9, 0x3e, 0x00, 0xfa, 0x3f, 0xc0, 0x00, 0x00, 0xfb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0,
+      0xf7, 0xf0, 0xf8, 0xff,
+    ];
+
+    let (value, raw) = from_slice(&bytes)?;
+    assert_eq!(
+      value,
+      Value::Array(vec![
+        Value::Float(1.5),
+        Value::Float(1.5),
+        Value::Float(1.5),
+        Value::Null,
+        Value::Null,
+        Value::Null,
+      ])
+    );
+
+    let kinds = match raw.kind {
+      RawKind::Array(items) => items.into_iter().map(|i| i.kind).collect::<Vec<_>>(),
+      _ => panic!("expected array"),
+    };
+    assert_eq!(
+      kinds,
+      vec![
+        RawKind::Float(FloatWidth::Half),
+        RawKind::Float(FloatWidth::Single),
+        RawKind::Float(FloatWidth::Double),
+        RawKind::Simple(23),
+        RawKind::Simple(16),
+        RawKind::Simple(255),
+      ]
+    );
+
+    // Two-byte encoding of a simple value below 32
+    assert!(from_slice(&[0xf8, 0x10]).is_err());
+
+    Ok(())
+  }
+
+  #[test]
+  fn decode_indefinite_length_items() -> Result<(), Error> {
+    // {_ "a": [_ h'01', (_ h'02', h'03')]}
+    let bytes = [
+      0xbf, 0x61, 0x61, 0x9f, 0x41, 0x01, 0x5f, 0x41, 0x02, 0x41, 0x03, 0xff, 0xff, 0xff,
+    ];
+
+    let (value, _) = from_slice(&bytes)?;
+    assert_eq!(
+      value,
+      Value::Map(vec![(
+        Value::Text("a".to_string()),
+        Value::Array(vec![Value::Bytes(vec![1]), Value::Bytes(vec![2, 3])])
+      )])
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn detect_non_preferred_serialization() -> Result<(), Error> {
+    // 1 with a 1-byte argument
+    let (_, raw) = from_slice(&[0x18, 0x01])?;
+    assert!(!raw.is_preferred());
+    assert_eq!(raw.preferred_info(), 1);
+
+    // 255 with a 1-byte argument
+    let (_, raw) = from_slice(&[0x18, 0xff])?;
+    assert!(raw.is_preferred());
+
+    // 256 with a 4-byte argument
+    let (_, raw) = from_slice(&[0x1a, 0x00, 0x00, 0x01, 0x00])?;
+    assert_eq!(raw.preferred_info(), 25);
+
+    // 1.5 as a single-precision float
+    let (_, raw) = from_slice(&[0xfa, 0x3f, 0xc0, 0x00, 0x00])?;
+    assert_eq!(raw.preferred_info(), 25);
+
+    // 100000.0 as a single-precision float
+    let (_, raw) = from_slice(&[0xfa, 0x47, 0xc3, 0x50, 0x00])?;
+    assert!(raw.is_preferred());
+
+    // (_ h'01' h'02') with a non-preferred second chunk
+    let (_, raw) = from_slice(&[0x5f, 0x41, 0x01, 0x58, 0x01, 0x02, 0xff])?;
+    assert!(raw.is_indefinite());
+    match raw.kind {
+      RawKind::Bytes(chunks) => {
+        assert!(chunks[0].is_preferred());
+        assert!(!chunks[1].is_preferred());
+      }
+      _ => panic!("expected byte string"),
+    }
+
+    Ok(())
+  }
+
+  #[test]
+  fn float_representability() {
+    assert!(is_float_representable(1.5, FloatWidth::Half));
+    assert!(is_float_representable(65504.0, FloatWidth::Half));
+    assert!(is_float_representable(
+      5.960464477539063e-8,
+      FloatWidth::Half
+    ));
+    assert!(!is_float_representable(0.1, FloatWidth::Half));
+    assert!(!is_float_representable(0.1, FloatWidth::Single));
+    assert!(is_float_representable(0.1, FloatWidth::Double));
+    assert!(is_float_representable(f64::INFINITY, FloatWidth::Half));
+  }
+}
@@ -0,0 +1,905 @@
+#![cfg(any(feature = "json", feature = "cbor"))]
+
+use super::compiled::CompiledSchema;
+use super::custom::CustomControls;
+#[cfg(all(feature = "cbor", not(target_arch = "wasm32")))]
+use super::stream::StreamValidator;
+use super::tags::TagRegistry;
+use super::*;
+use crate::ast::{OwnedCDDL, CDDL};
+
+use std::{fmt, sync::Arc};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::JsValue;
+
+/// Error parsing a CDDL schema or validating a document against it. The
+/// `JSON` and `CBOR` variants only exist with the `json` and `cbor` cargo
+/// features, so matching on an error requires a wildcard arm
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+  /// CDDL parsing error
+  CDDLParsing(String),
+  /// Error validating JSON
+  #[cfg(feature = "json")]
+  JSON(json::Error),
+  /// Error validating CBOR
+  #[cfg(feature = "cbor")]
+  CBOR(cbor::Error<std::io::Error>),
+  /// Document exceeds a limit of the validation options
+  LimitExceeded(String),
+  /// Validation of the document requires a disabled cargo feature
+  DisabledFeature(String),
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Error::CDDLParsing(error) => write!(f, "error parsing CDDL: {}", error),
+      #[cfg(feature = "json")]
+      Error::JSON(error) => write!(f, "{}", error),
+      #[cfg(feature = "cbor")]
+      Error::CBOR(error) => write!(f, "{}", error),
+      Error::LimitExceeded(error) => write!(f, "limit exceeded: {}", error),
+      Error::DisabledFeature(feature) => write!(f, "cargo feature {} is not enabled", feature),
+    }
+  }
+}
+
+impl std::error::Error for Error {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      #[cfg(feature = "json")]
+      Error::JSON(error) => Some(error),
+      #[cfg(feature = "cbor")]
+      Error::CBOR(error) => Some(error),
+      _ => None,
+    }
+  }
+}
+
+/// Decoded JSON value or CBOR data item to validate against a [`Schema`]. As
+/// with [`Error`], the variants depend on the enabled cargo features
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Document {
+  /// JSON value
+  #[cfg(feature = "json")]
+  JSON(serde_json::Value),
+  /// CBOR data item
+  #[cfg(feature = "cbor")]
+  CBOR(ciborium::value::Value),
+}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Value> for Document {
+  fn from(value: serde_json::Value) -> Self {
+    Document::JSON(value)
+  }
+}
+
+#[cfg(feature = "cbor")]
+impl From<ciborium::value::Value> for Document {
+  fn from(value: ciborium::value::Value) -> Self {
+    Document::CBOR(value)
+  }
+}
+
+/// Options for validating documents against a [`Schema`], independent of the
+/// cargo features and target the crate is compiled with
+///
+/// # Example
+///
+/// ```
+/// use cddl::validator::schema::ValidationOptions;
+///
+/// let options = ValidationOptions::new()
+///   .with_root_rule("person")
+///   .with_enabled_features(&["json"])
+///   .with_max_size(1 << 20)
+///   .with_max_depth(32)
+///   .with_first_error_only()
+///   .with_base16_byte_strings();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationOptions {
+  root_rule: Option<String>,
+  enabled_features: Option<Vec<String>>,
+  max_size: Option<usize>,
+  max_depth: Option<usize>,
+  max_buffer_size: Option<usize>,
+  is_first_error_only: bool,
+  is_base16_byte_strings: bool,
+}
+
+impl ValidationOptions {
+  /// Default options, validating against the root type rule without limits
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Validate against the type or group rule with the given name instead of
+  /// the root type rule
+  pub fn with_root_rule(mut self, rule: &str) -> Self {
+    self.root_rule = Some(rule.to_string());
+    self
+  }
+
+  /// Features enabled for the `.feature` control operator. Without enabled
+  /// features, targets of the `.feature` control operator are not validated.
+  /// Ignored unless the `additional-controls` cargo feature is enabled
+  pub fn with_enabled_features(mut self, features: &[&str]) -> Self {
+    self.enabled_features = Some(features.iter().map(|f| f.to_string()).collect());
+    self
+  }
+
+  /// Maximum size in bytes of serialized JSON and CBOR documents
+  pub fn with_max_size(mut self, bytes: usize) -> Self {
+    self.max_size = Some(bytes);
+    self
+  }
+
+  /// Maximum nesting depth of arrays, objects, maps and tags
+  pub fn with_max_depth(mut self, depth: usize) -> Self {
+    self.max_depth = Some(depth);
+    self
+  }
+
+  /// Maximum number of bytes buffered to decode a single data item when
+  /// validating a CBOR document read from a reader. Defaults to
+  /// [`DEFAULT_MAX_BUFFER_SIZE`](super::stream::DEFAULT_MAX_BUFFER_SIZE)
+  pub fn with_max_buffer_size(mut self, bytes: usize) -> Self {
+    self.max_buffer_size = Some(bytes);
+    self
+  }
+
+  /// Report only the first validation error. The document is still validated
+  /// as a whole, since the errors of type and group choices are only known to
+  /// apply once all of their alternatives are tried, so this limits the errors
+  /// reported rather than the time spent validating
+  pub fn with_first_error_only(mut self) -> Self {
+    self.is_first_error_only = true;
+    self
+  }
+
+  /// Decode JSON strings validated against byte string data types as base16
+  /// instead of base64url without padding. Ignored when validating CBOR
+  pub fn with_base16_byte_strings(mut self) -> Self {
+    self.is_base16_byte_strings = true;
+    self
+  }
+
+  fn check_size(&self, size: usize) -> Result<(), Error> {
+    match self.max_size {
+      Some(max_size) if size > max_size => Err(Error::LimitExceeded(format!(
+        "document size of {} bytes exceeds maximum of {} bytes",
+        size, max_size
+      ))),
+      _ => Ok(()),
+    }
+  }
+
+  fn check_depth(&self, exceeds: impl FnOnce(usize) -> bool) -> Result<(), Error> {
+    match self.max_depth {
+      Some(max_depth) if exceeds(max_depth) => Err(Error::LimitExceeded(format!(
+        "document nesting depth exceeds maximum of {}",
+        max_depth
+      ))),
+      _ => Ok(()),
+    }
+  }
+}
+
+#[cfg(feature = "json")]
+fn json_depth_exceeds(value: &serde_json::Value, max_depth: usize) -> bool {
+  match value {
+    serde_json::Value::Array(a) => {
+      max_depth == 0 || a.iter().any(|v| json_depth_exceeds(v, max_depth - 1))
+    }
+    serde_json::Value::Object(o) => {
+      max_depth == 0 || o.values().any(|v| json_depth_exceeds(v, max_depth - 1))
+    }
+    _ => false,
+  }
+}
+
+#[cfg(feature = "cbor")]
+fn cbor_depth_exceeds(value: &ciborium::value::Value, max_depth: usize) -> bool {
+  use ciborium::value::Value;
+
+  match value {
+    Value::Array(a) => max_depth == 0 || a.iter().any(|v| cbor_depth_exceeds(v, max_depth - 1)),
+    Value::Map(m) => {
+      max_depth == 0
+        || m.iter().any(|(k, v)| {
+          cbor_depth_exceeds(k, max_depth - 1) || cbor_depth_exceeds(v, max_depth - 1)
+        })
+    }
+    Value::Tag(_, v) => max_depth == 0 || cbor_depth_exceeds(v, max_depth - 1),
+    _ => false,
+  }
+}
+
+/// CDDL schema parsed once from its source, which it owns, for validating any
+/// number of JSON and CBOR documents. Unlike the `validate_*` functions and the
+/// constructors of the validators, the signatures of its methods do not depend
+/// on the cargo features and target the crate is compiled with. A schema is
+/// `'static`, `Send` and `Sync`, so it can be cached and shared across threads
+/// in an `Arc`. It is compiled into a [`CompiledSchema`] once it is parsed
+///
+/// # Example
+///
+/// ```
+/// use cddl::validator::schema::{Schema, ValidationOptions};
+///
+/// let schema = Schema::new(
+///   r#"
+///     person = {
+///       name: tstr,
+///       age: uint,
+///     }
+///   "#,
+/// )
+/// .unwrap();
+///
+/// let options = ValidationOptions::new();
+/// assert!(schema
+///   .validate_json(r#"{ "name": "John", "age": 42 }"#, &options)
+///   .is_ok());
+/// assert!(schema
+///   .validate_json(r#"{ "name": "John", "age": "42" }"#, &options)
+///   .is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Schema {
+  cddl: OwnedCDDL,
+  compiled: Arc<CompiledSchema<'static>>,
+  custom_controls: Option<Arc<CustomControls>>,
+  tag_registry: Option<Arc<TagRegistry>>,
+}
+
+impl From<OwnedCDDL> for Schema {
+  fn from(cddl: OwnedCDDL) -> Self {
+    let compiled = Arc::new(CompiledSchema::new(cddl.cddl()));
+
+    Schema {
+      cddl,
+      compiled,
+      custom_controls: None,
+      tag_registry: None,
+    }
+  }
+}
+
+impl Schema {
+  /// Parse a CDDL schema from the given source
+  pub fn new(source: impl Into<String>) -> Result<Self, Error> {
+    let cddl = OwnedCDDL::parse(source.into()).map_err(Error::CDDLParsing)?;
+
+    Ok(Schema::from(cddl))
+  }
+
+  /// Parse a CDDL schema from the given source, recognizing the given custom
+  /// control operators, whose callbacks are invoked when validating documents
+  pub fn with_custom_controls(
+    source: impl Into<String>,
+    controls: CustomControls,
+  ) -> Result<Self, Error> {
+    let cddl = OwnedCDDL::parse_with_custom_controls(source.into(), &controls.names())
+      .map_err(Error::CDDLParsing)?;
+
+    Ok(Schema {
+      custom_controls: Some(Arc::new(controls)),
+      ..Schema::from(cddl)
+    })
+  }
+
+  /// Check the content of CBOR tags with the checks of the given registry
+  /// instead of the predefined checks of common IANA registered tags. See
+  /// [`TagRegistry`]
+  pub fn with_tag_registry(mut self, registry: TagRegistry) -> Self {
+    self.tag_registry = Some(Arc::new(registry));
+    self
+  }
+
+  /// Source of the schema
+  pub fn source(&self) -> &str {
+    self.cddl.source()
+  }
+
+  /// AST of the schema
+  pub fn cddl(&self) -> &CDDL<'_> {
+    self.cddl.cddl()
+  }
+
+  /// Validate the given JSON document
+  pub fn validate_json(&self, json: &str, options: &ValidationOptions) -> Result<(), Error> {
+    #[cfg(feature = "json")]
+    {
+      options.check_size(json.len())?;
+
+      let (json, duplicate_keys) = json::from_str_with_duplicate_keys(json)
+        .map_err(|e| Error::JSON(json::Error::JSONParsing(e)))?;
+      options.check_depth(|max_depth| json_depth_exceeds(&json, max_depth))?;
+
+      self.run_json_validator(json, duplicate_keys, options)
+    }
+
+    #[cfg(not(feature = "json"))]
+    {
+      let _ = (json, options);
+      Err(Error::DisabledFeature("json".to_string()))
+    }
+  }
+
+  /// Validate the given CBOR document
+  pub fn validate_cbor(&self, cbor: &[u8], options: &ValidationOptions) -> Result<(), Error> {
+    #[cfg(feature = "cbor")]
+    {
+      options.check_size(cbor.len())?;
+
+      let (cbor, raw) =
+        raw::from_slice(cbor).map_err(|e| Error::CBOR(cbor::Error::CBORParsing(e)))?;
+      options.check_depth(|max_depth| cbor_depth_exceeds(&cbor, max_depth))?;
+
+      self.run_cbor_validator(cbor, Some(raw), options)
+    }
+
+    #[cfg(not(feature = "cbor"))]
+    {
+      let _ = (cbor, options);
+      Err(Error::DisabledFeature("cbor".to_string()))
+    }
+  }
+
+  /// Validate the CBOR document read from the given reader, validating arrays
+  /// and maps entry by entry as they are decoded instead of decoding the
+  /// document as a whole. See [`StreamValidator`](super::stream::StreamValidator)
+  /// for which data items are buffered. Not supported on wasm targets
+  pub fn validate_cbor_reader(
+    &self,
+    reader: impl std::io::Read,
+    options: &ValidationOptions,
+  ) -> Result<(), Error> {
+    self.validate_cbor_stream(reader, options, false)
+  }
+
+  /// Validate the CBOR sequence (RFC 8742) read from the given reader until
+  /// the end of its input. The data items of the sequence are matched against
+  /// the root rule, which must be a group rule or an array type rule, as if
+  /// they were the items of an array. Errors are located at the index of each
+  /// data item in the sequence, and the maximum size applies to the sequence
+  /// as a whole. See
+  /// [`StreamValidator::validate_sequence`](super::stream::StreamValidator::validate_sequence).
+  /// Not supported on wasm targets
+  pub fn validate_cbor_seq_reader(
+    &self,
+    reader: impl std::io::Read,
+    options: &ValidationOptions,
+  ) -> Result<(), Error> {
+    self.validate_cbor_stream(reader, options, true)
+  }
+
+  fn validate_cbor_stream(
+    &self,
+    reader: impl std::io::Read,
+    options: &ValidationOptions,
+    is_sequence: bool,
+  ) -> Result<(), Error> {
+    #[cfg(all(feature = "cbor", not(target_arch = "wasm32")))]
+    {
+      let mut reader = SizeLimitedReader {
+        reader,
+        remaining: options.max_size,
+        is_exceeded: false,
+      };
+      let result = self.run_stream_validator(&mut reader, options, is_sequence);
+      if reader.is_exceeded {
+        return options.check_size(usize::MAX);
+      }
+
+      match result {
+        Err(Error::CBOR(cbor::Error::CBORParsing(ciborium::de::Error::RecursionLimitExceeded)))
+          if options.max_depth.is_some() =>
+        {
+          options.check_depth(|_| true)
+        }
+        result => result,
+      }
+    }
+
+    #[cfg(not(all(feature = "cbor", not(target_arch = "wasm32"))))]
+    {
+      let _ = (reader, options, is_sequence);
+      Err(Error::DisabledFeature("cbor".to_string()))
+    }
+  }
+
+  /// Validate the given decoded JSON value or CBOR data item. The encoding of
+  /// CBOR data items is not known, so major type 7 constraints on the width
+  /// of floating-point numbers are validated against the value alone
+  pub fn validate_value(
+    &self,
+    value: impl Into<Document>,
+    options: &ValidationOptions,
+  ) -> Result<(), Error> {
+    match value.into() {
+      #[cfg(feature = "json")]
+      Document::JSON(json) => {
+        options.check_depth(|max_depth| json_depth_exceeds(&json, max_depth))?;
+        self.run_json_validator(json, Vec::new(), options)
+      }
+      #[cfg(feature = "cbor")]
+      Document::CBOR(cbor) => {
+        options.check_depth(|max_depth| cbor_depth_exceeds(&cbor, max_depth))?;
+        self.run_cbor_validator(cbor, None, options)
+      }
+    }
+  }
+
+  #[cfg(feature = "json")]
+  fn run_json_validator(
+    &self,
+    json: serde_json::Value,
+    duplicate_keys: Vec<json::DuplicateKey>,
+    options: &ValidationOptions,
+  ) -> Result<(), Error> {
+    #[cfg(all(feature = "additional-controls", not(target_arch = "wasm32")))]
+    let enabled_features = options
+      .enabled_features
+      .as_ref()
+      .map(|features| features.iter().map(|f| f.as_str()).collect::<Vec<_>>());
+
+    #[cfg(all(feature = "additional-controls", target_arch = "wasm32"))]
+    let jv = JSONValidator::new(self.cddl(), json, enabled_features(options));
+    #[cfg(all(feature = "additional-controls", not(target_arch = "wasm32")))]
+    let jv = JSONValidator::new(self.cddl(), json, enabled_features.as_deref());
+    #[cfg(not(feature = "additional-controls"))]
+    let jv = JSONValidator::new(self.cddl(), json);
+
+    let mut jv = jv
+      .with_duplicate_keys(duplicate_keys)
+      .with_compiled_schema(&self.compiled);
+    if let Some(controls) = &self.custom_controls {
+      jv = jv.with_custom_controls(controls);
+    }
+    if options.is_base16_byte_strings {
+      jv = jv.with_byte_string_encoding(json::ByteStringEncoding::Base16);
+    }
+
+    let result = match &options.root_rule {
+      Some(rule) => jv.validate_rule(rule),
+      None => jv.validate(),
+    };
+
+    match result {
+      Err(json::Error::Validation(mut errors)) if options.is_first_error_only => {
+        errors.truncate(1);
+        Err(Error::JSON(json::Error::Validation(errors)))
+      }
+      result => result.map_err(Error::JSON),
+    }
+  }
+
+  #[cfg(feature = "cbor")]
+  fn run_cbor_validator(
+    &self,
+    cbor: ciborium::value::Value,
+    raw: Option<raw::RawItem>,
+    options: &ValidationOptions,
+  ) -> Result<(), Error> {
+    #[cfg(all(feature = "additional-controls", not(target_arch = "wasm32")))]
+    let enabled_features = options
+      .enabled_features
+      .as_ref()
+      .map(|features| features.iter().map(|f| f.as_str()).collect::<Vec<_>>());
+
+    #[cfg(all(feature = "additional-controls", target_arch = "wasm32"))]
+    let mut cv = CBORValidator::new(self.cddl(), cbor, enabled_features(options));
+    #[cfg(all(feature = "additional-controls", not(target_arch = "wasm32")))]
+    let mut cv = CBORValidator::new(self.cddl(), cbor, enabled_features.as_deref());
+    #[cfg(not(feature = "additional-controls"))]
+    let mut cv = CBORValidator::new(self.cddl(), cbor);
+
+    cv = cv.with_compiled_schema(&self.compiled);
+    if let Some(controls) = &self.custom_controls {
+      cv = cv.with_custom_controls(controls);
+    }
+    if let Some(registry) = &self.tag_registry {
+      cv = cv.with_tag_registry(registry);
+    }
+    if let Some(raw) = raw {
+      cv = cv.with_encoding(raw);
+    }
+
+    let result = match &options.root_rule {
+      Some(rule) => cv.validate_rule(rule),
+      None => cv.validate(),
+    };
+
+    match result {
+      Err(cbor::Error::Validation(mut errors)) if options.is_first_error_only => {
+        errors.truncate(1);
+        Err(Error::CBOR(cbor::Error::Validation(errors)))
+      }
+      result => result.map_err(Error::CBOR),
+    }
+  }
+
+  #[cfg(all(feature = "cbor", not(target_arch = "wasm32")))]
+  fn run_stream_validator(
+    &self,
+    reader: impl std::io::Read,
+    options: &ValidationOptions,
+    is_sequence: bool,
+  ) -> Result<(), Error> {
+    #[cfg(feature = "additional-controls")]
+    let enabled_features = options
+      .enabled_features
+      .as_ref()
+      .map(|features| features.iter().map(|f| f.as_str()).collect::<Vec<_>>());
+
+    #[cfg(feature = "additional-controls")]
+    let sv = StreamValidator::new(self.cddl(), enabled_features.as_deref());
+    #[cfg(not(feature = "additional-controls"))]
+    let sv = StreamValidator::new(self.cddl());
+
+    let mut sv = sv.with_compiled_schema(&self.compiled);
+    if let Some(controls) = &self.custom_controls {
+      sv = sv.with_custom_controls(controls);
+    }
+    if let Some(registry) = &self.tag_registry {
+      sv = sv.with_tag_registry(registry);
+    }
+    if let Some(max_buffer_size) = options.max_buffer_size {
+      sv = sv.with_max_buffer_size(max_buffer_size);
+    }
+    if let Some(max_depth) = options.max_depth {
+      sv = sv.with_max_depth(max_depth);
+    }
+
+    let result = match (&options.root_rule, is_sequence) {
+      (Some(rule), false) => sv.validate_rule(reader, rule),
+      (None, false) => sv.validate(reader),
+      (Some(rule), true) => sv.validate_sequence_rule(reader, rule),
+      (None, true) => sv.validate_sequence(reader),
+    };
+
+    match result {
+      Err(cbor::Error::Validation(mut errors)) if options.is_first_error_only => {
+        errors.truncate(1);
+        Err(Error::CBOR(cbor::Error::Validation(errors)))
+      }
+      result => result.map_err(Error::CBOR),
+    }
+  }
+}
+
+// Reader failing once more than the given number of bytes, if any, are read
+#[cfg(all(feature = "cbor", not(target_arch = "wasm32")))]
+struct SizeLimitedReader<R> {
+  reader: R,
+  remaining: Option<usize>,
+  is_exceeded: bool,
+}
+
+#[cfg(all(feature = "cbor", not(target_arch = "wasm32")))]
+impl<R: std::io::Read> std::io::Read for SizeLimitedReader<R> {
+  fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+    let remaining = match self.remaining {
+      Some(remaining) => remaining,
+      None => return self.reader.read(buf),
+    };
+
+    let len = buf.len().min(remaining.saturating_add(1));
+    let read = self.reader.read(&mut buf[..len])?;
+    if read > remaining {
+      self.is_exceeded = true;
+      return Err(std::io::Error::new(
+        std::io::ErrorKind::Other,
+        "document size exceeds maximum",
+      ));
+    }
+
+    self.remaining = Some(remaining - read);
+
+    Ok(read)
+  }
+}
+
+#[cfg(all(feature = "additional-controls", target_arch = "wasm32"))]
+fn enabled_features(options: &ValidationOptions) -> Option<Box<[JsValue]>> {
+  options
+    .enabled_features
+    .as_ref()
+    .map(|features| features.iter().map(|f| JsValue::from(f.as_str())).collect())
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "json", feature = "cbor"))]
+mod tests {
+  use super::*;
+  use ciborium::value::Value;
+  use indoc::indoc;
+  use std::convert::TryFrom;
+
+  fn schema() -> Schema {
+    Schema::new(
+      indoc!(
+        r#"
+          person = {
+            name: tstr,
+            age: uint,
+            ? tags: [* tstr],
+          }
+
+          name = tstr .size (1..8)
+        "#
+      )
+      .to_string(),
+    )
+    .unwrap()
+  }
+
+  fn reasons(result: Result<(), Error>) -> Vec<String> {
+    match result {
+      Err(Error::JSON(json::Error::Validation(errors))) => {
+        errors.into_iter().map(|e| e.reason).collect()
+      }
+      Err(Error::CBOR(cbor::Error::Validation(errors))) => {
+        errors.into_iter().map(|e| e.reason).collect()
+      }
+      r => panic!("expected validation errors, got {:?}", r),
+    }
+  }
+
+  #[test]
+  fn validate_json_cbor_and_values() {
+    let schema = schema();
+    let options = ValidationOptions::new();
+
+    assert!(schema
+      .validate_json(r#"{ "name": "John", "age": 42 }"#, &options)
+      .is_ok());
+    assert_eq!(
+      reasons(schema.validate_json(r#"{ "name": 1, "age": "42" }"#, &options)).len(),
+      2
+    );
+
+    // { "name": "John", "age": 42 }
+    let cbor = b"\xa2\x64name\x64John\x63age\x18\x2a";
+    assert!(schema.validate_cbor(cbor, &options).is_ok());
+
+    assert!(schema
+      .validate_value(serde_json::json!({ "name": "John", "age": 42 }), &options)
+      .is_ok());
+    assert!(schema
+      .validate_value(
+        Value::Map(vec![
+          (
+            Value::Text("name".to_string()),
+            Value::Text("John".to_string())
+          ),
+          (Value::Text("age".to_string()), Value::Integer((-1).into())),
+        ]),
+        &options
+      )
+      .is_err());
+
+    assert!(matches!(
+      Schema::new("person = {"),
+      Err(Error::CDDLParsing(_))
+    ));
+  }
+
+  #[test]
+  fn validate_with_options() {
+    let schema = schema();
+
+    let options = ValidationOptions::new().with_root_rule("name");
+    assert!(schema.validate_json(r#""John""#, &options).is_ok());
+    assert!(schema.validate_json(r#""Johnathan""#, &options).is_err());
+    assert!(matches!(
+      schema.validate_json(
+        r#""John""#,
+        &ValidationOptions::new().with_root_rule("missing")
+      ),
+      Err(Error::JSON(json::Error::MissingRule(_)))
+    ));
+
+    let options = ValidationOptions::new().with_first_error_only();
+    assert_eq!(
+      reasons(schema.validate_json(r#"{ "name": 1, "age": "42" }"#, &options)).len(),
+      1
+    );
+
+    let bytes = Schema::new("a = bstr".to_string()).unwrap();
+    assert!(bytes
+      .validate_json(r#""AQID""#, &ValidationOptions::new())
+      .is_ok());
+    assert!(bytes
+      .validate_json(r#""010203""#, &ValidationOptions::new())
+      .is_err());
+    let options = ValidationOptions::new().with_base16_byte_strings();
+    assert!(bytes.validate_json(r#""010203""#, &options).is_ok());
+    assert!(bytes.validate_json(r#""AQID""#, &options).is_err());
+
+    let options = ValidationOptions::new().with_max_size(16);
+    assert!(matches!(
+      schema.validate_json(r#"{ "name": "John", "age": 42 }"#, &options),
+      Err(Error::LimitExceeded(_))
+    ));
+
+    let options = ValidationOptions::new().with_max_depth(1);
+    assert!(schema
+      .validate_json(r#"{ "name": "John", "age": 42 }"#, &options)
+      .is_ok());
+    assert!(matches!(
+      schema.validate_json(r#"{ "name": "John", "age": 42, "tags": ["a"] }"#, &options),
+      Err(Error::LimitExceeded(_))
+    ));
+    assert!(matches!(
+      schema.validate_value(
+        Value::Map(vec![
+          (
+            Value::Text("name".to_string()),
+            Value::Text("John".to_string())
+          ),
+          (Value::Text("age".to_string()), Value::Integer(42.into())),
+          (
+            Value::Text("tags".to_string()),
+            Value::Array(vec![Value::Text("a".to_string())])
+          ),
+        ]),
+        &options
+      ),
+      Err(Error::LimitExceeded(_))
+    ));
+  }
+
+  #[test]
+  fn validate_cbor_reader() {
+    let schema = schema();
+
+    // { "name": "John", "age": 42, "tags": ["a"] }
+    let cbor: &[u8] = b"\xa3\x64name\x64John\x63age\x18\x2a\x64tags\x81\x61a";
+    assert!(schema
+      .validate_cbor_reader(cbor, &ValidationOptions::new())
+      .is_ok());
+    assert!(schema
+      .validate_cbor_reader(&cbor[..cbor.len() - 1], &ValidationOptions::new())
+      .is_err());
+    assert!(schema
+      .validate_cbor_reader(
+        &b"\x64John"[..],
+        &ValidationOptions::new().with_root_rule("name")
+      )
+      .is_ok());
+
+    assert!(matches!(
+      schema.validate_cbor_reader(cbor, &ValidationOptions::new().with_max_size(16)),
+      Err(Error::LimitExceeded(_))
+    ));
+    assert!(matches!(
+      schema.validate_cbor_reader(cbor, &ValidationOptions::new().with_max_depth(1)),
+      Err(Error::LimitExceeded(_))
+    ));
+    assert!(matches!(
+      schema.validate_cbor_reader(cbor, &ValidationOptions::new().with_max_buffer_size(2)),
+      Err(Error::CBOR(cbor::Error::CBORParsing(_)))
+    ));
+  }
+
+  #[test]
+  fn validate_cbor_seq_reader() {
+    let schema = Schema::new("names = (* tstr .size (1..8))".to_string()).unwrap();
+
+    // "a", "b", "toolongname"
+    let cbor: &[u8] = b"\x61a\x61b\x6btoolongname";
+    assert!(schema
+      .validate_cbor_seq_reader(&cbor[..4], &ValidationOptions::new())
+      .is_ok());
+    match schema.validate_cbor_seq_reader(cbor, &ValidationOptions::new()) {
+      Err(Error::CBOR(cbor::Error::Validation(errors))) => {
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].cbor_location, "/2");
+      }
+      r => panic!("expected validation errors, got {:?}", r),
+    }
+
+    assert!(matches!(
+      schema.validate_cbor_seq_reader(cbor, &ValidationOptions::new().with_max_size(8)),
+      Err(Error::LimitExceeded(_))
+    ));
+  }
+
+  #[test]
+  fn validate_with_custom_controls() {
+    let mut controls = CustomControls::new();
+    controls
+      .register(".even", |value, _, _| match value {
+        custom::ControlValue::JSON(serde_json::Value::Number(n))
+          if n.as_u64().map_or(false, |n| n % 2 == 0) =>
+        {
+          Ok(())
+        }
+        custom::ControlValue::CBOR(Value::Integer(i))
+          if u64::try_from(*i).map_or(false, |i| i % 2 == 0) =>
+        {
+          Ok(())
+        }
+        _ => Err("expected even number".to_string()),
+      })
+      .unwrap();
+
+    let cddl = "counts = [* uint .even 0]";
+    assert!(Schema::new(cddl).is_err());
+    let schema = Schema::with_custom_controls(cddl, controls).unwrap();
+
+    let options = ValidationOptions::new();
+    assert!(schema.validate_json("[2, 4]", &options).is_ok());
+    assert_eq!(
+      reasons(schema.validate_json("[2, 3]", &options)),
+      vec!["expected even number"]
+    );
+    // [2, 2], [2, 3]
+    assert!(schema.validate_cbor(b"\x82\x02\x02", &options).is_ok());
+    assert!(schema.validate_cbor(b"\x82\x02\x03", &options).is_err());
+    assert!(schema
+      .validate_cbor_reader(&b"\x82\x02\x03"[..], &options)
+      .is_err());
+  }
+
+  #[test]
+  fn validate_with_tag_registry() {
+    let schema = Schema::new("id = #6.37(bstr)").unwrap();
+    let options = ValidationOptions::new();
+
+    // 37(h'01'), 37(h'00000000000000000000000000000000')
+    let short: &[u8] = b"\xd8\x25\x41\x01";
+    let mut uuid = b"\xd8\x25\x50".to_vec();
+    uuid.extend_from_slice(&[0; 16]);
+
+    assert!(schema.validate_cbor(&uuid, &options).is_ok());
+    assert_eq!(
+      reasons(schema.validate_cbor(short, &options)),
+      vec!["invalid content of tag 37, expected UUID byte string of 16 bytes, got 1 bytes"]
+    );
+    assert!(schema.validate_cbor_reader(short, &options).is_err());
+
+    let mut registry = TagRegistry::new();
+    registry.register(37, |value| match value {
+      Value::Bytes(b) if !b.is_empty() => Ok(()),
+      _ => Err("expected non-empty byte string".to_string()),
+    });
+    let schema = schema.with_tag_registry(registry);
+
+    assert!(schema.validate_cbor(short, &options).is_ok());
+    assert!(schema.validate_cbor_reader(short, &options).is_ok());
+    assert!(schema.validate_cbor(b"\xd8\x25\x40", &options).is_err());
+  }
+
+  #[test]
+  fn validate_concurrently() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Schema>();
+    assert_send_sync::<JSONValidator>();
+    assert_send_sync::<CBORValidator>();
+
+    let schema = std::sync::Arc::new(schema());
+    let handles = (0..4)
+      .map(|age| {
+        let schema = schema.clone();
+        std::thread::spawn(move || {
+          let json = format!(r#"{{ "name": "John", "age": {} }}"#, age);
+          schema.validate_json(&json, &ValidationOptions::new())
+        })
+      })
+      .collect::<Vec<_>>();
+
+    for handle in handles {
+      assert!(handle.join().unwrap().is_ok());
+    }
+  }
+}
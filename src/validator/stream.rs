@@ -0,0 +1,1657 @@
+#![cfg(feature = "cbor")]
+#![cfg(not(feature = "lsp"))]
+#![cfg(not(target_arch = "wasm32"))]
+
+use super::*;
+use crate::{
+  ast::{MemberKey, Type1, TypeChoice},
+  token,
+};
+
+use std::{
+  collections::{BTreeMap, BTreeSet, HashSet},
+  convert::TryFrom,
+  io::Read,
+};
+
+use ciborium::value::Value;
+
+use super::array::{occurrence_bounds, ArrayItem, ArrayPattern};
+use super::cbor::{
+  map_key_identity, map_key_location, token_value_into_cbor_value, ValidationError,
+};
+use super::compiled::{CompiledSchema, RuleLookup};
+use super::custom::CustomControls;
+use super::raw::{RawItem, RawKind, RECURSION_LIMIT};
+use super::tags::TagRegistry;
+
+/// Default maximum number of bytes buffered to decode a single data item
+pub const DEFAULT_MAX_BUFFER_SIZE: usize = 1 << 20;
+
+/// Maximum number of states of the automaton an array group is compiled into
+/// before falling back to validating the array as a whole
+const MAX_ARRAY_STATES: usize = 4096;
+
+/// Head of an encoded CBOR data item, i.e. its initial byte and argument
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Head {
+  /// Byte offset of the initial byte in the stream
+  pub offset: usize,
+  /// Major type
+  pub major: u8,
+  /// Additional information of the initial byte
+  pub info: u8,
+  /// Argument of the head. `None` if of indefinite length and for the break
+  /// stop code
+  pub argument: Option<u64>,
+}
+
+impl Head {
+  /// Whether or not the head is the break stop code terminating an
+  /// indefinite-length data item
+  pub fn is_break(&self) -> bool {
+    self.major == 7 && self.info == 31
+  }
+
+  fn encode(&self, buf: &mut Vec<u8>) {
+    buf.push(self.major << 5 | self.info);
+    if let Some(argument) = self.argument {
+      let len = raw::argument_len(self.info);
+      buf.extend_from_slice(&argument.to_be_bytes()[8 - len..]);
+    }
+  }
+}
+
+/// Pull parser reading the heads of CBOR data items one at a time from a
+/// reader. Data items are only buffered in memory when decoded as a whole, up
+/// to a maximum number of bytes, so that arbitrarily large inputs can be
+/// validated with bounded memory
+pub struct Decoder<R> {
+  reader: R,
+  offset: usize,
+  depth: usize,
+  max_depth: usize,
+  max_buffer_size: usize,
+}
+
+impl<R: Read> Decoder<R> {
+  /// New decoder reading from the given reader
+  pub fn new(reader: R) -> Self {
+    Decoder {
+      reader,
+      offset: 0,
+      depth: 0,
+      max_depth: RECURSION_LIMIT,
+      max_buffer_size: DEFAULT_MAX_BUFFER_SIZE,
+    }
+  }
+
+  /// Maximum number of bytes buffered to decode a single data item. Defaults
+  /// to [`DEFAULT_MAX_BUFFER_SIZE`]
+  pub fn with_max_buffer_size(mut self, bytes: usize) -> Self {
+    self.max_buffer_size = bytes;
+    self
+  }
+
+  /// Maximum nesting depth of arrays, maps and tags. Defaults to the
+  /// recursion limit of ciborium
+  pub fn with_max_depth(mut self, depth: usize) -> Self {
+    self.max_depth = depth.min(RECURSION_LIMIT);
+    self
+  }
+
+  /// Number of bytes read so far
+  pub fn offset(&self) -> usize {
+    self.offset
+  }
+
+  fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), raw::Error> {
+    self.reader.read_exact(buf).map_err(raw::Error::Io)?;
+    self.offset += buf.len();
+
+    Ok(())
+  }
+
+  /// Read the head of the next data item
+  pub fn head(&mut self) -> Result<Head, raw::Error> {
+    let mut initial = [0; 1];
+    self.read_exact(&mut initial)?;
+
+    self.head_from_initial(initial[0])
+  }
+
+  /// Read the head of the next data item, or `None` if the reader is at the
+  /// end of its input, as after the last data item of a CBOR sequence
+  pub fn try_head(&mut self) -> Result<Option<Head>, raw::Error> {
+    let mut initial = [0; 1];
+    loop {
+      match self.reader.read(&mut initial) {
+        Ok(0) => return Ok(None),
+        Ok(_) => break,
+        Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+        Err(e) => return Err(raw::Error::Io(e)),
+      }
+    }
+    self.offset += 1;
+
+    self.head_from_initial(initial[0]).map(Some)
+  }
+
+  // Read the argument following the given initial byte, which has just been
+  // read
+  fn head_from_initial(&mut self, initial: u8) -> Result<Head, raw::Error> {
+    let offset = self.offset - 1;
+    let (major, info) = (initial >> 5, initial & 0x1f);
+
+    let argument = match info {
+      0..=23 => Some(info as u64),
+      24..=27 => {
+        let mut b = [0; 8];
+        let len = raw::argument_len(info);
+        self.read_exact(&mut b[8 - len..])?;
+        Some(u64::from_be_bytes(b))
+      }
+      31 if matches!(major, 2..=5 | 7) => None,
+      _ => return Err(raw::Error::Syntax(offset)),
+    };
+
+    Ok(Head {
+      offset,
+      major,
+      info,
+      argument,
+    })
+  }
+
+  fn enter(&mut self) -> Result<(), raw::Error> {
+    if self.depth >= self.max_depth {
+      return Err(raw::Error::RecursionLimitExceeded);
+    }
+
+    self.depth += 1;
+
+    Ok(())
+  }
+
+  fn leave(&mut self) {
+    self.depth -= 1;
+  }
+
+  /// Decode the data item with the given head, which must have just been
+  /// read, retaining its encoding as [`raw::from_slice`] does
+  pub fn item(&mut self, head: Head) -> Result<(Value, RawItem), raw::Error> {
+    let mut buf = Vec::new();
+    self.consume(&head, Some(&mut buf))?;
+
+    let (value, mut raw) = raw::from_slice(&buf).map_err(|e| match e {
+      raw::Error::Syntax(offset) => raw::Error::Syntax(head.offset + offset),
+      raw::Error::Semantic(offset, msg) => {
+        raw::Error::Semantic(offset.map(|o| head.offset + o), msg)
+      }
+      e => e,
+    })?;
+    shift_offsets(&mut raw, head.offset);
+
+    Ok((value, raw))
+  }
+
+  /// Skip the data item with the given head, which must have just been read,
+  /// without buffering it
+  pub fn skip(&mut self, head: Head) -> Result<(), raw::Error> {
+    self.consume(&head, None)
+  }
+
+  // Read the remainder of the data item with the given head, appending its
+  // encoding, including the head, to the given buffer if any
+  fn consume(&mut self, head: &Head, mut buf: Option<&mut Vec<u8>>) -> Result<(), raw::Error> {
+    if let Some(buf) = buf.as_deref_mut() {
+      head.encode(buf);
+      self.check_buffer_size(buf.len(), 0, head.offset)?;
+    }
+
+    match (head.major, head.argument) {
+      (2..=3, Some(len)) => self.read_content(len, buf, head.offset),
+      (2..=3, None) => loop {
+        let chunk = self.head()?;
+        if chunk.is_break() {
+          if let Some(buf) = buf.as_deref_mut() {
+            chunk.encode(buf);
+          }
+
+          return Ok(());
+        }
+        if chunk.major != head.major || chunk.argument.is_none() {
+          return Err(raw::Error::Syntax(chunk.offset));
+        }
+
+        self.consume(&chunk, buf.as_deref_mut())?;
+      },
+      (4..=6, argument) => {
+        self.enter()?;
+
+        let count = match (head.major, argument) {
+          (6, _) => Some(1),
+          (4, Some(len)) => Some(len),
+          (5, Some(len)) => Some(len.saturating_mul(2)),
+          _ => None,
+        };
+
+        let mut consumed = 0;
+        while !matches!(count, Some(count) if consumed >= count) {
+          let item = self.head()?;
+          if count.is_none() && item.is_break() {
+            if let Some(buf) = buf.as_deref_mut() {
+              item.encode(buf);
+            }
+            break;
+          }
+
+          self.consume(&item, buf.as_deref_mut())?;
+          consumed += 1;
+        }
+
+        self.leave();
+
+        Ok(())
+      }
+      _ => Ok(()),
+    }
+  }
+
+  fn read_content(
+    &mut self,
+    len: u64,
+    buf: Option<&mut Vec<u8>>,
+    offset: usize,
+  ) -> Result<(), raw::Error> {
+    let read = match buf {
+      Some(buf) => {
+        let len = usize::try_from(len).map_err(|_| raw::Error::Syntax(offset))?;
+        self.check_buffer_size(buf.len(), len, offset)?;
+        (&mut self.reader)
+          .take(len as u64)
+          .read_to_end(buf)
+          .map_err(raw::Error::Io)? as u64
+      }
+      None => std::io::copy(&mut (&mut self.reader).take(len), &mut std::io::sink())
+        .map_err(raw::Error::Io)?,
+    };
+    self.offset += read as usize;
+
+    if read != len {
+      return Err(raw::Error::Io(std::io::ErrorKind::UnexpectedEof.into()));
+    }
+
+    Ok(())
+  }
+
+  fn check_buffer_size(
+    &self,
+    buffered: usize,
+    len: usize,
+    offset: usize,
+  ) -> Result<(), raw::Error> {
+    if buffered.saturating_add(len) > self.max_buffer_size {
+      return Err(raw::Error::Semantic(
+        Some(offset),
+        format!(
+          "data item exceeds the maximum buffer size of {} bytes",
+          self.max_buffer_size
+        ),
+      ));
+    }
+
+    Ok(())
+  }
+}
+
+// Make the offsets of a data item decoded from a buffer relative to the stream
+fn shift_offsets(raw: &mut RawItem, offset: usize) {
+  raw.offset += offset;
+
+  match &mut raw.kind {
+    RawKind::Bytes(items) | RawKind::Text(items) | RawKind::Array(items) => {
+      for item in items.iter_mut() {
+        shift_offsets(item, offset);
+      }
+    }
+    RawKind::Map { entries, .. } => {
+      for (k, v) in entries.iter_mut() {
+        shift_offsets(k, offset);
+        shift_offsets(v, offset);
+      }
+    }
+    RawKind::Tag(item) => shift_offsets(item, offset),
+    _ => (),
+  }
+}
+
+type StreamResult<V> = std::result::Result<V, cbor::Error<std::io::Error>>;
+
+// Constraint a data item is validated against
+enum Constraint<'a, 'c> {
+  // Any of the given types, i.e. a type rule and its type choice alternates
+  Types(Vec<&'c Type<'a>>),
+  Type2(&'c Type2<'a>),
+  Typename(&'c TypeGroupnameEntry<'a>),
+}
+
+impl<'a, 'c> From<&'c ArrayItem<'a, '_>> for Constraint<'a, 'c> {
+  fn from(item: &'c ArrayItem<'a, '_>) -> Self {
+    match item {
+      ArrayItem::Type(t) => Constraint::Types(vec![*t]),
+      ArrayItem::Typename(entry) => Constraint::Typename(entry),
+    }
+  }
+}
+
+// End of the items of an array or CBOR sequence
+#[derive(Clone, Copy)]
+enum ItemsEnd {
+  /// Array of definite length
+  Count(u64),
+  /// Array of indefinite length, terminated by the break stop code
+  Break,
+  /// CBOR sequence, terminated by the end of the input
+  Eof,
+}
+
+// Array or map type whose entries are validated as they are decoded
+enum Shape<'a, 'c> {
+  Array(&'c Group<'a>),
+  Map(&'c Group<'a>),
+}
+
+// Map group entry with a member key
+struct MapEntry<'a, 'c> {
+  key: &'c MemberKey<'a>,
+  value: &'c Type<'a>,
+  lower: usize,
+  upper: Option<usize>,
+}
+
+// Array pattern compiled into a nondeterministic automaton, whose set of
+// active states is advanced as array items are decoded
+struct ArrayAutomaton<'p, 'a, 'b> {
+  states: Vec<State<'p, 'a, 'b>>,
+  start: usize,
+}
+
+enum State<'p, 'a, 'b> {
+  Accept,
+  Item(usize, &'p ArrayItem<'a, 'b>, usize),
+  Split(Vec<usize>),
+}
+
+impl<'p, 'a, 'b> ArrayAutomaton<'p, 'a, 'b> {
+  fn new(pattern: &'p ArrayPattern<'a, 'b>) -> Option<Self> {
+    let mut automaton = ArrayAutomaton {
+      states: vec![State::Accept],
+      start: 0,
+    };
+    automaton.start = automaton.build(pattern, 0)?;
+
+    Some(automaton)
+  }
+
+  fn push(&mut self, state: State<'p, 'a, 'b>) -> Option<usize> {
+    if self.states.len() == MAX_ARRAY_STATES {
+      return None;
+    }
+
+    self.states.push(state);
+
+    Some(self.states.len() - 1)
+  }
+
+  // Build the states matching the pattern followed by the given state
+  fn build(&mut self, pattern: &'p ArrayPattern<'a, 'b>, next: usize) -> Option<usize> {
+    match pattern {
+      ArrayPattern::Item(id, item) => self.push(State::Item(*id, item, next)),
+      ArrayPattern::Choice(choices) => {
+        let starts = choices
+          .iter()
+          .map(|c| self.build(c, next))
+          .collect::<Option<Vec<_>>>()?;
+
+        self.push(State::Split(starts))
+      }
+      ArrayPattern::Sequence(sequence) => sequence
+        .iter()
+        .rev()
+        .try_fold(next, |next, (p, lower, upper)| {
+          self.repeat(p, *lower, *upper, next)
+        }),
+    }
+  }
+
+  fn repeat(
+    &mut self,
+    pattern: &'p ArrayPattern<'a, 'b>,
+    lower: usize,
+    upper: Option<usize>,
+    next: usize,
+  ) -> Option<usize> {
+    let mut start = match upper {
+      Some(upper) => {
+        let mut start = next;
+        for _ in lower..upper {
+          let repetition = self.build(pattern, start)?;
+          start = self.push(State::Split(vec![repetition, next]))?;
+        }
+
+        start
+      }
+      None => {
+        let split = self.push(State::Split(Vec::new()))?;
+        let repetition = self.build(pattern, split)?;
+        self.states[split] = State::Split(vec![repetition, next]);
+
+        split
+      }
+    };
+
+    for _ in 0..lower {
+      start = self.build(pattern, start)?;
+    }
+
+    Some(start)
+  }
+
+  // States reachable from the given states without consuming an item
+  fn closure(&self, states: impl IntoIterator<Item = usize>) -> BTreeSet<usize> {
+    let mut closure = BTreeSet::new();
+    let mut pending = states.into_iter().collect::<Vec<_>>();
+
+    while let Some(state) = pending.pop() {
+      if closure.insert(state) {
+        if let State::Split(next) = &self.states[state] {
+          pending.extend(next.iter().copied());
+        }
+      }
+    }
+
+    closure
+  }
+
+  // Distinct item constraints of the given states
+  fn items(&self, states: &BTreeSet<usize>) -> BTreeMap<usize, &'p ArrayItem<'a, 'b>> {
+    states
+      .iter()
+      .filter_map(|s| match &self.states[*s] {
+        State::Item(id, item, _) => Some((*id, *item)),
+        _ => None,
+      })
+      .collect()
+  }
+
+  // Advance the given states past an item matching the given item constraints
+  fn step(&self, states: &BTreeSet<usize>, matched: &BTreeSet<usize>) -> BTreeSet<usize> {
+    self.closure(states.iter().filter_map(|s| match &self.states[*s] {
+      State::Item(id, _, next) if matched.contains(id) => Some(*next),
+      _ => None,
+    }))
+  }
+
+  fn accepts(&self, states: &BTreeSet<usize>) -> bool {
+    states.contains(&0)
+  }
+}
+
+/// CBOR validator reading the data item to validate from a reader instead of
+/// decoding it into a `ciborium::value::Value` up front. Arrays and maps whose
+/// type is known from the head of the data item are validated entry by entry
+/// as they are decoded, recursively, so that only their individual entries are
+/// buffered. Array items are matched against array groups with the same
+/// semantics as [`CBORValidator`], and map entries are matched by key against
+/// map groups made up of entries with member keys. Any other data item, such
+/// as an array or map validated against multiple type choices of the same
+/// major type or against a group with group choices, is decoded as a whole up
+/// to the maximum buffer size and validated by a [`CBORValidator`]. Duplicate
+/// keys are detected by retaining the preferred serialization of each key of
+/// the map being validated, for up to a maximum buffer size worth of bytes per
+/// map. Keys beyond that are not checked for duplicates
+///
+/// # Example
+///
+/// ```
+/// use cddl::{cddl_from_str, validator::stream::StreamValidator};
+///
+/// let cddl = cddl_from_str("readings = [* { id: uint, value: float }]", true).unwrap();
+///
+/// // [{ "id": 1, "value": 1.5 }]
+/// let cbor: &[u8] = b"\x81\xa2\x62id\x01\x65value\xf9\x3e\x00";
+/// let mut sv = StreamValidator::new(&cddl, None);
+/// assert!(sv.validate(cbor).is_ok());
+/// ```
+pub struct StreamValidator<'a> {
+  cddl: &'a CDDL<'a>,
+  compiled: Option<&'a CompiledSchema<'a>>,
+  custom_controls: Option<&'a CustomControls>,
+  tag_registry: Option<&'a TagRegistry>,
+  max_buffer_size: usize,
+  max_depth: usize,
+  #[cfg(feature = "additional-controls")]
+  enabled_features: Option<&'a [&'a str]>,
+}
+
+impl<'a> StreamValidator<'a> {
+  #[cfg(feature = "additional-controls")]
+  /// New streaming validator from CDDL AST
+  pub fn new(cddl: &'a CDDL<'a>, enabled_features: Option<&'a [&'a str]>) -> Self {
+    StreamValidator {
+      cddl,
+      compiled: None,
+      custom_controls: None,
+      tag_registry: None,
+      max_buffer_size: DEFAULT_MAX_BUFFER_SIZE,
+      max_depth: RECURSION_LIMIT,
+      enabled_features,
+    }
+  }
+
+  #[cfg(not(feature = "additional-controls"))]
+  /// New streaming validator from CDDL AST
+  pub fn new(cddl: &'a CDDL<'a>) -> Self {
+    StreamValidator {
+      cddl,
+      compiled: None,
+      custom_controls: None,
+      tag_registry: None,
+      max_buffer_size: DEFAULT_MAX_BUFFER_SIZE,
+      max_depth: RECURSION_LIMIT,
+    }
+  }
+
+  /// Validate against the resolved CDDL AST of the given compiled schema,
+  /// instead of the CDDL AST the validator was created with, using its rule
+  /// index and the regular expressions and ABNF grammars it compiled
+  pub fn with_compiled_schema(mut self, compiled: &'a CompiledSchema<'a>) -> Self {
+    self.cddl = compiled.cddl();
+    self.compiled = Some(compiled);
+    self
+  }
+
+  /// Invoke the callbacks of the given custom control operators, which the
+  /// CDDL AST was parsed with, for the data items matching their target types
+  pub fn with_custom_controls(mut self, controls: &'a CustomControls) -> Self {
+    self.custom_controls = Some(controls);
+    self
+  }
+
+  /// Check the content of tagged data items matching `#6.n(...)` with the
+  /// checks of the given tag registry
+  pub fn with_tag_registry(mut self, registry: &'a TagRegistry) -> Self {
+    self.tag_registry = Some(registry);
+    self
+  }
+
+  /// Maximum number of bytes buffered to decode a single data item that is not
+  /// validated entry by entry. Defaults to [`DEFAULT_MAX_BUFFER_SIZE`]
+  pub fn with_max_buffer_size(mut self, bytes: usize) -> Self {
+    self.max_buffer_size = bytes;
+    self
+  }
+
+  /// Maximum nesting depth of arrays, maps and tags
+  pub fn with_max_depth(mut self, depth: usize) -> Self {
+    self.max_depth = depth;
+    self
+  }
+
+  /// Validate the data item read from the given reader against the root type
+  /// rule. Bytes following the data item are not read
+  pub fn validate<R: Read>(&mut self, reader: R) -> cbor::Result<std::io::Error> {
+    let mut decoder = self.decoder(reader);
+
+    let rule = self.cddl.rules.iter().find_map(|r| match r {
+      Rule::Type { rule, .. } if rule.generic_params.is_none() => Some(rule),
+      _ => None,
+    });
+
+    match rule {
+      Some(rule) => self.validate_type_rule(&mut decoder, rule),
+      None => Ok(()),
+    }
+  }
+
+  /// Validate the data item read from the given reader against the type or
+  /// group rule with the given name. Bytes following the data item are not
+  /// read
+  pub fn validate_rule<R: Read>(&mut self, reader: R, rule: &str) -> cbor::Result<std::io::Error> {
+    let mut decoder = self.decoder(reader);
+
+    match root_rule_from_name(self.cddl, rule).map_err(cbor::Error::MissingRule)? {
+      Rule::Type { rule, .. } => self.validate_type_rule(&mut decoder, rule),
+      Rule::Group { rule, .. } => {
+        let head = decoder.head().map_err(cbor::Error::CBORParsing)?;
+        let t2 = group_rule_as_type2(&rule.name, head.major == 4);
+        let errors = self.validate_constraint(&mut decoder, head, &Constraint::Type2(&t2), "")?;
+
+        into_result(errors)
+      }
+    }
+  }
+
+  /// Validate the CBOR sequence (RFC 8742) read from the given reader until
+  /// the end of its input against the first rule, which must be a group rule
+  /// or an array type rule. The data items of the sequence are matched
+  /// against the group as if they were the items of an array, with errors
+  /// located at the index of the data item in the sequence. A data item
+  /// failing to match is reported and skipped so that the remaining data
+  /// items are still validated
+  pub fn validate_sequence<R: Read>(&mut self, reader: R) -> cbor::Result<std::io::Error> {
+    let rule = self.cddl.rules.iter().find(|r| match r {
+      Rule::Type { rule, .. } => rule.generic_params.is_none(),
+      Rule::Group { rule, .. } => rule.generic_params.is_none(),
+    });
+
+    match rule {
+      Some(rule) => self.validate_sequence_against(&mut self.decoder(reader), rule),
+      None => Ok(()),
+    }
+  }
+
+  /// Validate the CBOR sequence (RFC 8742) read from the given reader until
+  /// the end of its input against the group rule or array type rule with the
+  /// given name. See [`StreamValidator::validate_sequence`]
+  pub fn validate_sequence_rule<R: Read>(
+    &mut self,
+    reader: R,
+    rule: &str,
+  ) -> cbor::Result<std::io::Error> {
+    let rule = root_rule_from_name(self.cddl, rule).map_err(cbor::Error::MissingRule)?;
+
+    self.validate_sequence_against(&mut self.decoder(reader), rule)
+  }
+
+  fn validate_sequence_against<R: Read>(
+    &self,
+    decoder: &mut Decoder<R>,
+    rule: &'a Rule<'a>,
+  ) -> cbor::Result<std::io::Error> {
+    let group_array;
+    let t2 = match rule {
+      Rule::Group { rule, .. } => {
+        group_array = group_rule_as_type2(&rule.name, true);
+        Some(&group_array)
+      }
+      Rule::Type { rule, .. } => match &rule.value.type_choices[..] {
+        [TypeChoice { type1, .. }] if type1.operator.is_none() => Some(&type1.type2),
+        _ => None,
+      },
+    };
+
+    let (t2, group) = match t2 {
+      Some(t2 @ Type2::Array { group, .. }) => (t2, group),
+      _ => {
+        return Err(cbor::Error::MissingRule(format!(
+          "rule {} is neither a group rule nor an array type rule, as required to validate a CBOR sequence",
+          rule.name()
+        )))
+      }
+    };
+
+    let errors = match ArrayPattern::from_group(self.lookup(), group) {
+      Some(pattern) => match ArrayAutomaton::new(&pattern) {
+        Some(automaton) => self.validate_items(decoder, ItemsEnd::Eof, &automaton, "")?,
+        None => self.validate_sequence_as_array(decoder, t2)?,
+      },
+      None => self.validate_sequence_as_array(decoder, t2)?,
+    };
+
+    into_result(errors)
+  }
+
+  // Validate the data items of a CBOR sequence decoded as a whole, as the
+  // items of an array, when its group cannot be matched item by item
+  fn validate_sequence_as_array<R: Read>(
+    &self,
+    decoder: &mut Decoder<R>,
+    t2: &Type2<'a>,
+  ) -> StreamResult<Vec<ValidationError>> {
+    let mut values = Vec::new();
+    let mut raws = Vec::new();
+    while let Some(head) = decoder.try_head().map_err(cbor::Error::CBORParsing)? {
+      let (value, raw) = decoder.item(head).map_err(cbor::Error::CBORParsing)?;
+      values.push(value);
+      raws.push(raw);
+    }
+
+    let raw = RawItem {
+      offset: 0,
+      info: 31,
+      argument: None,
+      kind: RawKind::Array(raws),
+    };
+
+    self.validate_value(&Value::Array(values), &raw, &Constraint::Type2(t2), "")
+  }
+
+  fn decoder<R: Read>(&self, reader: R) -> Decoder<R> {
+    Decoder::new(reader)
+      .with_max_buffer_size(self.max_buffer_size)
+      .with_max_depth(self.max_depth)
+  }
+
+  fn lookup(&self) -> RuleLookup<'a> {
+    RuleLookup {
+      cddl: self.cddl,
+      compiled: self.compiled,
+    }
+  }
+
+  fn validate_type_rule<R: Read>(
+    &self,
+    decoder: &mut Decoder<R>,
+    rule: &'a TypeRule<'a>,
+  ) -> cbor::Result<std::io::Error> {
+    let mut types = self.lookup().type_choice_alternates_from_ident(&rule.name);
+    types.insert(0, &rule.value);
+
+    let head = decoder.head().map_err(cbor::Error::CBORParsing)?;
+    let errors = self.validate_constraint(decoder, head, &Constraint::Types(types), "")?;
+
+    into_result(errors)
+  }
+
+  // Validate the data item with the given head, streaming its entries if its
+  // shape is known and decoding it as a whole otherwise
+  fn validate_constraint<R: Read>(
+    &self,
+    decoder: &mut Decoder<R>,
+    head: Head,
+    constraint: &Constraint<'a, '_>,
+    location: &str,
+  ) -> StreamResult<Vec<ValidationError>> {
+    match self.shape(constraint, &head) {
+      Some(Shape::Array(group)) => {
+        if let Some(pattern) = ArrayPattern::from_group(self.lookup(), group) {
+          if let Some(automaton) = ArrayAutomaton::new(&pattern) {
+            return self.validate_array(decoder, head, &automaton, location);
+          }
+        }
+      }
+      Some(Shape::Map(group)) => {
+        let mut entries = Vec::new();
+        if self.map_entries(group, &mut Vec::new(), &mut entries) {
+          return self.validate_map(decoder, head, &entries, location);
+        }
+      }
+      None => (),
+    }
+
+    let (value, raw) = decoder.item(head).map_err(cbor::Error::CBORParsing)?;
+
+    self.validate_value(&value, &raw, constraint, location)
+  }
+
+  // Validate a decoded data item with a CBORValidator
+  fn validate_value(
+    &self,
+    value: &Value,
+    raw: &RawItem,
+    constraint: &Constraint<'a, '_>,
+    location: &str,
+  ) -> StreamResult<Vec<ValidationError>> {
+    let validator = || {
+      #[cfg(feature = "additional-controls")]
+      let cv = CBORValidator::new(self.cddl, value.clone(), self.enabled_features);
+      #[cfg(not(feature = "additional-controls"))]
+      let cv = CBORValidator::new(self.cddl, value.clone());
+
+      let mut cv = cv.with_encoding(raw.clone());
+      if let Some(compiled) = self.compiled {
+        cv = cv.with_compiled_schema(compiled);
+      }
+      if let Some(controls) = self.custom_controls {
+        cv = cv.with_custom_controls(controls);
+      }
+      if let Some(registry) = self.tag_registry {
+        cv = cv.with_tag_registry(registry);
+      }
+
+      cv
+    };
+
+    match constraint {
+      Constraint::Types(types) => {
+        let mut errors = Vec::new();
+        for t in types.iter() {
+          let mut type_errors = validator().validate_at(location, |cv| cv.visit_type(t))?;
+          if type_errors.is_empty() {
+            return Ok(type_errors);
+          }
+
+          errors.append(&mut type_errors);
+        }
+
+        Ok(errors)
+      }
+      Constraint::Type2(t2) => validator().validate_at(location, |cv| cv.visit_type2(t2)),
+      Constraint::Typename(entry) => {
+        validator().validate_at(location, |cv| cv.visit_type_groupname_entry(entry))
+      }
+    }
+  }
+
+  fn validate_array<R: Read>(
+    &self,
+    decoder: &mut Decoder<R>,
+    head: Head,
+    automaton: &ArrayAutomaton,
+    location: &str,
+  ) -> StreamResult<Vec<ValidationError>> {
+    decoder.enter().map_err(cbor::Error::CBORParsing)?;
+
+    let end = match head.argument {
+      Some(len) => ItemsEnd::Count(len),
+      None => ItemsEnd::Break,
+    };
+    let errors = self.validate_items(decoder, end, automaton, location)?;
+
+    decoder.leave();
+
+    Ok(errors)
+  }
+
+  // Match the items of an array or CBOR sequence against the automaton of its
+  // group
+  fn validate_items<R: Read>(
+    &self,
+    decoder: &mut Decoder<R>,
+    end: ItemsEnd,
+    automaton: &ArrayAutomaton,
+    location: &str,
+  ) -> StreamResult<Vec<ValidationError>> {
+    let parsing = cbor::Error::CBORParsing;
+    let is_sequence = matches!(end, ItemsEnd::Eof);
+
+    let mut states = automaton.closure(std::iter::once(automaton.start));
+    let mut errors = Vec::new();
+    let mut idx = 0;
+    loop {
+      let item_head = match end {
+        ItemsEnd::Count(len) if idx as u64 >= len => break,
+        ItemsEnd::Eof => match decoder.try_head().map_err(parsing)? {
+          Some(item_head) => item_head,
+          None => break,
+        },
+        _ => decoder.head().map_err(parsing)?,
+      };
+      if matches!(end, ItemsEnd::Break) && item_head.is_break() {
+        break;
+      }
+
+      // Once an item of an array fails to match, the remaining items are
+      // skipped
+      if !errors.is_empty() && !is_sequence {
+        decoder.skip(item_head).map_err(parsing)?;
+        idx += 1;
+        continue;
+      }
+
+      let item_location = format!("{}/{}", location, idx);
+      let items = automaton.items(&states);
+
+      let mut matched = BTreeSet::new();
+      let mut item_errors = Vec::new();
+      if items.is_empty() {
+        decoder.skip(item_head).map_err(parsing)?;
+        let reason = if is_sequence {
+          format!("unexpected data item at index {} of the CBOR sequence", idx)
+        } else {
+          format!("unexpected array item at index {}", idx)
+        };
+        item_errors.push(validation_error(&item_location, reason));
+      } else if items.len() == 1 {
+        let (id, item) = items.into_iter().next().unwrap();
+        let mut e =
+          self.validate_constraint(decoder, item_head, &Constraint::from(item), &item_location)?;
+        if e.is_empty() {
+          matched.insert(id);
+        }
+        item_errors.append(&mut e);
+      } else {
+        // An item matching any of several constraints is decoded once and
+        // validated against each of them
+        let (value, raw) = decoder.item(item_head).map_err(parsing)?;
+        for (id, item) in items.into_iter() {
+          let mut e = self.validate_value(&value, &raw, &Constraint::from(item), &item_location)?;
+          if e.is_empty() {
+            matched.insert(id);
+          }
+          item_errors.append(&mut e);
+        }
+      }
+
+      idx += 1;
+
+      let next = automaton.step(&states, &matched);
+      if next.is_empty() {
+        errors.append(&mut item_errors);
+
+        // The data items of a sequence following one that fails to match are
+        // validated as if it were absent
+        if is_sequence {
+          continue;
+        }
+      }
+      states = next;
+    }
+
+    if (errors.is_empty() || is_sequence) && !automaton.accepts(&states) {
+      let reason = if is_sequence {
+        "CBOR sequence is missing required data items"
+      } else {
+        "array is missing required items"
+      };
+      errors.push(validation_error(location, reason.to_string()));
+    }
+
+    Ok(errors)
+  }
+
+  fn validate_map<R: Read>(
+    &self,
+    decoder: &mut Decoder<R>,
+    head: Head,
+    entries: &[MapEntry<'a, '_>],
+    location: &str,
+  ) -> StreamResult<Vec<ValidationError>> {
+    let parsing = cbor::Error::CBORParsing;
+
+    decoder.enter().map_err(parsing)?;
+
+    let mut errors = Vec::new();
+    let mut counts = vec![0; entries.len()];
+    let mut key_count = 0;
+    let mut keys = HashSet::new();
+    let mut keys_size = 0usize;
+    while !matches!(head.argument, Some(len) if key_count >= len) {
+      let key_head = decoder.head().map_err(parsing)?;
+      if head.argument.is_none() && key_head.is_break() {
+        break;
+      }
+
+      let (key, raw_key) = decoder.item(key_head).map_err(parsing)?;
+      key_count += 1;
+      let identity = map_key_identity(&key, Some(&raw_key));
+      if keys.contains(&identity) {
+        errors.push(validation_error(
+          location,
+          format!("map has duplicate key {:?}", key),
+        ));
+      }
+
+      let mut candidates = Vec::new();
+      for (idx, entry) in entries.iter().enumerate() {
+        if !matches!(entry.upper, Some(upper) if counts[idx] >= upper)
+          && self.key_matches(entry.key, &key, &raw_key, location)?
+        {
+          candidates.push(idx);
+        }
+      }
+
+      let value_location = map_key_location(location, &key);
+      let value_head = decoder.head().map_err(parsing)?;
+      match candidates[..] {
+        [] => {
+          decoder.skip(value_head).map_err(parsing)?;
+          errors.push(validation_error(
+            location,
+            format!("unexpected key {:?}", key),
+          ));
+        }
+        [idx] => {
+          counts[idx] += 1;
+          let constraint = Constraint::Types(vec![entries[idx].value]);
+          errors.append(&mut self.validate_constraint(
+            decoder,
+            value_head,
+            &constraint,
+            &value_location,
+          )?);
+        }
+        _ => {
+          // A value matching the keys of several entries is decoded once and
+          // validated against each of them, in order
+          let (value, raw) = decoder.item(value_head).map_err(parsing)?;
+
+          let mut first_errors = None;
+          for idx in candidates.iter() {
+            let constraint = Constraint::Types(vec![entries[*idx].value]);
+            let e = self.validate_value(&value, &raw, &constraint, &value_location)?;
+            if e.is_empty() {
+              counts[*idx] += 1;
+              first_errors = None;
+              break;
+            }
+
+            first_errors.get_or_insert((*idx, e));
+          }
+
+          if let Some((idx, mut e)) = first_errors {
+            counts[idx] += 1;
+            errors.append(&mut e);
+          }
+        }
+      }
+
+      if keys_size.saturating_add(identity.0.len()) <= self.max_buffer_size {
+        keys_size += identity.0.len();
+        keys.insert(identity);
+      }
+    }
+
+    decoder.leave();
+
+    for (entry, count) in entries.iter().zip(counts) {
+      if count < entry.lower {
+        let reason = match entry.key {
+          MemberKey::Bareword { ident, .. } => format!("object missing key: \"{}\"", ident),
+          MemberKey::Value { value, .. } => format!("object missing key: {}", value),
+          MemberKey::Type1 { t1, .. } => format!("object missing entries with key {}", t1),
+          MemberKey::NonMemberKey { .. } => continue,
+        };
+
+        errors.push(validation_error(location, reason));
+      }
+    }
+
+    Ok(errors)
+  }
+
+  fn key_matches(
+    &self,
+    mk: &MemberKey<'a>,
+    key: &Value,
+    raw_key: &RawItem,
+    location: &str,
+  ) -> StreamResult<bool> {
+    match mk {
+      MemberKey::Bareword { ident, .. } => {
+        Ok(matches!(key, Value::Text(text) if *text == ident.ident))
+      }
+      MemberKey::Value { value, .. } => Ok(token_value_into_cbor_value(value.clone()) == *key),
+      MemberKey::Type1 { t1, .. } => {
+        #[cfg(feature = "additional-controls")]
+        let cv = CBORValidator::new(self.cddl, key.clone(), self.enabled_features);
+        #[cfg(not(feature = "additional-controls"))]
+        let cv = CBORValidator::new(self.cddl, key.clone());
+
+        let mut cv = cv.with_encoding(raw_key.clone());
+        if let Some(compiled) = self.compiled {
+          cv = cv.with_compiled_schema(compiled);
+        }
+        if let Some(controls) = self.custom_controls {
+          cv = cv.with_custom_controls(controls);
+        }
+        if let Some(registry) = self.tag_registry {
+          cv = cv.with_tag_registry(registry);
+        }
+
+        Ok(
+          cv.validate_at(location, |cv| cv.visit_type1(t1))?
+            .is_empty(),
+        )
+      }
+      MemberKey::NonMemberKey { .. } => Ok(false),
+    }
+  }
+
+  // Shape of the data item with the given head if the constraint only allows
+  // for a single array or map type that can be streamed
+  fn shape<'c>(&self, constraint: &Constraint<'a, 'c>, head: &Head) -> Option<Shape<'a, 'c>> {
+    if !matches!(head.major, 4 | 5) {
+      return None;
+    }
+
+    let mut shapes = Vec::new();
+    let mut expanding = Vec::new();
+    let is_known = match constraint {
+      Constraint::Types(types) => types
+        .iter()
+        .all(|t| self.type_shapes(t, head.major, false, &mut expanding, &mut shapes)),
+      Constraint::Type2(t2) => {
+        self.type2_shapes(t2, head.major, false, &mut expanding, &mut shapes)
+      }
+      Constraint::Typename(entry) => {
+        entry.generic_args.is_none()
+          && self.ident_shapes(&entry.name, head.major, false, &mut expanding, &mut shapes)
+      }
+    };
+
+    if !is_known || shapes.len() != 1 {
+      return None;
+    }
+
+    shapes.pop()
+  }
+
+  // Collect the array and map types among the type choices that match a data
+  // item of the given major type. Returns false if there are type choices that
+  // may match the data item but cannot be streamed, including any array or map
+  // type whose choice is restricted by a control operator
+  fn type_shapes<'c>(
+    &self,
+    t: &'c Type<'a>,
+    major: u8,
+    is_restricted: bool,
+    expanding: &mut Vec<&'a str>,
+    shapes: &mut Vec<Shape<'a, 'c>>,
+  ) -> bool {
+    t.type_choices.iter().all(|TypeChoice { type1, .. }| {
+      self.type1_shapes(type1, major, is_restricted, expanding, shapes)
+    })
+  }
+
+  fn type1_shapes<'c>(
+    &self,
+    t1: &'c Type1<'a>,
+    major: u8,
+    is_restricted: bool,
+    expanding: &mut Vec<&'a str>,
+    shapes: &mut Vec<Shape<'a, 'c>>,
+  ) -> bool {
+    let is_restricted = is_restricted || t1.operator.is_some();
+
+    self.type2_shapes(&t1.type2, major, is_restricted, expanding, shapes)
+  }
+
+  fn type2_shapes<'c>(
+    &self,
+    t2: &'c Type2<'a>,
+    major: u8,
+    is_restricted: bool,
+    expanding: &mut Vec<&'a str>,
+    shapes: &mut Vec<Shape<'a, 'c>>,
+  ) -> bool {
+    match t2 {
+      Type2::Array { group, .. } if major == 4 => {
+        shapes.push(Shape::Array(group));
+        !is_restricted
+      }
+      Type2::Map { group, .. } if major == 5 => {
+        shapes.push(Shape::Map(group));
+        !is_restricted
+      }
+      Type2::Array { .. }
+      | Type2::Map { .. }
+      | Type2::IntValue { .. }
+      | Type2::UintValue { .. }
+      | Type2::FloatValue { .. }
+      | Type2::TextValue { .. }
+      | Type2::UTF8ByteString { .. }
+      | Type2::B16ByteString { .. }
+      | Type2::B64ByteString { .. }
+      | Type2::TaggedData { .. } => true,
+      Type2::DataMajorType { mt, .. } => *mt != major,
+      Type2::ParenthesizedType { pt, .. } => {
+        self.type_shapes(pt, major, is_restricted, expanding, shapes)
+      }
+      Type2::Typename {
+        ident,
+        generic_args: None,
+        ..
+      } => self.ident_shapes(ident, major, is_restricted, expanding, shapes),
+      _ => false,
+    }
+  }
+
+  fn ident_shapes<'c>(
+    &self,
+    ident: &Identifier<'a>,
+    major: u8,
+    is_restricted: bool,
+    expanding: &mut Vec<&'a str>,
+    shapes: &mut Vec<Shape<'a, 'c>>,
+  ) -> bool {
+    let lookup = self.lookup();
+
+    match lookup.rule_from_ident(ident) {
+      Some(Rule::Type { rule, .. }) => {
+        if rule.generic_params.is_some() || expanding.contains(&&*rule.name.ident) {
+          return false;
+        }
+
+        expanding.push(&rule.name.ident);
+        let is_known = std::iter::once(&rule.value)
+          .chain(lookup.type_choice_alternates_from_ident(ident))
+          .all(|t| self.type_shapes(t, major, is_restricted, expanding, shapes));
+        expanding.pop();
+
+        is_known
+      }
+      Some(Rule::Group { .. }) => false,
+      // Apart from any, prelude types do not match arrays or maps
+      None => is_prelude_scalar_type(&ident.ident),
+    }
+  }
+
+  // Flatten a map group into entries with member keys. Returns false if the
+  // group cannot be flattened, i.e. if it has group choices, occurrence
+  // indicators on groups or generic or recursive group names
+  fn map_entries<'c>(
+    &self,
+    group: &'c Group<'a>,
+    expanding: &mut Vec<&'a str>,
+    entries: &mut Vec<MapEntry<'a, 'c>>,
+  ) -> bool {
+    group.group_choices.len() == 1
+      && group.group_choices[0]
+        .group_entries
+        .iter()
+        .all(|(ge, _)| self.map_entry(ge, expanding, entries))
+  }
+
+  fn map_entry<'c>(
+    &self,
+    ge: &'c GroupEntry<'a>,
+    expanding: &mut Vec<&'a str>,
+    entries: &mut Vec<MapEntry<'a, 'c>>,
+  ) -> bool {
+    match ge {
+      GroupEntry::ValueMemberKey { ge, .. } => match &ge.member_key {
+        Some(MemberKey::NonMemberKey { .. }) => false,
+        Some(key) => {
+          let (lower, upper) = occurrence_bounds(ge.occur.as_ref().map(|o| &o.occur));
+          entries.push(MapEntry {
+            key,
+            value: &ge.entry_type,
+            lower,
+            upper,
+          });
+
+          true
+        }
+        None => match &ge.entry_type.type_choices[..] {
+          [TypeChoice { type1, .. }] if ge.occur.is_none() && type1.operator.is_none() => {
+            match &type1.type2 {
+              Type2::Typename {
+                ident,
+                generic_args: None,
+                ..
+              } => self.map_group_name(ident, expanding, entries),
+              _ => false,
+            }
+          }
+          _ => false,
+        },
+      },
+      GroupEntry::TypeGroupname { ge, .. } => {
+        ge.occur.is_none()
+          && ge.generic_args.is_none()
+          && self.map_group_name(&ge.name, expanding, entries)
+      }
+      GroupEntry::InlineGroup {
+        occur: None, group, ..
+      } => self.map_entries(group, expanding, entries),
+      GroupEntry::InlineGroup { .. } => false,
+    }
+  }
+
+  fn map_group_name<'c>(
+    &self,
+    ident: &Identifier<'a>,
+    expanding: &mut Vec<&'a str>,
+    entries: &mut Vec<MapEntry<'a, 'c>>,
+  ) -> bool {
+    let lookup = self.lookup();
+
+    let rule = match lookup.group_rule_from_ident(ident) {
+      Some(rule) => rule,
+      None => return false,
+    };
+
+    if rule.generic_params.is_some()
+      || expanding.contains(&&*rule.name.ident)
+      || !lookup.group_choice_alternates_from_ident(ident).is_empty()
+    {
+      return false;
+    }
+
+    expanding.push(&rule.name.ident);
+    let is_flattened = self.map_entry(&rule.entry, expanding, entries);
+    expanding.pop();
+
+    is_flattened
+  }
+}
+
+/// Whether or not the identifier names a prelude type other than `any`, none
+/// of which match arrays or maps
+fn is_prelude_scalar_type(ident: &str) -> bool {
+  matches!(
+    token::lookup_ident(ident),
+    Token::BOOL
+      | Token::FALSE
+      | Token::TRUE
+      | Token::NIL
+      | Token::NULL
+      | Token::UNDEFINED
+      | Token::UINT
+      | Token::NINT
+      | Token::INT
+      | Token::INTEGER
+      | Token::UNSIGNED
+      | Token::NUMBER
+      | Token::FLOAT
+      | Token::FLOAT16
+      | Token::FLOAT32
+      | Token::FLOAT64
+      | Token::FLOAT1632
+      | Token::FLOAT3264
+      | Token::TSTR
+      | Token::TEXT
+      | Token::BSTR
+      | Token::BYTES
+      | Token::TDATE
+      | Token::TIME
+      | Token::BIGUINT
+      | Token::BIGNINT
+      | Token::BIGINT
+      | Token::DECFRAC
+      | Token::BIGFLOAT
+      | Token::EB64URL
+      | Token::EB64LEGACY
+      | Token::EB16
+      | Token::ENCODEDCBOR
+      | Token::URI
+      | Token::B64URL
+      | Token::B64LEGACY
+      | Token::REGEXP
+      | Token::MIMEMESSAGE
+      | Token::CBORANY
+  )
+}
+
+fn validation_error(location: &str, reason: String) -> ValidationError {
+  ValidationError {
+    reason,
+    cddl_location: String::new(),
+    cbor_location: location.to_string(),
+    is_multi_type_choice: false,
+    is_multi_group_choice: false,
+    is_group_to_choice_enum: false,
+    type_group_name_entry: None,
+  }
+}
+
+fn into_result(errors: Vec<ValidationError>) -> cbor::Result<std::io::Error> {
+  if errors.is_empty() {
+    return Ok(());
+  }
+
+  Err(cbor::Error::Validation(errors))
+}
+
+#[cfg(test)]
+#[cfg(feature = "additional-controls")]
+mod tests {
+  use super::*;
+  use crate::cddl_from_str;
+  use indoc::indoc;
+
+  fn stream_reasons(cddl: &CDDL, cbor: &[u8], max_buffer_size: usize) -> Vec<String> {
+    let mut sv = StreamValidator::new(cddl, None).with_max_buffer_size(max_buffer_size);
+
+    match sv.validate(cbor) {
+      Ok(()) => Vec::new(),
+      Err(cbor::Error::Validation(errors)) => errors.into_iter().map(|e| e.reason).collect(),
+      Err(e) => vec![e.to_string()],
+    }
+  }
+
+  fn is_valid(cddl: &CDDL, cbor: &[u8]) -> bool {
+    let (value, raw) = raw::from_slice(cbor).unwrap();
+    let mut cv = CBORValidator::new(cddl, value, None).with_encoding(raw);
+    let result: cbor::Result<std::io::Error> = cv.validate();
+
+    result.is_ok()
+  }
+
+  fn to_cbor(json: &str) -> Vec<u8> {
+    let json: serde_json::Value = serde_json::from_str(json).unwrap();
+    let mut cbor = Vec::new();
+    ciborium::ser::into_writer(&json, &mut cbor).unwrap();
+
+    cbor
+  }
+
+  #[test]
+  fn stream_matches_cbor_validator() {
+    let cddl = cddl_from_str(
+      indoc!(
+        r#"
+          batch = [ header, * reading, ? trailer ]
+
+          header = { version: 1, ? source: tstr, * tstr => any }
+          reading = { id: uint, value: float / int, ? tags: [* tag] }
+          tag = tstr .size (1..8)
+          trailer = [ count: uint ]
+        "#
+      ),
+      true,
+    )
+    .unwrap();
+
+    for json in [
+      r#"[{ "version": 1 }]"#,
+      r#"[{ "version": 1, "source": "a", "x": [1] }, { "id": 1, "value": 1.5 }, [1]]"#,
+      r#"[{ "version": 1 }, { "id": 1, "value": 2, "tags": ["a", "b"] }, { "id": 2, "value": 3 }]"#,
+      r#"[]"#,
+      r#"[{ "version": 2 }]"#,
+      r#"[{ "version": 1 }, { "id": -1, "value": 1.5 }]"#,
+      r#"[{ "version": 1 }, { "value": 1.5 }]"#,
+      r#"[{ "version": 1 }, { "id": 1, "value": 1, "tags": ["toolongtag"] }]"#,
+      r#"[{ "version": 1 }, { "id": 1, "value": 1, "extra": 1 }]"#,
+      r#"[{ "version": 1 }, [1], { "id": 1, "value": 1 }]"#,
+      r#"{ "version": 1 }"#,
+    ] {
+      let cbor = to_cbor(json);
+      assert_eq!(
+        stream_reasons(&cddl, &cbor, DEFAULT_MAX_BUFFER_SIZE).is_empty(),
+        is_valid(&cddl, &cbor),
+        "{}",
+        json
+      );
+    }
+  }
+
+  #[test]
+  fn stream_with_bounded_buffer() {
+    let cddl = cddl_from_str("readings = [* { id: uint, value: bstr }]", true).unwrap();
+
+    // Indefinite-length array of 1000 readings, each with a 100 byte value
+    let mut cbor = vec![0x9f];
+    for id in 0..1000u16 {
+      cbor.extend_from_slice(&[0xa2, 0x62, b'i', b'd', 0x19]);
+      cbor.extend_from_slice(&id.to_be_bytes());
+      cbor.extend_from_slice(&[0x65, b'v', b'a', b'l', b'u', b'e', 0x58, 100]);
+      cbor.extend_from_slice(&[0; 100]);
+    }
+    cbor.push(0xff);
+
+    assert!(stream_reasons(&cddl, &cbor, 128).is_empty());
+
+    // The data item is too large to be decoded as a whole
+    let (value, _) = raw::from_slice(&cbor).unwrap();
+    let cddl = cddl_from_str("readings = any", true).unwrap();
+    assert!(stream_reasons(&cddl, &cbor, 128)[0].contains("maximum buffer size"));
+    assert!(is_valid(&cddl, &cbor));
+    assert!(matches!(value, Value::Array(a) if a.len() == 1000));
+  }
+
+  #[test]
+  fn stream_error_locations() {
+    let cddl = cddl_from_str("readings = [* { id: uint }]", true).unwrap();
+    let cbor = to_cbor(r#"[{ "id": 1 }, { "id": "2" }, { "id": 3 }]"#);
+
+    let mut sv = StreamValidator::new(&cddl, None);
+    match sv.validate(&cbor[..]) {
+      Err(cbor::Error::Validation(errors)) => {
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].cbor_location, "/1/id");
+      }
+      r => panic!("expected validation errors, got {:?}", r),
+    }
+
+    let cbor = to_cbor(r#"[{ "id": 1 }, { "name": "a" }]"#);
+    let reasons = stream_reasons(&cddl, &cbor, DEFAULT_MAX_BUFFER_SIZE);
+    assert!(reasons.contains(&"unexpected key Text(\"name\")".to_string()));
+    assert!(reasons.contains(&"object missing key: \"id\"".to_string()));
+
+    // Truncated input
+    assert!(matches!(
+      sv.validate(&cbor[..cbor.len() - 1]),
+      Err(cbor::Error::CBORParsing(_))
+    ));
+  }
+
+  #[test]
+  fn stream_duplicate_keys() {
+    let cddl = cddl_from_str("counts = { * int => uint }", true).unwrap();
+    let is_duplicate =
+      |reasons: Vec<String>| reasons.len() == 1 && reasons[0].starts_with("map has duplicate key");
+
+    // {1: 0, 2: 0}, {1: 0, 1: 0} with a 1-byte argument
+    assert!(stream_reasons(&cddl, b"\xa2\x01\x00\x02\x00", 1024).is_empty());
+    assert!(is_duplicate(stream_reasons(
+      &cddl,
+      b"\xa2\x01\x00\x18\x01\x00",
+      1024
+    )));
+
+    // {1: 0, 2: 0, 2: 0}, {1: 0, 2: 0, 1: 0} retaining a single key
+    assert!(stream_reasons(&cddl, b"\xa3\x01\x00\x02\x00\x02\x00", 1).is_empty());
+    assert!(is_duplicate(stream_reasons(
+      &cddl,
+      b"\xa3\x01\x00\x02\x00\x01\x00",
+      1
+    )));
+
+    // {null: 0, simple(16): 0} are distinct keys even though both are decoded
+    // as null
+    let cddl = cddl_from_str("things = { * any => uint }", true).unwrap();
+    assert!(stream_reasons(&cddl, b"\xa2\xf6\x00\xf0\x00", 1024).is_empty());
+    assert!(is_duplicate(stream_reasons(
+      &cddl,
+      b"\xa2\xf0\x00\xf0\x00",
+      1024
+    )));
+  }
+
+  #[test]
+  fn skip_and_decode_items() -> Result<(), raw::Error> {
+    // [_ h'0102', (_ "a", "b"), {1: 2}], 1 with a 1-byte argument
+    let bytes: &[u8] = &[
+      0x9f, 0x42, 0x01, 0x02, 0x7f, 0x61, b'a', 0x61, b'b', 0xff, 0xa1, 0x01, 0x02, 0xff, 0x18,
+      0x01,
+    ];
+
+    let mut decoder = Decoder::new(bytes);
+    let head = decoder.head()?;
+    assert_eq!((head.major, head.argument), (4, None));
+    decoder.skip(head)?;
+
+    let head = decoder.head()?;
+    assert_eq!(head.offset, 14);
+    let (value, raw) = decoder.item(head)?;
+    assert_eq!(value, Value::Integer(1.into()));
+    assert_eq!(raw.offset, 14);
+    assert!(!raw.is_preferred());
+
+    let mut decoder = Decoder::new(bytes).with_max_depth(1);
+    let head = decoder.head()?;
+    assert!(matches!(
+      decoder.item(head),
+      Err(raw::Error::RecursionLimitExceeded)
+    ));
+
+    Ok(())
+  }
+
+  #[test]
+  fn validate_cbor_sequence() {
+    let cddl = cddl_from_str(
+      indoc!(
+        r#"
+          log = (header, * entry)
+          header = { version: uint }
+          entry = [ts: uint, msg: tstr]
+          entries = [* entry]
+          name = tstr
+          either = (uint, uint // tstr)
+          pairs = [* pair<uint>]
+          pair<t> = (t, t)
+        "#
+      ),
+      true,
+    )
+    .unwrap();
+
+    let mut seq = to_cbor(r#"{ "version": 1 }"#);
+    let header_len = seq.len();
+    seq.append(&mut to_cbor(r#"[1, "a"]"#));
+    seq.append(&mut to_cbor(r#"[2, "b"]"#));
+
+    let mut sv = StreamValidator::new(&cddl, None);
+    assert!(sv.validate_sequence(&seq[..]).is_ok());
+    assert!(sv
+      .validate_sequence_rule(&seq[header_len..], "log")
+      .is_err());
+    assert!(sv
+      .validate_sequence_rule(&seq[header_len..], "entries")
+      .is_ok());
+    assert!(sv.validate_sequence_rule(&[][..], "entries").is_ok());
+
+    // Data items following one that fails to match are still validated
+    seq.append(&mut to_cbor(r#"[3, 3]"#));
+    seq.append(&mut to_cbor(r#"[4, "d"]"#));
+    seq.append(&mut to_cbor(r#""e""#));
+    match sv.validate_sequence(&seq[..]) {
+      Err(cbor::Error::Validation(errors)) => {
+        let locations = errors
+          .iter()
+          .map(|e| e.cbor_location.as_str())
+          .collect::<Vec<_>>();
+        assert_eq!(locations, ["/3/1", "/5"]);
+      }
+      r => panic!("expected validation errors, got {:?}", r),
+    }
+
+    match sv.validate_sequence(&[][..]) {
+      Err(cbor::Error::Validation(errors)) => {
+        assert_eq!(
+          errors[0].reason,
+          "CBOR sequence is missing required data items"
+        );
+      }
+      r => panic!("expected validation errors, got {:?}", r),
+    }
+
+    let mut ints = to_cbor("1");
+    ints.append(&mut to_cbor("2"));
+    assert!(sv.validate_sequence_rule(&ints[..], "either").is_ok());
+    assert!(sv
+      .validate_sequence_rule(&to_cbor(r#""a""#)[..], "either")
+      .is_ok());
+    assert!(sv.validate_sequence_rule(&ints[1..], "either").is_err());
+
+    // Groups with generic arguments are matched against the sequence decoded
+    // as a whole
+    assert!(sv.validate_sequence_rule(&ints[..], "pairs").is_ok());
+    assert!(sv.validate_sequence_rule(&ints[1..], "pairs").is_err());
+
+    // Truncated data item
+    assert!(matches!(
+      sv.validate_sequence(&seq[..seq.len() - 1]),
+      Err(cbor::Error::CBORParsing(_))
+    ));
+    assert!(matches!(
+      sv.validate_sequence_rule(&seq[..], "name"),
+      Err(cbor::Error::MissingRule(_))
+    ));
+  }
+}
@@ -0,0 +1,473 @@
+#![cfg(any(feature = "json", feature = "cbor"))]
+
+use ciborium::value::Value;
+
+use std::{convert::TryFrom, fmt, sync::Arc};
+
+/// Semantic check of the content of a CBOR tag. It receives the tagged data
+/// item and returns the reason it is not valid for the tag
+pub type TagCheck = dyn Fn(&Value) -> Result<(), String> + Send + Sync;
+
+/// Predefined semantic check of the content of a common IANA registered tag
+pub type PredefinedTagCheck = fn(&Value) -> Result<(), String>;
+
+#[derive(Clone)]
+struct RegisteredTag {
+  tag: u64,
+  check: Arc<TagCheck>,
+}
+
+/// Semantic checks of the content of CBOR tags, for the CBOR validators given
+/// the registry with `with_tag_registry`. The check of a tag is invoked
+/// whenever a tagged data item matches `#6.n(...)`, including the standard
+/// prelude types defined with tags, and the error it returns is reported as a
+/// validation error. Validators without a registry use the predefined checks
+/// of common IANA registered tags, as registered by [`TagRegistry::iana`].
+/// Registries are independent of each other, so the checks of one registry
+/// have no effect on validators without it.
+///
+/// # Example
+///
+/// ```
+/// use cddl::{
+///   cddl_from_str,
+///   validator::{cbor::CBORValidator, tags::TagRegistry, Validator},
+/// };
+/// use ciborium::value::Value;
+///
+/// // Tag 1002 duration, as per RFC 9581, requires key 1 to be present
+/// let mut registry = TagRegistry::iana();
+/// registry.register(1002, |value| match value {
+///   Value::Map(m) if m.iter().any(|(k, _)| *k == Value::Integer(1.into())) => Ok(()),
+///   _ => Err("expected map with the duration in key 1".to_string()),
+/// });
+///
+/// let cddl = cddl_from_str("duration = #6.1002({ * int => any })", true).unwrap();
+/// let duration = Value::Tag(1002, Box::new(Value::Map(Vec::new())));
+///
+/// let mut cv = CBORValidator::new(&cddl, duration, None).with_tag_registry(&registry);
+/// assert!(cv.validate().is_err());
+/// ```
+#[derive(Clone, Default)]
+pub struct TagRegistry {
+  tags: Vec<RegisteredTag>,
+}
+
+impl fmt::Debug for TagRegistry {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_set().entries(self.tags()).finish()
+  }
+}
+
+impl TagRegistry {
+  /// Empty tag registry, with which the content of data items matching
+  /// `#6.n(...)` is only constrained by the schema
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Tag registry pre-populated with the predefined checks of common IANA
+  /// registered tags. See [`predefined_check`]
+  pub fn iana() -> Self {
+    let mut registry = Self::new();
+    for tag in PREDEFINED_TAGS.iter() {
+      if let Some(check) = predefined_check(*tag) {
+        registry.register(*tag, check);
+      }
+    }
+
+    registry
+  }
+
+  /// Register a semantic check for the content of the CBOR tag with the given
+  /// number. Registering a check for a tag replaces the previously registered
+  /// check, or the predefined check applied to the standard prelude types
+  pub fn register<F>(&mut self, tag: u64, check: F)
+  where
+    F: Fn(&Value) -> Result<(), String> + Send + Sync + 'static,
+  {
+    let check: Arc<TagCheck> = Arc::new(check);
+    match self.tags.iter_mut().find(|t| t.tag == tag) {
+      Some(t) => t.check = check,
+      None => self.tags.push(RegisteredTag { tag, check }),
+    }
+  }
+
+  /// Remove the check of the CBOR tag with the given number. Returns whether
+  /// a check was registered for the tag
+  pub fn remove(&mut self, tag: u64) -> bool {
+    let len = self.tags.len();
+    self.tags.retain(|t| t.tag != tag);
+
+    self.tags.len() != len
+  }
+
+  /// Numbers of the CBOR tags with a registered check
+  pub fn tags(&self) -> Vec<u64> {
+    self.tags.iter().map(|t| t.tag).collect()
+  }
+}
+
+/// Check the content of the CBOR tag with the given number using the check of
+/// the given registry, or the predefined check of the tag without a registry.
+/// The standard prelude types defined with tags, whose content the schema
+/// leaves to the semantics of the tag, fall back to the predefined check of
+/// their tag if the registry has no check for it. Content of tags without a
+/// check is always valid
+#[cfg(feature = "cbor")]
+pub(crate) fn validate_tag(
+  registry: Option<&TagRegistry>,
+  tag: u64,
+  value: &Value,
+  is_prelude_type: bool,
+) -> Result<(), String> {
+  let registered = match registry {
+    Some(r) => r.tags.iter().find(|t| t.tag == tag),
+    None => None,
+  };
+
+  match registered {
+    Some(t) => (t.check)(value),
+    None if registry.is_none() || is_prelude_type => match predefined_check(tag) {
+      Some(check) => check(value),
+      None => Ok(()),
+    },
+    None => Ok(()),
+  }
+}
+
+/// Numbers of the tags with a predefined check
+const PREDEFINED_TAGS: [u64; 18] = [
+  0, 1, 2, 3, 4, 5, 24, 32, 33, 34, 35, 36, 37, 52, 54, 100, 1001, 1004,
+];
+
+/// Predefined check of the content of a common tag in the IANA CBOR tags
+/// registry, if any, e.g. for adding it to a registry created with
+/// [`TagRegistry::new`]
+pub fn predefined_check(tag: u64) -> Option<PredefinedTagCheck> {
+  match tag {
+    0 => Some(date_time_string),
+    1 => Some(epoch_date_time),
+    2 | 3 => Some(bignum),
+    4 | 5 => Some(exponent_mantissa),
+    24 => Some(encoded_cbor_data_item),
+    32 => Some(uri),
+    33 => Some(base64url),
+    34 => Some(base64),
+    35 => Some(regular_expression),
+    36 => Some(mime_message),
+    37 => Some(uuid),
+    52 => Some(ipv4),
+    54 => Some(ipv6),
+    100 => Some(days_since_epoch),
+    1001 => Some(extended_time),
+    1004 => Some(full_date),
+    _ => None,
+  }
+}
+
+// Tag 0, as per RFC 8949 section 3.4.1
+fn date_time_string(value: &Value) -> Result<(), String> {
+  match value {
+    Value::Text(s) => chrono::DateTime::parse_from_rfc3339(s)
+      .map(|_| ())
+      .map_err(|e| format!("invalid standard date/time string {:?}, {}", s, e)),
+    _ => Err(format!(
+      "expected standard date/time string, got {:?}",
+      value
+    )),
+  }
+}
+
+// Tag 1, as per RFC 8949 section 3.4.2
+fn epoch_date_time(value: &Value) -> Result<(), String> {
+  match value {
+    Value::Integer(_) => Ok(()),
+    Value::Float(f) if f.is_finite() => Ok(()),
+    _ => Err(format!(
+      "expected epoch-based date/time number, got {:?}",
+      value
+    )),
+  }
+}
+
+// Tags 2 and 3, as per RFC 8949 section 3.4.3
+fn bignum(value: &Value) -> Result<(), String> {
+  match value {
+    Value::Bytes(_) => Ok(()),
+    _ => Err(format!("expected bignum byte string, got {:?}", value)),
+  }
+}
+
+// Tags 4 and 5, as per RFC 8949 section 3.4.4
+fn exponent_mantissa(value: &Value) -> Result<(), String> {
+  match value {
+    Value::Array(a) if a.len() == 2 => match (&a[0], &a[1]) {
+      (Value::Integer(_), Value::Integer(_)) => Ok(()),
+      (Value::Integer(_), Value::Tag(2..=3, b)) if matches!(b.as_ref(), Value::Bytes(_)) => Ok(()),
+      _ => Err(format!(
+        "expected integer exponent and mantissa, got {:?}",
+        value
+      )),
+    },
+    _ => Err(format!(
+      "expected array of an exponent and a mantissa, got {:?}",
+      value
+    )),
+  }
+}
+
+// Tag 24, as per RFC 8949 section 3.4.5.1
+fn encoded_cbor_data_item(value: &Value) -> Result<(), String> {
+  match value {
+    Value::Bytes(b) => ciborium::de::from_reader::<Value, _>(&b[..])
+      .map(|_| ())
+      .map_err(|e| format!("invalid encoded CBOR data item, {}", e)),
+    _ => Err(format!(
+      "expected byte string with an encoded CBOR data item, got {:?}",
+      value
+    )),
+  }
+}
+
+// Tag 32, as per RFC 8949 section 3.4.5.3
+fn uri(value: &Value) -> Result<(), String> {
+  match value {
+    Value::Text(s) => uriparse::URI::try_from(&**s)
+      .map(|_| ())
+      .map_err(|e| format!("invalid URI {:?}, {}", s, e)),
+    _ => Err(format!("expected URI text string, got {:?}", value)),
+  }
+}
+
+// Tag 33, as per RFC 8949 section 3.4.5.3
+fn base64url(value: &Value) -> Result<(), String> {
+  match value {
+    Value::Text(s) => base64::decode_config(s, base64::URL_SAFE_NO_PAD)
+      .map(|_| ())
+      .map_err(|e| format!("invalid base64url text {:?}, {}", s, e)),
+    _ => Err(format!("expected base64url text string, got {:?}", value)),
+  }
+}
+
+// Tag 34, as per RFC 8949 section 3.4.5.3
+fn base64(value: &Value) -> Result<(), String> {
+  match value {
+    Value::Text(s) => base64::decode_config(s, base64::STANDARD)
+      .map(|_| ())
+      .map_err(|e| format!("invalid base64 text {:?}, {}", s, e)),
+    _ => Err(format!("expected base64 text string, got {:?}", value)),
+  }
+}
+
+// Tag 35, as per RFC 7049 section 2.4.4.3
+fn regular_expression(value: &Value) -> Result<(), String> {
+  match value {
+    Value::Text(s) => regex::Regex::new(s)
+      .map(|_| ())
+      .map_err(|e| format!("invalid regular expression {:?}, {}", s, e)),
+    _ => Err(format!(
+      "expected regular expression text string, got {:?}",
+      value
+    )),
+  }
+}
+
+// Tag 36, as per RFC 7049 section 2.4.4.3
+fn mime_message(value: &Value) -> Result<(), String> {
+  match value {
+    Value::Text(_) => Ok(()),
+    _ => Err(format!(
+      "expected MIME message text string, got {:?}",
+      value
+    )),
+  }
+}
+
+// Tag 37, as per the IANA CBOR tags registry
+fn uuid(value: &Value) -> Result<(), String> {
+  match value {
+    Value::Bytes(b) if b.len() == 16 => Ok(()),
+    Value::Bytes(b) => Err(format!(
+      "expected UUID byte string of 16 bytes, got {} bytes",
+      b.len()
+    )),
+    _ => Err(format!("expected UUID byte string, got {:?}", value)),
+  }
+}
+
+// Tag 52, as per RFC 9164
+fn ipv4(value: &Value) -> Result<(), String> {
+  ip_address_or_prefix(value, 4)
+}
+
+// Tag 54, as per RFC 9164
+fn ipv6(value: &Value) -> Result<(), String> {
+  ip_address_or_prefix(value, 16)
+}
+
+// Address, prefix or interface format of RFC 9164 for addresses of the given
+// length in bytes
+fn ip_address_or_prefix(value: &Value, len: usize) -> Result<(), String> {
+  let max_prefix_length = len as u64 * 8;
+  let prefix_length = |v: &Value| match v {
+    Value::Integer(i) => match u64::try_from(*i) {
+      Ok(p) if p <= max_prefix_length => Ok(p),
+      _ => Err(format!(
+        "expected prefix length of at most {}, got {:?}",
+        max_prefix_length, v
+      )),
+    },
+    _ => Err(format!("expected prefix length, got {:?}", v)),
+  };
+
+  match value {
+    // Address
+    Value::Bytes(b) if b.len() == len => Ok(()),
+    Value::Array(a) if a.len() == 2 || a.len() == 3 => match (&a[0], &a[1]) {
+      // Prefix, with trailing zero bytes removed
+      (Value::Integer(_), Value::Bytes(b)) if a.len() == 2 => {
+        let prefix_length = prefix_length(&a[0])?;
+        if b.last() == Some(&0) {
+          return Err("expected prefix without trailing zero bytes".to_string());
+        }
+
+        if b.len() as u64 > (prefix_length + 7) / 8 {
+          return Err(format!(
+            "expected prefix of at most {} bytes for prefix length {}, got {} bytes",
+            (prefix_length + 7) / 8,
+            prefix_length,
+            b.len()
+          ));
+        }
+
+        Ok(())
+      }
+      // Interface, with optional zone identifier
+      (Value::Bytes(b), p) if b.len() == len => {
+        if a.len() == 2 || !matches!(p, Value::Null) {
+          prefix_length(p)?;
+        }
+
+        match a.get(2) {
+          None | Some(Value::Integer(_)) | Some(Value::Bytes(_)) => Ok(()),
+          Some(zone) => Err(format!("expected zone identifier, got {:?}", zone)),
+        }
+      }
+      _ => Err(format!(
+        "expected IP prefix or interface format, got {:?}",
+        value
+      )),
+    },
+    _ => Err(format!(
+      "expected IP address of {} bytes, prefix or interface format, got {:?}",
+      len, value
+    )),
+  }
+}
+
+// Tag 100, as per RFC 8943
+fn days_since_epoch(value: &Value) -> Result<(), String> {
+  // Number of days from 0001-01-01 to 1970-01-01
+  const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+  match value {
+    Value::Integer(i) => i32::try_from(*i)
+      .ok()
+      .and_then(|days| days.checked_add(UNIX_EPOCH_DAYS_FROM_CE))
+      .and_then(chrono::NaiveDate::from_num_days_from_ce_opt)
+      .map(|_| ())
+      .ok_or_else(|| format!("days since epoch {:?} out of range", i)),
+    _ => Err(format!(
+      "expected days since epoch integer, got {:?}",
+      value
+    )),
+  }
+}
+
+// Tag 1004, as per RFC 8943
+fn full_date(value: &Value) -> Result<(), String> {
+  match value {
+    Value::Text(s) => super::validate_full_date(s),
+    _ => Err(format!("expected full-date text string, got {:?}", value)),
+  }
+}
+
+// Tag 1001, as per RFC 9581 section 3
+fn extended_time(value: &Value) -> Result<(), String> {
+  let m = match value {
+    Value::Map(m) => m,
+    _ => return Err(format!("expected extended time map, got {:?}", value)),
+  };
+
+  let entry = |key: i64| {
+    m.iter()
+      .find(|(k, _)| *k == Value::Integer(key.into()))
+      .map(|(_, v)| v)
+  };
+
+  for (k, _) in m.iter() {
+    if !matches!(k, Value::Integer(_) | Value::Text(_)) {
+      return Err(format!(
+        "expected extended time map key of type int or tstr, got {:?}",
+        k
+      ));
+    }
+  }
+
+  // Exactly one base time, in seconds (1), as a decimal fraction (4) or as a
+  // bigfloat (5)
+  let base_times = [1, 4, 5]
+    .iter()
+    .filter_map(|key| entry(*key).map(|v| (*key, v)))
+    .collect::<Vec<_>>();
+  let (base_key, base_time) = match base_times.as_slice() {
+    [base_time] => *base_time,
+    [] => return Err("expected base time in key 1, 4 or 5".to_string()),
+    _ => return Err("expected exactly one base time in key 1, 4 or 5".to_string()),
+  };
+
+  match base_key {
+    1 => epoch_date_time(base_time)?,
+    _ => exponent_mantissa(base_time)?,
+  }
+
+  // At most one fractional time in milliseconds (-3), microseconds (-6) or
+  // nanoseconds (-9), only in addition to an integer base time in seconds
+  let fractions = [-3, -6, -9]
+    .iter()
+    .filter_map(|key| entry(*key).map(|v| (*key, v)))
+    .collect::<Vec<_>>();
+  match fractions.as_slice() {
+    [] => (),
+    [(key, fraction)] => {
+      if !matches!(base_time, Value::Integer(_)) || base_key != 1 {
+        return Err(format!(
+          "fractional time in key {} requires an integer base time in key 1",
+          key
+        ));
+      }
+
+      let limit = 10u64.pow((-key) as u32);
+      match fraction {
+        Value::Integer(i) if matches!(u64::try_from(*i), Ok(f) if f < limit) => (),
+        _ => {
+          return Err(format!(
+            "expected fractional time in key {} less than {}, got {:?}",
+            key, limit, fraction
+          ))
+        }
+      }
+    }
+    _ => return Err("expected at most one fractional time in key -3, -6 or -9".to_string()),
+  }
+
+  // Time scale
+  match entry(-1) {
+    None | Some(Value::Integer(_)) => Ok(()),
+    Some(time_scale) => Err(format!(
+      "expected time scale integer in key -1, got {:?}",
+      time_scale
+    )),
+  }
+}
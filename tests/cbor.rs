@@ -72,8 +72,24 @@ fn validate_cbor_float() {
   validate_cbor_from_slice(cddl_input, cbor::FLOAT_1_0, None).unwrap();
   validate_cbor_from_slice(cddl_input, cbor::FLOAT_1E300, None).unwrap();
 
-  // TODO: check that large floats don't validate against a smaller size.
-  // E.g. CBOR #7.27 (64-bit) shouldn't validate against "float16" or "float32".
+  // Large floats don't validate against a smaller size. E.g. CBOR #7.27
+  // (64-bit) shouldn't validate against "float16" or "float32".
+  let cddl_input = r#"thing = float16"#;
+  validate_cbor_from_slice(cddl_input, cbor::FLOAT_1E5, None).unwrap_err();
+  validate_cbor_from_slice(cddl_input, cbor::FLOAT_1E300, None).unwrap_err();
+
+  let cddl_input = r#"thing = float32"#;
+  validate_cbor_from_slice(cddl_input, cbor::FLOAT_1E300, None).unwrap_err();
+
+  let cddl_input = r#"thing = float16-32"#;
+  validate_cbor_from_slice(cddl_input, cbor::FLOAT_1E5, None).unwrap();
+  validate_cbor_from_slice(cddl_input, cbor::FLOAT_1E300, None).unwrap_err();
+
+  // float32 is defined as #7.26 in the prelude, so both match the same floats
+  let cddl_input = r#"thing = #7.26"#;
+  validate_cbor_from_slice(cddl_input, cbor::FLOAT_1_0, None).unwrap();
+  validate_cbor_from_slice(cddl_input, cbor::FLOAT_1E5, None).unwrap();
+  validate_cbor_from_slice(cddl_input, cbor::FLOAT_1E300, None).unwrap_err();
 }
 
 #[test]
@@ -147,7 +163,7 @@ struct LongTuple(String, u32, u32);
 struct ShortTuple(String);
 
 #[derive(Debug, Serialize, Deserialize)]
-struct KitchenSink(String, u32, f64, bool);
+struct KitchenSink(String, u32, f32, bool);
 
 #[test]
 fn validate_cbor_group() {
@@ -180,14 +196,24 @@ fn validate_cbor_homogenous_array() {
 }
 
 #[test]
-#[ignore] // FIXME: broken
 fn validate_cbor_array_groups() {
   let cddl_input = r#"thing = [int, (int, int)]"#;
   validate_cbor_from_slice(cddl_input, cbor::ARRAY_123, None).unwrap();
-  // TODO: try splitting arrays into groups a few other ways:
-  // [(int, int, int)]
-  // [* (int)]
-  // [* (int, int)]
+
+  let cddl_input = r#"thing = [(int, int, int)]"#;
+  validate_cbor_from_slice(cddl_input, cbor::ARRAY_123, None).unwrap();
+  validate_cbor_from_slice(cddl_input, cbor::ARRAY_EMPTY, None).unwrap_err();
+
+  let cddl_input = r#"thing = [* (int)]"#;
+  validate_cbor_from_slice(cddl_input, cbor::ARRAY_123, None).unwrap();
+  validate_cbor_from_slice(cddl_input, cbor::ARRAY_EMPTY, None).unwrap();
+
+  let cddl_input = r#"thing = [* (int, int)]"#;
+  validate_cbor_from_slice(cddl_input, cbor::ARRAY_123, None).unwrap_err();
+  validate_cbor_from_slice(cddl_input, cbor::ARRAY_EMPTY, None).unwrap();
+
+  let cddl_input = r#"thing = [int, * (int, int)]"#;
+  validate_cbor_from_slice(cddl_input, cbor::ARRAY_123, None).unwrap();
 }
 
 #[test]
@@ -235,6 +261,85 @@ fn validate_cbor_array_record() {
   validate_cbor_from_slice(cddl_input, cbor::ARRAY_123, None).unwrap_err();
 }
 
+#[test]
+fn validate_cbor_rfc8610_array_examples() {
+  use ciborium::{cbor, value::Value};
+
+  let to_cbor = |value: Value| {
+    let mut cbor_bytes = Vec::new();
+    ciborium::ser::into_writer(&value, &mut cbor_bytes).unwrap();
+    cbor_bytes
+  };
+
+  // RFC 8610, Section 3.4
+  let cddl_input = r#"
+    Geography = [
+      city           : tstr,
+      gpsCoordinates : GpsCoordinates,
+    ]
+
+    GpsCoordinates = {
+      longitude      : uint,            ; degrees, scaled by 10^7
+      latitude       : uint,            ; degrees, scaled by 10^7
+    }
+  "#;
+  let coordinates = cbor!({ "longitude" => 163738950, "latitude" => 482083330 }).unwrap();
+  let input = to_cbor(cbor!(["Vienna", coordinates]).unwrap());
+  validate_cbor_from_slice(cddl_input, &input, None).unwrap();
+  let input = to_cbor(cbor!([coordinates, "Vienna"]).unwrap());
+  validate_cbor_from_slice(cddl_input, &input, None).unwrap_err();
+  let input = to_cbor(cbor!(["Vienna"]).unwrap());
+  validate_cbor_from_slice(cddl_input, &input, None).unwrap_err();
+
+  // RFC 8610, Section 3.7
+  let cddl_input = r#"
+    advanced-header = [
+      ~basic-header,
+      field3: bytes,
+      field4: ~time,
+    ]
+
+    basic-header = [
+      field1: int,
+      field2: text,
+    ]
+  "#;
+  let bytes = Value::Bytes(vec![0x01, 0x02]);
+  let input = to_cbor(cbor!([1, "a", bytes, 1]).unwrap());
+  validate_cbor_from_slice(cddl_input, &input, None).unwrap();
+  let input = to_cbor(cbor!([1, "a", bytes, 1.5]).unwrap());
+  validate_cbor_from_slice(cddl_input, &input, None).unwrap();
+  let input = to_cbor(cbor!([[1, "a"], bytes, 1]).unwrap());
+  validate_cbor_from_slice(cddl_input, &input, None).unwrap_err();
+  let input = to_cbor(cbor!([1, "a", bytes]).unwrap());
+  validate_cbor_from_slice(cddl_input, &input, None).unwrap_err();
+  // ~time is the untagged epoch-based date/time
+  let time = Value::Tag(1, Box::new(Value::Integer(1.into())));
+  let input = to_cbor(cbor!([1, "a", bytes, time]).unwrap());
+  validate_cbor_from_slice(cddl_input, &input, None).unwrap_err();
+
+  // RFC 8610, Appendix H
+  let cddl_input = include_str!("fixtures/cddl/reputon.cddl");
+  let input = to_cbor(cbor!({ "application" => "conformance", "reputons" => [] }).unwrap());
+  validate_cbor_from_slice(cddl_input, &input, None).unwrap();
+  let input = to_cbor(
+    cbor!({
+      "application" => "conformance",
+      "reputons" => [
+        { "rater" => "a", "assertion" => "b", "rated" => "c", "rating" => 0.5 },
+        { "rater" => "d", "assertion" => "e", "rated" => "f", "rating" => 1.0, "sample-size" => 2 },
+      ],
+    })
+    .unwrap(),
+  );
+  validate_cbor_from_slice(cddl_input, &input, None).unwrap();
+  let input =
+    to_cbor(cbor!({ "application" => "conformance", "reputons" => [{ "rater" => "a" }] }).unwrap());
+  validate_cbor_from_slice(cddl_input, &input, None).unwrap_err();
+  let input = to_cbor(cbor!({ "application" => "conformance", "reputons" => [1] }).unwrap());
+  validate_cbor_from_slice(cddl_input, &input, None).unwrap_err();
+}
+
 #[test]
 fn validate_cbor_map() {
   let input = PersonStruct {